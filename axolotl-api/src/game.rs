@@ -94,15 +94,54 @@ data_registries!(
 );
 
 pub trait Registry<T> {
+    /// # Panics
+    /// If the registry is frozen
     fn register(&mut self, namespace: impl Into<String>, item: T) -> usize;
+    /// # Panics
+    /// If the registry is frozen
     fn register_with_id(&mut self, namespace: impl Into<String>, id: usize, item: T);
 
     fn get_by_id(&self, id: usize) -> Option<&T>;
 
     fn get_id(&self, namespace: impl AsRef<str>) -> Option<usize>;
+    /// The key the id was registered with. None if the registry does not keep the keys
+    fn get_key(&self, id: usize) -> Option<&str> {
+        None
+    }
 
     fn get_by_namespace(&self, namespace: impl AsRef<str>) -> Option<&T>;
     fn get_by_namespace_key(&self, key: &OwnedNameSpaceKey) -> Option<&T> {
         self.get_by_namespace(key.to_string())
     }
+    /// Every entry ordered by id.
+    ///
+    /// The default stops at the first id without a value or key. Registries with gaps in their ids override it
+    fn iter<'registry>(
+        &'registry self,
+    ) -> Box<dyn Iterator<Item = (usize, &'registry str, &'registry T)> + 'registry>
+    where
+        T: 'registry,
+    {
+        Box::new((0..).map_while(move |id| Some((id, self.get_key(id)?, self.get_by_id(id)?))))
+    }
+
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Stops any more values from being registered. Called once loading is done so ids stay stable
+    ///
+    /// Does nothing by default
+    fn freeze(&mut self) {}
+
+    fn is_frozen(&self) -> bool {
+        false
+    }
+    /// The ids of every value in the tag. Nested tags are already resolved
+    fn get_tag(&self, tag: &str) -> Option<&[usize]> {
+        None
+    }
 }
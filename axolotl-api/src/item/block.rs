@@ -8,6 +8,8 @@ use serde::{Deserialize, Deserializer};
 
 use crate::events::{Event, EventHandler, NoError};
 use crate::game::Game;
+use crate::item::metadata::{BlockMetadata, PistonBehavior, DEFAULT_BLOCK_METADATA};
+use crate::item::placement::PlacementContext;
 use crate::item::ItemType;
 use crate::world::BlockPosition;
 use crate::{NamespacedId, NumericId};
@@ -119,6 +121,18 @@ pub trait BlockState: Debug + Clone {
     fn get(&self, name: &str) -> Option<&BlockStateValue>;
 
     fn set(&mut self, name: impl Into<String>, value: BlockStateValue);
+    /// If the block has a `waterlogged` property
+    fn can_waterlog(&self) -> bool {
+        self.get("waterlogged").is_some()
+    }
+
+    fn is_waterlogged(&self) -> bool {
+        match self.get("waterlogged") {
+            Some(BlockStateValue::Bool(value)) => *value,
+            Some(BlockStateValue::String(value)) => value == "true",
+            _ => false,
+        }
+    }
 }
 /// Block Place Event
 pub struct BlockPlaceEvent<'game, G: Game> {
@@ -145,8 +159,44 @@ pub trait Block<G: Game>:
     fn create_default_state(&self) -> Self::State;
 
     fn is_air(&self) -> bool;
+    /// Hardness, resistance, light and other static properties
+    fn metadata(&self) -> &BlockMetadata {
+        &DEFAULT_BLOCK_METADATA
+    }
+    fn hardness(&self) -> f32 {
+        self.metadata().hardness
+    }
+    fn blast_resistance(&self) -> f32 {
+        self.metadata().resistance
+    }
+    fn luminance(&self) -> u8 {
+        self.metadata().luminance
+    }
+    fn opacity(&self) -> u8 {
+        self.metadata().opacity
+    }
+    fn friction(&self) -> f32 {
+        self.metadata().friction
+    }
+    fn piston_behavior(&self) -> PistonBehavior {
+        self.metadata().piston_behavior
+    }
 
     fn get_default_state(&self) -> Cow<'_, Self::State> {
         Cow::Owned(self.create_default_state())
     }
+    /// The state the block should be placed with.
+    ///
+    /// Returns None if the block can not be placed. Such as a torch without support
+    fn get_placement_state(&self, context: &PlacementContext) -> Option<Self::State> {
+        Some(self.create_default_state())
+    }
+    /// Other blocks placed with this block. Such as the upper half of a door
+    fn get_additional_placements(
+        &self,
+        context: &PlacementContext,
+        state: &Self::State,
+    ) -> Vec<(BlockPosition, Self::State)> {
+        Vec::new()
+    }
 }
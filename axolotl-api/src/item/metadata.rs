@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+/// How a block reacts to a piston pushing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PistonBehavior {
+    #[default]
+    #[serde(alias = "NORMAL")]
+    Normal,
+    /// The block breaks and drops. Such as torches
+    #[serde(alias = "DESTROY")]
+    Destroy,
+    /// The piston can not move the block. Such as obsidian
+    #[serde(alias = "BLOCK")]
+    Block,
+    #[serde(alias = "IGNORE")]
+    Ignore,
+    /// Can be pushed but not pulled. Such as glazed terracotta
+    #[serde(alias = "PUSH_ONLY")]
+    PushOnly,
+}
+
+/// Static properties of a block. Loaded from the Minecraft data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockMetadata {
+    /// Negative for unbreakable blocks. Such as bedrock
+    pub hardness: f32,
+    #[serde(alias = "blast_resistance", alias = "explosion_resistance")]
+    pub resistance: f32,
+    /// The light level the block emits. 0 to 15
+    #[serde(alias = "light_emission", alias = "light_level")]
+    pub luminance: u8,
+    /// How much light is removed when passing through the block. 0 to 15
+    #[serde(alias = "light_opacity", alias = "filter_light")]
+    pub opacity: u8,
+    #[serde(alias = "slipperiness")]
+    pub friction: f32,
+    #[serde(alias = "push_reaction")]
+    pub piston_behavior: PistonBehavior,
+    /// The block only drops when broken with the correct tool
+    #[serde(alias = "requires_correct_tool_for_drops")]
+    pub requires_tool: bool,
+}
+/// The metadata used for blocks without data
+pub static DEFAULT_BLOCK_METADATA: BlockMetadata = BlockMetadata {
+    hardness: 1.0,
+    resistance: 1.0,
+    luminance: 0,
+    opacity: 15,
+    friction: 0.6,
+    piston_behavior: PistonBehavior::Normal,
+    requires_tool: false,
+};
+pub static AIR_BLOCK_METADATA: BlockMetadata = BlockMetadata {
+    hardness: 0.0,
+    resistance: 0.0,
+    luminance: 0,
+    opacity: 0,
+    friction: 0.6,
+    piston_behavior: PistonBehavior::Destroy,
+    requires_tool: false,
+};
+impl Default for BlockMetadata {
+    fn default() -> Self {
+        DEFAULT_BLOCK_METADATA.clone()
+    }
+}
+impl BlockMetadata {
+    pub fn is_unbreakable(&self) -> bool {
+        self.hardness < 0.0
+    }
+    /// The number of ticks it takes to break the block. None if the block can not be broken.
+    ///
+    /// `tool_speed` is the mining speed of the held item with enchantments and effects applied. 1 for a hand
+    pub fn break_ticks(&self, tool_speed: f32, correct_tool: bool) -> Option<u32> {
+        if self.is_unbreakable() {
+            return None;
+        }
+        if self.hardness == 0.0 {
+            return Some(0);
+        }
+        let divider = if correct_tool || !self.requires_tool {
+            30.0
+        } else {
+            100.0
+        };
+        let damage = tool_speed / self.hardness / divider;
+        if damage >= 1.0 {
+            return Some(0);
+        }
+        Some((1.0 / damage).ceil() as u32)
+    }
+    /// If an explosion of the given power breaks the block. Ignores the distance from the explosion
+    pub fn resists_explosion(&self, power: f32) -> bool {
+        (self.resistance + 0.3) * 0.3 >= power
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::item::metadata::BlockMetadata;
+
+    #[test]
+    pub fn test_break_ticks() {
+        let stone = BlockMetadata {
+            hardness: 1.5,
+            resistance: 6.0,
+            requires_tool: true,
+            ..Default::default()
+        };
+        // Wooden pickaxe and bare hand
+        assert_eq!(stone.break_ticks(2.0, true), Some(23));
+        assert_eq!(stone.break_ticks(1.0, false), Some(150));
+        let bedrock = BlockMetadata {
+            hardness: -1.0,
+            ..Default::default()
+        };
+        assert_eq!(bedrock.break_ticks(100.0, true), None);
+    }
+}
@@ -4,9 +4,13 @@ use auto_impl::auto_impl;
 
 use crate::events::{Event, EventHandler};
 use crate::game::Game;
+use crate::item::properties::{ItemProperties, ToolTier, DEFAULT_ITEM_PROPERTIES};
 use crate::{NamespacedKey, NumericId};
 
 pub mod block;
+pub mod metadata;
+pub mod placement;
+pub mod properties;
 pub mod recipes;
 pub mod vanilla;
 pub trait ItemStack<G: Game> {
@@ -32,7 +36,26 @@ impl<G: Game> Event for ItemLeftClick<G> {
     }
 }
 #[auto_impl(Arc, &)]
-pub trait Item<G: Game>: ItemType + NumericId + EventHandler<ItemLeftClick<G>> {}
+pub trait Item<G: Game>: ItemType + NumericId + EventHandler<ItemLeftClick<G>> {
+    /// Stack size, durability and tool data
+    fn properties(&self) -> &ItemProperties {
+        &DEFAULT_ITEM_PROPERTIES
+    }
+    fn max_stack_size(&self) -> u8 {
+        self.properties().max_stack_size
+    }
+    /// 0 if the item can not be damaged
+    fn max_damage(&self) -> u32 {
+        self.properties().max_damage
+    }
+    fn tool_tier(&self) -> Option<ToolTier> {
+        self.properties().tool_tier
+    }
+    /// The block tags this item is the correct tool for
+    fn block_tags(&self) -> &[String] {
+        &self.properties().block_tags
+    }
+}
 
 pub trait HasHarvestLevel {
     fn get_harvest_level() -> f32;
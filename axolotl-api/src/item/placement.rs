@@ -0,0 +1,77 @@
+use std::fmt::Debug;
+
+use crate::world::{BlockFace, BlockPosition};
+
+/// The view of the world a block has while it is being placed
+pub trait PlacementWorld {
+    /// If the block at the position can support blocks such as torches
+    fn is_solid(&self, position: &BlockPosition) -> bool;
+    /// If the block at the position can be replaced. Air, water, grass, etc
+    fn is_replaceable(&self, position: &BlockPosition) -> bool;
+    /// If the position contains a water source. Used for water-logging
+    fn is_water_source(&self, position: &BlockPosition) -> bool;
+}
+
+/// Everything known about a block placement before the state is picked
+pub struct PlacementContext<'world> {
+    /// The block that was clicked
+    pub clicked_position: BlockPosition,
+    pub clicked_face: BlockFace,
+    /// Where on the clicked face the cursor was. Each value is between 0 and 1
+    pub cursor: (f32, f32, f32),
+    pub player_yaw: f32,
+    pub player_pitch: f32,
+    pub sneaking: bool,
+    pub world: &'world dyn PlacementWorld,
+}
+impl Debug for PlacementContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlacementContext")
+            .field("clicked_position", &self.clicked_position)
+            .field("clicked_face", &self.clicked_face)
+            .field("cursor", &self.cursor)
+            .field("player_yaw", &self.player_yaw)
+            .field("player_pitch", &self.player_pitch)
+            .field("sneaking", &self.sneaking)
+            .finish()
+    }
+}
+impl PlacementContext<'_> {
+    /// The position the block will be placed at.
+    ///
+    /// If the clicked block can be replaced the block is placed in it
+    pub fn placement_position(&self) -> BlockPosition {
+        if self.world.is_replaceable(&self.clicked_position) {
+            self.clicked_position
+        } else {
+            self.clicked_position.relative(self.clicked_face)
+        }
+    }
+    /// The horizontal direction the player is looking
+    pub fn horizontal_facing(&self) -> BlockFace {
+        BlockFace::from_yaw(self.player_yaw)
+    }
+    /// The direction the player is looking including up and down
+    pub fn looking_direction(&self) -> BlockFace {
+        if self.player_pitch < -45.0 {
+            BlockFace::Up
+        } else if self.player_pitch > 45.0 {
+            BlockFace::Down
+        } else {
+            self.horizontal_facing()
+        }
+    }
+    /// If the block should be placed in the top half. Used by slabs, stairs and trapdoors
+    pub fn is_top_half(&self) -> bool {
+        match self.clicked_face {
+            BlockFace::Down => true,
+            BlockFace::Up => false,
+            _ => self.cursor.1 > 0.5,
+        }
+    }
+    /// If the block has a solid block below it
+    pub fn has_support_below(&self) -> bool {
+        self.world
+            .is_solid(&self.placement_position().relative(BlockFace::Down))
+    }
+}
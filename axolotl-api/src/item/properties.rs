@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+/// The material tier of a tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolTier {
+    #[serde(alias = "WOOD")]
+    Wood,
+    #[serde(alias = "STONE")]
+    Stone,
+    #[serde(alias = "IRON")]
+    Iron,
+    #[serde(alias = "DIAMOND")]
+    Diamond,
+    #[serde(alias = "GOLD")]
+    Gold,
+    #[serde(alias = "NETHERITE")]
+    Netherite,
+}
+impl ToolTier {
+    /// The mining level. A tool can only harvest blocks that need the same level or lower
+    pub fn level(&self) -> u8 {
+        match self {
+            ToolTier::Wood | ToolTier::Gold => 0,
+            ToolTier::Stone => 1,
+            ToolTier::Iron => 2,
+            ToolTier::Diamond => 3,
+            ToolTier::Netherite => 4,
+        }
+    }
+    /// The mining speed against blocks the tool is made for
+    pub fn speed(&self) -> f32 {
+        match self {
+            ToolTier::Wood => 2.0,
+            ToolTier::Stone => 4.0,
+            ToolTier::Iron => 6.0,
+            ToolTier::Diamond => 8.0,
+            ToolTier::Gold => 12.0,
+            ToolTier::Netherite => 9.0,
+        }
+    }
+    /// The durability of tools made from this tier
+    pub fn uses(&self) -> u32 {
+        match self {
+            ToolTier::Wood => 59,
+            ToolTier::Stone => 131,
+            ToolTier::Iron => 250,
+            ToolTier::Diamond => 1561,
+            ToolTier::Gold => 32,
+            ToolTier::Netherite => 2031,
+        }
+    }
+    /// Reads the tier and tool kind from a vanilla item name. Such as `diamond_pickaxe`
+    ///
+    /// Returns the tier and the block tag the tool mines. Swords return no tag
+    pub fn from_item_name(name: &str) -> Option<(Self, Option<&'static str>)> {
+        let (tier, tool) = name.split_once('_')?;
+        let tier = match tier {
+            "wooden" => ToolTier::Wood,
+            "stone" => ToolTier::Stone,
+            "iron" => ToolTier::Iron,
+            "diamond" => ToolTier::Diamond,
+            "golden" => ToolTier::Gold,
+            "netherite" => ToolTier::Netherite,
+            _ => return None,
+        };
+        let tag = match tool {
+            "pickaxe" => Some("mineable/pickaxe"),
+            "axe" => Some("mineable/axe"),
+            "shovel" => Some("mineable/shovel"),
+            "hoe" => Some("mineable/hoe"),
+            "sword" => None,
+            _ => return None,
+        };
+        Some((tier, tag))
+    }
+}
+
+/// Static properties of an item. Loaded from the Minecraft data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemProperties {
+    #[serde(alias = "stack_size", alias = "max_count")]
+    pub max_stack_size: u8,
+    /// 0 if the item can not be damaged
+    #[serde(alias = "max_durability", alias = "durability")]
+    pub max_damage: u32,
+    pub tool_tier: Option<ToolTier>,
+    /// The block tags the item is the correct tool for. Such as `mineable/pickaxe`
+    #[serde(alias = "mineable")]
+    pub block_tags: Vec<String>,
+}
+/// The properties used for items without data
+pub static DEFAULT_ITEM_PROPERTIES: ItemProperties = ItemProperties {
+    max_stack_size: 64,
+    max_damage: 0,
+    tool_tier: None,
+    block_tags: Vec::new(),
+};
+impl Default for ItemProperties {
+    fn default() -> Self {
+        DEFAULT_ITEM_PROPERTIES.clone()
+    }
+}
+impl ItemProperties {
+    pub fn is_damageable(&self) -> bool {
+        self.max_damage > 0
+    }
+    /// Damageable items never stack
+    pub fn is_stackable(&self) -> bool {
+        self.max_stack_size > 1 && !self.is_damageable()
+    }
+    /// If the item is the correct tool for a block with the tag
+    pub fn is_correct_tool(&self, block_tag: &str) -> bool {
+        let block_tag = block_tag.strip_prefix("minecraft:").unwrap_or(block_tag);
+        self.block_tags
+            .iter()
+            .any(|tag| tag.strip_prefix("minecraft:").unwrap_or(tag) == block_tag)
+    }
+    /// The mining speed against a block with the tags
+    pub fn mining_speed<'tag>(&self, block_tags: impl IntoIterator<Item = &'tag str>) -> f32 {
+        match self.tool_tier {
+            Some(tier) if block_tags.into_iter().any(|tag| self.is_correct_tool(tag)) => {
+                tier.speed()
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::item::properties::{ItemProperties, ToolTier};
+
+    #[test]
+    pub fn test_tool_properties() {
+        assert_eq!(
+            ToolTier::from_item_name("diamond_pickaxe"),
+            Some((ToolTier::Diamond, Some("mineable/pickaxe")))
+        );
+        assert_eq!(
+            ToolTier::from_item_name("golden_sword"),
+            Some((ToolTier::Gold, None))
+        );
+        assert_eq!(ToolTier::from_item_name("stone_bricks"), None);
+
+        let pickaxe = ItemProperties {
+            max_stack_size: 1,
+            max_damage: ToolTier::Iron.uses(),
+            tool_tier: Some(ToolTier::Iron),
+            block_tags: vec!["minecraft:mineable/pickaxe".to_string()],
+        };
+        assert!(!pickaxe.is_stackable());
+        assert_eq!(pickaxe.mining_speed(["mineable/pickaxe"]), 6.0);
+        assert_eq!(pickaxe.mining_speed(["mineable/axe"]), 1.0);
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(unused)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 extern crate core;
 
 use std::fmt;
@@ -25,6 +26,7 @@ pub mod math;
 pub mod name;
 pub mod player;
 pub mod server;
+pub mod snbt;
 pub mod world;
 pub mod world_gen;
 
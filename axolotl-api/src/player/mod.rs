@@ -2,13 +2,21 @@ use auto_impl::auto_impl;
 use std::panic::Location;
 use uuid::Uuid;
 
+use crate::player::profile::GameProfile;
 use crate::world::{World, WorldLocation, WorldLocationID};
 
+pub mod offline;
+pub mod profile;
+
 #[auto_impl(Arc, &, Box)]
 pub trait GenericPlayer {
     fn get_uuid(&self) -> &Uuid;
 
     fn get_saved_name(&self) -> &str;
+    /// The account of the player. Used to send the skin to other players
+    fn get_profile(&self) -> Option<&GameProfile> {
+        None
+    }
 }
 #[auto_impl(Arc, &, Box)]
 pub trait Player: GenericPlayer {
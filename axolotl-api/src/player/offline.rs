@@ -0,0 +1,33 @@
+//! Identities of players on servers with online mode disabled
+use md5::{Digest, Md5};
+use uuid::{Builder, Uuid};
+
+/// Vanilla hashes this followed by the name
+pub const OFFLINE_PLAYER_PREFIX: &str = "OfflinePlayer:";
+
+/// The uuid vanilla gives a player when online mode is disabled. A version 3 uuid of `OfflinePlayer:<name>`.
+///
+/// The name is case sensitive
+pub fn offline_uuid(name: &str) -> Uuid {
+    let mut hasher = Md5::new();
+    hasher.update(OFFLINE_PLAYER_PREFIX.as_bytes());
+    hasher.update(name.as_bytes());
+    Builder::from_md5_bytes(hasher.finalize().into()).into_uuid()
+}
+/// Mojang accounts use version 4 uuids. Offline players use version 3
+pub fn is_offline_uuid(id: &Uuid) -> bool {
+    id.get_version_num() == 3
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::player::offline::{is_offline_uuid, offline_uuid};
+
+    #[test]
+    pub fn test_offline_uuid() {
+        let id = offline_uuid("Notch");
+        assert_eq!(id.to_string(), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+        assert!(is_offline_uuid(&id));
+        assert_ne!(offline_uuid("notch"), id);
+    }
+}
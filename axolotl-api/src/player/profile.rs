@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The name of the property holding the skin and cape
+pub const TEXTURES_PROPERTY: &str = "textures";
+
+/// A property of a [GameProfile]. The value is base64 and signed by Mojang
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// A player's account. Matches the format returned by the session server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameProfile {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+impl GameProfile {
+    pub fn new(id: Uuid, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            properties: vec![],
+        }
+    }
+    pub fn get_property(&self, name: &str) -> Option<&ProfileProperty> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+    /// The skin and cape. None for offline players
+    pub fn textures(&self) -> Option<&ProfileProperty> {
+        self.get_property(TEXTURES_PROPERTY)
+    }
+    /// Replaces the property with the same name
+    pub fn set_property(&mut self, property: ProfileProperty) {
+        self.properties.retain(|value| value.name != property.name);
+        self.properties.push(property);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::player::profile::GameProfile;
+
+    #[test]
+    pub fn test_session_server_format() {
+        let profile: GameProfile = serde_json::from_str(
+            r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[{"name":"textures","value":"e30="}]}"#,
+        )
+        .unwrap();
+        assert_eq!(profile.name, "Notch");
+        assert_eq!(profile.textures().unwrap().value, "e30=");
+        assert_eq!(profile.textures().unwrap().signature, None);
+    }
+}
@@ -0,0 +1,604 @@
+//! Stringified NBT. The format used by commands and data packs. Such as `{Count:1b,id:"minecraft:stone"}`
+//!
+//! [from_snbt] follows the vanilla parser. `true` and `false` are bytes and unquoted strings that look like numbers are numbers.
+//! [to_snbt] writes a string [from_snbt] reads back to the same value.
+//!
+//! Vanilla has no way to write NaN or infinite floats. They are written like Java prints them, `NaNf` and `Infinityd`,
+//! and [from_snbt] reads those back as floats where vanilla would read them as strings
+use std::error::Error;
+use std::fmt::{Display, Formatter, Write};
+
+use axolotl_nbt::value::{NameLessValue, Value};
+
+/// How many characters before the error are shown
+const ERROR_CONTEXT: usize = 16;
+/// How deep lists and compounds can be nested. The same as vanilla
+pub const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnbtError {
+    pub message: String,
+    /// The byte offset of the error
+    pub position: usize,
+    /// The input right before the error
+    pub context: String,
+}
+impl Display for SnbtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at position {}: ...{}<--[HERE]",
+            self.message, self.position, self.context
+        )
+    }
+}
+impl Error for SnbtError {}
+
+/// Parses a whole SNBT string. Anything after the value is an error
+pub fn from_snbt(input: &str) -> Result<NameLessValue, SnbtError> {
+    let mut parser = SnbtParser {
+        input,
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position != input.len() {
+        return Err(parser.error("Trailing data"));
+    }
+    Ok(value)
+}
+/// Writes the value as SNBT
+pub fn to_snbt(value: &NameLessValue) -> String {
+    let mut out = String::new();
+    write_tag(&mut out, tag_of(value));
+    out
+}
+
+struct SnbtParser<'a> {
+    input: &'a str,
+    position: usize,
+    /// How many lists and compounds the parser is in
+    depth: usize,
+}
+impl<'a> SnbtParser<'a> {
+    fn error(&self, message: impl Into<String>) -> SnbtError {
+        let start = self.input[..self.position]
+            .char_indices()
+            .rev()
+            .nth(ERROR_CONTEXT - 1)
+            .map_or(0, |(index, _)| index);
+        SnbtError {
+            message: message.into(),
+            position: self.position,
+            context: self.input[start..self.position].to_string(),
+        }
+    }
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+    fn next(&mut self) -> Option<char> {
+        let next = self.peek()?;
+        self.position += next.len_utf8();
+        Some(next)
+    }
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+    fn expect(&mut self, expected: char) -> Result<(), SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(next) if next == expected => {
+                self.next();
+                Ok(())
+            }
+            Some(next) => Err(self.error(format!("Expected '{}' but found '{}'", expected, next))),
+            None => Err(self.error(format!("Expected '{}' but the input ended", expected))),
+        }
+    }
+    /// Consumes the separator. False if the next character ends the list or compound
+    fn separator(&mut self, end: char) -> Result<bool, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.next();
+                Ok(true)
+            }
+            Some(next) if next == end => Ok(false),
+            _ => Err(self.error(format!("Expected ',' or '{}'", end))),
+        }
+    }
+
+    fn value(&mut self) -> Result<NameLessValue, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.nested(Self::compound),
+            Some('[') => self.nested(Self::list_or_array),
+            Some('"' | '\'') => Ok(NameLessValue::String(self.quoted()?)),
+            Some(_) => {
+                let unquoted = self.unquoted();
+                if unquoted.is_empty() {
+                    return Err(self.error("Expected a value"));
+                }
+                Ok(parse_unquoted(unquoted))
+            }
+            None => Err(self.error("Expected a value but the input ended")),
+        }
+    }
+    /// Parses a list or compound. Errors instead of overflowing the stack on deeply nested input
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<NameLessValue, SnbtError>,
+    ) -> Result<NameLessValue, SnbtError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(format!("Tag is nested deeper than {}", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+    fn compound(&mut self) -> Result<NameLessValue, SnbtError> {
+        self.expect('{')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some('}') {
+            self.skip_whitespace();
+            // Quoted keys can be empty
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                _ => {
+                    let key = self.unquoted();
+                    if key.is_empty() {
+                        return Err(self.error("Expected a key"));
+                    }
+                    key.to_string()
+                }
+            };
+            self.expect(':')?;
+            let value = self.value()?;
+            values.push(named(key, value));
+            if !self.separator('}')? {
+                break;
+            }
+            self.skip_whitespace();
+        }
+        self.expect('}')?;
+        Ok(NameLessValue::Compound(values))
+    }
+    fn list_or_array(&mut self) -> Result<NameLessValue, SnbtError> {
+        self.expect('[')?;
+        let rest = &self.input[self.position..];
+        let mut chars = rest.chars();
+        if let (Some(kind @ ('B' | 'I' | 'L')), Some(';')) = (chars.next(), chars.next()) {
+            self.position += 2;
+            return self.array(kind);
+        }
+        let mut values: Vec<NameLessValue> = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let start = self.position;
+            let value = self.value()?;
+            if let Some(first) = values.first() {
+                if type_name(first) != type_name(&value) {
+                    self.position = start;
+                    return Err(self.error(format!(
+                        "Can not insert {} into a list of {}",
+                        type_name(&value),
+                        type_name(first)
+                    )));
+                }
+            }
+            values.push(value);
+            if !self.separator(']')? {
+                break;
+            }
+            self.skip_whitespace();
+        }
+        self.expect(']')?;
+        Ok(NameLessValue::List(values))
+    }
+    fn array(&mut self, kind: char) -> Result<NameLessValue, SnbtError> {
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let start = self.position;
+            let value = self.value()?;
+            let value = match (kind, value) {
+                ('B', NameLessValue::Byte(value)) => value as i64,
+                ('I', NameLessValue::Int(value)) => value as i64,
+                ('L', NameLessValue::Long(value)) => value,
+                (_, value) => {
+                    self.position = start;
+                    return Err(self.error(format!(
+                        "Can not insert {} into a {} array",
+                        type_name(&value),
+                        kind
+                    )));
+                }
+            };
+            values.push(value);
+            if !self.separator(']')? {
+                break;
+            }
+            self.skip_whitespace();
+        }
+        self.expect(']')?;
+        Ok(match kind {
+            'B' => NameLessValue::ByteArray(values.into_iter().map(|value| value as i8).collect()),
+            'I' => NameLessValue::IntArray(values.into_iter().map(|value| value as i32).collect()),
+            _ => NameLessValue::LongArray(values),
+        })
+    }
+    fn quoted(&mut self) -> Result<String, SnbtError> {
+        let quote = self.next().expect("Checked by the caller");
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => match self.next() {
+                    Some(escaped) if escaped == '\\' || escaped == quote => value.push(escaped),
+                    Some(escaped) => {
+                        return Err(self.error(format!("Invalid escape sequence '\\{}'", escaped)))
+                    }
+                    None => return Err(self.error("Unterminated string")),
+                },
+                Some(next) if next == quote => return Ok(value),
+                Some(next) => value.push(next),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+    fn unquoted(&mut self) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.next();
+        }
+        &self.input[start..self.position]
+    }
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+/// Numbers by their suffix. Anything else is a string
+fn parse_unquoted(value: &str) -> NameLessValue {
+    match value {
+        "true" => return NameLessValue::Byte(1),
+        "false" => return NameLessValue::Byte(0),
+        _ => {}
+    }
+    let (number, suffix) = match value.char_indices().last() {
+        Some((index, suffix)) if suffix.is_ascii_alphabetic() => (&value[..index], Some(suffix)),
+        _ => (value, None),
+    };
+    let integer = is_integer(number);
+    let decimal = is_decimal(number) || is_non_finite(number);
+    let parsed = match suffix.map(|suffix| suffix.to_ascii_lowercase()) {
+        Some('b') if integer => number.parse().ok().map(NameLessValue::Byte),
+        Some('s') if integer => number.parse().ok().map(NameLessValue::Short),
+        Some('l') if integer => number.parse().ok().map(NameLessValue::Long),
+        Some('f') if decimal => number.parse().ok().map(NameLessValue::Float),
+        Some('d') if decimal => number.parse().ok().map(NameLessValue::Double),
+        None if integer => number.parse().ok().map(NameLessValue::Int),
+        // Without a suffix doubles need a dot
+        None if is_decimal(number) && number.contains('.') => {
+            number.parse().ok().map(NameLessValue::Double)
+        }
+        _ => None,
+    };
+    // Out of range numbers are strings like vanilla
+    parsed.unwrap_or_else(|| NameLessValue::String(value.to_string()))
+}
+/// `[-+]?(?:0|[1-9][0-9]*)`
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    digits == "0"
+        || (!digits.is_empty()
+            && !digits.starts_with('0')
+            && digits.chars().all(|c| c.is_ascii_digit()))
+}
+/// `[-+]?(?:[0-9]+[.]?|[0-9]*[.][0-9]+)(?:e[-+]?[0-9]+)?`
+fn is_decimal(value: &str) -> bool {
+    let value = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (mantissa, exponent) = match value.find(['e', 'E']) {
+        Some(index) => (&value[..index], Some(&value[index + 1..])),
+        None => (value, None),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    let exponent = exponent.is_none_or(|exponent| {
+        let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        !exponent.is_empty() && digits(exponent)
+    });
+    (!whole.is_empty() || !fraction.is_empty()) && digits(whole) && digits(fraction) && exponent
+}
+
+/// How Java prints NaN and infinity
+fn is_non_finite(value: &str) -> bool {
+    matches!(value, "NaN" | "Infinity" | "-Infinity")
+}
+fn write_non_finite(out: &mut String, value: f64, suffix: char) -> std::fmt::Result {
+    let name = if value.is_nan() {
+        "NaN"
+    } else if value > 0.0 {
+        "Infinity"
+    } else {
+        "-Infinity"
+    };
+    write!(out, "{}{}", name, suffix)
+}
+
+fn type_name(value: &NameLessValue) -> &'static str {
+    match tag_of(value) {
+        Tag::Byte(_) => "Byte",
+        Tag::Short(_) => "Short",
+        Tag::Int(_) => "Int",
+        Tag::Long(_) => "Long",
+        Tag::Float(_) => "Float",
+        Tag::Double(_) => "Double",
+        Tag::String(_) => "String",
+        Tag::List(_) => "List",
+        Tag::Compound(_) => "Compound",
+        Tag::ByteArray(_) => "Byte Array",
+        Tag::IntArray(_) => "Int Array",
+        Tag::LongArray(_) => "Long Array",
+    }
+}
+
+/// A borrowed view of a value without its name
+enum Tag<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(&'a str),
+    List(&'a [NameLessValue]),
+    Compound(&'a [Value]),
+    ByteArray(&'a [i8]),
+    IntArray(&'a [i32]),
+    LongArray(&'a [i64]),
+}
+fn tag_of(value: &NameLessValue) -> Tag {
+    match value {
+        NameLessValue::Boolean(value) => Tag::Byte(*value as i8),
+        NameLessValue::Byte(value) => Tag::Byte(*value),
+        NameLessValue::Short(value) => Tag::Short(*value),
+        NameLessValue::Int(value) => Tag::Int(*value),
+        NameLessValue::Long(value) => Tag::Long(*value),
+        NameLessValue::Float(value) => Tag::Float(*value),
+        NameLessValue::Double(value) => Tag::Double(*value),
+        NameLessValue::String(value) => Tag::String(value),
+        NameLessValue::List(value) => Tag::List(value),
+        NameLessValue::Compound(value) => Tag::Compound(value),
+        NameLessValue::ByteArray(value) => Tag::ByteArray(value),
+        NameLessValue::IntArray(value) => Tag::IntArray(value),
+        NameLessValue::LongArray(value) => Tag::LongArray(value),
+    }
+}
+fn named_tag_of(value: &Value) -> (&str, Tag) {
+    match value {
+        Value::Boolean { name, value } => (name, Tag::Byte(*value as i8)),
+        Value::Byte { name, value } => (name, Tag::Byte(*value)),
+        Value::Short { name, value } => (name, Tag::Short(*value)),
+        Value::Int { name, value } => (name, Tag::Int(*value)),
+        Value::Long { name, value } => (name, Tag::Long(*value)),
+        Value::Float { name, value } => (name, Tag::Float(*value)),
+        Value::Double { name, value } => (name, Tag::Double(*value)),
+        Value::String { name, value } => (name, Tag::String(value)),
+        Value::List { name, value } => (name, Tag::List(value)),
+        Value::Compound { name, value } => (name, Tag::Compound(value)),
+        Value::ByteArray { name, value } => (name, Tag::ByteArray(value)),
+        Value::IntArray { name, value } => (name, Tag::IntArray(value)),
+        Value::LongArray { name, value } => (name, Tag::LongArray(value)),
+    }
+}
+fn named(name: String, value: NameLessValue) -> Value {
+    match value {
+        NameLessValue::Boolean(value) => Value::Boolean { name, value },
+        NameLessValue::Byte(value) => Value::Byte { name, value },
+        NameLessValue::Short(value) => Value::Short { name, value },
+        NameLessValue::Int(value) => Value::Int { name, value },
+        NameLessValue::Long(value) => Value::Long { name, value },
+        NameLessValue::Float(value) => Value::Float { name, value },
+        NameLessValue::Double(value) => Value::Double { name, value },
+        NameLessValue::String(value) => Value::String { name, value },
+        NameLessValue::List(value) => Value::List { name, value },
+        NameLessValue::Compound(value) => Value::Compound { name, value },
+        NameLessValue::ByteArray(value) => Value::ByteArray { name, value },
+        NameLessValue::IntArray(value) => Value::IntArray { name, value },
+        NameLessValue::LongArray(value) => Value::LongArray { name, value },
+    }
+}
+
+fn write_tag(out: &mut String, tag: Tag) {
+    // Writing to a String can not fail
+    let _ = match tag {
+        Tag::Byte(value) => write!(out, "{}b", value),
+        Tag::Short(value) => write!(out, "{}s", value),
+        Tag::Int(value) => write!(out, "{}", value),
+        Tag::Long(value) => write!(out, "{}L", value),
+        Tag::Float(value) if !value.is_finite() => write_non_finite(out, value as f64, 'f'),
+        Tag::Double(value) if !value.is_finite() => write_non_finite(out, value, 'd'),
+        Tag::Float(value) => write!(out, "{:?}f", value),
+        Tag::Double(value) => write!(out, "{:?}d", value),
+        Tag::String(value) => {
+            write_quoted(out, value);
+            Ok(())
+        }
+        Tag::List(values) => {
+            write_sequence(out, None, values, |out, value| {
+                write_tag(out, tag_of(value))
+            });
+            Ok(())
+        }
+        Tag::Compound(values) => {
+            out.push('{');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                let (name, tag) = named_tag_of(value);
+                if !name.is_empty() && name.chars().all(is_unquoted_char) {
+                    out.push_str(name);
+                } else {
+                    write_quoted(out, name);
+                }
+                out.push(':');
+                write_tag(out, tag);
+            }
+            out.push('}');
+            Ok(())
+        }
+        Tag::ByteArray(values) => {
+            write_sequence(out, Some('B'), values, |out, value| {
+                let _ = write!(out, "{}B", value);
+            });
+            Ok(())
+        }
+        Tag::IntArray(values) => {
+            write_sequence(out, Some('I'), values, |out, value| {
+                let _ = write!(out, "{}", value);
+            });
+            Ok(())
+        }
+        Tag::LongArray(values) => {
+            write_sequence(out, Some('L'), values, |out, value| {
+                let _ = write!(out, "{}L", value);
+            });
+            Ok(())
+        }
+    };
+}
+fn write_sequence<T>(
+    out: &mut String,
+    kind: Option<char>,
+    values: &[T],
+    mut write: impl FnMut(&mut String, &T),
+) {
+    out.push('[');
+    if let Some(kind) = kind {
+        out.push(kind);
+        out.push(';');
+    }
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        write(out, value);
+    }
+    out.push(']');
+}
+/// Uses double quotes unless the string contains them and no single quotes
+fn write_quoted(out: &mut String, value: &str) {
+    let quote = if value.contains('"') && !value.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+    out.push(quote);
+    for c in value.chars() {
+        if c == quote || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push(quote);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_nbt::value::{NameLessValue, Value};
+
+    use crate::snbt::{from_snbt, to_snbt, MAX_DEPTH};
+
+    #[test]
+    pub fn test_item() {
+        let value =
+            from_snbt(r#"{Count:1b,id:"minecraft:stone", tag: {Damage: 3, Lore: ['a"b', "c"]}}"#)
+                .unwrap();
+        let NameLessValue::Compound(values) = &value else {
+            panic!("Not a compound");
+        };
+        assert!(matches!(&values[0], Value::Byte { name, value: 1 } if name == "Count"));
+        assert!(
+            matches!(&values[1], Value::String { name, value } if name == "id" && value == "minecraft:stone")
+        );
+        let snbt = to_snbt(&value);
+        assert_eq!(
+            snbt,
+            r#"{Count:1b,id:"minecraft:stone",tag:{Damage:3,Lore:['a"b',"c"]}}"#
+        );
+        assert_eq!(from_snbt(&snbt).unwrap(), value);
+    }
+    #[test]
+    pub fn test_numbers() {
+        for (snbt, expected) in [
+            ("1b", NameLessValue::Byte(1)),
+            ("-2s", NameLessValue::Short(-2)),
+            ("3", NameLessValue::Int(3)),
+            ("4L", NameLessValue::Long(4)),
+            ("0.5f", NameLessValue::Float(0.5)),
+            ("1.5", NameLessValue::Double(1.5)),
+            ("1e3d", NameLessValue::Double(1000.0)),
+            ("true", NameLessValue::Byte(1)),
+            ("300b", NameLessValue::String("300b".to_string())),
+            ("stone", NameLessValue::String("stone".to_string())),
+            ("[I; 1, -2]", NameLessValue::IntArray(vec![1, -2])),
+            ("[L;5L]", NameLessValue::LongArray(vec![5])),
+        ] {
+            let value = from_snbt(snbt).unwrap();
+            assert_eq!(value, expected, "{}", snbt);
+            assert_eq!(from_snbt(&to_snbt(&value)).unwrap(), value);
+        }
+    }
+    #[test]
+    pub fn test_errors() {
+        let error = from_snbt("[1, 2b]").unwrap_err();
+        assert_eq!(error.message, "Can not insert Byte into a list of Int");
+        assert_eq!(error.position, 4);
+        assert!(from_snbt("{a:1").is_err());
+        assert!(from_snbt("{a:1} x").is_err());
+        assert!(from_snbt("\"open").is_err());
+        assert!(from_snbt("[B;1,2]").is_err());
+        assert!(from_snbt("{:1}").is_err());
+
+        let error = from_snbt(&"[".repeat(100_000)).unwrap_err();
+        assert_eq!(error.position, MAX_DEPTH);
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(from_snbt(&nested).is_ok());
+    }
+    #[test]
+    pub fn test_empty_key() {
+        let value = from_snbt(r#"{"":1}"#).unwrap();
+        assert!(
+            matches!(&value, NameLessValue::Compound(values) if matches!(&values[0], Value::Int { name, value: 1 } if name.is_empty()))
+        );
+        assert_eq!(to_snbt(&value), r#"{"":1}"#);
+    }
+    #[test]
+    pub fn test_non_finite() {
+        for value in [
+            NameLessValue::Float(f32::INFINITY),
+            NameLessValue::Float(f32::NEG_INFINITY),
+            NameLessValue::Double(f64::INFINITY),
+            NameLessValue::Double(f64::NEG_INFINITY),
+        ] {
+            assert_eq!(from_snbt(&to_snbt(&value)).unwrap(), value);
+        }
+        assert_eq!(to_snbt(&NameLessValue::Float(f32::NAN)), "NaNf");
+        assert!(
+            matches!(from_snbt("NaNf").unwrap(), NameLessValue::Float(value) if value.is_nan())
+        );
+        assert!(
+            matches!(from_snbt("NaNd").unwrap(), NameLessValue::Double(value) if value.is_nan())
+        );
+        assert_eq!(
+            from_snbt("NaN").unwrap(),
+            NameLessValue::String("NaN".to_string())
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::world::BlockPosition;
+
+/// One of the six faces of a block.
+///
+/// The order matches the ids used by the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockFace {
+    Down = 0,
+    Up = 1,
+    North = 2,
+    South = 3,
+    West = 4,
+    East = 5,
+}
+impl BlockFace {
+    pub const ALL: [BlockFace; 6] = [
+        BlockFace::Down,
+        BlockFace::Up,
+        BlockFace::North,
+        BlockFace::South,
+        BlockFace::West,
+        BlockFace::East,
+    ];
+    pub const HORIZONTAL: [BlockFace; 4] = [
+        BlockFace::North,
+        BlockFace::South,
+        BlockFace::West,
+        BlockFace::East,
+    ];
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.get(usize::try_from(id).ok()?).copied()
+    }
+    pub fn opposite(&self) -> Self {
+        match self {
+            BlockFace::Down => BlockFace::Up,
+            BlockFace::Up => BlockFace::Down,
+            BlockFace::North => BlockFace::South,
+            BlockFace::South => BlockFace::North,
+            BlockFace::West => BlockFace::East,
+            BlockFace::East => BlockFace::West,
+        }
+    }
+    #[inline]
+    pub fn is_horizontal(&self) -> bool {
+        !matches!(self, BlockFace::Down | BlockFace::Up)
+    }
+    /// The unit offset of the face
+    pub fn offset(&self) -> (i64, i16, i64) {
+        match self {
+            BlockFace::Down => (0, -1, 0),
+            BlockFace::Up => (0, 1, 0),
+            BlockFace::North => (0, 0, -1),
+            BlockFace::South => (0, 0, 1),
+            BlockFace::West => (-1, 0, 0),
+            BlockFace::East => (1, 0, 0),
+        }
+    }
+    /// The axis the face is on. `x`, `y` or `z`
+    pub fn axis(&self) -> &'static str {
+        match self {
+            BlockFace::Down | BlockFace::Up => "y",
+            BlockFace::North | BlockFace::South => "z",
+            BlockFace::West | BlockFace::East => "x",
+        }
+    }
+    /// The name used in block states
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockFace::Down => "down",
+            BlockFace::Up => "up",
+            BlockFace::North => "north",
+            BlockFace::South => "south",
+            BlockFace::West => "west",
+            BlockFace::East => "east",
+        }
+    }
+    /// The horizontal direction a yaw in degrees is facing
+    pub fn from_yaw(yaw: f32) -> Self {
+        let index = ((yaw / 90.0).round() as i32).rem_euclid(4);
+        match index {
+            0 => BlockFace::South,
+            1 => BlockFace::West,
+            2 => BlockFace::North,
+            _ => BlockFace::East,
+        }
+    }
+}
+
+impl BlockPosition {
+    /// The position next to this one on the given face
+    pub fn relative(&self, face: BlockFace) -> Self {
+        let (x, y, z) = face.offset();
+        Self {
+            x: self.x + x,
+            y: self.y + y,
+            z: self.z + z,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world::{BlockFace, BlockPosition};
+
+    #[test]
+    pub fn test_yaw() {
+        assert_eq!(BlockFace::from_yaw(0.0), BlockFace::South);
+        assert_eq!(BlockFace::from_yaw(90.0), BlockFace::West);
+        assert_eq!(BlockFace::from_yaw(-180.0), BlockFace::North);
+        assert_eq!(BlockFace::from_yaw(-90.0), BlockFace::East);
+        assert_eq!(BlockFace::from_yaw(359.0), BlockFace::South);
+    }
+    #[test]
+    pub fn test_relative() {
+        let pos = BlockPosition::new(0, 64, 0);
+        assert_eq!(pos.relative(BlockFace::Down), BlockPosition::new(0, 63, 0));
+        assert_eq!(pos.relative(BlockFace::West), BlockPosition::new(-1, 64, 0));
+        assert_eq!(BlockFace::from_id(2), Some(BlockFace::North));
+        assert_eq!(BlockFace::from_id(6), None);
+    }
+}
@@ -6,19 +6,26 @@ use bytemuck::{Pod, Zeroable};
 use serde::de::MapAccess;
 use serde::{Deserialize, Serialize};
 
+pub use face::BlockFace;
 pub use location::GenericLocation;
 pub use location::Location;
 pub use location::WorldLocation;
+pub use scoped::{DimensionId, DimensionMismatch, WorldChunkPos, WorldPos};
 
 use crate::item::block::{Block, BlockState};
 use crate::world_gen::chunk::ChunkPos;
+use crate::world_gen::seed::WorldSeed;
 
+mod face;
 mod location;
+pub mod protection;
+pub mod scoped;
+pub mod view;
 
 pub struct WorldGenerator {
-    pub seed: u64,
+    pub seed: WorldSeed,
 }
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Zeroable, Serialize, Deserialize)]
 pub struct BlockPosition {
     pub x: i64,
     pub y: i16,
@@ -40,24 +47,38 @@ impl BlockPosition {
         self.z *= 16;
     }
     pub fn make_relative_ref(&mut self) {
-        self.x %= 16;
-        self.z %= 16;
+        self.x &= 15;
+        self.z &= 15;
     }
+    /// The position within the 16x16x16 section. Always 0-15, including negative coordinates
     #[inline(always)]
-    pub fn section(&mut self) -> usize {
-        let section_index = ((self.y as usize) / 16);
-        self.y %= 16;
-        section_index
+    pub fn section_coords(&self) -> (u8, u8, u8) {
+        (
+            (self.x & 15) as u8,
+            (self.y & 15) as u8,
+            (self.z & 15) as u8,
+        )
+    }
+    /// The chunk position without making the block relative
+    #[inline(always)]
+    pub fn chunk_pos(&self) -> ChunkPos {
+        ChunkPos::new((self.x >> 4) as i32, (self.z >> 4) as i32)
+    }
+    /// Returns the y of the section this block is in. Negative below y 0
+    /// Makes the y relative to the section
+    #[inline(always)]
+    pub fn section(&mut self) -> i16 {
+        let section = self.y >> 4;
+        self.y &= 15;
+        section
     }
     /// Returns the chunk position of the chunk this block is in
     /// Makes the x.y relative to the chunk
     #[inline(always)]
     pub fn chunk(&mut self) -> ChunkPos {
-        let x = (self.x / 16);
-        let z = (self.z / 16);
-        self.x %= 16;
-        self.z %= 16;
-        ChunkPos::new(x as i32, z as i32)
+        let chunk = self.chunk_pos();
+        self.make_relative_ref();
+        chunk
     }
 }
 impl<L: Location> From<L> for BlockPosition {
@@ -102,6 +123,33 @@ pub trait World: Send + Sync + Hash + Debug + PartialEq {
         chunk_pos: ChunkPos,
         blocks: impl Iterator<Item = (BlockPosition, Self::WorldBlock)>,
     );
+    /// The dimension positions must be tagged with. Defaults to the id of [World::get_name]
+    #[cfg(feature = "scoped-positions")]
+    fn dimension(&self) -> DimensionId {
+        DimensionId::from_name(self.get_name())
+    }
+    /// [World::set_block] for a position that is checked to be in this world
+    #[cfg(feature = "scoped-positions")]
+    fn set_block_at(
+        &self,
+        location: WorldPos,
+        block: Self::WorldBlock,
+        require_loaded: bool,
+    ) -> Result<bool, DimensionMismatch> {
+        let location = location.local_to(self.dimension())?;
+        Ok(self.set_block(location, block, require_loaded))
+    }
+    /// [World::set_blocks] for a chunk that is checked to be in this world
+    #[cfg(feature = "scoped-positions")]
+    fn set_blocks_in(
+        &self,
+        chunk_pos: WorldChunkPos,
+        blocks: impl Iterator<Item = (BlockPosition, Self::WorldBlock)>,
+    ) -> Result<(), DimensionMismatch> {
+        let chunk_pos = chunk_pos.local_to(self.dimension())?;
+        self.set_blocks(chunk_pos, blocks);
+        Ok(())
+    }
 }
 /// A WorldLocationID. This will Deserialize from either a string or a map
 ///
@@ -174,3 +222,47 @@ impl<'de> Deserialize<'de> for WorldLocationID {
         deserializer.deserialize_any(WorldLocationIDVisitor)
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world::BlockPosition;
+    use crate::world_gen::chunk::ChunkPos;
+
+    #[test]
+    pub fn test_negative_chunk() {
+        for x in -1040i64..=1040 {
+            for z in [-1040i64, -17, -16, -15, -1, 0, 15, 16, 1039] {
+                let mut pos = BlockPosition::new(x, -64, z);
+                let chunk = pos.chunk();
+                assert_eq!(
+                    chunk,
+                    ChunkPos::new(x.div_euclid(16) as i32, z.div_euclid(16) as i32)
+                );
+                assert_eq!(pos.x, x.rem_euclid(16));
+                assert_eq!(pos.z, z.rem_euclid(16));
+                // Back to the original block
+                assert_eq!(chunk.x() as i64 * 16 + pos.x, x);
+                assert_eq!(chunk.z() as i64 * 16 + pos.z, z);
+            }
+        }
+        assert_eq!(
+            BlockPosition::new(-1, 0, -16).chunk_pos(),
+            ChunkPos::new(-1, -1)
+        );
+        assert_eq!(
+            BlockPosition::new(-17, 0, 16).chunk_pos(),
+            ChunkPos::new(-2, 1)
+        );
+    }
+    #[test]
+    pub fn test_section_coords() {
+        for x in -64i64..64 {
+            for y in -64i16..64 {
+                let (local_x, local_y, local_z) = BlockPosition::new(x, y, -x).section_coords();
+                assert_eq!(local_x as i64, x.rem_euclid(16));
+                assert_eq!(local_y as i16, y.rem_euclid(16));
+                assert_eq!(local_z as i64, (-x).rem_euclid(16));
+            }
+        }
+    }
+}
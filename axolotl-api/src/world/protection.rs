@@ -0,0 +1,74 @@
+//! Hooks for plugins that protect parts of a world
+use std::fmt::Debug;
+
+use auto_impl::auto_impl;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::world::BlockPosition;
+
+/// The answer of a [ProtectionProvider]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Decision {
+    Allow,
+    Deny,
+    /// The provider has no opinion. The next provider decides
+    #[default]
+    Pass,
+}
+impl Decision {
+    /// Uses `other` if this decision passes
+    pub fn or(self, other: impl FnOnce() -> Decision) -> Decision {
+        match self {
+            Decision::Pass => other(),
+            decision => decision,
+        }
+    }
+    /// Pass counts as allowed
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, Decision::Deny)
+    }
+}
+
+/// What a player is trying to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProtectedAction {
+    PlaceBlock,
+    BreakBlock,
+    OpenContainer,
+}
+
+/// Consulted before a player places or breaks a block or opens a container
+#[auto_impl(Arc, &, Box)]
+pub trait ProtectionProvider: Debug + Send + Sync {
+    fn can_modify(&self, player: &Uuid, position: &BlockPosition) -> Decision;
+    /// Defaults to [ProtectionProvider::can_modify]
+    fn can_perform(
+        &self,
+        player: &Uuid,
+        position: &BlockPosition,
+        action: ProtectedAction,
+    ) -> Decision {
+        self.can_modify(player, position)
+    }
+}
+
+/// Asks each provider in order. The first one that does not pass decides
+impl<P: ProtectionProvider> ProtectionProvider for Vec<P> {
+    fn can_modify(&self, player: &Uuid, position: &BlockPosition) -> Decision {
+        self.iter().fold(Decision::Pass, |decision, provider| {
+            decision.or(|| provider.can_modify(player, position))
+        })
+    }
+
+    fn can_perform(
+        &self,
+        player: &Uuid,
+        position: &BlockPosition,
+        action: ProtectedAction,
+    ) -> Decision {
+        self.iter().fold(Decision::Pass, |decision, provider| {
+            decision.or(|| provider.can_perform(player, position, action))
+        })
+    }
+}
@@ -0,0 +1,166 @@
+//! Positions tagged with the dimension they belong to.
+//!
+//! A [BlockPosition] says nothing about which world it is in. [WorldPos] and [WorldChunkPos] carry a [DimensionId]
+//! so a position from one world can not be used in another without [WorldPos::local_to] checking it.
+//!
+//! The [World](crate::world::World) methods that take these are behind the `scoped-positions` feature
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::world::{BlockPosition, WorldLocationID};
+use crate::world_gen::chunk::ChunkPos;
+
+/// Identifies a dimension. Made from the name of the world so it is the same across restarts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DimensionId(pub u64);
+impl DimensionId {
+    /// FNV-1a of the name
+    pub const fn from_name(name: &str) -> Self {
+        let bytes = name.as_bytes();
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut index = 0;
+        while index < bytes.len() {
+            hash ^= bytes[index] as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+            index += 1;
+        }
+        Self(hash)
+    }
+}
+impl From<&WorldLocationID> for DimensionId {
+    fn from(id: &WorldLocationID) -> Self {
+        Self::from_name(&id.to_string())
+    }
+}
+impl Display for DimensionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// A position was given to a dimension it is not in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    pub expected: DimensionId,
+    pub found: DimensionId,
+}
+impl Display for DimensionMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected a position in dimension {} but it was in {}",
+            self.expected, self.found
+        )
+    }
+}
+impl Error for DimensionMismatch {}
+
+/// A block position within a dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WorldPos {
+    pub dimension: DimensionId,
+    pub position: BlockPosition,
+}
+impl WorldPos {
+    pub fn new(dimension: DimensionId, position: BlockPosition) -> Self {
+        Self {
+            dimension,
+            position,
+        }
+    }
+    /// The position if it is in the dimension
+    pub fn local_to(&self, dimension: DimensionId) -> Result<BlockPosition, DimensionMismatch> {
+        if self.dimension != dimension {
+            return Err(DimensionMismatch {
+                expected: dimension,
+                found: self.dimension,
+            });
+        }
+        Ok(self.position)
+    }
+    pub fn same_dimension(&self, other: &WorldPos) -> bool {
+        self.dimension == other.dimension
+    }
+    pub fn chunk_pos(&self) -> WorldChunkPos {
+        WorldChunkPos::new(self.dimension, self.position.chunk_pos())
+    }
+    /// The position moved by the offset. Stays in the same dimension
+    pub fn offset(&self, x: i64, y: i16, z: i64) -> Self {
+        Self::new(
+            self.dimension,
+            BlockPosition::new(
+                self.position.x + x,
+                self.position.y + y,
+                self.position.z + z,
+            ),
+        )
+    }
+    /// The same coordinates in another dimension. Nether scaling is not applied
+    pub fn with_dimension(&self, dimension: DimensionId) -> Self {
+        Self::new(dimension, self.position)
+    }
+}
+impl From<WorldPos> for BlockPosition {
+    fn from(pos: WorldPos) -> Self {
+        pos.position
+    }
+}
+
+/// A chunk position within a dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorldChunkPos {
+    pub dimension: DimensionId,
+    pub chunk: ChunkPos,
+}
+impl WorldChunkPos {
+    pub fn new(dimension: DimensionId, chunk: ChunkPos) -> Self {
+        Self { dimension, chunk }
+    }
+    /// The chunk if it is in the dimension
+    pub fn local_to(&self, dimension: DimensionId) -> Result<ChunkPos, DimensionMismatch> {
+        if self.dimension != dimension {
+            return Err(DimensionMismatch {
+                expected: dimension,
+                found: self.dimension,
+            });
+        }
+        Ok(self.chunk)
+    }
+}
+
+impl BlockPosition {
+    /// Tags the position with the dimension it is in
+    pub fn in_dimension(self, dimension: DimensionId) -> WorldPos {
+        WorldPos::new(dimension, self)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world::scoped::DimensionId;
+    use crate::world::{BlockPosition, WorldLocationID};
+
+    #[test]
+    pub fn test_local_to() {
+        let overworld = DimensionId::from(&WorldLocationID::new(
+            "vanilla".to_string(),
+            "overworld".to_string(),
+        ));
+        let nether = DimensionId::from_name("vanilla/the_nether");
+        assert_eq!(overworld, DimensionId::from_name("vanilla/overworld"));
+        assert_ne!(overworld, nether);
+
+        let pos = BlockPosition::new(-17, 64, 3).in_dimension(overworld);
+        assert_eq!(
+            pos.local_to(overworld).unwrap(),
+            BlockPosition::new(-17, 64, 3)
+        );
+        let error = pos.with_dimension(nether).local_to(overworld).unwrap_err();
+        assert_eq!(error.found, nether);
+        assert!(pos.chunk_pos().local_to(nether).is_err());
+        assert_eq!(pos.chunk_pos().chunk.x(), -2);
+    }
+}
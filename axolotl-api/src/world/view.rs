@@ -0,0 +1,126 @@
+//! The world as seen by plugins.
+//!
+//! Plugins only get a [WorldView]. Every change made through it fires its event and asks the protection hooks first,
+//! so a plugin can not skip them the way direct access to the chunks could
+use std::fmt::{Display, Formatter};
+
+use uuid::Uuid;
+
+use crate::events::{Event, NoError};
+use crate::world::{BlockPosition, GenericLocation};
+use crate::OwnedNameSpaceKey;
+
+/// Why a [WorldView] refused a change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewError {
+    /// The chunk is not loaded
+    NotLoaded,
+    /// A protection hook denied the change
+    Denied,
+    /// An event handler cancelled the change
+    Cancelled,
+    /// The world does not support this yet
+    Unsupported,
+}
+impl Display for ViewError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViewError::NotLoaded => write!(f, "The chunk is not loaded"),
+            ViewError::Denied => write!(f, "The change was denied by a protection hook"),
+            ViewError::Cancelled => write!(f, "The change was cancelled by an event handler"),
+            ViewError::Unsupported => write!(f, "Not supported by this world"),
+        }
+    }
+}
+impl std::error::Error for ViewError {}
+
+/// A sound played at a location
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sound {
+    pub sound: OwnedNameSpaceKey,
+    pub location: GenericLocation,
+    pub volume: f32,
+    pub pitch: f32,
+}
+impl Sound {
+    pub fn new(sound: OwnedNameSpaceKey, location: GenericLocation) -> Self {
+        Self {
+            sound,
+            location,
+            volume: 1.0,
+            pitch: 1.0,
+        }
+    }
+}
+
+/// Fired before a block is changed through a [WorldView]. Returning false cancels the change
+#[derive(Debug, Clone)]
+pub struct BlockChangeEvent<B> {
+    /// The player the change is made for. None if the plugin made it itself
+    pub actor: Option<Uuid>,
+    pub position: BlockPosition,
+    /// None is air
+    pub old: Option<B>,
+    pub new: B,
+}
+impl<B> Event for BlockChangeEvent<B> {
+    type Error = NoError;
+    type Result = bool;
+
+    fn get_name() -> &'static str {
+        "block_change"
+    }
+}
+
+/// Fired before an entity is spawned through a [WorldView]. Returning false cancels the spawn
+#[derive(Debug, Clone)]
+pub struct EntitySpawnEvent {
+    pub actor: Option<Uuid>,
+    pub entity_type: OwnedNameSpaceKey,
+    pub location: GenericLocation,
+}
+impl Event for EntitySpawnEvent {
+    type Error = NoError;
+    type Result = bool;
+
+    fn get_name() -> &'static str {
+        "entity_spawn"
+    }
+}
+
+/// Work deferred with [WorldView::schedule_task]. Receives the world when it runs
+pub type ViewTask<B, E> = Box<dyn FnOnce(&dyn WorldView<Block = B, Entity = E>) + Send>;
+
+/// The capabilities a plugin has over a world
+pub trait WorldView: Send + Sync {
+    type Block;
+    type Entity;
+
+    fn name(&self) -> &str;
+    /// None if the block is air or the chunk is not loaded
+    fn get_block(&self, position: BlockPosition) -> Option<Self::Block>;
+    /// Changes the block after the protection hooks and the [BlockChangeEvent].
+    ///
+    /// The protection hooks are asked for the actor. Returns the old block. None is air
+    fn set_block(
+        &self,
+        actor: Option<Uuid>,
+        position: BlockPosition,
+        block: Self::Block,
+    ) -> Result<Option<Self::Block>, ViewError>;
+    /// Spawns the entity after the [EntitySpawnEvent]. Returns the id of the new entity
+    fn spawn_entity(
+        &self,
+        actor: Option<Uuid>,
+        entity: Self::Entity,
+        location: GenericLocation,
+    ) -> Result<Uuid, ViewError>;
+    /// Plays the sound to the players near it
+    fn play_sound(&self, sound: Sound) -> Result<(), ViewError>;
+    /// Runs the task after the number of ticks
+    fn schedule_task(
+        &self,
+        delay_ticks: u32,
+        task: ViewTask<Self::Block, Self::Entity>,
+    ) -> Result<(), ViewError>;
+}
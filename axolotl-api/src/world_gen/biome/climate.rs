@@ -0,0 +1,115 @@
+//! The temperature of a biome at a position.
+//!
+//! Follows vanilla. The temperature drops above [TEMPERATURE_DROP_START] and frozen biomes have warmer patches.
+//! The noises are seeded like vanilla's, so snow lines and patches are in the same places
+use serde::{Deserialize, Serialize};
+
+use crate::world_gen::noise::density::perlin::simplex::PerlinSimplexNoise;
+use crate::world_gen::seed::LegacyRandom;
+
+/// Below this temperature it snows instead of rains and water freezes
+pub const SNOW_TEMPERATURE: f32 = 0.15;
+/// Sea level + 17
+pub const TEMPERATURE_DROP_START: i32 = 80;
+/// The temperature of the warm patches in frozen biomes
+pub const FROZEN_PATCH_TEMPERATURE: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureModifier {
+    #[default]
+    None,
+    /// Used by frozen oceans. Some patches are warm enough to not freeze
+    Frozen,
+}
+
+pub fn cold_enough_to_snow(temperature: f32) -> bool {
+    temperature < SNOW_TEMPERATURE
+}
+/// The index into the grass and foliage color maps. The maps are 256x256
+pub fn color_map_index(temperature: f32, downfall: f32) -> usize {
+    let temperature = temperature.clamp(0.0, 1.0);
+    let downfall = downfall.clamp(0.0, 1.0) * temperature;
+    let x = ((1.0 - temperature) * 255.0) as usize;
+    let y = ((1.0 - downfall) * 255.0) as usize;
+    y << 8 | x
+}
+
+/// The noises used for temperatures. The same for every seed like vanilla
+#[derive(Debug, Clone)]
+pub struct ClimateNoise {
+    temperature: PerlinSimplexNoise,
+    frozen: PerlinSimplexNoise,
+    biome_info: PerlinSimplexNoise,
+}
+impl Default for ClimateNoise {
+    fn default() -> Self {
+        Self {
+            temperature: PerlinSimplexNoise::new(&mut LegacyRandom::new(1234), &[0]),
+            frozen: PerlinSimplexNoise::new(&mut LegacyRandom::new(3456), &[-2, -1, 0]),
+            biome_info: PerlinSimplexNoise::new(&mut LegacyRandom::new(2345), &[0]),
+        }
+    }
+}
+impl ClimateNoise {
+    /// The temperature of a biome at the position
+    pub fn temperature_at(
+        &self,
+        temperature: f32,
+        modifier: TemperatureModifier,
+        x: i64,
+        y: i32,
+        z: i64,
+    ) -> f32 {
+        let temperature = self.modify(temperature, modifier, x, z);
+        if y <= TEMPERATURE_DROP_START {
+            return temperature;
+        }
+        let variation = (self
+            .temperature
+            .get((x as f32 / 8.0) as f64, (z as f32 / 8.0) as f64)
+            * 8.0) as f32;
+        temperature - (variation + y as f32 - TEMPERATURE_DROP_START as f32) * 0.05 / 40.0
+    }
+    fn modify(&self, temperature: f32, modifier: TemperatureModifier, x: i64, z: i64) -> f32 {
+        match modifier {
+            TemperatureModifier::None => temperature,
+            TemperatureModifier::Frozen => {
+                let (x, z) = (x as f64, z as f64);
+                let patches = self.frozen.get(x * 0.05, z * 0.05) * 7.0
+                    + self.biome_info.get(x * 0.2, z * 0.2);
+                if patches < 0.3 && self.biome_info.get(x * 0.09, z * 0.09) < 0.8 {
+                    FROZEN_PATCH_TEMPERATURE
+                } else {
+                    temperature
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world_gen::biome::climate::{
+        cold_enough_to_snow, color_map_index, ClimateNoise, TemperatureModifier,
+    };
+
+    #[test]
+    pub fn test_temperature_at() {
+        let climate = ClimateNoise::default();
+        // Plains
+        assert_eq!(
+            climate.temperature_at(0.8, TemperatureModifier::None, 10, 64, -3),
+            0.8
+        );
+        // Windswept hills get snow at the top
+        let low = climate.temperature_at(0.2, TemperatureModifier::None, 10, 90, -3);
+        let high = climate.temperature_at(0.2, TemperatureModifier::None, 10, 250, -3);
+        assert!(high < low);
+        assert!(cold_enough_to_snow(high));
+        assert!(!cold_enough_to_snow(0.2));
+
+        assert_eq!(color_map_index(1.0, 1.0), 0);
+        assert_eq!(color_map_index(0.0, 0.0), 255 << 8 | 255);
+    }
+}
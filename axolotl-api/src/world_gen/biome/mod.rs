@@ -5,9 +5,11 @@ use std::str::FromStr;
 use serde::de::{Error, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize, Serializer};
 
+use crate::world_gen::biome::climate::TemperatureModifier;
 use crate::world_gen::Precipitation;
 use crate::OwnedNameSpaceKey;
 
+pub mod climate;
 pub mod parameter;
 pub mod vanilla;
 
@@ -29,6 +31,33 @@ pub trait Biome: Debug {
 
     fn spawners(&self) -> &Spawners;
     fn temperature(&self) -> f32;
+    fn temperature_modifier(&self) -> TemperatureModifier {
+        TemperatureModifier::None
+    }
+
+    fn sky_color(&self) -> i32 {
+        self.get_effects().sky_color
+    }
+    fn fog_color(&self) -> i32 {
+        self.get_effects().fog_color
+    }
+    fn water_color(&self) -> i32 {
+        self.get_effects().water_color
+    }
+    fn water_fog_color(&self) -> i32 {
+        self.get_effects().water_fog_color
+    }
+    /// None if the biome uses the color map
+    fn grass_color(&self) -> Option<i32> {
+        self.get_effects().grass_color
+    }
+    /// None if the biome uses the color map
+    fn foliage_color(&self) -> Option<i32> {
+        self.get_effects().foliage_color
+    }
+    fn ambient_particle(&self) -> Option<&AmbientParticle> {
+        self.get_effects().particle.as_ref()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -92,14 +121,62 @@ impl<'de> Deserialize<'de> for Air {
     }
 }
 
+/// How a biome looks and sounds on the client. Sent in the biome registry codec
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Effects {
     pub fog_color: i32,
     pub water_color: i32,
     pub water_fog_color: i32,
     pub sky_color: i32,
+    /// None uses the grass color map
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grass_color: Option<i32>,
+    /// None uses the foliage color map
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foliage_color: Option<i32>,
+    #[serde(default, skip_serializing_if = "GrassColorModifier::is_none")]
+    pub grass_color_modifier: GrassColorModifier,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambient_sound: Option<OwnedNameSpaceKey>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additions_sound: Option<AdditionsSound>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub music: Option<Music>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub particle: Option<AmbientParticle>,
     pub mood_sound: MoodSound,
 }
+impl Effects {
+    /// The grass color with the modifier applied. `swamp_noise` is the biome info noise at the position.
+    ///
+    /// `color_map` is the grass color map color at the position. Used if the biome has no [Effects::grass_color]
+    pub fn grass_color_at(&self, color_map: i32, swamp_noise: f64) -> i32 {
+        self.grass_color_modifier
+            .modify(self.grass_color.unwrap_or(color_map), swamp_noise)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrassColorModifier {
+    #[default]
+    None,
+    DarkForest,
+    Swamp,
+}
+impl GrassColorModifier {
+    pub fn is_none(&self) -> bool {
+        matches!(self, GrassColorModifier::None)
+    }
+    /// Matches the vanilla client
+    pub fn modify(&self, color: i32, swamp_noise: f64) -> i32 {
+        match self {
+            GrassColorModifier::None => color,
+            GrassColorModifier::DarkForest => ((color & 0xFEFEFE) + 0x28340A) >> 1,
+            GrassColorModifier::Swamp if swamp_noise < -0.1 => 0x4C763C,
+            GrassColorModifier::Swamp => 0x6A7039,
+        }
+    }
+}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoodSound {
     pub sound: OwnedNameSpaceKey,
@@ -107,6 +184,30 @@ pub struct MoodSound {
     pub offset: f32,
     pub block_search_extent: i32,
 }
+/// A sound played at random while in the biome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdditionsSound {
+    pub sound: OwnedNameSpaceKey,
+    pub tick_chance: f64,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Music {
+    pub sound: OwnedNameSpaceKey,
+    pub min_delay: i32,
+    pub max_delay: i32,
+    pub replace_current_music: bool,
+}
+/// Particles floating around in the biome. Such as the ash in basalt deltas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbientParticle {
+    pub options: ParticleOptions,
+    pub probability: f32,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleOptions {
+    #[serde(rename = "type")]
+    pub particle_type: OwnedNameSpaceKey,
+}
 // TODO Add Deserialization and Serialization
 #[derive(Debug, Clone)]
 pub struct Features {
@@ -147,3 +248,74 @@ pub struct Spawners {
     /// AXOLOTL!
     axolotls: Vec<SpawnerValue>,
 }
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world_gen::biome::{Effects, GrassColorModifier};
+
+    #[test]
+    pub fn test_effects() {
+        let effects: Effects = serde_json::from_str(
+            r#"{
+                "fog_color": 6840176,
+                "foliage_color": 6975545,
+                "grass_color_modifier": "swamp",
+                "mood_sound": {
+                    "block_search_extent": 8,
+                    "offset": 2.0,
+                    "sound": "minecraft:ambient.cave",
+                    "tick_delay": 6000
+                },
+                "particle": {
+                    "options": { "type": "minecraft:white_ash" },
+                    "probability": 0.118093334
+                },
+                "sky_color": 7907327,
+                "water_color": 6388580,
+                "water_fog_color": 2302743
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(effects.grass_color_modifier, GrassColorModifier::Swamp);
+        assert_eq!(effects.foliage_color, Some(6975545));
+        assert_eq!(effects.grass_color, None);
+        assert_eq!(effects.grass_color_at(0x91BD59, -0.5), 0x4C763C);
+        assert_eq!(effects.grass_color_at(0x91BD59, 0.5), 0x6A7039);
+        assert_eq!(
+            effects.particle.unwrap().options.particle_type.to_string(),
+            "minecraft:white_ash"
+        );
+        let json = serde_json::to_value(
+            serde_json::from_str::<Effects>(
+                r#"{"fog_color": 1, "water_color": 2, "water_fog_color": 3, "sky_color": 4,
+                "mood_sound": {"block_search_extent": 8, "offset": 2.0, "sound": "minecraft:ambient.cave", "tick_delay": 6000}}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(json.get("grass_color").is_none());
+        assert!(json.get("grass_color_modifier").is_none());
+    }
+
+    #[test]
+    pub fn test_grass_color() {
+        let mut effects: Effects = serde_json::from_str(
+            r#"{"fog_color": 1, "water_color": 2, "water_fog_color": 3, "sky_color": 4,
+            "grass_color_modifier": "dark_forest",
+            "mood_sound": {"block_search_extent": 8, "offset": 2.0, "sound": "minecraft:ambient.cave", "tick_delay": 6000}}"#,
+        )
+        .unwrap();
+        // The color map is modified when the biome has no color of its own
+        assert_eq!(
+            effects.grass_color_at(0x59AE30, 0.0),
+            GrassColorModifier::DarkForest.modify(0x59AE30, 0.0)
+        );
+        effects.grass_color = Some(0x123456);
+        assert_eq!(
+            effects.grass_color_at(0x59AE30, 0.0),
+            GrassColorModifier::DarkForest.modify(0x123456, 0.0)
+        );
+        effects.grass_color_modifier = GrassColorModifier::None;
+        assert_eq!(effects.grass_color_at(0x59AE30, 0.0), 0x123456);
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::world_gen::biome::climate::TemperatureModifier;
 use crate::world_gen::biome::{Biome, Carvers, Effects, Features, Spawners};
 use crate::world_gen::Precipitation;
 use crate::OwnedNameSpaceKey;
@@ -26,6 +27,8 @@ pub struct DataPackBiome {
     //pub spawn_costs: ,
     pub spawners: Spawners,
     pub temperature: f32,
+    #[serde(default)]
+    pub temperature_modifier: TemperatureModifier,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -74,4 +77,8 @@ impl Biome for DataPackBiome {
     fn temperature(&self) -> f32 {
         self.temperature
     }
+
+    fn temperature_modifier(&self) -> TemperatureModifier {
+        self.temperature_modifier
+    }
 }
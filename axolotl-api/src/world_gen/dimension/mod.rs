@@ -34,11 +34,69 @@ pub struct Dimension {
     #[serde(rename = "ultrawarm")]
     pub ultra_warm: bool,
 }
+impl Dimension {
+    pub fn world_height(&self) -> WorldHeight {
+        WorldHeight::new(self.min_y as i32, self.height as u32)
+    }
+}
+
+/// The vertical bounds of a dimension. Both values are multiples of 16
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct WorldHeight {
+    pub min_y: i32,
+    pub height: u32,
+}
+impl Default for WorldHeight {
+    fn default() -> Self {
+        Self::OVERWORLD
+    }
+}
+impl WorldHeight {
+    /// -64 to 319
+    pub const OVERWORLD: WorldHeight = WorldHeight::new(-64, 384);
+    /// The pre 1.18 height. 0 to 255
+    pub const LEGACY: WorldHeight = WorldHeight::new(0, 256);
+
+    pub const fn new(min_y: i32, height: u32) -> Self {
+        Self { min_y, height }
+    }
+    /// The highest block that can be placed
+    #[inline]
+    pub const fn max_y(&self) -> i32 {
+        self.min_y + self.height as i32 - 1
+    }
+    #[inline]
+    pub const fn contains(&self, y: i32) -> bool {
+        y >= self.min_y && y <= self.max_y()
+    }
+    #[inline]
+    pub const fn section_count(&self) -> usize {
+        (self.height as usize) >> 4
+    }
+    /// The y of the lowest section
+    #[inline]
+    pub const fn min_section(&self) -> i32 {
+        self.min_y >> 4
+    }
+    /// The index of the section containing y. None if y is outside of the world
+    #[inline]
+    pub const fn section_index(&self, y: i32) -> Option<usize> {
+        if !self.contains(y) {
+            return None;
+        }
+        Some(((y >> 4) - self.min_section()) as usize)
+    }
+}
+impl From<&Dimension> for WorldHeight {
+    fn from(value: &Dimension) -> Self {
+        value.world_height()
+    }
+}
 
 #[cfg(test)]
 pub mod tests {
     use crate::world_gen::dimension::light_limit::UniformValue;
-    use crate::world_gen::dimension::MonsterSpawnLightLevel;
+    use crate::world_gen::dimension::{MonsterSpawnLightLevel, WorldHeight};
 
     #[test]
     pub fn test() {
@@ -49,4 +107,21 @@ pub mod tests {
         let json = serde_json::to_string(&light).unwrap();
         println!("{}", json);
     }
+    #[test]
+    pub fn test_world_height() {
+        let height = WorldHeight::OVERWORLD;
+        assert_eq!(height.section_count(), 24);
+        assert_eq!(height.max_y(), 319);
+        assert_eq!(height.section_index(-64), Some(0));
+        assert_eq!(height.section_index(-1), Some(3));
+        assert_eq!(height.section_index(0), Some(4));
+        assert_eq!(height.section_index(319), Some(23));
+        assert_eq!(height.section_index(320), None);
+        assert_eq!(height.section_index(-65), None);
+
+        let legacy = WorldHeight::LEGACY;
+        assert_eq!(legacy.section_count(), 16);
+        assert_eq!(legacy.section_index(255), Some(15));
+        assert_eq!(legacy.section_index(-1), None);
+    }
 }
@@ -4,6 +4,7 @@ use std::fmt::Debug;
 use uuid::Uuid;
 
 use crate::world::World;
+use crate::world_gen::seed::WorldSeed;
 
 pub trait WorldGenerator: Debug {}
 
@@ -12,7 +13,7 @@ pub trait WorldCreator: Default {
     where
         Self: Sized;
     fn set_name(self, name: impl Into<String>) -> Self;
-    fn set_seed(self, seed: i64) -> Self;
+    fn set_seed(self, seed: WorldSeed) -> Self;
     fn generator(self, generator: impl WorldGenerator + 'static) -> Self;
 }
 
@@ -34,11 +35,12 @@ pub trait WorldManager {
 #[cfg(test)]
 pub mod test {
     use crate::world_gen::manager::WorldGenerator;
+    use crate::world_gen::seed::WorldSeed;
 
     #[derive(Debug, Default)]
     pub struct TestWorldCreator {
         name: Option<String>,
-        seed: Option<i64>,
+        seed: Option<WorldSeed>,
         generator: Option<Box<dyn WorldGenerator>>,
     }
 
@@ -57,7 +59,7 @@ pub mod test {
             self.name = Some(name.into());
             self
         }
-        fn set_seed(mut self, seed: i64) -> Self {
+        fn set_seed(mut self, seed: WorldSeed) -> Self {
             self.seed = Some(seed);
             self
         }
@@ -3,5 +3,6 @@ pub mod chunk;
 pub mod dimension;
 pub mod manager;
 pub mod noise;
+pub mod seed;
 
 pub trait Precipitation {}
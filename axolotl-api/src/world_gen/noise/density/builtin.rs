@@ -145,7 +145,11 @@ pub mod one_param {
     {
         type FunctionDefinition = OneParamDefinition;
 
-        fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+        fn new<G, DS: DensityState>(
+            game: &G,
+            state: &DS,
+            def: Self::FunctionDefinition,
+        ) -> Result<Self, BuildDefResult>
         where
             G: Game,
         {
@@ -183,27 +187,27 @@ pub mod one_param {
                 match name.get_key() {
                     "abs" => Ok(OneParamDefinition {
                         fun_type: OneArgBuiltInFunctionType::Abs,
-                        one: arguments.remove("argument").unwrap(),
+                        one: arguments.remove("argument").ok_or("Missing key argument")?,
                     }),
                     "cube" => Ok(OneParamDefinition {
                         fun_type: OneArgBuiltInFunctionType::Cube,
-                        one: arguments.remove("argument").unwrap(),
+                        one: arguments.remove("argument").ok_or("Missing key argument")?,
                     }),
                     "square" => Ok(OneParamDefinition {
                         fun_type: OneArgBuiltInFunctionType::Square,
-                        one: arguments.remove("argument").unwrap(),
+                        one: arguments.remove("argument").ok_or("Missing key argument")?,
                     }),
                     "half_negative" => Ok(OneParamDefinition {
                         fun_type: OneArgBuiltInFunctionType::HalfNegative,
-                        one: arguments.remove("argument").unwrap(),
+                        one: arguments.remove("argument").ok_or("Missing key argument")?,
                     }),
                     "quarter_negative" => Ok(OneParamDefinition {
                         fun_type: OneArgBuiltInFunctionType::QuarterNegative,
-                        one: arguments.remove("argument").unwrap(),
+                        one: arguments.remove("argument").ok_or("Missing key argument")?,
                     }),
                     "squeeze" => Ok(OneParamDefinition {
                         fun_type: OneArgBuiltInFunctionType::Squeeze,
-                        one: arguments.remove("argument").unwrap(),
+                        one: arguments.remove("argument").ok_or("Missing key argument")?,
                     }),
                     _ => Err(BuildDefResult::NotFound(FunctionArgument::Function {
                         name,
@@ -260,7 +264,11 @@ pub mod two_param {
     {
         type FunctionDefinition = TwoParamDefinition;
 
-        fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+        fn new<G, DS: DensityState>(
+            game: &G,
+            state: &DS,
+            def: Self::FunctionDefinition,
+        ) -> Result<Self, BuildDefResult>
         where
             G: Game,
         {
@@ -279,23 +287,39 @@ pub mod two_param {
                 match name.get_key() {
                     "add" => Ok(TwoParamDefinition {
                         fun_type: TwoParamBuiltInFunctionType::Add,
-                        one: arguments.remove("argument1").unwrap(),
-                        two: arguments.remove("argument2").unwrap(),
+                        one: arguments
+                            .remove("argument1")
+                            .ok_or("Missing key argument1")?,
+                        two: arguments
+                            .remove("argument2")
+                            .ok_or("Missing key argument2")?,
                     }),
                     "mul" => Ok(TwoParamDefinition {
-                        fun_type: TwoParamBuiltInFunctionType::Add,
-                        one: arguments.remove("argument1").unwrap(),
-                        two: arguments.remove("argument2").unwrap(),
+                        fun_type: TwoParamBuiltInFunctionType::Mul,
+                        one: arguments
+                            .remove("argument1")
+                            .ok_or("Missing key argument1")?,
+                        two: arguments
+                            .remove("argument2")
+                            .ok_or("Missing key argument2")?,
                     }),
                     "max" => Ok(TwoParamDefinition {
-                        fun_type: TwoParamBuiltInFunctionType::Add,
-                        one: arguments.remove("argument1").unwrap(),
-                        two: arguments.remove("argument2").unwrap(),
+                        fun_type: TwoParamBuiltInFunctionType::Max,
+                        one: arguments
+                            .remove("argument1")
+                            .ok_or("Missing key argument1")?,
+                        two: arguments
+                            .remove("argument2")
+                            .ok_or("Missing key argument2")?,
                     }),
                     "min" => Ok(TwoParamDefinition {
-                        fun_type: TwoParamBuiltInFunctionType::Add,
-                        one: arguments.remove("argument1").unwrap(),
-                        two: arguments.remove("argument2").unwrap(),
+                        fun_type: TwoParamBuiltInFunctionType::Min,
+                        one: arguments
+                            .remove("argument1")
+                            .ok_or("Missing key argument1")?,
+                        two: arguments
+                            .remove("argument2")
+                            .ok_or("Missing key argument2")?,
                     }),
                     _ => Err(BuildDefResult::NotFound(FunctionArgument::Function {
                         name,
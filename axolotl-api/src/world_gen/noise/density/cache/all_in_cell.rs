@@ -21,12 +21,12 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         game: &G,
         state: &'function DS,
         def: Self::FunctionDefinition,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
-        let function = state.build_from_def(game, *def);
-        Self { function }
+        let function = state.build_from_def(game, *def)?;
+        Ok(Self { function })
     }
 
     fn compute(&self, state: &impl DensityContext) -> f64 {
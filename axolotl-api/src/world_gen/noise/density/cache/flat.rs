@@ -1,15 +1,23 @@
+use std::cell::Cell;
+
 use crate::game::Game;
+use crate::world_gen::chunk::into_condensed_location_i32;
 use crate::world_gen::noise::density::loading::{DensityLoader, FunctionArgument};
 use crate::world_gen::noise::density::perlin::Perlin;
 use crate::world_gen::noise::density::{
     BuildDefResult, DensityContext, DensityFunction, DensityState, Function,
 };
+use crate::world_gen::noise::router::BlockContext;
 use crate::world_gen::noise::Noise;
 use crate::NamespacedKey;
 
 #[derive(Debug, Clone)]
 pub struct FlatCache<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> {
     pub function: Function<'function, P>,
+    /// The last quart column computed and its value. Kept together so one column's value is never returned for another.
+    ///
+    /// Not shared between clones. [crate::world_gen::noise::router::NoiseChunk::interpolate_function] clones the function for every chunk
+    pub cache: Cell<Option<(u64, f64)>>,
 }
 
 impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'function, P>
@@ -21,16 +29,30 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         game: &G,
         state: &'function DS,
         def: Self::FunctionDefinition,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
-        let function = state.build_from_def(game, *def);
-        FlatCache { function }
+        let function = state.build_from_def(game, *def)?;
+        Ok(FlatCache {
+            function,
+            cache: Cell::new(None),
+        })
     }
 
+    /// Computed at y 0 on the corner of the 4x4 column. See [crate::world_gen::noise::router::NoiseChunk::flat]
     fn compute(&self, state: &impl DensityContext) -> f64 {
-        todo!()
+        let x = state.get_x() & !3;
+        let z = state.get_z() & !3;
+        let i = into_condensed_location_i32(x, z);
+        match self.cache.get() {
+            Some((column, value)) if column == i => value,
+            _ => {
+                let value = self.function.compute(&BlockContext { x, y: 0, z });
+                self.cache.set(Some((i, value)));
+                value
+            }
+        }
     }
     fn build_definition(
         value: FunctionArgument,
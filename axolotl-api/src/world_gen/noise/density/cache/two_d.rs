@@ -28,16 +28,16 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         game: &G,
         state: &'function DS,
         def: Self::FunctionDefinition,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
-        let function = state.build_from_def(game, *def);
-        Self {
+        let function = state.build_from_def(game, *def)?;
+        Ok(Self {
             function,
             cache: Arc::new(AtomicF64::new(0.0)),
             last_value: Arc::new(Default::default()),
-        }
+        })
     }
 
     fn compute(&self, state: &impl DensityContext) -> f64 {
@@ -22,7 +22,11 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
 {
     type FunctionDefinition = (f64, f64, Box<FunctionArgument>);
 
-    fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+    fn new<G, DS: DensityState>(
+        game: &G,
+        state: &DS,
+        def: Self::FunctionDefinition,
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
@@ -77,7 +81,11 @@ pub struct YClampedGradient {
 impl<P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'_, P> for YClampedGradient {
     type FunctionDefinition = (f64, f64, f64, f64);
 
-    fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+    fn new<G, DS: DensityState>(
+        game: &G,
+        state: &DS,
+        def: Self::FunctionDefinition,
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
@@ -26,17 +26,17 @@ macro_rules! define_group {
                 game: &G,
                 state: &'function DS,
                 def: Self::FunctionDefinition,
-            ) -> Self
+            ) -> Result<Self, BuildDefResult>
             where
                 G: Game,
             {
-                match def {
+                Ok(match def {
                     $(
                         $defs::$ty_name(def) => {
-                            $name::$ty_name($tp::<P>::new(game, state, def))
+                            $name::$ty_name($tp::<P>::new(game, state, def)?)
                         }
                     ),*
-                }
+                })
             }
             #[inline(always)]
             fn max(&self) -> f64 {
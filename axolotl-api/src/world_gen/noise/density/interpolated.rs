@@ -2,7 +2,9 @@ use std::marker::PhantomData;
 
 use crate::game::Game;
 use crate::world_gen::noise::density::perlin::Perlin;
-use crate::world_gen::noise::density::{DensityContext, DensityFunction, DensityState};
+use crate::world_gen::noise::density::{
+    BuildDefResult, DensityContext, DensityFunction, DensityState,
+};
 use crate::world_gen::noise::Noise;
 
 ///https://minecraft.fandom.com/wiki/Density_function#interpolated
@@ -16,7 +18,11 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
 {
     type FunctionDefinition = ();
 
-    fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+    fn new<G, DS: DensityState>(
+        game: &G,
+        state: &DS,
+        def: Self::FunctionDefinition,
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
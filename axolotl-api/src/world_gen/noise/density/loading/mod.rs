@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 use std::fmt;
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-use serde::de::{Error, MapAccess, Visitor};
+use serde::de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 #[cfg(feature = "tabled")]
 use tabled::Tabled;
@@ -11,7 +11,7 @@ use tabled::Tabled;
 use crate::game::Game;
 use crate::world_gen::noise::density::perlin::Perlin;
 use crate::world_gen::noise::density::spline::Spline;
-use crate::world_gen::noise::density::Function;
+use crate::world_gen::noise::density::{BuildDefResult, Function};
 use crate::world_gen::noise::{BiomeSource, NameSpaceKeyOrType, Noise};
 use crate::{NamespacedKey, OwnedNameSpaceKey};
 
@@ -23,8 +23,9 @@ macro_rules! get_constant {
             .as_ref()
         {
             FunctionArgument::ConstantFloat(v) => *v,
+            FunctionArgument::ConstantInt(v) => *v as f64,
             _ => {
-                return Err("Max argument must be a constant float".into());
+                return Err(concat!("Argument ", $name, " must be a constant number").into());
             }
         }
     };
@@ -42,8 +43,9 @@ macro_rules! get_noise {
                 {
                     value.clone()
                 } else {
-                    // TODO error
-                    panic!("Noise {} not found", key);
+                    return Err(
+                        $crate::world_gen::noise::density::BuildDefResult::UnknownNoise(key),
+                    );
                 }
             }
             NameSpaceKeyOrType::Type(v) => v,
@@ -59,13 +61,13 @@ pub trait DensityLoader {
         &self,
         game: &G,
         def: FunctionArgument,
-    ) -> Function<P>;
+    ) -> Result<Function<P>, BuildDefResult>;
 
     fn build_from_def_with_cache<G: Game, P: Perlin<Noise = Noise, Seed = [u8; 16]>>(
         &self,
         game: &G,
         def: NameSpaceKeyOrType<FunctionArgument>,
-    ) -> Function<P>;
+    ) -> Result<Function<P>, BuildDefResult>;
 }
 
 #[cfg_attr(feature = "tabled", derive(Tabled))]
@@ -92,32 +94,119 @@ impl FunctionArgument {
     }
 }
 
-struct FunctionArgumentVisitor;
+/// Every density function type in vanilla. Types in the `minecraft` namespace must be one of these
+pub const VANILLA_DENSITY_TYPES: &[&str] = &[
+    "abs",
+    "add",
+    "beardifier",
+    "blend_alpha",
+    "blend_density",
+    "blend_offset",
+    "cache_2d",
+    "cache_all_in_cell",
+    "cache_once",
+    "clamp",
+    "constant",
+    "cube",
+    "end_islands",
+    "flat_cache",
+    "half_negative",
+    "interpolated",
+    "max",
+    "min",
+    "mul",
+    "noise",
+    "old_blended_noise",
+    "quarter_negative",
+    "range_choice",
+    "shift",
+    "shift_a",
+    "shift_b",
+    "shifted_noise",
+    "slide",
+    "spline",
+    "square",
+    "squeeze",
+    "terrain_shaper_spline",
+    "weird_scaled_sampler",
+    "y_clamped_gradient",
+];
 
-impl<'de> Visitor<'de> for FunctionArgumentVisitor {
-    type Value = FunctionArgument;
+/// The location of a value inside a JSON document. Displayed as `a.b.c`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonPath(Vec<String>);
+impl JsonPath {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self(vec![root.into()])
+    }
+    pub fn join(&self, key: impl Into<String>) -> Self {
+        let mut path = self.0.clone();
+        path.push(key.into());
+        Self(path)
+    }
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("<root>");
+        }
+        f.write_str(&self.0.join("."))
+    }
+}
 
-    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-        formatter.write_str("map, string, or number")
+/// Deserializes a [FunctionArgument] and reports the [JsonPath] of the value that failed.
+///
+/// ```ignore
+/// let seed = FunctionArgumentSeed::new(JsonPath::new("noise_router").join("final_density"));
+/// let argument = seed.deserialize(&mut serde_json::Deserializer::from_str(json))?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FunctionArgumentSeed {
+    pub path: JsonPath,
+}
+impl FunctionArgumentSeed {
+    pub fn new(path: JsonPath) -> Self {
+        Self { path }
     }
+}
+impl<'de> DeserializeSeed<'de> for FunctionArgumentSeed {
+    type Value = FunctionArgument;
 
-    fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
-        E: Error,
+        D: Deserializer<'de>,
     {
-        Ok(FunctionArgument::ConstantInt(v as i64))
+        deserializer.deserialize_any(FunctionArgumentVisitor { path: self.path })
     }
-    fn visit_i16<E>(self, v: i16) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(FunctionArgument::ConstantInt(v as i64))
+}
+
+struct FunctionArgumentVisitor {
+    path: JsonPath,
+}
+impl FunctionArgumentVisitor {
+    fn invalid<T, E: Error>(&self, found: &str) -> Result<T, E> {
+        Err(E::custom(format_args!(
+            "expected a density function, number or string, found {} at {}",
+            found, self.path
+        )))
+    }
+}
+
+impl<'de> Visitor<'de> for FunctionArgumentVisitor {
+    type Value = FunctionArgument;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "map, string, or number at {}", self.path)
     }
-    fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
+
+    fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E>
     where
         E: Error,
     {
-        Ok(FunctionArgument::ConstantInt(v as i64))
+        self.invalid("a boolean")
     }
     fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
     where
@@ -125,35 +214,14 @@ impl<'de> Visitor<'de> for FunctionArgumentVisitor {
     {
         Ok(FunctionArgument::ConstantInt(v))
     }
-    fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(FunctionArgument::ConstantInt(v as i64))
-    }
-    fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(FunctionArgument::ConstantInt(v as i64))
-    }
-    fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(FunctionArgument::ConstantInt(v as i64))
-    }
     fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
     where
         E: Error,
     {
-        Ok(FunctionArgument::ConstantInt(v as i64))
-    }
-    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(FunctionArgument::ConstantFloat(v as f64))
+        match i64::try_from(v) {
+            Ok(v) => Ok(FunctionArgument::ConstantInt(v)),
+            Err(_) => Ok(FunctionArgument::ConstantFloat(v as f64)),
+        }
     }
     fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
     where
@@ -182,35 +250,81 @@ impl<'de> Visitor<'de> for FunctionArgumentVisitor {
             Ok(FunctionArgument::String(v))
         }
     }
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.invalid("null")
+    }
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.invalid("null")
+    }
+    fn visit_seq<A>(self, _: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.invalid("a list")
+    }
 
     fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
     {
-        let key = map.next_key::<String>()?;
-        if let Some(key) = key.as_ref() {
+        let mut name = None;
+        let mut arguments = HashMap::with_capacity(map.size_hint().unwrap_or(0).min(16));
+        while let Some(key) = map.next_key::<String>()? {
+            let path = self.path.join(key.as_str());
+            let with_path = |error: A::Error| A::Error::custom(format_args!("{error} at {path}"));
             if key.eq("type") {
-                let value = map.next_value::<OwnedNameSpaceKey>()?;
-                let mut arguments = HashMap::with_capacity(map.size_hint().unwrap_or(0));
-                while let Some(key) = map.next_key::<String>()? {
-                    if key.eq("spline") {
-                        let spline = map.next_value::<Spline>()?;
-                        arguments.insert(key, Box::new(FunctionArgument::Spline(Box::new(spline))));
-                    } else if key.eq("noise") {
-                        let noise = map.next_value::<NameSpaceKeyOrType<Noise>>()?;
-                        arguments.insert(key, Box::new(FunctionArgument::Noise(noise)));
-                    } else {
-                        arguments.insert(key, Box::new(map.next_value::<FunctionArgument>()?));
-                    }
+                let value = map.next_value::<String>().map_err(with_path)?;
+                let value = OwnedNameSpaceKey::from_str(&value).map_err(|_| {
+                    A::Error::custom(format_args!(
+                        "invalid density type `{value}` at {}",
+                        self.path
+                    ))
+                })?;
+                if value.get_namespace() == "minecraft"
+                    && !VANILLA_DENSITY_TYPES.contains(&value.get_key())
+                {
+                    return Err(A::Error::custom(format_args!(
+                        "unknown density type `{}:{}` at {}",
+                        value.get_namespace(),
+                        value.get_key(),
+                        self.path
+                    )));
                 }
-                return Ok(FunctionArgument::Function {
-                    name: value,
-                    arguments,
-                });
+                name = Some(value);
+                continue;
+            }
+            let argument = if key.eq("spline") {
+                let spline = map.next_value::<Spline>().map_err(with_path)?;
+                FunctionArgument::Spline(Box::new(spline))
+            } else if key.eq("noise") {
+                let noise = map
+                    .next_value::<NameSpaceKeyOrType<Noise>>()
+                    .map_err(with_path)?;
+                FunctionArgument::Noise(noise)
+            } else {
+                // Errors inside the argument already contain their path
+                map.next_value_seed(FunctionArgumentSeed::new(path))?
+            };
+            if arguments.insert(key, Box::new(argument)).is_some() {
+                return Err(A::Error::custom(format_args!(
+                    "duplicate key at {}",
+                    self.path
+                )));
             }
         }
-
-        Err(serde::de::Error::custom("Invalid function argument"))
+        let Some(name) = name else {
+            return Err(A::Error::custom(format_args!(
+                "missing key `type` at {}",
+                self.path
+            )));
+        };
+        Ok(FunctionArgument::Function { name, arguments })
     }
 }
 
@@ -219,7 +333,16 @@ impl<'de> Deserialize<'de> for FunctionArgument {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(FunctionArgumentVisitor)
+        FunctionArgumentSeed::default().deserialize(deserializer)
+    }
+}
+impl FunctionArgument {
+    /// Parses a density function from JSON. Errors contain the path below `root`
+    pub fn from_json(json: &str, root: JsonPath) -> Result<Self, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let value = FunctionArgumentSeed::new(root).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
     }
 }
 
@@ -228,7 +351,7 @@ pub mod tests {
     use std::fs::{read_dir, read_to_string};
     use std::path::PathBuf;
 
-    use crate::world_gen::noise::density::loading::FunctionArgument;
+    use crate::world_gen::noise::density::loading::{FunctionArgument, JsonPath};
 
     #[test]
     pub fn test() {
@@ -239,6 +362,47 @@ pub mod tests {
         read_folder(buf);
     }
 
+    #[test]
+    pub fn test_error_path() {
+        let root = JsonPath::new("dimensions")
+            .join("overworld")
+            .join("noise_router")
+            .join("final_density");
+        let json = r#"{"type": "minecraft:add", "argument1": 1.0, "argument2": {"type": "minecraft:nois"}}"#;
+        let error = FunctionArgument::from_json(json, root.clone())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with("unknown density type `minecraft:nois` at dimensions.overworld.noise_router.final_density.argument2"),
+            "{error}"
+        );
+
+        let error = FunctionArgument::from_json(r#"{"argument": 1}"#, root)
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("missing key `type`"), "{error}");
+    }
+    #[test]
+    pub fn test_malformed() {
+        for json in [
+            "",
+            "[]",
+            "null",
+            "true",
+            "{",
+            r#"{"type": 5}"#,
+            r#"{"type": "minecraft:abs", "argument": [1, 2]}"#,
+            r#"{"type": "minecraft:abs", "argument": {"type": "minecraft:abs", "argument": null}}"#,
+            r#"{"type": "minecraft:spline", "spline": 7}"#,
+            r#"{"type": "minecraft:noise", "noise": [true]}"#,
+        ] {
+            assert!(
+                FunctionArgument::from_json(json, JsonPath::default()).is_err(),
+                "{json}"
+            );
+        }
+    }
+
     pub fn read_folder(path: PathBuf) {
         for entry in read_dir(path).unwrap() {
             let entry = entry.unwrap();
@@ -13,6 +13,7 @@ use crate::world_gen::noise::density::perlin::Perlin;
 use crate::world_gen::noise::density::shift::NoiseFunctions;
 use crate::world_gen::noise::density::spline::SplineFunction;
 use crate::world_gen::noise::{NameSpaceKeyOrType, Noise};
+use crate::OwnedNameSpaceKey;
 
 pub mod builtin;
 pub mod cache;
@@ -28,6 +29,8 @@ pub enum BuildDefResult {
     InvalidFormat,
     DescriptiveError(&'static str),
     NotFound(FunctionArgument),
+    /// The noise is not in the noise registry
+    UnknownNoise(OwnedNameSpaceKey),
 }
 
 impl From<&'static str> for BuildDefResult {
@@ -57,13 +60,13 @@ pub trait DensityState {
         &self,
         game: &G,
         def: FunctionArgument,
-    ) -> Function<P>;
+    ) -> Result<Function<P>, BuildDefResult>;
 
     fn build_from_def_with_cache<G: Game, P: Perlin<Noise = Noise, Seed = [u8; 16]>>(
         &self,
         game: &G,
         def: NameSpaceKeyOrType<FunctionArgument>,
-    ) -> Function<P>;
+    ) -> Result<Function<P>, BuildDefResult>;
 }
 
 /// The DensityFunction is a generic trait for all density functions.
@@ -74,12 +77,12 @@ pub trait DensityFunction<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>>:
     Debug + Clone
 {
     type FunctionDefinition;
-
+    /// Errors if the definition references a noise that is not registered
     fn new<G, DS: DensityState<Perlin = P>>(
         game: &G,
         state: &'function DS,
         def: Self::FunctionDefinition,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game;
     fn compute(&self, state: &impl DensityContext) -> f64;
@@ -107,8 +110,12 @@ pub struct Constant(f64);
 impl<P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'_, P> for Constant {
     type FunctionDefinition = f64;
 
-    fn new<G, DS: DensityState>(_: &G, _: &DS, def: Self::FunctionDefinition) -> Self {
-        Self(def)
+    fn new<G, DS: DensityState>(
+        _: &G,
+        _: &DS,
+        def: Self::FunctionDefinition,
+    ) -> Result<Self, BuildDefResult> {
+        Ok(Self(def))
     }
 
     fn compute(&self, state: &impl DensityContext) -> f64 {
@@ -140,11 +147,16 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
 {
     type FunctionDefinition = ();
 
-    fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+    /// Never succeeds. Use [DensityState::build_from_def]
+    fn new<G, DS: DensityState>(
+        game: &G,
+        state: &DS,
+        def: Self::FunctionDefinition,
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
-        panic!("This should never be called")
+        Err("A Function is built by DensityState::build_from_def".into())
     }
 
     #[inline]
@@ -0,0 +1,258 @@
+//! Vanilla's `ImprovedNoise` with batch sampling.
+//!
+//! The `simd` feature computes the gradients and interpolation of 4 or 8 samples at once with `std::simd`. It needs a nightly compiler.
+//! Without it the batch functions sample one point at a time. Both give the same values
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::world_gen::noise::density::perlin::Perlin;
+
+const GRADIENT: [[f64; 3]; 16] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+    [1.0, 1.0, 0.0],
+    [0.0, -1.0, 1.0],
+    [-1.0, 1.0, 0.0],
+    [0.0, -1.0, -1.0],
+];
+/// The offsets of the cell corners. Ordered x, then y, then z
+const CORNERS: [[f64; 3]; 8] = [
+    [0.0, 0.0, 0.0],
+    [1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [1.0, 1.0, 0.0],
+    [0.0, 0.0, 1.0],
+    [1.0, 0.0, 1.0],
+    [0.0, 1.0, 1.0],
+    [1.0, 1.0, 1.0],
+];
+
+#[inline]
+fn smoothstep(value: f64) -> f64 {
+    value * value * value * (value * (value * 6.0 - 15.0) + 10.0)
+}
+#[inline]
+fn lerp(delta: f64, start: f64, end: f64) -> f64 {
+    start + delta * (end - start)
+}
+
+/// A single octave of Perlin noise
+#[derive(Debug, Clone)]
+pub struct ImprovedNoise {
+    permutation: [u8; 256],
+    pub xo: f64,
+    pub yo: f64,
+    pub zo: f64,
+}
+impl ImprovedNoise {
+    /// Draws the offsets and shuffles the permutation in vanilla's order.
+    ///
+    /// The values only match vanilla's if `random` does. `rand`'s `gen` and `gen_range` are not Java's `nextDouble` and `nextInt`
+    pub fn new(random: &mut impl Rng) -> Self {
+        let xo = random.gen::<f64>() * 256.0;
+        let yo = random.gen::<f64>() * 256.0;
+        let zo = random.gen::<f64>() * 256.0;
+        let mut permutation = [0u8; 256];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        for i in 0..256 {
+            let j = random.gen_range(0..256 - i);
+            permutation.swap(i, i + j);
+        }
+        Self {
+            permutation,
+            xo,
+            yo,
+            zo,
+        }
+    }
+    #[inline]
+    fn p(&self, index: i32) -> i32 {
+        self.permutation[(index & 255) as usize] as i32
+    }
+    /// The position within the cell and the gradient of each corner
+    #[inline]
+    fn prepare(&self, x: f64, y: f64, z: f64) -> ([f64; 3], [usize; 8]) {
+        let (x, y, z) = (x + self.xo, y + self.yo, z + self.zo);
+        let (grid_x, grid_y, grid_z) = (x.floor(), y.floor(), z.floor());
+        let delta = [x - grid_x, y - grid_y, z - grid_z];
+        let (grid_x, grid_y, grid_z) = (grid_x as i32, grid_y as i32, grid_z as i32);
+
+        let i = self.p(grid_x);
+        let j = self.p(grid_x + 1);
+        let k = self.p(i + grid_y);
+        let l = self.p(i + grid_y + 1);
+        let m = self.p(j + grid_y);
+        let n = self.p(j + grid_y + 1);
+        let hashes = [
+            self.p(k + grid_z),
+            self.p(m + grid_z),
+            self.p(l + grid_z),
+            self.p(n + grid_z),
+            self.p(k + grid_z + 1),
+            self.p(m + grid_z + 1),
+            self.p(l + grid_z + 1),
+            self.p(n + grid_z + 1),
+        ]
+        .map(|hash| (hash & 15) as usize);
+        (delta, hashes)
+    }
+    pub fn noise(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (delta, gradients) = self.prepare(x, y, z);
+        let mut dots = [0f64; 8];
+        for (corner, offset) in CORNERS.iter().enumerate() {
+            let gradient = GRADIENT[gradients[corner]];
+            let mut dot = 0.0;
+            for axis in 0..3 {
+                dot += gradient[axis] * (delta[axis] - offset[axis]);
+            }
+            dots[corner] = dot;
+        }
+        let [s, t, u] = delta.map(smoothstep);
+        let near = lerp(t, lerp(s, dots[0], dots[1]), lerp(s, dots[2], dots[3]));
+        let far = lerp(t, lerp(s, dots[4], dots[5]), lerp(s, dots[6], dots[7]));
+        lerp(u, near, far)
+    }
+    /// Samples every point. `out` must be at least as long as `points`
+    pub fn noise_batch(&self, points: &[[f64; 3]], out: &mut [f64]) {
+        let mut points_chunks = points.chunks_exact(8);
+        let mut out_chunks = out.chunks_exact_mut(8);
+        for (points, out) in (&mut points_chunks).zip(&mut out_chunks) {
+            out.copy_from_slice(&self.noise8(points.try_into().unwrap()));
+        }
+        let offset = points.len() - points_chunks.remainder().len();
+        for (point, out) in points[offset..].iter().zip(&mut out[offset..]) {
+            *out = self.noise(point[0], point[1], point[2]);
+        }
+    }
+}
+
+/// A single octave without settings. Seeded with a [StdRng]
+impl Perlin for ImprovedNoise {
+    type Seed = u64;
+    type Noise = ();
+
+    fn new(random: Self::Seed, _noise: Self::Noise) -> Self {
+        ImprovedNoise::new(&mut StdRng::seed_from_u64(random))
+    }
+
+    fn get_setting(&self) -> &Self::Noise {
+        &()
+    }
+
+    fn get(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise(x, y, z)
+    }
+
+    fn get_batch(&self, points: &[[f64; 3]], out: &mut [f64]) {
+        self.noise_batch(points, out);
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl ImprovedNoise {
+    pub fn noise4(&self, points: &[[f64; 3]; 4]) -> [f64; 4] {
+        points.map(|point| self.noise(point[0], point[1], point[2]))
+    }
+    pub fn noise8(&self, points: &[[f64; 3]; 8]) -> [f64; 8] {
+        points.map(|point| self.noise(point[0], point[1], point[2]))
+    }
+}
+
+#[cfg(feature = "simd")]
+macro_rules! simd_noise {
+    ($name:ident, $lanes:literal, $simd:ident) => {
+        pub fn $name(&self, points: &[[f64; 3]; $lanes]) -> [f64; $lanes] {
+            use std::simd::$simd;
+            // Hashing is a table lookup per lane
+            let mut delta = [[0f64; $lanes]; 3];
+            let mut gradients = [[[0f64; $lanes]; 3]; 8];
+            for (lane, point) in points.iter().enumerate() {
+                let (lane_delta, hashes) = self.prepare(point[0], point[1], point[2]);
+                for axis in 0..3 {
+                    delta[axis][lane] = lane_delta[axis];
+                }
+                for (corner, hash) in hashes.into_iter().enumerate() {
+                    for axis in 0..3 {
+                        gradients[corner][axis][lane] = GRADIENT[hash][axis];
+                    }
+                }
+            }
+            let delta = delta.map($simd::from_array);
+            let mut dots = [$simd::splat(0.0); 8];
+            for (corner, offset) in CORNERS.iter().enumerate() {
+                let mut dot = $simd::splat(0.0);
+                for axis in 0..3 {
+                    dot += $simd::from_array(gradients[corner][axis])
+                        * (delta[axis] - $simd::splat(offset[axis]));
+                }
+                dots[corner] = dot;
+            }
+            let [s, t, u] = delta.map(|value| {
+                value
+                    * value
+                    * value
+                    * (value * (value * $simd::splat(6.0) - $simd::splat(15.0))
+                        + $simd::splat(10.0))
+            });
+            let lerp = |delta: $simd, start: $simd, end: $simd| start + delta * (end - start);
+            let near = lerp(t, lerp(s, dots[0], dots[1]), lerp(s, dots[2], dots[3]));
+            let far = lerp(t, lerp(s, dots[4], dots[5]), lerp(s, dots[6], dots[7]));
+            lerp(u, near, far).to_array()
+        }
+    };
+}
+#[cfg(feature = "simd")]
+impl ImprovedNoise {
+    simd_noise!(noise4, 4, f64x4);
+    simd_noise!(noise8, 8, f64x8);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::world_gen::noise::density::perlin::improved::ImprovedNoise;
+    use crate::world_gen::noise::density::perlin::Perlin;
+
+    #[test]
+    pub fn test_batch_matches_scalar() {
+        let noise = ImprovedNoise::new(&mut StdRng::seed_from_u64(42));
+        let points: Vec<[f64; 3]> = (0..21)
+            .map(|i| [i as f64 * 0.37, i as f64 * -1.3, i as f64 * 2.11])
+            .collect();
+        let mut out = vec![0f64; points.len()];
+        noise.noise_batch(&points, &mut out);
+        for (point, value) in points.iter().zip(&out) {
+            let scalar = noise.noise(point[0], point[1], point[2]);
+            assert_eq!(scalar, *value);
+            assert!(scalar.abs() <= 1.1);
+        }
+        let four = [points[0], points[1], points[2], points[3]];
+        assert_eq!(noise.noise4(&four)[3], out[3]);
+    }
+    #[test]
+    pub fn test_perlin() {
+        let noise = <ImprovedNoise as Perlin>::new(42, ());
+        assert_eq!(
+            noise.permutation,
+            ImprovedNoise::new(&mut StdRng::seed_from_u64(42)).permutation
+        );
+        let points = [[0.5, 1.5, 2.5], [10.25, -3.0, 7.75]];
+        let mut out = [0f64; 2];
+        noise.get_batch(&points, &mut out);
+        assert_eq!(out[1], noise.get(10.25, -3.0, 7.75));
+    }
+}
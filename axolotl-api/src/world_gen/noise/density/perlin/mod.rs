@@ -5,6 +5,8 @@ use rand::{Rng, SeedableRng};
 pub use holder::NoiseHolder;
 
 mod holder;
+pub mod improved;
+pub mod simplex;
 
 pub trait Perlin: Debug + Clone {
     type Seed;
@@ -14,4 +16,12 @@ pub trait Perlin: Debug + Clone {
     fn get_setting(&self) -> &Self::Noise;
 
     fn get(&self, x: f64, y: f64, z: f64) -> f64;
+    /// Samples many points at once. `out` must be at least as long as `points`.
+    ///
+    /// Implementations can override this to use SIMD. See [improved::ImprovedNoise::noise_batch]
+    fn get_batch(&self, points: &[[f64; 3]], out: &mut [f64]) {
+        for (point, out) in points.iter().zip(out) {
+            *out = self.get(point[0], point[1], point[2]);
+        }
+    }
 }
@@ -0,0 +1,194 @@
+//! Vanilla's `SimplexNoise` and `PerlinSimplexNoise`.
+//!
+//! Biomes use these for their temperature noises. Only the 2D noise is ported because nothing else needs the 3D one
+use crate::world_gen::seed::LegacyRandom;
+
+/// The x and y of vanilla's gradients. Only the first 12 are used
+const GRADIENT: [[f64; 2]; 12] = [
+    [1.0, 1.0],
+    [-1.0, 1.0],
+    [1.0, -1.0],
+    [-1.0, -1.0],
+    [1.0, 0.0],
+    [-1.0, 0.0],
+    [1.0, 0.0],
+    [-1.0, 0.0],
+    [0.0, 1.0],
+    [0.0, -1.0],
+    [0.0, 1.0],
+    [0.0, -1.0],
+];
+/// Ints a [SimplexNoise] takes from its random
+const SIMPLEX_RANDOM_COUNT: usize = 262;
+
+#[inline]
+fn skew() -> f64 {
+    0.5 * (3f64.sqrt() - 1.0)
+}
+#[inline]
+fn unskew() -> f64 {
+    (3.0 - 3f64.sqrt()) / 6.0
+}
+
+/// A single octave of simplex noise
+#[derive(Debug, Clone)]
+pub struct SimplexNoise {
+    permutation: [u8; 256],
+    pub xo: f64,
+    pub yo: f64,
+    pub zo: f64,
+}
+impl SimplexNoise {
+    /// Draws the offsets and shuffles the permutation in vanilla's order
+    pub fn new(random: &mut LegacyRandom) -> Self {
+        let xo = random.next_double() * 256.0;
+        let yo = random.next_double() * 256.0;
+        let zo = random.next_double() * 256.0;
+        let mut permutation: [u8; 256] = std::array::from_fn(|i| i as u8);
+        for i in 0..256 {
+            let j = random.next_bounded(256 - i as i32) as usize;
+            permutation.swap(i, i + j);
+        }
+        Self {
+            permutation,
+            xo,
+            yo,
+            zo,
+        }
+    }
+    #[inline]
+    fn p(&self, i: i32) -> i32 {
+        self.permutation[(i & 255) as usize] as i32
+    }
+    #[inline]
+    fn corner(gradient: i32, x: f64, y: f64) -> f64 {
+        let falloff = 0.5 - x * x - y * y;
+        if falloff < 0.0 {
+            return 0.0;
+        }
+        let falloff = falloff * falloff;
+        let [gradient_x, gradient_y] = GRADIENT[gradient as usize];
+        falloff * falloff * (gradient_x * x + gradient_y * y)
+    }
+    /// Between -1 and 1
+    pub fn get(&self, x: f64, y: f64) -> f64 {
+        let skewed = (x + y) * skew();
+        let cell_x = (x + skewed).floor() as i32;
+        let cell_y = (y + skewed).floor() as i32;
+        let unskewed = (cell_x + cell_y) as f64 * unskew();
+        let x0 = x - (cell_x as f64 - unskewed);
+        let y0 = y - (cell_y as f64 - unskewed);
+        let (offset_x, offset_y) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let x1 = x0 - offset_x as f64 + unskew();
+        let y1 = y0 - offset_y as f64 + unskew();
+        let x2 = x0 - 1.0 + 2.0 * unskew();
+        let y2 = y0 - 1.0 + 2.0 * unskew();
+
+        let (i, j) = (cell_x & 255, cell_y & 255);
+        let gradient0 = self.p(i + self.p(j)) % 12;
+        let gradient1 = self.p(i + offset_x + self.p(j + offset_y)) % 12;
+        let gradient2 = self.p(i + 1 + self.p(j + 1)) % 12;
+        70.0 * (Self::corner(gradient0, x0, y0)
+            + Self::corner(gradient1, x1, y1)
+            + Self::corner(gradient2, x2, y2))
+    }
+}
+
+/// Octaves of [SimplexNoise]. Each octave has half the frequency and twice the weight of the one before it
+#[derive(Debug, Clone)]
+pub struct PerlinSimplexNoise {
+    /// Highest frequency first
+    levels: Vec<Option<SimplexNoise>>,
+    input_factor: f64,
+    value_factor: f64,
+}
+impl PerlinSimplexNoise {
+    /// Takes the random the same way vanilla does.
+    ///
+    /// # Panics
+    /// If `octaves` is empty or has an octave above 0. Vanilla seeds those from a second random and biomes never use them
+    pub fn new(random: &mut LegacyRandom, octaves: &[i32]) -> Self {
+        let lowest = *octaves
+            .iter()
+            .min()
+            .expect("At least one octave is required");
+        let highest = *octaves.iter().max().unwrap();
+        assert!(highest <= 0, "Octaves above 0 are not supported");
+        let count = highest - lowest + 1;
+
+        let mut levels = vec![None; count as usize];
+        let first = SimplexNoise::new(random);
+        if highest == 0 && octaves.contains(&0) {
+            levels[0] = Some(first);
+        }
+        for level in highest + 1..count {
+            if level >= 0 && octaves.contains(&(highest - level)) {
+                levels[level as usize] = Some(SimplexNoise::new(random));
+            } else {
+                random.consume_count(SIMPLEX_RANDOM_COUNT);
+            }
+        }
+        Self {
+            levels,
+            input_factor: 2f64.powi(highest),
+            value_factor: 1.0 / (2f64.powi(count) - 1.0),
+        }
+    }
+    /// Sampled without the offsets of the octaves like the biome noises
+    pub fn get(&self, x: f64, y: f64) -> f64 {
+        let mut value = 0.0;
+        let mut input_factor = self.input_factor;
+        let mut value_factor = self.value_factor;
+        for level in self.levels.iter() {
+            if let Some(level) = level {
+                value += level.get(x * input_factor, y * input_factor) * value_factor;
+            }
+            input_factor /= 2.0;
+            value_factor *= 2.0;
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world_gen::noise::density::perlin::simplex::{PerlinSimplexNoise, SimplexNoise};
+    use crate::world_gen::seed::LegacyRandom;
+
+    #[test]
+    pub fn test_simplex() {
+        let noise = SimplexNoise::new(&mut LegacyRandom::new(1234));
+        let mut permutation = noise.permutation;
+        permutation.sort();
+        assert!(permutation
+            .iter()
+            .enumerate()
+            .all(|(i, value)| i == *value as usize));
+        assert!((0.0..256.0).contains(&noise.xo));
+        // Zero on the lattice points
+        assert_eq!(noise.get(0.0, 0.0), 0.0);
+        for i in 0..64 {
+            let value = noise.get(i as f64 * 0.37, i as f64 * -1.3);
+            assert!(value.abs() <= 1.0);
+        }
+    }
+    #[test]
+    pub fn test_octaves() {
+        // A single octave at 0 is the first simplex noise drawn
+        let single = PerlinSimplexNoise::new(&mut LegacyRandom::new(2345), &[0]);
+        let noise = SimplexNoise::new(&mut LegacyRandom::new(2345));
+        assert_eq!(single.get(3.5, -7.25), noise.get(3.5, -7.25));
+
+        // The frozen ocean noise. The lower octaves are drawn after the first and sampled at lower frequencies
+        let mut random = LegacyRandom::new(3456);
+        let first = SimplexNoise::new(&mut random);
+        let second = SimplexNoise::new(&mut random);
+        let third = SimplexNoise::new(&mut random);
+        let frozen = PerlinSimplexNoise::new(&mut LegacyRandom::new(3456), &[-2, -1, 0]);
+        let (x, y) = (12.5, 40.25);
+        let expected = first.get(x, y) / 7.0
+            + second.get(x / 2.0, y / 2.0) * 2.0 / 7.0
+            + third.get(x / 4.0, y / 4.0) * 4.0 / 7.0;
+        assert!((frozen.get(x, y) - expected).abs() < 1e-12);
+    }
+}
@@ -76,17 +76,21 @@ define_as_noise!(
 );
 macro_rules! generic_new_noise {
     () => {
-        fn new<G, DS: DensityState>(game: &G, state: &DS, def: Self::FunctionDefinition) -> Self
+        fn new<G, DS: DensityState>(
+            game: &G,
+            state: &DS,
+            def: Self::FunctionDefinition,
+        ) -> Result<Self, BuildDefResult>
         where
             G: Game,
         {
             let noise = get_noise!(def, game);
 
             let value = P::new(state.seed(), noise);
-            Self {
+            Ok(Self {
                 perlin: value,
                 phantom: Default::default(),
-            }
+            })
         }
     };
 }
@@ -216,20 +220,20 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         game: &G,
         state: &'function DS,
         def: ShiftedNoiseLayout,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
         let noise = get_noise!(def.noise, game);
         let value = P::new(state.seed(), noise);
-        Self {
+        Ok(Self {
             perlin: value,
             xz_scale: def.xz_scale,
             y_scale: def.y_scale,
-            shift_x: state.build_from_def(game, *def.shift_x),
-            shift_y: state.build_from_def(game, *def.shift_y),
-            shift_z: state.build_from_def(game, *def.shift_z),
-        }
+            shift_x: state.build_from_def(game, *def.shift_x)?,
+            shift_y: state.build_from_def(game, *def.shift_y)?,
+            shift_z: state.build_from_def(game, *def.shift_z)?,
+        })
     }
 
     fn compute(&self, state: &impl DensityContext) -> f64 {
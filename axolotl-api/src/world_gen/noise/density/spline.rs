@@ -9,7 +9,9 @@ use crate::math;
 use crate::math::{lerp, linear_extend};
 use crate::world_gen::noise::density::loading::FunctionArgument;
 use crate::world_gen::noise::density::perlin::Perlin;
-use crate::world_gen::noise::density::{DensityContext, DensityFunction, DensityState, Function};
+use crate::world_gen::noise::density::{
+    BuildDefResult, DensityContext, DensityFunction, DensityState, Function,
+};
 use crate::world_gen::noise::{NameSpaceKeyOrType, Noise};
 
 #[derive(Debug, Clone)]
@@ -112,11 +114,11 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         game: &G,
         state: &'function DS,
         mut def: Self::FunctionDefinition,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
-        let function = state.build_from_def_with_cache::<G, P>(game, def.coordinate);
+        let function = state.build_from_def_with_cache::<G, P>(game, def.coordinate)?;
         let mut points: Vec<Point<Spline>> = def.points;
 
         let mut locations = Vec::with_capacity(points.len());
@@ -127,7 +129,7 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         for point in points.into_iter() {
             let value: SplineFunction<'function, P> = match point.value {
                 SplineOrConstant::Spline(spline) => {
-                    SplineFunction::<'function, P>::new(game, state, *spline)
+                    SplineFunction::<'function, P>::new(game, state, *spline)?
                 }
                 SplineOrConstant::Constant(constant) => SplineFunction::Constant(constant),
             };
@@ -140,14 +142,14 @@ impl<'function, P: Perlin<Noise = Noise, Seed = [u8; 16]>> DensityFunction<'func
         }
         let (min, max) =
             SplineFunction::calculate_min_max(&function, &locations, &values, &derivatives);
-        SplineFunction::Spline {
+        Ok(SplineFunction::Spline {
             function,
             derivatives,
             locations,
             values,
             min,
             max,
-        }
+        })
     }
     #[inline]
     fn compute(&self, state: &impl DensityContext) -> f64 {
@@ -246,11 +248,11 @@ where
         game: &G,
         state: &'function DS,
         def: Self::FunctionDefinition,
-    ) -> Self
+    ) -> Result<Self, BuildDefResult>
     where
         G: Game,
     {
-        panic!("Cannot create a SplineOrConstant from a definition")
+        Err("Cannot create a SplineOrConstant from a definition".into())
     }
     #[inline]
     fn compute(&self, state: &impl DensityContext) -> f64 {
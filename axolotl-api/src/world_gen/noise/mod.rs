@@ -12,10 +12,12 @@ pub use min_max::MinMax;
 use crate::game::Game;
 use crate::world_gen::noise::density::loading::DensityLoader;
 use crate::world_gen::noise::density::perlin::Perlin;
+use crate::world_gen::seed::WorldSeed;
 use crate::OwnedNameSpaceKey;
 
 pub mod density;
 mod min_max;
+pub mod router;
 
 #[derive(Debug, Clone)]
 pub enum NameSpaceKeyOrType<T> {
@@ -125,7 +127,8 @@ pub trait ChunkGenerator {
     type ChunkSettings: for<'a> Deserialize<'a>;
     type Chunk;
     type GameTy: Game;
-    fn new(game: Arc<Self::GameTy>, chunk_settings: Self::ChunkSettings) -> Self;
+    /// `seed` is the seed of the world. Any randomness in the generator is derived from it
+    fn new(game: Arc<Self::GameTy>, chunk_settings: Self::ChunkSettings, seed: WorldSeed) -> Self;
 
     fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Self::Chunk;
     fn generate_chunk_into(&self, chunk: &mut Self::Chunk);
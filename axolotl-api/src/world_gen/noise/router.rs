@@ -0,0 +1,313 @@
+//! Evaluates density functions for a whole chunk at once.
+//!
+//! Calling a function for every block repeats a lot of work. [NoiseChunk] samples the final density only at the corners of each cell
+//! and interpolates the blocks between them. Values that only depend on x and z are computed once per column
+use crate::world_gen::chunk::ChunkPos;
+use crate::world_gen::noise::density::perlin::Perlin;
+use crate::world_gen::noise::density::{DensityContext, DensityFunction, Function};
+use crate::world_gen::noise::{Noise, NoiseParameters};
+
+/// A single block handed to density functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockContext {
+    pub x: i32,
+    pub y: i16,
+    pub z: i32,
+}
+impl DensityContext for BlockContext {
+    fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    fn get_y(&self) -> i16 {
+        self.y
+    }
+
+    fn get_z(&self) -> i32 {
+        self.z
+    }
+}
+
+/// The shape of the noise. Created once per generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoiseRouter {
+    pub min_y: i32,
+    pub height: i32,
+    /// Blocks per cell horizontally. Must divide 16
+    pub cell_width: i32,
+    /// Blocks per cell vertically. Must divide the height
+    pub cell_height: i32,
+}
+impl NoiseRouter {
+    pub fn new(parameters: &NoiseParameters) -> Self {
+        Self {
+            min_y: parameters.min_y,
+            height: parameters.height,
+            cell_width: parameters.size_horizontal * 4,
+            cell_height: parameters.size_vertical * 4,
+        }
+    }
+    /// The context used while generating one chunk
+    pub fn for_chunk(&self, chunk: ChunkPos) -> NoiseChunk {
+        NoiseChunk {
+            router: *self,
+            chunk,
+        }
+    }
+}
+
+/// A value for every column of a chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnValues(pub Vec<f64>);
+impl ColumnValues {
+    /// Local coordinates
+    #[inline]
+    pub fn get(&self, x: usize, z: usize) -> f64 {
+        self.0[z * 16 + x]
+    }
+}
+
+/// A value for every block of a chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityBuffer {
+    pub min_y: i32,
+    pub height: i32,
+    pub values: Vec<f64>,
+}
+impl DensityBuffer {
+    /// Local x and z. `y` is the world height
+    #[inline]
+    pub fn get(&self, x: usize, y: i32, z: usize) -> f64 {
+        let y = (y - self.min_y) as usize;
+        self.values[(y * 16 + z) * 16 + x]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseChunk {
+    pub router: NoiseRouter,
+    pub chunk: ChunkPos,
+}
+impl NoiseChunk {
+    fn min_x(&self) -> i32 {
+        self.chunk.x() * 16
+    }
+    fn min_z(&self) -> i32 {
+        self.chunk.z() * 16
+    }
+    /// A function that ignores y. Called once per column
+    pub fn columns(&self, function: impl Fn(&BlockContext) -> f64) -> ColumnValues {
+        let mut values = Vec::with_capacity(256);
+        for z in 0..16 {
+            for x in 0..16 {
+                values.push(function(&BlockContext {
+                    x: self.min_x() + x,
+                    y: 0,
+                    z: self.min_z() + z,
+                }));
+            }
+        }
+        ColumnValues(values)
+    }
+    /// Matches `flat_cache`. Called once per 4x4 columns and shared by all of them
+    pub fn flat(&self, function: impl Fn(&BlockContext) -> f64) -> ColumnValues {
+        let mut quarts = [0f64; 16];
+        for quart_z in 0..4 {
+            for quart_x in 0..4 {
+                quarts[quart_z * 4 + quart_x] = function(&BlockContext {
+                    x: self.min_x() + quart_x as i32 * 4,
+                    y: 0,
+                    z: self.min_z() + quart_z as i32 * 4,
+                });
+            }
+        }
+        let mut values = Vec::with_capacity(256);
+        for z in 0..16 {
+            for x in 0..16 {
+                values.push(quarts[(z / 4) * 4 + x / 4]);
+            }
+        }
+        ColumnValues(values)
+    }
+    /// The number of times [NoiseChunk::interpolate] calls the function
+    pub fn corner_count(&self) -> usize {
+        let horizontal = (16 / self.router.cell_width + 1) as usize;
+        let vertical = (self.router.height / self.router.cell_height + 1) as usize;
+        horizontal * horizontal * vertical
+    }
+    /// Samples the function at the cell corners and interpolates every block inside the cells
+    pub fn interpolate(&self, function: impl Fn(&BlockContext) -> f64) -> DensityBuffer {
+        self.interpolate_batched(|corners, out| {
+            for (corner, out) in corners.iter().zip(out) {
+                *out = function(corner);
+            }
+        })
+    }
+    /// Interpolates a density function. The function is cloned first so its caches, like `flat_cache`, belong to this chunk
+    pub fn interpolate_function<P: Perlin<Noise = Noise, Seed = [u8; 16]>>(
+        &self,
+        function: &Function<P>,
+    ) -> DensityBuffer {
+        let function = function.clone();
+        self.interpolate(|block| function.compute(block))
+    }
+    /// Samples the noise at every corner with one [Perlin::get_batch] call. `then` turns a corner and its noise into the density
+    pub fn interpolate_noise(
+        &self,
+        noise: &impl Perlin,
+        then: impl Fn(&BlockContext, f64) -> f64,
+    ) -> DensityBuffer {
+        self.interpolate_batched(|corners, out| {
+            let points: Vec<[f64; 3]> = corners
+                .iter()
+                .map(|corner| [corner.x as f64, corner.y as f64, corner.z as f64])
+                .collect();
+            noise.get_batch(&points, out);
+            for (corner, out) in corners.iter().zip(out) {
+                *out = then(corner, *out);
+            }
+        })
+    }
+    /// [NoiseChunk::interpolate] with every corner handed over at once. So noise can be sampled in batches. See [crate::world_gen::noise::density::perlin::Perlin::get_batch]
+    pub fn interpolate_batched(
+        &self,
+        function: impl FnOnce(&[BlockContext], &mut [f64]),
+    ) -> DensityBuffer {
+        let router = self.router;
+        let cells_xz = (16 / router.cell_width) as usize;
+        let cells_y = (router.height / router.cell_height) as usize;
+        let corners_xz = cells_xz + 1;
+        let corner = |x: usize, y: usize, z: usize| (y * corners_xz + z) * corners_xz + x;
+
+        let mut positions = Vec::with_capacity(self.corner_count());
+        for y in 0..=cells_y {
+            for z in 0..corners_xz {
+                for x in 0..corners_xz {
+                    positions.push(BlockContext {
+                        x: self.min_x() + x as i32 * router.cell_width,
+                        y: (router.min_y + y as i32 * router.cell_height) as i16,
+                        z: self.min_z() + z as i32 * router.cell_width,
+                    });
+                }
+            }
+        }
+        let mut corners = vec![0f64; positions.len()];
+        function(&positions, &mut corners);
+
+        let height = router.height as usize;
+        let mut values = vec![0f64; 256 * height];
+        for block_y in 0..height {
+            let cell_y = (block_y / router.cell_height as usize).min(cells_y - 1);
+            let delta_y =
+                (block_y - cell_y * router.cell_height as usize) as f64 / router.cell_height as f64;
+            for block_z in 0..16 {
+                let cell_z = block_z / router.cell_width as usize;
+                let delta_z =
+                    (block_z % router.cell_width as usize) as f64 / router.cell_width as f64;
+                for block_x in 0..16 {
+                    let cell_x = block_x / router.cell_width as usize;
+                    let delta_x =
+                        (block_x % router.cell_width as usize) as f64 / router.cell_width as f64;
+                    values[(block_y * 16 + block_z) * 16 + block_x] = lerp3(
+                        delta_x,
+                        delta_y,
+                        delta_z,
+                        [
+                            corners[corner(cell_x, cell_y, cell_z)],
+                            corners[corner(cell_x + 1, cell_y, cell_z)],
+                            corners[corner(cell_x, cell_y + 1, cell_z)],
+                            corners[corner(cell_x + 1, cell_y + 1, cell_z)],
+                            corners[corner(cell_x, cell_y, cell_z + 1)],
+                            corners[corner(cell_x + 1, cell_y, cell_z + 1)],
+                            corners[corner(cell_x, cell_y + 1, cell_z + 1)],
+                            corners[corner(cell_x + 1, cell_y + 1, cell_z + 1)],
+                        ],
+                    );
+                }
+            }
+        }
+        DensityBuffer {
+            min_y: router.min_y,
+            height: router.height,
+            values,
+        }
+    }
+}
+
+#[inline]
+fn lerp(delta: f64, start: f64, end: f64) -> f64 {
+    start + delta * (end - start)
+}
+/// The corners are ordered x, then y, then z
+#[inline]
+fn lerp3(delta_x: f64, delta_y: f64, delta_z: f64, corners: [f64; 8]) -> f64 {
+    let near = lerp(
+        delta_y,
+        lerp(delta_x, corners[0], corners[1]),
+        lerp(delta_x, corners[2], corners[3]),
+    );
+    let far = lerp(
+        delta_y,
+        lerp(delta_x, corners[4], corners[5]),
+        lerp(delta_x, corners[6], corners[7]),
+    );
+    lerp(delta_z, near, far)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::Cell;
+
+    use crate::world_gen::chunk::ChunkPos;
+    use crate::world_gen::noise::density::perlin::improved::ImprovedNoise;
+    use crate::world_gen::noise::density::perlin::Perlin;
+    use crate::world_gen::noise::router::NoiseRouter;
+
+    #[test]
+    pub fn test_interpolate() {
+        let router = NoiseRouter {
+            min_y: -64,
+            height: 384,
+            cell_width: 4,
+            cell_height: 8,
+        };
+        let chunk = router.for_chunk(ChunkPos::new(-2, 3));
+        let calls = Cell::new(0);
+        // Linear functions are interpolated exactly
+        let buffer = chunk.interpolate(|block| {
+            calls.set(calls.get() + 1);
+            block.x as f64 * 0.5 - block.y as f64 + block.z as f64 * 2.0
+        });
+        assert_eq!(calls.get(), chunk.corner_count());
+        assert!(calls.get() < 16 * 16 * 384 / 20);
+        for (x, y, z) in [(0, -64, 0), (5, 13, 9), (15, 319, 15)] {
+            let expected = (-32 + x as i32) as f64 * 0.5 - y as f64 + (48 + z as i32) as f64 * 2.0;
+            assert!((buffer.get(x, y, z) - expected).abs() < 1e-9);
+        }
+
+        let calls = Cell::new(0);
+        let flat = chunk.flat(|block| {
+            calls.set(calls.get() + 1);
+            block.x as f64
+        });
+        assert_eq!(calls.get(), 16);
+        assert_eq!(flat.get(3, 0), flat.get(0, 2));
+    }
+    #[test]
+    pub fn test_interpolate_noise() {
+        let router = NoiseRouter {
+            min_y: 0,
+            height: 64,
+            cell_width: 4,
+            cell_height: 8,
+        };
+        let chunk = router.for_chunk(ChunkPos::new(1, -1));
+        let noise = <ImprovedNoise as Perlin>::new(7, ());
+        let shape = |y: i16| y as f64 / 32.0;
+        let batched = chunk.interpolate_noise(&noise, |corner, value| value - shape(corner.y));
+        let scalar = chunk.interpolate(|block| {
+            noise.get(block.x as f64, block.y as f64, block.z as f64) - shape(block.y)
+        });
+        assert_eq!(batched, scalar);
+    }
+}
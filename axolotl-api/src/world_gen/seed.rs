@@ -0,0 +1,209 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+const MULTIPLIER: i64 = 0x5DEECE66D;
+const ADDEND: i64 = 0xB;
+const MASK: i64 = (1 << 48) - 1;
+const SILVER_RATIO_64: i64 = 0x6A09E667F3BCC909;
+const GOLDEN_RATIO_64: i64 = 0x9E3779B97F4A7C15u64 as i64;
+
+/// The seed of a world. Generators and features should derive their seeds from this
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WorldSeed(pub i64);
+
+impl WorldSeed {
+    pub fn new(seed: i64) -> Self {
+        Self(seed)
+    }
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+    /// Parses a seed the same way the vanilla client does.
+    ///
+    /// Numbers are used as is. Any other text is hashed with [java_string_hash].
+    /// Returns None if the seed is empty or `0`. Vanilla uses a random seed in that case
+    pub fn parse(seed: &str) -> Option<Self> {
+        let seed = seed.trim();
+        if seed.is_empty() {
+            return None;
+        }
+        match seed.parse::<i64>() {
+            Ok(0) => None,
+            Ok(value) => Some(Self(value)),
+            Err(_) => Some(Self(java_string_hash(seed) as i64)),
+        }
+    }
+    /// [WorldSeed::parse] with a random seed if the value is empty
+    pub fn from_string(seed: &str) -> Self {
+        Self::parse(seed).unwrap_or_else(Self::random)
+    }
+    /// Upgrades the seed to the 128 bits used by Xoroshiro.
+    ///
+    /// Returns (low, high)
+    pub fn upgrade_to_128bit(&self) -> (i64, i64) {
+        let low = self.0 ^ SILVER_RATIO_64;
+        let high = low.wrapping_add(GOLDEN_RATIO_64);
+        (mix_stafford_13(low), mix_stafford_13(high))
+    }
+    /// The seed format used by [crate::world_gen::noise::density::perlin::Perlin]
+    pub fn xoroshiro_seed(&self) -> [u8; 16] {
+        let (low, high) = self.upgrade_to_128bit();
+        let mut seed = [0u8; 16];
+        seed[0..8].copy_from_slice(&low.to_be_bytes());
+        seed[8..16].copy_from_slice(&high.to_be_bytes());
+        seed
+    }
+    /// A seed for the index. Both are mixed so neighbouring seeds and indices do not share seeds like `seed + index` would
+    pub fn fork(&self, index: u64) -> Self {
+        let index = mix_stafford_13((index as i64).wrapping_mul(GOLDEN_RATIO_64));
+        Self(mix_stafford_13(self.0 ^ index))
+    }
+    /// The seed used to decorate the chunk starting at the block position.
+    ///
+    /// Each feature then uses [WorldSeed::feature_seed]
+    pub fn decoration_seed(&self, min_block_x: i32, min_block_z: i32) -> i64 {
+        let mut random = LegacyRandom::new(self.0);
+        let x = random.next_long() | 1;
+        let z = random.next_long() | 1;
+        (min_block_x as i64)
+            .wrapping_mul(x)
+            .wrapping_add((min_block_z as i64).wrapping_mul(z))
+            ^ self.0
+    }
+    /// The seed of a single feature. `index` is the feature index within the generation `step`
+    pub fn feature_seed(decoration_seed: i64, index: i32, step: i32) -> i64 {
+        decoration_seed
+            .wrapping_add(index as i64)
+            .wrapping_add(10000i64.wrapping_mul(step as i64))
+    }
+    /// The seed for carvers and structure starts in a chunk
+    pub fn large_feature_seed(&self, chunk_x: i32, chunk_z: i32) -> i64 {
+        let mut random = LegacyRandom::new(self.0);
+        let x = random.next_long();
+        let z = random.next_long();
+        (chunk_x as i64).wrapping_mul(x) ^ (chunk_z as i64).wrapping_mul(z) ^ self.0
+    }
+    /// The seed used by structure placement for a region and salt
+    pub fn large_feature_seed_with_salt(&self, region_x: i32, region_z: i32, salt: i32) -> i64 {
+        (region_x as i64)
+            .wrapping_mul(341873128712)
+            .wrapping_add((region_z as i64).wrapping_mul(132897987541))
+            .wrapping_add(self.0)
+            .wrapping_add(salt as i64)
+    }
+}
+impl Display for WorldSeed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl From<i64> for WorldSeed {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+impl From<WorldSeed> for i64 {
+    fn from(value: WorldSeed) -> Self {
+        value.0
+    }
+}
+
+/// Java's `String.hashCode`
+pub fn java_string_hash(value: &str) -> i32 {
+    value
+        .encode_utf16()
+        .fold(0i32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as i32))
+}
+
+fn mix_stafford_13(value: i64) -> i64 {
+    let mut value = value as u64;
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (value ^ (value >> 31)) as i64
+}
+
+/// The linear congruential generator of `java.util.Random`.
+///
+/// Vanilla still uses it for structure and carver seeds and the biome temperature noises
+#[derive(Debug, Clone)]
+pub struct LegacyRandom {
+    seed: i64,
+}
+impl LegacyRandom {
+    pub fn new(seed: i64) -> Self {
+        Self {
+            seed: (seed ^ MULTIPLIER) & MASK,
+        }
+    }
+    pub fn set_seed(&mut self, seed: i64) {
+        self.seed = (seed ^ MULTIPLIER) & MASK;
+    }
+    pub fn next(&mut self, bits: u32) -> i32 {
+        self.seed = (self.seed.wrapping_mul(MULTIPLIER).wrapping_add(ADDEND)) & MASK;
+        (self.seed >> (48 - bits)) as i32
+    }
+    pub fn next_int(&mut self) -> i32 {
+        self.next(32)
+    }
+    pub fn next_long(&mut self) -> i64 {
+        ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
+    }
+    /// `nextInt(bound)`. `bound` must be positive
+    pub fn next_bounded(&mut self, bound: i32) -> i32 {
+        if bound & (bound - 1) == 0 {
+            return ((bound as i64 * self.next(31) as i64) >> 31) as i32;
+        }
+        loop {
+            let value = self.next(31);
+            let result = value % bound;
+            if value.wrapping_sub(result).wrapping_add(bound - 1) >= 0 {
+                return result;
+            }
+        }
+    }
+    /// Between 0 and 1
+    pub fn next_double(&mut self) -> f64 {
+        let high = self.next(26) as i64;
+        let low = self.next(27) as i64;
+        ((high << 27) + low) as f64 / (1u64 << 53) as f64
+    }
+    /// Skips `count` ints
+    pub fn consume_count(&mut self, count: usize) {
+        for _ in 0..count {
+            self.next_int();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world_gen::seed::{java_string_hash, LegacyRandom, WorldSeed};
+
+    #[test]
+    pub fn test_parse() {
+        assert_eq!(WorldSeed::parse("123"), Some(WorldSeed(123)));
+        assert_eq!(WorldSeed::parse("-5"), Some(WorldSeed(-5)));
+        assert_eq!(WorldSeed::parse("0"), None);
+        assert_eq!(WorldSeed::parse("  "), None);
+        assert_eq!(java_string_hash("hello"), 99162322);
+        assert_eq!(WorldSeed::parse("hello"), Some(WorldSeed(99162322)));
+    }
+    #[test]
+    pub fn test_legacy_random() {
+        let mut random = LegacyRandom::new(0);
+        assert_eq!(random.next_long(), -4962768465676381896);
+        // new Random(0).nextDouble() and new Random(0).nextInt(100)
+        assert_eq!(LegacyRandom::new(0).next_double(), 0.730967787376657);
+        assert_eq!(LegacyRandom::new(0).next_bounded(100), 60);
+    }
+    #[test]
+    pub fn test_fork() {
+        let seed = WorldSeed::new(42);
+        assert_eq!(seed.fork(1), WorldSeed::new(42).fork(1));
+        assert_ne!(seed.fork(1), seed.fork(0));
+        assert_ne!(seed.fork(1), WorldSeed::new(43).fork(0));
+        assert_ne!(seed.fork(0), seed);
+    }
+}
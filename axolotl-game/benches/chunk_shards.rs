@@ -0,0 +1,202 @@
+//! Compares one lock around the chunk map against [ChunkShards]
+//! and reading a hot chunk through its lock against reading its section snapshots.
+//!
+//! # Comparing against a baseline
+//! ```bash
+//! cargo bench -p axolotl-game --bench chunk_shards -- --save-baseline main
+//! # Make the change
+//! cargo bench -p axolotl-game --bench chunk_shards -- --baseline main
+//! ```
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use parking_lot::RwLock;
+
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_game::world::chunk::placed_block::PlacedBlock;
+use axolotl_game::world::chunk::{AxolotlChunk, ChunkShards, InnerChunkHandle};
+use axolotl_game::world::generator::AxolotlGenerator;
+use axolotl_game::AxolotlGame;
+use axolotl_items::blocks::generic_block::VanillaStateIdOrValue;
+use axolotl_items::blocks::InnerMinecraftBlock;
+
+const THREADS: usize = 8;
+const RADIUS: i32 = 16;
+
+fn positions() -> Vec<ChunkPos> {
+    let mut positions = Vec::new();
+    for x in -RADIUS..RADIUS {
+        for z in -RADIUS..RADIUS {
+            positions.push(ChunkPos::new(x, z));
+        }
+    }
+    positions
+}
+/// Every thread reads every chunk while one thread keeps inserting and removing chunks
+fn contended(get: impl Fn(&ChunkPos) + Sync, churn: impl Fn(i32) + Sync) {
+    let positions = positions();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for value in 0..256 {
+                churn(value);
+            }
+        });
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for pos in positions.iter() {
+                    get(black_box(pos));
+                }
+            });
+        }
+    });
+}
+
+pub fn single_lock(c: &mut Criterion) {
+    let map: RwLock<AHashMap<ChunkPos, Arc<i32>>> = RwLock::default();
+    for pos in positions() {
+        map.write().insert(pos, Arc::new(pos.x()));
+    }
+    c.bench_function("single_lock_contended_reads", |b| {
+        b.iter(|| {
+            contended(
+                |pos| {
+                    black_box(map.read().get(pos).cloned());
+                },
+                |value| {
+                    let pos = ChunkPos::new(RADIUS + value, 0);
+                    map.write().insert(pos, Arc::new(value));
+                    map.write().remove(&pos);
+                },
+            )
+        })
+    });
+}
+
+pub fn sharded(c: &mut Criterion) {
+    let map: ChunkShards<Arc<i32>> = ChunkShards::default();
+    for pos in positions() {
+        map.insert(pos, Arc::new(pos.x()));
+    }
+    c.bench_function("sharded_contended_reads", |b| {
+        b.iter(|| {
+            contended(
+                |pos| {
+                    black_box(map.get(pos));
+                },
+                |value| {
+                    let pos = ChunkPos::new(RADIUS + value, 0);
+                    map.insert(pos, Arc::new(value));
+                    map.remove(&pos);
+                },
+            )
+        })
+    });
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BenchWorld {}
+impl World for BenchWorld {
+    type Chunk = AxolotlChunk<Self>;
+    type WorldBlock = PlacedBlock<Self>;
+    type NoiseGenerator = AxolotlGenerator<Self>;
+
+    fn get_name(&self) -> &str {
+        "bench"
+    }
+
+    fn tick(&mut self) {}
+
+    fn generator(&self) -> &Self::NoiseGenerator {
+        &AxolotlGenerator::Debug()
+    }
+
+    fn set_block(
+        &self,
+        _location: BlockPosition,
+        _block: Self::WorldBlock,
+        _require_loaded: bool,
+    ) -> bool {
+        false
+    }
+
+    fn set_blocks(
+        &self,
+        _chunk_pos: ChunkPos,
+        _blocks: impl Iterator<Item = (BlockPosition, Self::WorldBlock)>,
+    ) {
+    }
+}
+/// Blocks with different states so the sections have palettes. No game data is needed
+fn blocks() -> Vec<PlacedBlock<BenchWorld>> {
+    let air = Arc::new(InnerMinecraftBlock::<AxolotlGame<BenchWorld>>::Air {
+        id: 0,
+        key: "air".to_string(),
+    });
+    (0..16)
+        .map(|state| PlacedBlock {
+            state: VanillaStateIdOrValue::Id(state),
+            block: air.clone(),
+        })
+        .collect()
+}
+/// Every thread reads the same column of the chunk while one thread keeps setting a block in a section at the top
+fn hot_chunk(get: impl Fn(BlockPosition) + Sync, set: impl Fn(i32) + Sync) {
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for value in 0..256 {
+                set(value);
+            }
+        });
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for y in 0..64 {
+                    get(black_box(BlockPosition::new(3, y, 3)));
+                }
+            });
+        }
+    });
+}
+
+pub fn hot_chunk_reads(c: &mut Criterion) {
+    let blocks = blocks();
+    let mut chunk = AxolotlChunk::new(ChunkPos::new(0, 0));
+    for y in 0..64 {
+        for x in 0..16 {
+            for z in 0..16 {
+                let block = &blocks[(x + z + y as i64) as usize % blocks.len()];
+                chunk.set_block(BlockPosition::new(x, y, z), block.clone());
+            }
+        }
+    }
+    let handle = InnerChunkHandle::new(chunk);
+    let set = |value: i32| {
+        let block = blocks[value as usize % blocks.len()].clone();
+        handle.set_block(BlockPosition::new(8, 200, 8), block);
+    };
+
+    c.bench_function("chunk_lock_contended_reads", |b| {
+        b.iter(|| {
+            hot_chunk(
+                |pos| {
+                    black_box(handle.value.read().get_block(pos).cloned());
+                },
+                set,
+            )
+        })
+    });
+    c.bench_function("section_snapshot_contended_reads", |b| {
+        b.iter(|| {
+            hot_chunk(
+                |pos| {
+                    black_box(handle.get_block(pos));
+                },
+                set,
+            )
+        })
+    });
+}
+
+criterion_group!(benches, single_lock, sharded, hot_chunk_reads);
+criterion_main!(benches);
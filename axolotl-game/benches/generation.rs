@@ -0,0 +1,296 @@
+//! Chunk generation and serialization benchmarks.
+//!
+//! Needs the same data as the tests. Set `DATA_DUMP` and `AXOLOTL_DATA` if they are not in the working directory.
+//!
+//! # Comparing against a baseline
+//! Save a baseline before making a change and compare against it after.
+//! ```bash
+//! cargo bench -p axolotl-game --bench generation -- --save-baseline main
+//! # Make the change
+//! cargo bench -p axolotl-game --bench generation -- --baseline main
+//! ```
+//! Criterion reports every benchmark that regressed beyond the noise threshold
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axolotl_nbt::binary::Binary;
+use axolotl_nbt::serde_impl;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use axolotl_api::game::Registry;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::biome::climate::TemperatureModifier;
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::world_gen::noise::density::perlin::improved::ImprovedNoise;
+use axolotl_api::world_gen::noise::router::NoiseRouter;
+use axolotl_api::world_gen::noise::{ChunkGenerator, NameSpaceKeyOrType, NoiseParameters};
+use axolotl_api::world_gen::seed::WorldSeed;
+use axolotl_api::OwnedNameSpaceKey;
+use axolotl_game::world::chunk::placed_block::PlacedBlock;
+use axolotl_game::world::chunk::AxolotlChunk;
+use axolotl_game::world::generator::AxolotlGenerator;
+use axolotl_game::world::level::accessor::IntoRawChunk;
+use axolotl_game::world::level::biome_source::BiomeSourceSettings;
+use axolotl_game::world::level::flat::{FlatGenerator, FlatSettings, Layer};
+use axolotl_game::world::level::noise::NoiseGenerator;
+use axolotl_game::world::level::surface::{BiomeClimate, SurfaceStage};
+use axolotl_game::{AxolotlGame, GameConfig};
+use axolotl_world::region::file::RegionFile;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BenchWorld {}
+impl World for BenchWorld {
+    type Chunk = AxolotlChunk<Self>;
+    type WorldBlock = PlacedBlock<Self>;
+    type NoiseGenerator = AxolotlGenerator<Self>;
+
+    fn get_name(&self) -> &str {
+        "bench"
+    }
+
+    fn tick(&mut self) {}
+
+    /// Generates empty chunks
+    fn generator(&self) -> &Self::NoiseGenerator {
+        &AxolotlGenerator::Debug()
+    }
+
+    fn set_block(
+        &self,
+        _location: BlockPosition,
+        _block: Self::WorldBlock,
+        _require_loaded: bool,
+    ) -> bool {
+        false
+    }
+
+    fn set_blocks(
+        &self,
+        _chunk_pos: ChunkPos,
+        _blocks: impl Iterator<Item = (BlockPosition, Self::WorldBlock)>,
+    ) {
+    }
+}
+type Game = AxolotlGame<BenchWorld>;
+
+fn load_game() -> Arc<Game> {
+    let config = GameConfig {
+        data_dump: PathBuf::from(option_env!("DATA_DUMP").unwrap_or("data_dump")),
+        data_packs: vec![],
+        axolotl_data: PathBuf::from(option_env!("AXOLOTL_DATA").unwrap_or("axolotl_data")),
+    };
+    Game::load(config)
+        .map(Arc::new)
+        .expect("Failed to load the game")
+}
+
+fn flat_settings() -> FlatSettings {
+    let layer = |block: &str, height| Layer {
+        block: block.to_string(),
+        height,
+    };
+    FlatSettings {
+        biome: "minecraft:plains".to_string(),
+        features: false,
+        lakes: false,
+        layers: vec![
+            layer("minecraft:bedrock", 1),
+            layer("minecraft:dirt", 2),
+            layer("minecraft:grass_block", 1),
+        ],
+        structure_overrides: vec![],
+    }
+}
+/// A chunk with many different blocks so the palettes have more than one entry
+fn mixed_chunk(game: &Game) -> AxolotlChunk<BenchWorld> {
+    let mut chunk = AxolotlChunk::new(ChunkPos::new(0, 0));
+    for y in 0..64 {
+        for x in 0..16 {
+            for z in 0..16 {
+                let id = (x + z + y as i64) as usize % 64;
+                if let Some(block) = game.registries.blocks.get_by_id(id) {
+                    chunk.set_block(
+                        BlockPosition::new(x, y, z),
+                        PlacedBlock::from(block.clone()),
+                    );
+                }
+            }
+        }
+    }
+    chunk
+}
+
+pub fn generation(c: &mut Criterion) {
+    let game = load_game();
+    let flat = FlatGenerator::new(game.clone(), flat_settings(), WorldSeed::new(0));
+    c.bench_function("generate_flat_chunk", |b| {
+        b.iter(|| black_box(flat.generate_chunk(black_box(0), black_box(0))))
+    });
+
+    // Measured on a flat chunk so the terrain is the same every run
+    let surface = SurfaceStage::new(&game);
+    let cold: BiomeClimate = (-0.5, TemperatureModifier::None);
+    c.bench_function("surface_stage_cold", |b| {
+        b.iter_batched(
+            || flat.generate_chunk(0, 0),
+            |mut chunk| {
+                surface.apply(&mut chunk, |_, _| Some(cold));
+                chunk
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+pub fn palette(c: &mut Criterion) {
+    let game = load_game();
+    let blocks: Vec<PlacedBlock<BenchWorld>> = (0..64)
+        .filter_map(|id| game.registries.blocks.get_by_id(id))
+        .map(|block| PlacedBlock::from(block.clone()))
+        .collect();
+    c.bench_function("palette_insert_section", |b| {
+        b.iter_batched(
+            || AxolotlChunk::<BenchWorld>::new(ChunkPos::new(0, 0)),
+            |mut chunk| {
+                for x in 0..16 {
+                    for z in 0..16 {
+                        for y in 0..16 {
+                            let block = &blocks[(x + z + y) as usize % blocks.len()];
+                            chunk.set_block(BlockPosition::new(x, y as i16, z), block.clone());
+                        }
+                    }
+                }
+                chunk
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+pub fn serialization(c: &mut Criterion) {
+    let game = load_game();
+    let chunk = mixed_chunk(&game);
+    c.bench_function("serialize_sections", |b| {
+        b.iter_batched(
+            || chunk.clone(),
+            |chunk| black_box(chunk.into_raw_chunk()),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let raw_chunk = chunk.clone().into_raw_chunk();
+    let mut buffer = Vec::with_capacity(1 << 16);
+    c.bench_function("serialize_chunk_nbt", |b| {
+        b.iter(|| {
+            buffer.clear();
+            serde_impl::to_writer::<Binary, _, _>(&mut buffer, &raw_chunk).unwrap();
+            black_box(buffer.len())
+        })
+    });
+
+    let folder = std::env::temp_dir().join("axolotl_bench_region");
+    std::fs::create_dir_all(&folder).unwrap();
+    let path = folder.join("r.0.0.mca");
+    std::fs::File::create(&path).unwrap();
+    let mut region = RegionFile::new(path.clone(), false).unwrap();
+    c.bench_function("region_write_chunk", |b| {
+        b.iter(|| {
+            region.write_chunk(raw_chunk.clone()).unwrap();
+        })
+    });
+    region.save().unwrap();
+    drop(region);
+    std::fs::remove_dir_all(folder).ok();
+}
+
+/// Compares sampling the noise generator's density at every block with the cell interpolation of [NoiseRouter]
+pub fn noise_router(c: &mut Criterion) {
+    let game = load_game();
+    let generator = NoiseGenerator::new(
+        game,
+        (
+            BiomeSourceSettings::Fixed {
+                biome: OwnedNameSpaceKey::new("minecraft".to_string(), "plains".to_string()),
+            },
+            NameSpaceKeyOrType::NameSpaceKey(OwnedNameSpaceKey::new(
+                "minecraft".to_string(),
+                "overworld".to_string(),
+            )),
+        ),
+        WorldSeed::new(0),
+    );
+    let router = *generator.router();
+    c.bench_function("density_per_block", |b| {
+        b.iter(|| {
+            let mut values = Vec::with_capacity(16 * 16 * router.height as usize);
+            for y in router.min_y..router.min_y + router.height {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        values.push(generator.density_at(x, y, z));
+                    }
+                }
+            }
+            black_box(values)
+        })
+    });
+    c.bench_function("density_router_interpolated", |b| {
+        b.iter(|| black_box(generator.density(black_box(ChunkPos::new(0, 0)))))
+    });
+    c.bench_function("generate_noise_chunk", |b| {
+        b.iter(|| black_box(generator.generate_chunk(black_box(0), black_box(0))))
+    });
+}
+
+/// Run with `--features simd` on nightly to compare the SIMD batch against the scalar samples
+pub fn noise_sampling(c: &mut Criterion) {
+    let noise = ImprovedNoise::new(&mut rand::thread_rng());
+    let points: Vec<[f64; 3]> = (0..1024)
+        .map(|i| [i as f64 * 0.25, (i % 48) as f64 * 8.0, i as f64 * 0.5])
+        .collect();
+    let mut out = vec![0f64; points.len()];
+    c.bench_function("improved_noise_scalar", |b| {
+        b.iter(|| {
+            for (point, out) in points.iter().zip(out.iter_mut()) {
+                *out = noise.noise(point[0], point[1], point[2]);
+            }
+            black_box(&out);
+        })
+    });
+    c.bench_function("improved_noise_batch", |b| {
+        b.iter(|| {
+            noise.noise_batch(&points, &mut out);
+            black_box(&out);
+        })
+    });
+
+    // The cell corners of a chunk, sampled one by one and through `Perlin::get_batch`
+    let chunk = NoiseRouter::new(&NoiseParameters {
+        height: 384,
+        min_y: -64,
+        size_horizontal: 1,
+        size_vertical: 2,
+    })
+    .for_chunk(ChunkPos::new(0, 0));
+    c.bench_function("corners_scalar", |b| {
+        b.iter(|| {
+            black_box(
+                chunk.interpolate(|block| {
+                    noise.noise(block.x as f64, block.y as f64, block.z as f64)
+                }),
+            )
+        })
+    });
+    c.bench_function("corners_batch", |b| {
+        b.iter(|| black_box(chunk.interpolate_noise(&noise, |_, value| value)))
+    });
+}
+
+criterion_group!(
+    benches,
+    generation,
+    palette,
+    serialization,
+    noise_router,
+    noise_sampling
+);
+criterion_main!(benches);
@@ -0,0 +1,132 @@
+//! The channels used to talk to a world.
+//!
+//! The world only depends on these traits. [flume] implements them by default.
+//! Embedders running an async runtime can implement them for their own channels and await updates with [UpdateReceiver::recv_async]
+use std::fmt::Debug;
+use std::future::Future;
+
+use flume::r#async::RecvFut;
+use flume::{RecvError, TryRecvError};
+
+/// The other side of the channel was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("The channel is disconnected")]
+pub struct Disconnected;
+
+pub trait UpdateSender<T>: Clone + Send + Sync {
+    /// Never drops the value. Bounded channels block while they are full
+    fn send(&self, value: T) -> Result<(), Disconnected>;
+}
+
+pub trait UpdateReceiver<T>: Send {
+    type Recv<'receiver>: Future<Output = Result<T, Disconnected>> + Send + 'receiver
+    where
+        Self: 'receiver;
+    /// None if nothing is waiting
+    fn try_recv(&mut self) -> Result<Option<T>, Disconnected>;
+    /// Blocks the thread until a value arrives
+    fn recv(&mut self) -> Result<T, Disconnected>;
+    /// Waits for a value without blocking the thread
+    fn recv_async(&mut self) -> Self::Recv<'_>;
+    /// Everything currently waiting
+    fn drain(&mut self) -> Vec<T> {
+        let mut values = Vec::new();
+        while let Ok(Some(value)) = self.try_recv() {
+            values.push(value);
+        }
+        values
+    }
+}
+
+impl<T: Send> UpdateSender<T> for flume::Sender<T> {
+    fn send(&self, value: T) -> Result<(), Disconnected> {
+        flume::Sender::send(self, value).map_err(|_| Disconnected)
+    }
+}
+
+/// Maps the flume error to [Disconnected]
+pub struct FlumeRecv<'receiver, T>(RecvFut<'receiver, T>);
+impl<T> Future for FlumeRecv<'_, T> {
+    type Output = Result<T, Disconnected>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        std::pin::Pin::new(&mut self.0)
+            .poll(cx)
+            .map_err(|RecvError::Disconnected| Disconnected)
+    }
+}
+
+impl<T: Send> UpdateReceiver<T> for flume::Receiver<T> {
+    type Recv<'receiver>
+        = FlumeRecv<'receiver, T>
+    where
+        T: 'receiver;
+
+    fn try_recv(&mut self) -> Result<Option<T>, Disconnected> {
+        match flume::Receiver::try_recv(self) {
+            Ok(value) => Ok(Some(value)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Disconnected),
+        }
+    }
+
+    fn recv(&mut self) -> Result<T, Disconnected> {
+        flume::Receiver::recv(self).map_err(|_| Disconnected)
+    }
+
+    fn recv_async(&mut self) -> Self::Recv<'_> {
+        FlumeRecv(flume::Receiver::recv_async(self))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    use crate::channel::{Disconnected, UpdateReceiver, UpdateSender};
+
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    pub fn test_flume_channel() {
+        let (sender, mut receiver) = flume::unbounded::<u32>();
+        UpdateSender::send(&sender, 1).unwrap();
+        UpdateSender::send(&sender, 2).unwrap();
+        assert_eq!(receiver.drain(), vec![1, 2]);
+
+        let waker = Arc::new(NoopWaker).into();
+        let mut context = Context::from_waker(&waker);
+        {
+            let mut future = pin!(receiver.recv_async());
+            assert!(future.as_mut().poll(&mut context).is_pending());
+            UpdateSender::send(&sender, 3).unwrap();
+            assert_eq!(future.poll(&mut context), Poll::Ready(Ok(3)));
+        }
+        drop(sender);
+        assert_eq!(UpdateReceiver::try_recv(&mut receiver), Err(Disconnected));
+    }
+
+    #[test]
+    pub fn test_bounded_channel_keeps_values() {
+        let (sender, mut receiver) = flume::bounded::<u32>(1);
+        let sending = std::thread::spawn(move || {
+            for value in 0..4 {
+                UpdateSender::send(&sender, value).unwrap();
+            }
+        });
+        let received: Vec<u32> = (0..4)
+            .map(|_| UpdateReceiver::recv(&mut receiver).unwrap())
+            .collect();
+        sending.join().unwrap();
+        assert_eq!(received, vec![0, 1, 2, 3]);
+    }
+}
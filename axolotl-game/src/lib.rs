@@ -19,21 +19,28 @@ use thiserror::Error;
 use axolotl_api::game::{AxolotlVersion, DataRegistries, Game, Registries, Registry};
 use axolotl_api::world::{BlockPosition, World};
 use axolotl_api::world_gen::biome::vanilla::DataPackBiome;
+use axolotl_api::world_gen::biome::Effects;
 use axolotl_api::world_gen::dimension::Dimension;
 use axolotl_api::world_gen::noise::{Noise, NoiseSetting};
 use axolotl_api::{NamespacedId, NamespacedKey};
 use axolotl_items::blocks::MinecraftBlock;
 use axolotl_items::items::MinecraftItem;
 use axolotl_world::level::MinecraftVersion;
+use axolotl_world::validate::PaletteLookup;
 use registry::SimpleRegistry;
 
 use crate::chat::AxolotlChatType;
 use crate::item_stack::AxolotlItemStack;
 use crate::world::generator::AxolotlDensityLoader;
+use crate::world::level::surface::BiomeClimate;
 use crate::world::perlin::GameNoise;
 
+pub mod channel;
 pub mod chat;
 pub mod item_stack;
+pub mod mapping;
+pub mod pack;
+pub mod profile;
 pub mod registry;
 pub mod world;
 
@@ -71,6 +78,20 @@ pub enum Error {
     SerdeError(#[from] serde_impl::Error),
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    ItemsError(#[from] axolotl_items::Error),
+    #[error("The world was opened read only")]
+    ReadOnly,
+    #[error("The world is in use by another process. {0:?} is locked")]
+    WorldLocked(PathBuf),
+    #[error(transparent)]
+    PngError(#[from] png::EncodingError),
+    #[error(transparent)]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("Invalid freeze dried chunk: {0}")]
+    InvalidFreezeDried(&'static str),
+    #[error("Invalid chunk dump: {0}")]
+    InvalidChunkDump(String),
 }
 
 pub(crate) use get_type;
@@ -110,7 +131,8 @@ impl<W: World> AxolotlGame<W> {
                 "Data dump not found",
             )));
         }
-        let data_registries = AxolotlDataRegistries::new(&config.data_dump)?;
+        let mut data_registries = AxolotlDataRegistries::new(&config.data_dump)?;
+        data_registries.freeze();
         let density_loader = AxolotlDensityLoader(SimpleRegistry::load_from_path(
             config
                 .data_dump
@@ -121,9 +143,11 @@ impl<W: World> AxolotlGame<W> {
         )?);
 
         // TODO: Load Data Packs
+        let mut registries = AxolotlRegistries::new(&config.axolotl_data, &config.data_dump)?;
+        registries.freeze();
         Ok(Self {
             data_registries,
-            registries: AxolotlRegistries::new(&config.axolotl_data, &config.data_dump)?,
+            registries,
             density_loader,
             minecraft_version,
             axolotl_version,
@@ -136,6 +160,20 @@ impl<W: World> AxolotlGame<W> {
             key.get_key()
         ))
     }
+    /// The colors, sounds and particles of a biome. Used to build the biome registry codec
+    pub fn biome_effects(&self, key: impl NamespacedKey) -> Option<&Effects> {
+        self.registries
+            .biomes
+            .get_by_namespace(format!("{}:{}", key.get_namespace(), key.get_key()))
+            .map(|biome| &biome.effects)
+    }
+    /// The base temperature and temperature modifier of a biome. Used by [world::level::surface]
+    pub fn biome_climate(&self, key: impl NamespacedKey) -> Option<BiomeClimate> {
+        self.registries
+            .biomes
+            .get_by_namespace(format!("{}:{}", key.get_namespace(), key.get_key()))
+            .map(|biome| (biome.temperature, biome.temperature_modifier))
+    }
 }
 impl<W: World> Debug for AxolotlGame<W> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -147,6 +185,15 @@ impl<W: World> Debug for AxolotlGame<W> {
             .finish()
     }
 }
+impl<W: World> PaletteLookup for AxolotlGame<W> {
+    fn is_known_block(&self, key: &axolotl_api::OwnedNameSpaceKey) -> bool {
+        self.registries.blocks.get_by_namespace_key(key).is_some()
+    }
+
+    fn is_known_biome(&self, key: &axolotl_api::OwnedNameSpaceKey) -> bool {
+        self.registries.biomes.get_by_namespace_key(key).is_some()
+    }
+}
 impl<W: World> Game for AxolotlGame<W> {
     type Biome = DataPackBiome;
     type World = W;
@@ -178,6 +225,7 @@ impl<W: World> Game for AxolotlGame<W> {
 pub struct AxolotlRegistries<W: World> {
     pub biomes: SimpleRegistry<DataPackBiome>,
     pub blocks: SimpleRegistry<MinecraftBlock<AxolotlGame<W>>>,
+    pub items: SimpleRegistry<MinecraftItem<AxolotlGame<W>>>,
     pub chat_types: SimpleRegistry<AxolotlChatType>,
 }
 impl<W: World> Debug for AxolotlRegistries<W> {
@@ -186,6 +234,7 @@ impl<W: World> Debug for AxolotlRegistries<W> {
         f.debug_struct("AxolotlRegistries")
             .field("biomes", &self.biomes.values.len())
             .field("blocks", &self.blocks.values.len())
+            .field("items", &self.items.values.len())
             .field("chat_types", &self.chat_types.values.len())
             .field("World Type", &name)
             .finish()
@@ -200,15 +249,27 @@ impl<W: World> AxolotlRegistries<W> {
                 .join("minecraft")
                 .join("chat_type"),
         )?;
-        let materials = axolotl_items::load_materials(axolotl_data.as_ref().to_path_buf()).unwrap();
+        let materials = axolotl_items::load_materials(axolotl_data.as_ref().to_path_buf())?;
         let mut block_registry = SimpleRegistry::new();
         axolotl_items::load_blocks(
             axolotl_data.as_ref().to_path_buf(),
             data_dump.as_ref().to_path_buf(),
             &materials,
             &mut block_registry,
-        )
-        .unwrap();
+        )?;
+        let tags = data_dump
+            .as_ref()
+            .join("data")
+            .join("minecraft")
+            .join("tags");
+        block_registry.load_tags(tags.join("blocks"))?;
+        let mut item_registry = SimpleRegistry::new();
+        axolotl_items::load_items(
+            axolotl_data.as_ref().to_path_buf(),
+            &block_registry,
+            &mut item_registry,
+        )?;
+        item_registry.load_tags(tags.join("items"))?;
 
         Ok(AxolotlRegistries {
             biomes: SimpleRegistry::load_from_path(
@@ -220,10 +281,20 @@ impl<W: World> AxolotlRegistries<W> {
                     .join("biome"),
             )?,
             blocks: block_registry,
+            items: item_registry,
             chat_types,
         })
     }
 }
+impl<W: World> AxolotlRegistries<W> {
+    /// Freezes every registry. Ids can not change after this
+    pub fn freeze(&mut self) {
+        self.biomes.freeze();
+        self.blocks.freeze();
+        self.items.freeze();
+        self.chat_types.freeze();
+    }
+}
 impl<W: World> Registries<AxolotlGame<W>> for AxolotlRegistries<W> {
     type BiomeRegistry = SimpleRegistry<DataPackBiome>;
     type BlockRegistry = SimpleRegistry<MinecraftBlock<AxolotlGame<W>>>;
@@ -239,7 +310,7 @@ impl<W: World> Registries<AxolotlGame<W>> for AxolotlRegistries<W> {
     }
 
     fn get_item_registry(&self) -> &Self::ItemRegistry {
-        todo!()
+        &self.items
     }
 
     fn get_chat_type_registry(&self) -> &Self::ChatTypeRegistry {
@@ -251,15 +322,15 @@ impl<W: World> Registries<AxolotlGame<W>> for AxolotlRegistries<W> {
     }
 
     fn get_mut_block_registry(&mut self) -> &mut Self::BlockRegistry {
-        todo!()
+        &mut self.blocks
     }
 
     fn get_mut_item_registry(&mut self) -> &mut Self::ItemRegistry {
-        todo!()
+        &mut self.items
     }
 
     fn get_mut_chat_type_registry(&mut self) -> &mut Self::ChatTypeRegistry {
-        todo!()
+        &mut self.chat_types
     }
 }
 
@@ -278,6 +349,11 @@ impl Debug for AxolotlDataRegistries {
     }
 }
 impl AxolotlDataRegistries {
+    pub fn freeze(&mut self) {
+        self.noises.freeze();
+        self.noise_settings.freeze();
+        self.dimensions.freeze();
+    }
     pub fn new(data_dump: impl AsRef<Path>) -> Result<Self, Error> {
         let data_dump = data_dump.as_ref();
         let noises = SimpleRegistry::load_from_path(
@@ -333,6 +409,6 @@ impl DataRegistries for AxolotlDataRegistries {
     }
 
     fn get_mut_dimensions_registry(&mut self) -> &mut Self::DimensionRegistry {
-        todo!()
+        &mut self.dimensions
     }
 }
@@ -0,0 +1,165 @@
+//! Id tables for protocol versions other than the one the server runs.
+//!
+//! The registries only know the ids of [NATIVE_PROTOCOL]. Each other version gets a table that maps a native id to the id of that version.
+//! Tables are loaded from `<version>.json` files in [MAPPINGS_FOLDER]
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use ahash::AHashMap;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+pub const MAPPINGS_FOLDER: &str = "mappings";
+/// 1.19.2. Matches [crate::world::chunk::consts::DATA_VERSION]
+pub const NATIVE_PROTOCOL: i32 = 760;
+
+/// Native ids to the ids of another version. Indexed by the native id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdTable {
+    pub ids: Vec<u32>,
+    /// Used for ids the version does not have. Such as blocks added after it
+    pub fallback: u32,
+}
+impl IdTable {
+    /// An empty table leaves the ids unchanged
+    pub fn map(&self, id: u32) -> u32 {
+        if self.ids.is_empty() {
+            return id;
+        }
+        self.ids.get(id as usize).copied().unwrap_or(self.fallback)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// The tables of one protocol version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionMappings {
+    pub protocol_version: i32,
+    /// The name of the version. Such as `1.19`
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub block_states: IdTable,
+    #[serde(default)]
+    pub items: IdTable,
+    #[serde(default)]
+    pub biomes: IdTable,
+}
+
+/// Every loaded [VersionMappings]. Ids are unchanged for [NATIVE_PROTOCOL] and versions without a table
+#[derive(Debug, Clone)]
+pub struct ProtocolMappings {
+    pub native: i32,
+    versions: AHashMap<i32, VersionMappings>,
+}
+impl Default for ProtocolMappings {
+    fn default() -> Self {
+        Self {
+            native: NATIVE_PROTOCOL,
+            versions: AHashMap::new(),
+        }
+    }
+}
+impl ProtocolMappings {
+    /// Loads every json file in the folder. Missing folders load nothing
+    pub fn load(folder: impl AsRef<Path>) -> Result<Self, Error> {
+        let folder = folder.as_ref();
+        let mut mappings = Self::default();
+        if !folder.exists() {
+            return Ok(mappings);
+        }
+        for entry in folder.read_dir()? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "json")
+            {
+                continue;
+            }
+            let version: VersionMappings =
+                serde_json::from_reader(BufReader::new(File::open(&path)?))?;
+            debug!(
+                "Loaded mappings for {} ({}) from {}",
+                version.name,
+                version.protocol_version,
+                path.display()
+            );
+            mappings.insert(version);
+        }
+        Ok(mappings)
+    }
+    /// Replaces the tables of the version
+    pub fn insert(&mut self, mappings: VersionMappings) {
+        if mappings.protocol_version == self.native {
+            warn!("Ignoring mappings for the native protocol");
+            return;
+        }
+        self.versions.insert(mappings.protocol_version, mappings);
+    }
+    pub fn get(&self, version: i32) -> Option<&VersionMappings> {
+        self.versions.get(&version)
+    }
+    /// True if players of the version can be translated to
+    pub fn supports(&self, version: i32) -> bool {
+        version == self.native || self.versions.contains_key(&version)
+    }
+    pub fn versions(&self) -> impl Iterator<Item = &VersionMappings> {
+        self.versions.values()
+    }
+
+    pub fn map_block_state(&self, version: i32, state: u32) -> u32 {
+        self.get(version)
+            .map_or(state, |mappings| mappings.block_states.map(state))
+    }
+
+    pub fn map_item(&self, version: i32, item: u32) -> u32 {
+        self.get(version)
+            .map_or(item, |mappings| mappings.items.map(item))
+    }
+
+    pub fn map_biome(&self, version: i32, biome: u32) -> u32 {
+        self.get(version)
+            .map_or(biome, |mappings| mappings.biomes.map(biome))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::mapping::{ProtocolMappings, VersionMappings, NATIVE_PROTOCOL};
+
+    #[test]
+    pub fn test_map_block_state() {
+        let version: VersionMappings = serde_json::from_str(
+            r#"{
+                "protocol_version": 759,
+                "name": "1.19",
+                "block_states": { "ids": [0, 1, 5, 2], "fallback": 1 }
+            }"#,
+        )
+        .unwrap();
+        let mut mappings = ProtocolMappings::default();
+        mappings.insert(version);
+
+        assert!(mappings.supports(759));
+        assert!(mappings.supports(NATIVE_PROTOCOL));
+        assert!(!mappings.supports(758));
+        assert_eq!(mappings.map_block_state(759, 2), 5);
+        // Missing from the version
+        assert_eq!(mappings.map_block_state(759, 100), 1);
+        // Untouched
+        assert_eq!(mappings.map_block_state(NATIVE_PROTOCOL, 2), 2);
+        // No item table
+        assert_eq!(mappings.map_item(759, 7), 7);
+    }
+}
@@ -0,0 +1,248 @@
+//! Builds the resource and data packs a server sends to its clients
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+use axolotl_api::NamespacedKey;
+
+use crate::Error;
+
+/// The pack format of Minecraft 1.19.3 resource packs
+pub const RESOURCE_PACK_FORMAT: u32 = 12;
+/// The pack format of Minecraft 1.19.3 data packs
+pub const DATA_PACK_FORMAT: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackKind {
+    Resource,
+    Data,
+}
+impl PackKind {
+    pub fn pack_format(&self) -> u32 {
+        match self {
+            PackKind::Resource => RESOURCE_PACK_FORMAT,
+            PackKind::Data => DATA_PACK_FORMAT,
+        }
+    }
+    /// The folder the content is placed in
+    pub fn root(&self) -> &'static str {
+        match self {
+            PackKind::Resource => "assets",
+            PackKind::Data => "data",
+        }
+    }
+}
+
+/// The `pack` section of `pack.mcmeta`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackMeta {
+    pub pack_format: u32,
+    pub description: String,
+}
+#[derive(Serialize)]
+struct PackMcMeta<'a> {
+    pack: &'a PackMeta,
+}
+
+/// Custom blocks and items add their models, textures and data with this
+pub trait PackContent {
+    fn add_to_pack(&self, pack: &mut PackBuilder) -> Result<(), Error>;
+}
+
+/// Collects the files of a pack. Files are sorted so the same content always has the same hash
+#[derive(Debug, Clone)]
+pub struct PackBuilder {
+    pub kind: PackKind,
+    pub meta: PackMeta,
+    files: BTreeMap<String, Vec<u8>>,
+}
+impl PackBuilder {
+    pub fn new(kind: PackKind, description: impl Into<String>) -> Self {
+        Self {
+            kind,
+            meta: PackMeta {
+                pack_format: kind.pack_format(),
+                description: description.into(),
+            },
+            files: BTreeMap::new(),
+        }
+    }
+    /// Adds a file relative to the root of the zip. Replaces any file with the same path
+    pub fn add_file(&mut self, path: impl Into<String>, data: impl Into<Vec<u8>>) -> &mut Self {
+        self.files.insert(path.into(), data.into());
+        self
+    }
+    pub fn add_json(
+        &mut self,
+        path: impl Into<String>,
+        value: &impl Serialize,
+    ) -> Result<&mut Self, Error> {
+        let data = serde_json::to_vec(value)?;
+        Ok(self.add_file(path, data))
+    }
+    /// Adds a file to `assets/<namespace>/<folder>/<key>` or `data/<namespace>/<folder>/<key>`
+    pub fn add_namespaced(
+        &mut self,
+        folder: &str,
+        key: &impl NamespacedKey,
+        extension: &str,
+        data: impl Into<Vec<u8>>,
+    ) -> &mut Self {
+        let path = format!(
+            "{}/{}/{}/{}.{}",
+            self.kind.root(),
+            key.get_namespace(),
+            folder,
+            key.get_key(),
+            extension
+        );
+        self.add_file(path, data)
+    }
+    pub fn add_block_model(
+        &mut self,
+        key: &impl NamespacedKey,
+        model: &impl Serialize,
+    ) -> Result<&mut Self, Error> {
+        let data = serde_json::to_vec(model)?;
+        Ok(self.add_namespaced("models/block", key, "json", data))
+    }
+    pub fn add_item_model(
+        &mut self,
+        key: &impl NamespacedKey,
+        model: &impl Serialize,
+    ) -> Result<&mut Self, Error> {
+        let data = serde_json::to_vec(model)?;
+        Ok(self.add_namespaced("models/item", key, "json", data))
+    }
+    pub fn add_blockstates(
+        &mut self,
+        key: &impl NamespacedKey,
+        blockstates: &impl Serialize,
+    ) -> Result<&mut Self, Error> {
+        let data = serde_json::to_vec(blockstates)?;
+        Ok(self.add_namespaced("blockstates", key, "json", data))
+    }
+    /// `folder` is such as `block` or `item`. The data is a PNG
+    pub fn add_texture(
+        &mut self,
+        folder: &str,
+        key: &impl NamespacedKey,
+        png: impl Into<Vec<u8>>,
+    ) -> &mut Self {
+        self.add_namespaced(&format!("textures/{}", folder), key, "png", png)
+    }
+    pub fn add_content(&mut self, content: &impl PackContent) -> Result<&mut Self, Error> {
+        content.add_to_pack(self)?;
+        Ok(self)
+    }
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+    /// Zips the pack and hashes it
+    pub fn build(&self) -> Result<BuiltPack, Error> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        // A fixed time keeps the hash stable between builds
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(DateTime::default());
+        zip.start_file("pack.mcmeta", options)?;
+        zip.write_all(&serde_json::to_vec(&PackMcMeta { pack: &self.meta })?)?;
+        for (path, data) in self.files.iter() {
+            zip.start_file(path.as_str(), options)?;
+            zip.write_all(data)?;
+        }
+        let data = zip.finish()?.into_inner();
+        let sha1 = Sha1::digest(&data).into();
+        Ok(BuiltPack {
+            kind: self.kind,
+            meta: self.meta.clone(),
+            data,
+            sha1,
+        })
+    }
+}
+
+/// A zipped pack ready to be hosted
+#[derive(Debug, Clone)]
+pub struct BuiltPack {
+    pub kind: PackKind,
+    pub meta: PackMeta,
+    pub data: Vec<u8>,
+    pub sha1: [u8; 20],
+}
+impl BuiltPack {
+    /// The hash in the format sent to the client
+    pub fn sha1_hex(&self) -> String {
+        self.sha1
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, &self.data)?;
+        Ok(())
+    }
+    /// What the server sends to clients once the pack is hosted at the url
+    pub fn advertise(
+        &self,
+        url: impl Into<String>,
+        required: bool,
+        prompt: Option<String>,
+    ) -> PackAdvertisement {
+        PackAdvertisement {
+            url: url.into(),
+            hash: self.sha1_hex(),
+            required,
+            prompt,
+        }
+    }
+}
+
+/// The contents of the resource pack packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackAdvertisement {
+    pub url: String,
+    /// The lowercase hex SHA-1 of the zip
+    pub hash: String,
+    /// The client is kicked if it declines
+    pub required: bool,
+    /// A chat component shown when asking the player
+    pub prompt: Option<String>,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::Cursor;
+
+    use axolotl_api::OwnedNameSpaceKey;
+
+    use crate::pack::{PackBuilder, PackKind};
+
+    #[test]
+    pub fn test_build() {
+        let key: OwnedNameSpaceKey = "example:ruby_block".parse().unwrap();
+        let mut pack = PackBuilder::new(PackKind::Resource, "Example");
+        pack.add_block_model(&key, &serde_json::json!({"parent": "block/cube_all"}))
+            .unwrap();
+        assert!(pack.contains("assets/example/models/block/ruby_block.json"));
+
+        let built = pack.build().unwrap();
+        assert_eq!(built.sha1, pack.build().unwrap().sha1);
+        assert_eq!(built.sha1_hex().len(), 40);
+        let mut zip = zip::ZipArchive::new(Cursor::new(built.data)).unwrap();
+        assert!(zip.by_name("pack.mcmeta").is_ok());
+    }
+}
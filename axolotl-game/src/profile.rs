@@ -0,0 +1,240 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ahash::AHashMap;
+use log::debug;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::player::offline::offline_uuid;
+use axolotl_api::player::profile::{GameProfile, ProfileProperty};
+
+use crate::Error;
+
+pub const PROFILE_CACHE_FILE: &str = "axolotl_profiles.json";
+/// Skins change rarely. Vanilla caches names for a month
+pub const DEFAULT_PROFILE_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedProfile {
+    pub profile: GameProfile,
+    /// Seconds since the unix epoch
+    pub expires: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|value| value.as_secs())
+        .unwrap_or_default()
+}
+
+/// Profiles and skins of players that have joined. Keyed by uuid.
+///
+/// Filled on join so the skins can be sent to other players and to offline mode servers
+#[derive(Debug)]
+pub struct ProfileCache {
+    profiles: RwLock<AHashMap<Uuid, CachedProfile>>,
+    pub ttl: Duration,
+    /// None if the cache is never saved
+    pub path: Option<PathBuf>,
+}
+impl ProfileCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            profiles: RwLock::new(AHashMap::new()),
+            ttl,
+            path: None,
+        }
+    }
+    /// Loads [PROFILE_CACHE_FILE] from the folder. Expired profiles are dropped
+    pub fn open(folder: impl AsRef<Path>, ttl: Duration) -> Result<Self, Error> {
+        let path = folder.as_ref().join(PROFILE_CACHE_FILE);
+        let mut cache = Self::new(ttl);
+        if path.exists() {
+            let profiles: Vec<CachedProfile> =
+                serde_json::from_reader(BufReader::new(File::open(&path)?))?;
+            let now = now();
+            cache.profiles.get_mut().extend(
+                profiles
+                    .into_iter()
+                    .filter(|cached| cached.expires > now)
+                    .map(|cached| (cached.profile.id, cached)),
+            );
+            debug!("Loaded {} cached profiles", cache.len());
+        }
+        cache.path = Some(path);
+        Ok(cache)
+    }
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let profiles: Vec<CachedProfile> = self.profiles.read().values().cloned().collect();
+        serde_json::to_writer(BufWriter::new(File::create(path)?), &profiles)?;
+        Ok(())
+    }
+    /// Replaces the cached profile and restarts its TTL
+    pub fn insert(&self, profile: GameProfile) {
+        let expires = now() + self.ttl.as_secs();
+        self.profiles
+            .write()
+            .insert(profile.id, CachedProfile { profile, expires });
+    }
+    /// None if missing or expired
+    pub fn get(&self, id: &Uuid) -> Option<GameProfile> {
+        self.profiles
+            .read()
+            .get(id)
+            .filter(|cached| cached.expires > now())
+            .map(|cached| cached.profile.clone())
+    }
+    pub fn get_by_name(&self, name: &str) -> Option<GameProfile> {
+        let now = now();
+        self.profiles
+            .read()
+            .values()
+            .find(|cached| cached.expires > now && cached.profile.name.eq_ignore_ascii_case(name))
+            .map(|cached| cached.profile.clone())
+    }
+    /// The skin to send with the player info of the player. Used by the entity tracker
+    pub fn textures(&self, id: &Uuid) -> Option<ProfileProperty> {
+        self.get(id).and_then(|profile| profile.textures().cloned())
+    }
+    /// Called when a player joins. See [Minecraft19PlayerAccess::join](crate::world::level::accessor::v_19::player::Minecraft19PlayerAccess::join)
+    ///
+    /// A profile with textures replaces the cached one. One without gets the cached textures.
+    /// Offline mode logins have the offline uuid so their textures are found by name
+    pub fn on_join(&self, mut profile: GameProfile) -> GameProfile {
+        if profile.textures().is_some() {
+            self.insert(profile.clone());
+            return profile;
+        }
+        let textures = self.textures(&profile.id).or_else(|| {
+            if profile.id != offline_uuid(&profile.name) {
+                return None;
+            }
+            self.get_by_name(&profile.name)
+                .and_then(|cached| cached.textures().cloned())
+        });
+        if let Some(textures) = textures {
+            profile.set_property(textures);
+        }
+        profile
+    }
+    /// Every profile that has not expired
+    pub fn profiles(&self) -> Vec<GameProfile> {
+        let now = now();
+        self.profiles
+            .read()
+            .values()
+            .filter(|cached| cached.expires > now)
+            .map(|cached| cached.profile.clone())
+            .collect()
+    }
+    pub fn remove(&self, id: &Uuid) -> Option<GameProfile> {
+        self.profiles
+            .write()
+            .remove(id)
+            .map(|cached| cached.profile)
+    }
+    /// Drops the expired profiles. Returns the number removed
+    pub fn purge_expired(&self) -> usize {
+        let now = now();
+        let mut profiles = self.profiles.write();
+        let before = profiles.len();
+        profiles.retain(|_, cached| cached.expires > now);
+        before - profiles.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.read().is_empty()
+    }
+}
+impl Default for ProfileCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROFILE_TTL)
+    }
+}
+
+/// Maps the online uuid of a player to the uuid they get with online mode disabled
+#[derive(Debug, Clone, Default)]
+pub struct IdentityMap {
+    online_to_offline: AHashMap<Uuid, Uuid>,
+    offline_to_online: AHashMap<Uuid, Uuid>,
+}
+impl IdentityMap {
+    /// Uses the names of the online profiles
+    pub fn from_profiles(profiles: impl IntoIterator<Item = GameProfile>) -> Self {
+        let mut map = Self::default();
+        for profile in profiles {
+            map.insert(profile.id, &profile.name);
+        }
+        map
+    }
+    pub fn insert(&mut self, online: Uuid, name: &str) {
+        let offline = offline_uuid(name);
+        self.online_to_offline.insert(online, offline);
+        self.offline_to_online.insert(offline, online);
+    }
+    pub fn offline(&self, online: &Uuid) -> Option<Uuid> {
+        self.online_to_offline.get(online).copied()
+    }
+    pub fn online(&self, offline: &Uuid) -> Option<Uuid> {
+        self.offline_to_online.get(offline).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.online_to_offline.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.online_to_offline.is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use axolotl_api::player::offline::offline_uuid;
+    use axolotl_api::player::profile::{GameProfile, ProfileProperty, TEXTURES_PROPERTY};
+
+    use crate::profile::ProfileCache;
+
+    #[test]
+    pub fn test_on_join() {
+        let cache = ProfileCache::default();
+        let id = Uuid::new_v4();
+        let mut online = GameProfile::new(id, "Player");
+        online.set_property(ProfileProperty {
+            name: TEXTURES_PROPERTY.to_string(),
+            value: "e30=".to_string(),
+            signature: None,
+        });
+        cache.on_join(online.clone());
+        assert_eq!(cache.get_by_name("player"), Some(online.clone()));
+        assert_eq!(cache.on_join(GameProfile::new(id, "Player")), online);
+        // An offline login of the same player
+        let offline = cache.on_join(GameProfile::new(offline_uuid("Player"), "Player"));
+        assert_eq!(offline.id, offline_uuid("Player"));
+        assert_eq!(offline.textures(), online.textures());
+        // Another player that happens to share the name is not given the skin
+        let other = cache.on_join(GameProfile::new(Uuid::new_v4(), "Player"));
+        assert_eq!(other.textures(), None);
+
+        let expired = ProfileCache::new(Duration::ZERO);
+        expired.insert(online);
+        assert_eq!(expired.get(&id), None);
+        assert_eq!(expired.purge_expired(), 1);
+    }
+}
@@ -4,7 +4,7 @@ use ahash::AHashMap;
 use log::warn;
 use serde::de::DeserializeOwned;
 use serde::ser::SerializeMap;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use axolotl_api::data::{ForPacket, PacketVersion};
 use axolotl_api::game::Registry;
@@ -21,35 +21,178 @@ pub struct SerializeRegistry<'registry, T: Serialize> {
 #[derive(Debug, Default)]
 pub struct SimpleRegistry<T> {
     pub key_map: AHashMap<String, usize>,
-    pub values: Vec<T>,
+    /// Indexed by id. None for ids skipped by [Registry::register_with_id]
+    pub values: Vec<Option<T>>,
+    /// The key of each value. Indexed by id
+    pub keys: Vec<Option<String>>,
+    pub tags: AHashMap<String, Vec<usize>>,
     pub next_id: usize,
     pub name: Option<String>,
+    pub frozen: bool,
 }
 impl<T> SimpleRegistry<T> {
     pub fn new() -> Self {
         Self {
             key_map: Default::default(),
             values: vec![],
+            keys: vec![],
+            tags: Default::default(),
             next_id: 0,
             name: None,
+            frozen: false,
         }
     }
+    fn assert_not_frozen(&self, namespace: &str) {
+        if self.frozen {
+            panic!(
+                "Attempted to register {} in the frozen registry {}",
+                namespace,
+                self.name.as_deref().unwrap_or("unnamed")
+            );
+        }
+    }
+    /// Adds the value with the id to the tag
+    pub fn add_to_tag(&mut self, tag: impl Into<String>, id: usize) {
+        let values = self.tags.entry(tag.into()).or_default();
+        if !values.contains(&id) {
+            values.push(id);
+        }
+    }
+    /// Loads the tags from a data pack folder. Such as `data/minecraft/tags/blocks`.
+    ///
+    /// The namespace is the folder above `tags`.
+    /// Tags may reference other tags with `#`. Unknown entries are skipped
+    pub fn load_tags(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let namespace = tag_namespace(path).unwrap_or_else(|| {
+            warn!("No namespace found for {:?}. Using minecraft", path);
+            "minecraft"
+        });
+        let mut raw_tags = AHashMap::new();
+        read_tag_folder(path, namespace, "", &mut raw_tags)?;
+        for tag in raw_tags.keys() {
+            let mut ids = Vec::new();
+            self.resolve_tag(tag, &raw_tags, &mut Vec::new(), &mut ids);
+            for id in ids {
+                self.add_to_tag(tag.clone(), id);
+            }
+        }
+        Ok(())
+    }
+    fn resolve_tag(
+        &self,
+        tag: &str,
+        raw_tags: &AHashMap<String, RawTag>,
+        visited: &mut Vec<String>,
+        ids: &mut Vec<usize>,
+    ) {
+        if visited.iter().any(|value| value == tag) {
+            warn!("Tag {} references itself", tag);
+            return;
+        }
+        let Some(raw_tag) = raw_tags.get(tag) else {
+            warn!("Unknown tag {}", tag);
+            return;
+        };
+        visited.push(tag.to_string());
+        for value in raw_tag.values.iter() {
+            let key = value.id();
+            if let Some(nested) = key.strip_prefix('#') {
+                self.resolve_tag(nested, raw_tags, visited, ids);
+            } else if let Some(id) = self.key_map.get(key) {
+                if !ids.contains(id) {
+                    ids.push(*id);
+                }
+            } else if value.required() {
+                warn!("Tag {} contains unknown value {}", tag, key);
+            }
+        }
+        visited.pop();
+    }
+}
+#[derive(Debug, Deserialize)]
+struct RawTag {
+    #[serde(default)]
+    values: Vec<RawTagValue>,
+}
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawTagValue {
+    Id(String),
+    Optional { id: String, required: bool },
+}
+impl RawTagValue {
+    fn id(&self) -> &str {
+        match self {
+            RawTagValue::Id(id) => id,
+            RawTagValue::Optional { id, .. } => id,
+        }
+    }
+    fn required(&self) -> bool {
+        match self {
+            RawTagValue::Id(_) => true,
+            RawTagValue::Optional { required, .. } => *required,
+        }
+    }
+}
+/// The folder name above `tags`. `data/example/tags/blocks` is in the `example` namespace
+fn tag_namespace(path: &Path) -> Option<&str> {
+    let components: Vec<_> = path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect();
+    let tags = components
+        .iter()
+        .rposition(|component| *component == "tags")?;
+    components.get(tags.checked_sub(1)?).copied()
+}
+fn read_tag_folder(
+    path: &Path,
+    namespace: &str,
+    parent: &str,
+    tags: &mut AHashMap<String, RawTag>,
+) -> Result<(), Error> {
+    if !path.is_dir() {
+        warn!("Path {:?} is not a directory", path);
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if entry.file_type()?.is_dir() {
+            read_tag_folder(&path, namespace, &format!("{parent}{name}/"), tags)?;
+        } else if let Some(name) = name.strip_suffix(".json") {
+            let file = std::fs::File::open(&path)?;
+            match serde_json::from_reader(file) {
+                Ok(tag) => {
+                    tags.insert(format!("{namespace}:{parent}{name}"), tag);
+                }
+                Err(e) => {
+                    warn!("Failed to parse tag {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+    Ok(())
 }
 impl<T: ForPacket> SimpleRegistry<T> {
     pub fn as_packet_array(&self) -> Vec<T::PacketVersion<'_>> {
         let mut values: Vec<T::PacketVersion<'_>> = self
             .key_map
             .iter()
-            .map(|(key, id)| {
+            .filter_map(|(key, id)| {
                 let mut v = key.splitn(2, ':');
                 let id = *id;
-                self.values[id].as_packet_version(
+                Some(self.values.get(id)?.as_ref()?.as_packet_version(
                     id,
                     NameSpaceKey::Ref(NameSpaceRef::new(
                         v.next().expect("Illegal Namespace Key in Registry"),
                         v.next().expect("Illegal Namespace Key in Registry"),
                     )),
-                )
+                ))
             })
             .collect();
         values.sort_by(|a, b| a.id().cmp(b.id()));
@@ -150,40 +293,184 @@ impl<T: DeserializeOwned> SimpleRegistry<T> {
     }
 }
 
+/// Skips the ids without a value
+pub struct SimpleRegistryIter<'registry, T> {
+    keys: std::slice::Iter<'registry, Option<String>>,
+    values: std::iter::Enumerate<std::slice::Iter<'registry, Option<T>>>,
+}
+impl<'registry, T> Iterator for SimpleRegistryIter<'registry, T> {
+    type Item = (usize, &'registry str, &'registry T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (id, value) = self.values.next()?;
+            let key = self.keys.next()?;
+            if let (Some(key), Some(value)) = (key, value) {
+                return Some((id, key.as_str(), value));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.values.size_hint().1)
+    }
+}
+
 impl<T> Registry<T> for SimpleRegistry<T> {
     fn register(&mut self, namespace: impl Into<String>, item: T) -> usize {
         let namespace = namespace.into();
+        self.assert_not_frozen(&namespace);
         let id = self.next_id;
         self.next_id += 1;
-        self.key_map.insert(namespace, id);
-        self.values.push(item);
+        self.key_map.insert(namespace.clone(), id);
+        self.keys.push(Some(namespace));
+        self.values.push(Some(item));
         id
     }
 
+    /// Ids past the end leave a gap. A taken id is replaced with the next free id
     fn register_with_id(&mut self, namespace: impl Into<String>, id: usize, item: T) {
-        if id != self.next_id {
-            if id < self.next_id {
-                warn!("Tried to register an item with an id that is already taken");
-            } else {
-                todo!("Handle registering an item with an id that is too high");
-            }
+        let namespace = namespace.into();
+        self.assert_not_frozen(&namespace);
+        if self.values.get(id).is_some_and(Option::is_some) {
+            let next_id = self.register(namespace, item);
+            warn!("Id {} is already taken. Registered as {}", id, next_id);
+            return;
         }
-        self.key_map.insert(namespace.into(), self.next_id);
-        self.values.push(item);
-        self.next_id += 1;
+        if id >= self.next_id {
+            self.values.resize_with(id + 1, || None);
+            self.keys.resize_with(id + 1, || None);
+            self.next_id = id + 1;
+        }
+        self.key_map.insert(namespace.clone(), id);
+        self.keys[id] = Some(namespace);
+        self.values[id] = Some(item);
     }
 
     fn get_by_id(&self, id: usize) -> Option<&T> {
-        self.values.get(id)
+        self.values.get(id)?.as_ref()
     }
 
     fn get_id(&self, namespace: impl AsRef<str>) -> Option<usize> {
         self.key_map.get(namespace.as_ref()).copied()
     }
 
+    fn get_key(&self, id: usize) -> Option<&str> {
+        self.keys.get(id)?.as_deref()
+    }
+
     fn get_by_namespace(&self, namespace: impl AsRef<str>) -> Option<&T> {
         self.key_map
             .get(namespace.as_ref())
-            .and_then(|id| self.values.get(*id))
+            .and_then(|id| self.get_by_id(*id))
+    }
+
+    fn iter<'registry>(
+        &'registry self,
+    ) -> Box<dyn Iterator<Item = (usize, &'registry str, &'registry T)> + 'registry>
+    where
+        T: 'registry,
+    {
+        Box::new(SimpleRegistryIter {
+            keys: self.keys.iter(),
+            values: self.values.iter().enumerate(),
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.values.iter().filter(|value| value.is_some()).count()
+    }
+
+    fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn get_tag(&self, tag: &str) -> Option<&[usize]> {
+        self.tags.get(tag).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::game::Registry;
+
+    use crate::registry::{tag_namespace, SimpleRegistry};
+
+    #[test]
+    pub fn test_ids_and_iteration() {
+        let mut registry = SimpleRegistry::new();
+        registry.register("minecraft:stone", 1);
+        registry.register("minecraft:dirt", 2);
+        registry.add_to_tag("minecraft:natural", 1);
+        assert_eq!(registry.get_key(1), Some("minecraft:dirt"));
+        assert_eq!(registry.get_id("minecraft:stone"), Some(0));
+        let entries: Vec<_> = registry.iter().collect();
+        assert_eq!(
+            entries,
+            vec![(0, "minecraft:stone", &1), (1, "minecraft:dirt", &2)]
+        );
+        assert_eq!(registry.get_tag("minecraft:natural"), Some(&[1][..]));
+        registry.freeze();
+        assert!(registry.is_frozen());
+    }
+    #[test]
+    pub fn test_register_with_id() {
+        let mut registry = SimpleRegistry::new();
+        registry.register_with_id("minecraft:stone", 0, 1);
+        registry.register_with_id("minecraft:dirt", 3, 2);
+        assert_eq!(registry.get_by_id(3), Some(&2));
+        assert_eq!(registry.get_by_id(1), None);
+        assert_eq!(registry.get_key(2), None);
+        assert_eq!(registry.len(), 2);
+        let entries: Vec<_> = registry.iter().collect();
+        assert_eq!(
+            entries,
+            vec![(0, "minecraft:stone", &1), (3, "minecraft:dirt", &2)]
+        );
+        // Fills the gap
+        registry.register_with_id("minecraft:sand", 1, 3);
+        assert_eq!(registry.get_id("minecraft:sand"), Some(1));
+        // Taken. Moved to the end
+        registry.register_with_id("minecraft:gravel", 0, 4);
+        assert_eq!(registry.get_id("minecraft:gravel"), Some(4));
+        assert_eq!(registry.register("minecraft:clay", 5), 5);
+    }
+    #[test]
+    pub fn test_len_after_reregister() {
+        let mut registry = SimpleRegistry::new();
+        registry.register("minecraft:stone", 1);
+        registry.register("minecraft:stone", 2);
+        assert_eq!(registry.len(), registry.iter().count());
+    }
+    #[test]
+    pub fn test_load_tags_namespace() {
+        let path = std::path::Path::new("data/example/tags/blocks");
+        assert_eq!(tag_namespace(path), Some("example"));
+        assert_eq!(tag_namespace(std::path::Path::new("blocks")), None);
+
+        let folder = std::env::temp_dir()
+            .join("axolotl_registry_tags")
+            .join("data")
+            .join("example")
+            .join("tags")
+            .join("blocks");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("shiny.json"), r#"{"values": ["example:gem"]}"#).unwrap();
+        let mut registry = SimpleRegistry::new();
+        registry.register("example:gem", 1);
+        registry.load_tags(&folder).unwrap();
+        assert_eq!(registry.get_tag("example:shiny"), Some(&[0][..]));
+        assert_eq!(registry.get_tag("minecraft:shiny"), None);
+    }
+    #[test]
+    #[should_panic]
+    pub fn test_register_frozen() {
+        let mut registry = SimpleRegistry::new();
+        registry.freeze();
+        registry.register("minecraft:stone", 1);
     }
 }
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::mem;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use axolotl_api::world::{BlockFace, BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::NamespacedId;
+use axolotl_items::blocks::redstone;
+use axolotl_items::blocks::InnerMinecraftBlock;
+
+use crate::world::chunk::placed_block::PlacedBlock;
+
+/// A pending update to a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockUpdate {
+    /// A neighbor changed. The block should check if it can still exist. Such as a torch losing its support
+    ///
+    /// Observers ignore these
+    Neighbor {
+        position: BlockPosition,
+        source: BlockPosition,
+    },
+    /// The state of the block changed. Observers watching the position fire
+    StateChange { position: BlockPosition },
+}
+impl BlockUpdate {
+    /// The block being updated
+    pub fn position(&self) -> BlockPosition {
+        match self {
+            BlockUpdate::Neighbor { position, .. } => *position,
+            BlockUpdate::StateChange { position } => *position,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BlockUpdateQueue {
+    queue: Mutex<VecDeque<BlockUpdate>>,
+}
+impl BlockUpdateQueue {
+    pub fn push(&self, update: BlockUpdate) {
+        self.queue.lock().push_back(update);
+    }
+    /// Sends a neighbor update to all six blocks around the source
+    pub fn neighbor_changed(&self, source: BlockPosition) {
+        let mut queue = self.queue.lock();
+        for face in BlockFace::ALL {
+            queue.push_back(BlockUpdate::Neighbor {
+                position: source.relative(face),
+                source,
+            });
+        }
+    }
+    /// The block at the position changed state.
+    ///
+    /// Queues the observer visible change and updates the neighbors
+    pub fn state_changed(&self, position: BlockPosition) {
+        self.push(BlockUpdate::StateChange { position });
+        self.neighbor_changed(position);
+    }
+
+    /// The queued updates to blocks in the chunk. In the order they will run
+    pub fn pending_in(&self, chunk: ChunkPos) -> Vec<BlockUpdate> {
+        self.queue
+            .lock()
+            .iter()
+            .filter(|update| update.position().chunk_pos() == chunk)
+            .copied()
+            .collect()
+    }
+
+    pub fn take(&self) -> VecDeque<BlockUpdate> {
+        mem::take(&mut *self.queue.lock())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().is_empty()
+    }
+}
+
+/// Finds every observer watching the changed position.
+///
+/// An observer watches the block its face points at
+pub fn observers_of<W: World>(
+    changed: BlockPosition,
+    get_block: impl Fn(BlockPosition) -> Option<PlacedBlock<W>>,
+) -> Vec<BlockPosition> {
+    let mut observers = Vec::new();
+    for face in BlockFace::ALL {
+        let position = changed.relative(face);
+        let Some(block) = get_block(position) else {
+            continue;
+        };
+        if block.block.key() != "observer" {
+            continue;
+        }
+        let facing = block.vanilla_state().and_then(redstone::get_facing);
+        if facing == Some(face.opposite()) {
+            observers.push(position);
+        }
+    }
+    observers
+}
+
+/// Moves a note block to its next note. None if the block is not a note block
+pub fn cycle_note_block<W: World>(block: &PlacedBlock<W>) -> Option<PlacedBlock<W>> {
+    let InnerMinecraftBlock::GenericBlock(generic) = block.block.as_ref() else {
+        return None;
+    };
+    if generic.0.key != "note_block" {
+        return None;
+    }
+    let state = redstone::cycle_note(&generic.0.states, block.vanilla_state()?)?;
+    Some(PlacedBlock::with_state(block.block.clone(), state))
+}
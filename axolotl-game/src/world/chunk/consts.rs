@@ -25,3 +25,8 @@ pub const MAX_Y_SECTION: i8 = 19;
 pub const DATA_VERSION: i32 = 3120;
 
 pub const LONGS_PER_BLOC_SECTION: usize = 256;
+
+/// Biomes are stored in 4x4x4 cells
+pub const BIOMES_PER_SECTION: usize = 64;
+/// Biome palettes are indexed with at most this many bits
+pub const MAX_BITS_PER_BIOME: usize = 32;
@@ -0,0 +1,435 @@
+//! A readable JSON form of a chunk.
+//!
+//! [ChunkDump::from_chunk] captures the exact state of a chunk for bug reports. [ChunkDump::load_into] builds a chunk from a dump
+//! so tests can describe chunks as JSON fixtures. Fixtures can leave out `blocks` to fill a section with the first block of the palette
+//! and `state` to use the default state of a block.
+//!
+//! [ChunkDump::with_block_updates] adds the updates still queued for the chunk
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use axolotl_api::game::Registry;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::{NamespacedId, OwnedNameSpaceKey};
+use axolotl_items::blocks::generic_block::VanillaStateIdOrValue;
+use axolotl_world::chunk::compact_array::CompactArray;
+use minecraft_protocol::packets::play::client::chunk::GetVanillaId;
+
+use crate::world::block_update::{BlockUpdate, BlockUpdateQueue};
+use crate::world::chunk::consts::{BIOMES_PER_SECTION, MAX_BITS_PER_BIOME, SECTION_SIZE};
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
+use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+use crate::world::chunk::AxolotlChunk;
+use crate::{AxolotlGame, Error};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockDump {
+    pub name: String,
+    /// The vanilla state id. None is the default state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BiomeDump {
+    Single(String),
+    Full {
+        palette: Vec<String>,
+        bits_per_entry: u8,
+        data: Vec<u64>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionDump {
+    pub y: i8,
+    /// The number of each block in the section. Only informational, it is not read back
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub block_counts: BTreeMap<String, u32>,
+    /// Empty for a section of only air
+    #[serde(default)]
+    pub palette: Vec<BlockDump>,
+    /// An index into the palette for each block. Ordered `y << 8 | z << 4 | x`.
+    /// None fills the section with the first block of the palette
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<u16>>,
+    pub biomes: BiomeDump,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkDump {
+    pub x: i32,
+    pub z: i32,
+    /// The y above the highest block of each column. Ordered `z << 4 | x`. Only informational, it is not read back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub world_surface: Vec<i32>,
+    pub sections: Vec<SectionDump>,
+    /// The block updates queued for the chunk. Only informational, it is not read back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub block_updates: Vec<BlockUpdate>,
+    /// Chunks do not store block entities yet so this is always empty.
+    /// It is still written so a dump shows that none were captured
+    #[serde(default)]
+    pub block_entities: Vec<serde_json::Value>,
+}
+impl ChunkDump {
+    pub fn from_chunk<W: World>(chunk: &AxolotlChunk<W>) -> Self {
+        let min_y = chunk.height().min_y;
+        let mut world_surface = Vec::with_capacity(256);
+        for z in 0..16 {
+            for x in 0..16 {
+                world_surface.push(chunk.highest_block(x, z).map_or(min_y, |(y, _)| y + 1));
+            }
+        }
+        Self {
+            x: chunk.chunk_pos.x(),
+            z: chunk.chunk_pos.z(),
+            world_surface,
+            sections: chunk
+                .sections
+                .as_ref()
+                .iter()
+                .map(|section| {
+                    let (palette, blocks, block_counts) = dump_blocks(|index| {
+                        section.blocks.get_block(BlockPosition::new(
+                            x_of(index),
+                            y_of(index),
+                            z_of(index),
+                        ))
+                    });
+                    SectionDump {
+                        y: section.y,
+                        block_counts,
+                        palette,
+                        blocks,
+                        biomes: dump_biomes(&section.biomes),
+                    }
+                })
+                .collect(),
+            block_updates: Vec::new(),
+            block_entities: Vec::new(),
+        }
+    }
+    /// Adds the updates queued for this chunk
+    pub fn with_block_updates(mut self, queue: &BlockUpdateQueue) -> Self {
+        self.block_updates = queue.pending_in(ChunkPos::new(self.x, self.z));
+        self
+    }
+    /// Replaces the sections of the chunk with the ones in the dump. Sections not in the dump are left alone
+    pub fn load_into<W: World>(
+        &self,
+        game: &AxolotlGame<W>,
+        chunk: &mut AxolotlChunk<W>,
+    ) -> Result<(), Error> {
+        chunk.chunk_pos = ChunkPos::new(self.x, self.z);
+        for dump in &self.sections {
+            let palette = dump
+                .palette
+                .iter()
+                .map(|block| load_block(game, block))
+                .collect::<Result<Vec<_>, _>>()?;
+            let section_y = dump.y as i16 * 16;
+            let biomes = load_biomes(&dump.biomes)?;
+            let Some(section) = chunk.sections.get_mut(dump.y as i32) else {
+                return Err(Error::InvalidChunkDump(format!(
+                    "Section {} is outside of the world height",
+                    dump.y
+                )));
+            };
+            section.biomes = biomes;
+            section.blocks = AxolotlBlockSection::Empty;
+            for index in 0..SECTION_SIZE {
+                let block = match &dump.blocks {
+                    Some(blocks) => {
+                        let Some(&palette_index) = blocks.get(index) else {
+                            return Err(Error::InvalidChunkDump(format!(
+                                "Section {} has {} blocks",
+                                dump.y,
+                                blocks.len()
+                            )));
+                        };
+                        palette.get(palette_index as usize).ok_or_else(|| {
+                            Error::InvalidChunkDump(format!(
+                                "Section {} uses palette index {}",
+                                dump.y, palette_index
+                            ))
+                        })?
+                    }
+                    None => match palette.first() {
+                        Some(block) => block,
+                        None => break,
+                    },
+                };
+                let position =
+                    BlockPosition::new(x_of(index), section_y + y_of(index), z_of(index));
+                chunk.set_block(position, block.clone());
+            }
+        }
+        Ok(())
+    }
+}
+fn x_of(index: usize) -> i64 {
+    (index & 15) as i64
+}
+fn z_of(index: usize) -> i64 {
+    ((index >> 4) & 15) as i64
+}
+fn y_of(index: usize) -> i16 {
+    (index >> 8) as i16
+}
+
+fn name_of<W: World>(block: &PlacedBlock<W>) -> String {
+    format!("{}:{}", block.block.namespace(), block.block.key())
+}
+type DumpedBlocks = (Vec<BlockDump>, Option<Vec<u16>>, BTreeMap<String, u32>);
+fn dump_blocks<'a, W: World + 'a>(
+    block_at: impl Fn(usize) -> Option<&'a PlacedBlock<W>>,
+) -> DumpedBlocks {
+    let mut palette: Vec<BlockDump> = Vec::new();
+    let mut blocks = Vec::with_capacity(SECTION_SIZE);
+    let mut counts = BTreeMap::new();
+    for index in 0..SECTION_SIZE {
+        let block = match block_at(index) {
+            Some(block) => BlockDump {
+                name: name_of(block),
+                state: Some(block.get_vanilla_id() as u32),
+            },
+            None => BlockDump {
+                name: "minecraft:air".to_string(),
+                state: Some(0),
+            },
+        };
+        *counts.entry(block.name.clone()).or_default() += 1;
+        let palette_index = match palette.iter().position(|entry| entry == &block) {
+            Some(palette_index) => palette_index,
+            None => {
+                palette.push(block);
+                palette.len() - 1
+            }
+        };
+        blocks.push(palette_index as u16);
+    }
+    if palette.len() == 1 {
+        if palette[0].name == "minecraft:air" {
+            return (Vec::new(), None, BTreeMap::new());
+        }
+        return (palette, None, counts);
+    }
+    (palette, Some(blocks), counts)
+}
+fn dump_biomes(biomes: &AxolotlBiomeSection) -> BiomeDump {
+    match biomes {
+        AxolotlBiomeSection::SingleBiome(biome) => BiomeDump::Single(biome.to_string()),
+        AxolotlBiomeSection::Full {
+            biome_palette,
+            biomes,
+        } => BiomeDump::Full {
+            palette: biome_palette.iter().map(ToString::to_string).collect(),
+            bits_per_entry: biomes.bits_per_block as u8,
+            data: biomes.data.clone(),
+        },
+    }
+}
+
+fn load_block<W: World>(game: &AxolotlGame<W>, dump: &BlockDump) -> Result<PlacedBlock<W>, Error> {
+    let block = game
+        .registries
+        .blocks
+        .get_by_namespace(&dump.name)
+        .ok_or_else(|| Error::InvalidChunkDump(format!("Unknown block {}", dump.name)))?
+        .clone();
+    Ok(match dump.state {
+        Some(state) => PlacedBlock {
+            state: VanillaStateIdOrValue::Id(state as usize),
+            block,
+        },
+        None => PlacedBlock::from(block),
+    })
+}
+fn load_biome(biome: &str) -> Result<OwnedNameSpaceKey, Error> {
+    OwnedNameSpaceKey::from_str(biome)
+        .map_err(|_| Error::InvalidChunkDump(format!("Invalid biome {}", biome)))
+}
+fn load_biomes(dump: &BiomeDump) -> Result<AxolotlBiomeSection, Error> {
+    Ok(match dump {
+        BiomeDump::Single(biome) => AxolotlBiomeSection::SingleBiome(load_biome(biome)?),
+        BiomeDump::Full {
+            palette,
+            bits_per_entry,
+            data,
+        } => {
+            let bits = *bits_per_entry as usize;
+            if !(1..=MAX_BITS_PER_BIOME).contains(&bits) {
+                return Err(Error::InvalidChunkDump(format!(
+                    "Biomes use {} bits per entry",
+                    bits
+                )));
+            }
+            let longs = BIOMES_PER_SECTION.div_ceil(CompactArray::calc_values_per_u64(bits));
+            if data.len() != longs {
+                return Err(Error::InvalidChunkDump(format!(
+                    "Biomes have {} longs instead of {}",
+                    data.len(),
+                    longs
+                )));
+            }
+            let biomes = CompactArray::new_from_vec(bits, data.clone(), BIOMES_PER_SECTION);
+            if let Some(index) = biomes
+                .iter()
+                .take(BIOMES_PER_SECTION)
+                .find(|index| *index as usize >= palette.len())
+            {
+                return Err(Error::InvalidChunkDump(format!(
+                    "Biomes use palette index {}",
+                    index
+                )));
+            }
+            AxolotlBiomeSection::Full {
+                biome_palette: palette
+                    .iter()
+                    .map(|biome| load_biome(biome))
+                    .collect::<Result<_, _>>()?,
+                biomes,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_api::OwnedNameSpaceKey;
+    use axolotl_world::chunk::compact_array::CompactArray;
+
+    use crate::world::block_update::{BlockUpdate, BlockUpdateQueue};
+    use crate::world::chunk::dump::{BiomeDump, ChunkDump, SectionDump};
+    use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
+    use crate::world::chunk::AxolotlChunk;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+    use crate::Error;
+
+    #[test]
+    pub fn test_dump_empty_chunk() {
+        let chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(4, -2));
+        let dump = ChunkDump::from_chunk(&chunk);
+        assert_eq!(dump.sections.len(), 24);
+        assert!(dump.world_surface.iter().all(|y| *y == -64));
+        assert!(dump
+            .sections
+            .iter()
+            .all(|section| section.palette.is_empty() && section.blocks.is_none()));
+
+        let json = serde_json::to_string(&dump).unwrap();
+        assert_eq!(serde_json::from_str::<ChunkDump>(&json).unwrap(), dump);
+        let fixture: ChunkDump = serde_json::from_str(
+            r#"{"x": 0, "z": 0, "sections": [{"y": 0, "palette": [{"name": "minecraft:stone"}], "biomes": "minecraft:plains"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            fixture.sections[0].biomes,
+            BiomeDump::Single("minecraft:plains".to_string())
+        );
+    }
+
+    #[test]
+    pub fn test_round_trip() {
+        let game = test_game(&["stone", "dirt"]);
+        let stone = test_block(&game, "stone");
+        let dirt = test_block(&game, "dirt");
+        let plains = OwnedNameSpaceKey::new("minecraft".to_string(), "plains".to_string());
+        let mut chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(3, -1));
+        for y in -4..20 {
+            chunk.sections.get_mut(y).unwrap().biomes = AxolotlBiomeSection::new(plains.clone());
+        }
+        for x in 0..16 {
+            chunk.set_block(BlockPosition::new(x, -64, 0), stone.clone());
+            chunk.set_block(BlockPosition::new(x, 70, x), dirt.clone());
+        }
+        chunk.set_block(BlockPosition::new(4, 319, 9), stone.clone());
+        chunk.set_block(BlockPosition::new(5, 319, 9), dirt.clone());
+        let mut biomes = CompactArray::new(1, 64);
+        for index in 0..64u64 {
+            biomes.set(index, index % 3 / 2);
+        }
+        chunk.sections.get_mut(4).unwrap().biomes = AxolotlBiomeSection::Full {
+            biome_palette: vec![
+                plains,
+                OwnedNameSpaceKey::new("minecraft".to_string(), "desert".to_string()),
+            ],
+            biomes,
+        };
+
+        let dump = ChunkDump::from_chunk(&chunk);
+        let json = serde_json::to_string_pretty(&dump).unwrap();
+        let parsed: ChunkDump = serde_json::from_str(&json).unwrap();
+        let mut loaded = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+        parsed.load_into(&game, &mut loaded).unwrap();
+        assert_eq!(loaded.chunk_pos, ChunkPos::new(3, -1));
+        assert_eq!(ChunkDump::from_chunk(&loaded), dump);
+        assert_eq!(loaded.get_block(BlockPosition::new(5, 319, 9)), Some(&dirt));
+    }
+
+    #[test]
+    pub fn test_dump_block_updates() {
+        let queue = BlockUpdateQueue::default();
+        let position = BlockPosition::new(17, 64, 1);
+        queue.state_changed(position);
+        queue.state_changed(BlockPosition::new(100, 64, 100));
+        let chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(1, 0));
+        let dump = ChunkDump::from_chunk(&chunk).with_block_updates(&queue);
+        // The state change and the six neighbors
+        assert_eq!(dump.block_updates.len(), 7);
+        assert_eq!(dump.block_updates[0], BlockUpdate::StateChange { position });
+
+        let json = serde_json::to_value(&dump).unwrap();
+        assert_eq!(json["block_entities"], serde_json::json!([]));
+        assert_eq!(json["block_updates"][0]["type"], "state_change");
+        let parsed: ChunkDump = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, dump);
+    }
+
+    #[test]
+    pub fn test_invalid_biomes() {
+        let game = test_game(&["stone"]);
+        let load = |bits_per_entry: u8, data: Vec<u64>| {
+            let dump = ChunkDump {
+                x: 0,
+                z: 0,
+                world_surface: vec![],
+                sections: vec![SectionDump {
+                    y: 0,
+                    block_counts: Default::default(),
+                    palette: vec![],
+                    blocks: None,
+                    biomes: BiomeDump::Full {
+                        palette: vec!["minecraft:plains".to_string()],
+                        bits_per_entry,
+                        data,
+                    },
+                }],
+                block_updates: vec![],
+                block_entities: vec![],
+            };
+            dump.load_into(
+                &game,
+                &mut AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0)),
+            )
+        };
+        assert!(load(1, vec![0]).is_ok());
+        assert!(matches!(load(0, vec![0]), Err(Error::InvalidChunkDump(_))));
+        assert!(matches!(load(65, vec![0]), Err(Error::InvalidChunkDump(_))));
+        assert!(matches!(load(1, vec![]), Err(Error::InvalidChunkDump(_))));
+        assert!(matches!(
+            load(4, vec![0; 2]),
+            Err(Error::InvalidChunkDump(_))
+        ));
+        // The palette has no second biome
+        assert!(matches!(load(1, vec![2]), Err(Error::InvalidChunkDump(_))));
+    }
+}
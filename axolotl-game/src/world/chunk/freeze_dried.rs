@@ -0,0 +1,518 @@
+//! A compact binary chunk format for moving worlds between game nodes.
+//!
+//! Freeze dried chunks skip NBT. Each section is its palette followed by the packed longs the section already stores,
+//! so reading copies the longs straight into the section storage. Entities are kept as NBT.
+//!
+//! A stream is [MAGIC], [FORMAT_VERSION], then any number of chunks each starting with [CHUNK_MARKER] and finally [END_MARKER].
+//! Numbers are little endian
+use std::io::{Read, Write};
+
+use axolotl_nbt::serde_impl;
+use log::warn;
+
+use axolotl_api::game::Registry;
+use axolotl_api::world::World;
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::{NamespacedId, OwnedNameSpaceKey};
+use axolotl_items::blocks::generic_block::VanillaStateIdOrValue;
+use axolotl_world::chunk::compact_array::CompactArray;
+use axolotl_world::entity::RawEntities;
+
+use crate::world::chunk::consts::{
+    BIOMES_PER_SECTION, BITS_PER_BLOCK, MAX_BITS_PER_BIOME, SECTION_SIZE,
+};
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::pool::SectionPool;
+use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
+use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+use crate::world::chunk::AxolotlChunk;
+use crate::{AxolotlGame, Error};
+
+pub const MAGIC: [u8; 4] = *b"AXFD";
+pub const FORMAT_VERSION: u8 = 1;
+pub const CHUNK_MARKER: u8 = 1;
+pub const END_MARKER: u8 = 0;
+
+const EMPTY: u8 = 0;
+const SINGLE: u8 = 1;
+const FULL: u8 = 2;
+
+fn write_u8(w: &mut impl Write, value: u8) -> Result<(), Error> {
+    w.write_all(&[value])?;
+    Ok(())
+}
+fn write_u16(w: &mut impl Write, value: u16) -> Result<(), Error> {
+    w.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+fn write_u32(w: &mut impl Write, value: u32) -> Result<(), Error> {
+    w.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+fn write_str(w: &mut impl Write, value: &str) -> Result<(), Error> {
+    write_u16(w, value.len() as u16)?;
+    w.write_all(value.as_bytes())?;
+    Ok(())
+}
+fn write_longs(w: &mut impl Write, longs: &[u64]) -> Result<(), Error> {
+    write_u32(w, longs.len() as u32)?;
+    for long in longs {
+        w.write_all(&long.to_le_bytes())?;
+    }
+    Ok(())
+}
+fn read_u8(r: &mut impl Read) -> Result<u8, Error> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+fn read_u16(r: &mut impl Read) -> Result<u16, Error> {
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+fn read_u32(r: &mut impl Read) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+fn read_i32(r: &mut impl Read) -> Result<i32, Error> {
+    Ok(read_u32(r)? as i32)
+}
+fn read_str(r: &mut impl Read) -> Result<String, Error> {
+    let mut buf = vec![0u8; read_u16(r)? as usize];
+    r.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|_| Error::InvalidFreezeDried("Invalid UTF-8 in a name"))
+}
+/// Reads the longs into `into`. The stream must hold exactly as many longs as `into` already has
+fn read_longs_into(r: &mut impl Read, into: &mut [u64]) -> Result<(), Error> {
+    if read_u32(r)? as usize != into.len() {
+        return Err(Error::InvalidFreezeDried("Wrong number of longs"));
+    }
+    r.read_exact(bytemuck::cast_slice_mut(into))?;
+    for long in into.iter_mut() {
+        *long = u64::from_le(*long);
+    }
+    Ok(())
+}
+
+fn write_block(w: &mut impl Write, block: &PlacedBlock<impl World>) -> Result<(), Error> {
+    write_str(
+        w,
+        &format!("{}:{}", block.block.namespace(), block.block.key()),
+    )?;
+    let state = match &block.state {
+        VanillaStateIdOrValue::Id(id) => *id,
+        VanillaStateIdOrValue::Value(value) => value.state_id,
+    };
+    write_u32(w, state as u32)
+}
+/// Unknown blocks become air
+fn read_block<W: World>(r: &mut impl Read, game: &AxolotlGame<W>) -> Result<PlacedBlock<W>, Error> {
+    let name = read_str(r)?;
+    let state = read_u32(r)? as usize;
+    if let Some(block) = game.registries.blocks.get_by_namespace(&name) {
+        return Ok(PlacedBlock {
+            state: VanillaStateIdOrValue::Id(state),
+            block: block.clone(),
+        });
+    }
+    warn!("Invalid block: {}", name);
+    let air = game
+        .registries
+        .blocks
+        .get_by_namespace("minecraft:air")
+        .ok_or(Error::InvalidFreezeDried("minecraft:air is missing"))?;
+    Ok(PlacedBlock::from(air.clone()))
+}
+
+/// Writes chunks to any [Write]
+#[derive(Debug)]
+pub struct FreezeDriedWriter<Wr: Write> {
+    inner: Wr,
+    chunks: usize,
+}
+impl<Wr: Write> FreezeDriedWriter<Wr> {
+    /// Writes the header
+    pub fn new(mut inner: Wr) -> Result<Self, Error> {
+        inner.write_all(&MAGIC)?;
+        write_u8(&mut inner, FORMAT_VERSION)?;
+        Ok(Self { inner, chunks: 0 })
+    }
+    pub fn write_chunk<W: World>(
+        &mut self,
+        chunk: &AxolotlChunk<W>,
+        entities: Option<&RawEntities>,
+    ) -> Result<(), Error> {
+        let w = &mut self.inner;
+        write_u8(w, CHUNK_MARKER)?;
+        write_u32(w, chunk.chunk_pos.x() as u32)?;
+        write_u32(w, chunk.chunk_pos.z() as u32)?;
+        write_u16(w, chunk.sections.len() as u16)?;
+        for section in chunk.sections.as_ref() {
+            write_u8(w, section.y as u8)?;
+            match &section.blocks {
+                AxolotlBlockSection::Empty => write_u8(w, EMPTY)?,
+                AxolotlBlockSection::SingleBlock(block) => {
+                    write_u8(w, SINGLE)?;
+                    write_block(w, block)?;
+                }
+                AxolotlBlockSection::Full {
+                    blocks,
+                    block_palette,
+                } => {
+                    write_u8(w, FULL)?;
+                    write_u16(w, block_palette.len() as u16)?;
+                    for block in block_palette {
+                        write_block(w, block)?;
+                    }
+                    write_longs(w, &blocks.data)?;
+                }
+            }
+            match &section.biomes {
+                AxolotlBiomeSection::SingleBiome(biome) => {
+                    write_u8(w, SINGLE)?;
+                    write_str(w, &biome.to_string())?;
+                }
+                AxolotlBiomeSection::Full {
+                    biome_palette,
+                    biomes,
+                } => {
+                    write_u8(w, FULL)?;
+                    write_u16(w, biome_palette.len() as u16)?;
+                    for biome in biome_palette {
+                        write_str(w, &biome.to_string())?;
+                    }
+                    write_u8(w, biomes.bits_per_block as u8)?;
+                    write_u16(w, biomes.length as u16)?;
+                    write_longs(w, &biomes.data)?;
+                }
+            }
+        }
+        match entities {
+            Some(entities) => {
+                write_u8(w, 1)?;
+                let mut nbt = Vec::new();
+                serde_impl::to_writer(&mut nbt, entities)?;
+                write_u32(w, nbt.len() as u32)?;
+                w.write_all(&nbt)?;
+            }
+            None => write_u8(w, 0)?,
+        }
+        self.chunks += 1;
+        Ok(())
+    }
+    pub fn chunks_written(&self) -> usize {
+        self.chunks
+    }
+    /// Writes the end of the stream and returns the inner writer
+    pub fn finish(mut self) -> Result<Wr, Error> {
+        write_u8(&mut self.inner, END_MARKER)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// What was read besides the sections
+#[derive(Debug)]
+pub struct ThawedChunk {
+    pub chunk_pos: ChunkPos,
+    pub entities: Option<RawEntities>,
+}
+
+/// Reads chunks from any [Read]
+#[derive(Debug)]
+pub struct FreezeDriedReader<R: Read> {
+    inner: R,
+    finished: bool,
+}
+impl<R: Read> FreezeDriedReader<R> {
+    /// Checks the header
+    pub fn new(mut inner: R) -> Result<Self, Error> {
+        let mut magic = [0u8; 4];
+        inner.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(Error::InvalidFreezeDried("Not a freeze dried stream"));
+        }
+        if read_u8(&mut inner)? != FORMAT_VERSION {
+            return Err(Error::InvalidFreezeDried("Unsupported format version"));
+        }
+        Ok(Self {
+            inner,
+            finished: false,
+        })
+    }
+    /// Reads the next chunk into `chunk`. Returns None at the end of the stream
+    ///
+    /// The old section storage goes back to the pool of the chunk and the new storage is taken from it.
+    /// Sections outside of the chunk height are skipped
+    pub fn read_chunk_into<W: World>(
+        &mut self,
+        game: &AxolotlGame<W>,
+        chunk: &mut AxolotlChunk<W>,
+    ) -> Result<Option<ThawedChunk>, Error> {
+        if self.finished {
+            return Ok(None);
+        }
+        let r = &mut self.inner;
+        match read_u8(r)? {
+            END_MARKER => {
+                self.finished = true;
+                return Ok(None);
+            }
+            CHUNK_MARKER => {}
+            _ => return Err(Error::InvalidFreezeDried("Expected a chunk")),
+        }
+        let chunk_pos = ChunkPos::new(read_i32(r)?, read_i32(r)?);
+        chunk.chunk_pos = chunk_pos;
+        let pool = chunk.pool.clone();
+        for _ in 0..read_u16(r)? {
+            let y = read_u8(r)? as i8;
+            let mut section = chunk.sections.get_mut(y as i32);
+            // Recycled first so the storage can be taken again for this section
+            if let (Some(section), Some(pool)) = (section.as_deref_mut(), &pool) {
+                pool.recycle_section(section);
+            }
+            let blocks = read_blocks(r, game, pool.as_deref())?;
+            let biomes = read_biomes(r, pool.as_deref())?;
+            match section {
+                Some(section) => {
+                    section.blocks = blocks;
+                    section.biomes = biomes;
+                }
+                None => warn!("Skipping section {} outside of the world height", y),
+            }
+        }
+        let entities = match read_u8(r)? {
+            0 => None,
+            _ => {
+                // Not allocated up front. The length is not trusted
+                let len = read_u32(r)? as u64;
+                let mut nbt = Vec::new();
+                if r.by_ref().take(len).read_to_end(&mut nbt)? as u64 != len {
+                    return Err(Error::InvalidFreezeDried("Entities were cut off"));
+                }
+                Some(serde_impl::from_reader_binary(nbt.as_slice())?)
+            }
+        };
+        Ok(Some(ThawedChunk {
+            chunk_pos,
+            entities,
+        }))
+    }
+}
+fn read_blocks<W: World>(
+    r: &mut impl Read,
+    game: &AxolotlGame<W>,
+    pool: Option<&SectionPool<W>>,
+) -> Result<AxolotlBlockSection<W>, Error> {
+    match read_u8(r)? {
+        EMPTY => Ok(AxolotlBlockSection::Empty),
+        SINGLE => Ok(AxolotlBlockSection::SingleBlock(read_block(r, game)?)),
+        FULL => {
+            let mut block_palette = pool.map(SectionPool::block_palette).unwrap_or_default();
+            for _ in 0..read_u16(r)? {
+                block_palette.push(read_block(r, game)?);
+            }
+            let mut blocks = pool
+                .map(SectionPool::block_storage)
+                .unwrap_or_else(|| CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE));
+            read_longs_into(r, &mut blocks.data)?;
+            Ok(AxolotlBlockSection::Full {
+                blocks,
+                block_palette,
+            })
+        }
+        _ => Err(Error::InvalidFreezeDried("Invalid block section")),
+    }
+}
+fn read_biome(r: &mut impl Read) -> Result<OwnedNameSpaceKey, Error> {
+    let name = read_str(r)?;
+    let (namespace, key) = name.split_once(':').unwrap_or(("minecraft", name.as_str()));
+    Ok(OwnedNameSpaceKey::new(
+        namespace.to_string(),
+        key.to_string(),
+    ))
+}
+fn read_biomes<W: World>(
+    r: &mut impl Read,
+    pool: Option<&SectionPool<W>>,
+) -> Result<AxolotlBiomeSection, Error> {
+    match read_u8(r)? {
+        SINGLE => Ok(AxolotlBiomeSection::SingleBiome(read_biome(r)?)),
+        FULL => {
+            let mut biome_palette = pool.map(SectionPool::biome_palette).unwrap_or_default();
+            for _ in 0..read_u16(r)? {
+                biome_palette.push(read_biome(r)?);
+            }
+            let bits = read_u8(r)? as usize;
+            let length = read_u16(r)? as usize;
+            if !(1..=MAX_BITS_PER_BIOME).contains(&bits) {
+                return Err(Error::InvalidFreezeDried("Invalid bits per biome"));
+            }
+            if length != BIOMES_PER_SECTION {
+                return Err(Error::InvalidFreezeDried("Invalid number of biomes"));
+            }
+            let mut biomes = pool
+                .map(|pool| pool.biome_storage(bits, length))
+                .unwrap_or_else(|| CompactArray::new(bits, length));
+            read_longs_into(r, &mut biomes.data)?;
+            Ok(AxolotlBiomeSection::Full {
+                biome_palette,
+                biomes,
+            })
+        }
+        _ => Err(Error::InvalidFreezeDried("Invalid biome section")),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_api::OwnedNameSpaceKey;
+    use axolotl_world::chunk::compact_array::CompactArray;
+
+    use crate::world::chunk::freeze_dried::{
+        write_longs, write_str, write_u16, write_u32, write_u8, FreezeDriedReader,
+        FreezeDriedWriter, CHUNK_MARKER, EMPTY, FORMAT_VERSION, FULL, MAGIC,
+    };
+    use crate::world::chunk::pool::SectionPool;
+    use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
+    use crate::world::chunk::AxolotlChunk;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+    use crate::Error;
+
+    #[test]
+    pub fn test_stream_header() {
+        let mut writer = FreezeDriedWriter::new(Vec::new()).unwrap();
+        writer
+            .write_chunk(&AxolotlChunk::<TestWorld>::new(ChunkPos::new(-3, 7)), None)
+            .unwrap();
+        assert_eq!(writer.chunks_written(), 1);
+        let bytes = writer.finish().unwrap();
+        assert_eq!(&bytes[..4], &MAGIC);
+        assert_eq!(*bytes.last().unwrap(), 0);
+        // 24 empty sections with a biome name each
+        assert!(bytes.len() < 24 * 8 + 32);
+
+        assert!(FreezeDriedReader::new(Cursor::new(&bytes[1..])).is_err());
+        assert!(FreezeDriedReader::new(Cursor::new(bytes)).is_ok());
+    }
+
+    #[test]
+    pub fn test_round_trip() {
+        let game = test_game(&["stone", "dirt"]);
+        let stone = test_block(&game, "stone");
+        let dirt = test_block(&game, "dirt");
+        let plains = OwnedNameSpaceKey::new("minecraft".to_string(), "plains".to_string());
+        let desert = OwnedNameSpaceKey::new("minecraft".to_string(), "desert".to_string());
+
+        let mut chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(2, -5));
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(BlockPosition::new(x, -64, z), stone.clone());
+            }
+        }
+        chunk.set_block(BlockPosition::new(3, -60, 7), dirt.clone());
+        let mut biomes = CompactArray::new(1, 64);
+        biomes.set(10u64, 1);
+        chunk.sections.get_mut(-4).unwrap().biomes = AxolotlBiomeSection::Full {
+            biome_palette: vec![plains.clone(), desert.clone()],
+            biomes: biomes.clone(),
+        };
+        let mut writer = FreezeDriedWriter::new(Vec::new()).unwrap();
+        writer.write_chunk(&chunk, None).unwrap();
+        writer.write_chunk(&chunk, None).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let pool = Arc::new(SectionPool::default());
+        let mut thawed = AxolotlChunk::with_pool(ChunkPos::new(0, 0), pool.clone());
+        let mut reader = FreezeDriedReader::new(Cursor::new(bytes)).unwrap();
+        for _ in 0..2 {
+            let read = reader.read_chunk_into(&game, &mut thawed).unwrap().unwrap();
+            assert_eq!(read.chunk_pos, ChunkPos::new(2, -5));
+            assert_eq!(
+                thawed.get_block(BlockPosition::new(15, -64, 0)),
+                Some(&stone)
+            );
+            assert_eq!(thawed.get_block(BlockPosition::new(3, -60, 7)), Some(&dirt));
+            match &thawed.sections.get(-4).unwrap().biomes {
+                AxolotlBiomeSection::Full {
+                    biome_palette,
+                    biomes: read_biomes,
+                } => {
+                    assert_eq!(biome_palette, &vec![plains.clone(), desert.clone()]);
+                    assert_eq!(read_biomes, &biomes);
+                }
+                other => panic!("Expected full biomes {:?}", other),
+            }
+        }
+        assert!(reader
+            .read_chunk_into(&game, &mut thawed)
+            .unwrap()
+            .is_none());
+        // The second chunk reused the storage of the first
+        assert!(pool.metrics.hits() > 0);
+    }
+
+    /// A chunk with one section of full biomes
+    fn biome_stream(bits: u8, longs: usize, entities: Option<u32>) -> Vec<u8> {
+        let mut w = Vec::new();
+        w.extend_from_slice(&MAGIC);
+        write_u8(&mut w, FORMAT_VERSION).unwrap();
+        write_u8(&mut w, CHUNK_MARKER).unwrap();
+        write_u32(&mut w, 0).unwrap();
+        write_u32(&mut w, 0).unwrap();
+        write_u16(&mut w, 1).unwrap();
+        write_u8(&mut w, 0).unwrap();
+        write_u8(&mut w, EMPTY).unwrap();
+        write_u8(&mut w, FULL).unwrap();
+        write_u16(&mut w, 1).unwrap();
+        write_str(&mut w, "minecraft:plains").unwrap();
+        write_u8(&mut w, bits).unwrap();
+        write_u16(&mut w, 64).unwrap();
+        write_longs(&mut w, &vec![0; longs]).unwrap();
+        match entities {
+            Some(len) => {
+                write_u8(&mut w, 1).unwrap();
+                write_u32(&mut w, len).unwrap();
+            }
+            None => write_u8(&mut w, 0).unwrap(),
+        }
+        w
+    }
+    #[test]
+    pub fn test_invalid_sections() {
+        let game = test_game(&[]);
+        let read = |bytes: Vec<u8>| {
+            let mut chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+            FreezeDriedReader::new(Cursor::new(bytes))
+                .unwrap()
+                .read_chunk_into(&game, &mut chunk)
+                .map(|chunk| chunk.is_some())
+        };
+        // 3 bits fit 21 biomes in a long
+        assert!(read(biome_stream(3, 4, None)).unwrap());
+        assert!(matches!(
+            read(biome_stream(0, 4, None)),
+            Err(Error::InvalidFreezeDried(_))
+        ));
+        assert!(matches!(
+            read(biome_stream(64, 64, None)),
+            Err(Error::InvalidFreezeDried(_))
+        ));
+        assert!(matches!(
+            read(biome_stream(3, 3, None)),
+            Err(Error::InvalidFreezeDried(_))
+        ));
+        // Must not allocate the claimed length
+        assert!(matches!(
+            read(biome_stream(3, 4, Some(u32::MAX))),
+            Err(Error::InvalidFreezeDried(_))
+        ));
+    }
+}
@@ -0,0 +1,236 @@
+//! Keeps the recent block changes of each chunk so they can be rolled back
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ahash::AHashMap;
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::world::chunk::placed_block::PlacedBlock;
+
+/// One hour
+pub const DEFAULT_RETENTION_TICKS: u64 = 20 * 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct JournalEntry<W: World> {
+    pub position: BlockPosition,
+    /// None is air
+    pub old: Option<PlacedBlock<W>>,
+    pub new: Option<PlacedBlock<W>>,
+    pub actor: Option<Uuid>,
+    pub tick: u64,
+}
+
+/// An inclusive box of blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRegion {
+    pub min: BlockPosition,
+    pub max: BlockPosition,
+}
+impl BlockRegion {
+    /// The corners can be given in any order
+    pub fn new(a: BlockPosition, b: BlockPosition) -> Self {
+        Self {
+            min: BlockPosition::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: BlockPosition::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+    pub fn contains(&self, position: &BlockPosition) -> bool {
+        (self.min.x..=self.max.x).contains(&position.x)
+            && (self.min.y..=self.max.y).contains(&position.y)
+            && (self.min.z..=self.max.z).contains(&position.z)
+    }
+    /// If any block of the chunk is inside the region
+    pub fn intersects_chunk(&self, chunk: &ChunkPos) -> bool {
+        let min_x = chunk.x() as i64 * 16;
+        let min_z = chunk.z() as i64 * 16;
+        min_x <= self.max.x
+            && min_x + 15 >= self.min.x
+            && min_z <= self.max.z
+            && min_z + 15 >= self.min.z
+    }
+}
+
+/// Which actors changes are rolled back for
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ActorFilter {
+    #[default]
+    Any,
+    Only(Vec<Uuid>),
+    Except(Vec<Uuid>),
+}
+impl ActorFilter {
+    pub fn matches(&self, actor: Option<Uuid>) -> bool {
+        match self {
+            ActorFilter::Any => true,
+            ActorFilter::Only(actors) => actor.is_some_and(|actor| actors.contains(&actor)),
+            ActorFilter::Except(actors) => actor.is_none_or(|actor| !actors.contains(&actor)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChunkJournal<W: World> {
+    entries: RwLock<AHashMap<ChunkPos, VecDeque<JournalEntry<W>>>>,
+    /// Entries older than this many ticks are dropped
+    pub retention_ticks: u64,
+    tick: AtomicU64,
+}
+impl<W: World> Default for ChunkJournal<W> {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETENTION_TICKS)
+    }
+}
+impl<W: World> ChunkJournal<W> {
+    pub fn new(retention_ticks: u64) -> Self {
+        Self {
+            entries: RwLock::new(AHashMap::new()),
+            retention_ticks,
+            tick: AtomicU64::new(0),
+        }
+    }
+    pub fn current_tick(&self) -> u64 {
+        self.tick.load(Ordering::Relaxed)
+    }
+    /// Moves the journal to the tick and drops the entries that left the retention window
+    pub fn set_tick(&self, tick: u64) {
+        self.tick.store(tick, Ordering::Relaxed);
+        let oldest = tick.saturating_sub(self.retention_ticks);
+        let mut entries = self.entries.write();
+        entries.retain(|_, chunk| {
+            while chunk.front().is_some_and(|entry| entry.tick < oldest) {
+                chunk.pop_front();
+            }
+            !chunk.is_empty()
+        });
+    }
+    pub fn record(
+        &self,
+        chunk: ChunkPos,
+        position: BlockPosition,
+        old: Option<PlacedBlock<W>>,
+        new: &PlacedBlock<W>,
+        actor: Option<Uuid>,
+    ) {
+        let new = if new.is_air() {
+            None
+        } else {
+            Some(new.clone())
+        };
+        let entry = JournalEntry {
+            position,
+            old,
+            new,
+            actor,
+            tick: self.current_tick(),
+        };
+        self.entries
+            .write()
+            .entry(chunk)
+            .or_default()
+            .push_back(entry);
+    }
+    /// Removes the matching entries. Newest first
+    pub fn take_matching(
+        &self,
+        region: &BlockRegion,
+        ticks: &Range<u64>,
+        actors: &ActorFilter,
+    ) -> Vec<(ChunkPos, JournalEntry<W>)> {
+        let mut taken = Vec::new();
+        let mut entries = self.entries.write();
+        for (chunk_pos, chunk) in entries.iter_mut() {
+            if !region.intersects_chunk(chunk_pos) {
+                continue;
+            }
+            chunk.retain(|entry| {
+                let matches = region.contains(&entry.position)
+                    && ticks.contains(&entry.tick)
+                    && actors.matches(entry.actor);
+                if matches {
+                    taken.push((*chunk_pos, entry.clone()));
+                }
+                !matches
+            });
+        }
+        entries.retain(|_, chunk| !chunk.is_empty());
+        taken.sort_by(|(_, a), (_, b)| b.tick.cmp(&a.tick));
+        taken
+    }
+
+    /// Puts entries taken by [ChunkJournal::take_matching] back. Used when they could not be applied
+    pub fn restore(&self, chunk: ChunkPos, restored: impl IntoIterator<Item = JournalEntry<W>>) {
+        let mut entries = self.entries.write();
+        let entries = entries.entry(chunk).or_default();
+        entries.extend(restored);
+        entries
+            .make_contiguous()
+            .sort_by(|a, b| a.tick.cmp(&b.tick));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().values().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use uuid::Uuid;
+
+    use crate::world::chunk::journal::{ActorFilter, BlockRegion, ChunkJournal, JournalEntry};
+    use crate::world::test_world::TestWorld;
+
+    #[test]
+    pub fn test_region() {
+        let region = BlockRegion::new(
+            BlockPosition::new(20, 80, -1),
+            BlockPosition::new(-5, 60, 3),
+        );
+        assert!(region.contains(&BlockPosition::new(0, 70, 0)));
+        assert!(!region.contains(&BlockPosition::new(0, 90, 0)));
+        assert!(region.intersects_chunk(&ChunkPos::new(-1, -1)));
+        assert!(!region.intersects_chunk(&ChunkPos::new(2, 0)));
+
+        let actor = Uuid::new_v4();
+        assert!(ActorFilter::Only(vec![actor]).matches(Some(actor)));
+        assert!(!ActorFilter::Only(vec![actor]).matches(None));
+        assert!(ActorFilter::Except(vec![actor]).matches(None));
+    }
+
+    #[test]
+    pub fn test_ticks() {
+        let journal = ChunkJournal::<TestWorld>::new(100);
+        let chunk = ChunkPos::new(0, 0);
+        let entry = |x: i64, tick: u64| JournalEntry {
+            position: BlockPosition::new(x, 64, 0),
+            old: None,
+            new: None,
+            actor: None,
+            tick,
+        };
+        journal.restore(chunk, [entry(0, 10), entry(1, 150), entry(2, 50)]);
+        let region = BlockRegion::new(BlockPosition::new(0, 0, 0), BlockPosition::new(15, 100, 15));
+        let taken = journal.take_matching(&region, &(40..200), &ActorFilter::Any);
+        assert_eq!(taken.len(), 2);
+        assert_eq!(taken[0].1.tick, 150);
+        assert_eq!(journal.len(), 1);
+        journal.restore(chunk, taken.into_iter().map(|(_, entry)| entry));
+        assert_eq!(journal.len(), 3);
+
+        journal.set_tick(160);
+        assert_eq!(journal.current_tick(), 160);
+        assert_eq!(journal.len(), 1);
+        journal.set_tick(251);
+        assert!(journal.is_empty());
+    }
+}
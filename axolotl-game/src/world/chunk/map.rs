@@ -1,27 +1,50 @@
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::mem;
-use std::ops::{Deref, DerefMut};
-use std::sync::atomic::AtomicBool;
+use std::ops::{Deref, DerefMut, Range};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use ahash::AHashMap;
 use log::{debug, info, warn};
 use parking_lot::{Mutex, RwLock};
+use uuid::Uuid;
 
-use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::item::placement::PlacementWorld;
+use axolotl_api::world::protection::{Decision, ProtectedAction, ProtectionProvider};
+use axolotl_api::world::{BlockFace, BlockPosition, World};
 use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::world_gen::dimension::WorldHeight;
 use axolotl_api::world_gen::noise::ChunkGenerator;
+use axolotl_api::NamespacedId;
+use axolotl_items::blocks::placement::requires_support;
+use axolotl_world::level::LevelDat;
 
+use crate::world::block_update::{cycle_note_block, BlockUpdate, BlockUpdateQueue};
+use crate::world::chunk::dump::ChunkDump;
+use crate::world::chunk::journal::{ActorFilter, BlockRegion, ChunkJournal};
 use crate::world::chunk::placed_block::PlacedBlock;
-use crate::world::chunk::{AxolotlChunk, ChunkHandle, InnerChunkHandle, LoadState};
+use crate::world::chunk::pool::SectionPool;
+use crate::world::chunk::tickets::ChunkTickets;
+use crate::world::chunk::trace::{ChunkEvent, ChunkTracer};
+use crate::world::chunk::{AxolotlChunk, ChunkHandle, ChunkShards, InnerChunkHandle, LoadState};
+use crate::world::coalesce::UpdateCoalescer;
+use crate::world::entity::tracker::PlayerTracker;
+use crate::world::events::WorldEvents;
 use crate::world::generator::AxolotlGenerator;
-use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::level::accessor::{LevelReader, LevelWriter, PlayerAccess};
+use crate::world::level::configs::WorldSettings;
+use crate::world::players::WorldPlayers;
+use crate::world::protection::ChunkClaims;
+use crate::world::recorder::{EventRecorder, WorldEvent};
+use crate::world::scheduler::TickScheduler;
+use crate::world::simulation::Simulation;
+use crate::world::sleep::SleepManager;
 use crate::world::ChunkUpdate;
-use crate::Error;
+use crate::{AxolotlGame, Error};
 
 type Queue<T> = Mutex<VecDeque<T>>;
-type ThreadSafeChunks<W> = RwLock<AHashMap<ChunkPos, ChunkHandle<W>>>;
+type ThreadSafeChunks<W> = ChunkShards<ChunkHandle<W>>;
 
 #[derive(Debug)]
 pub struct ChunkMap<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> {
@@ -29,6 +52,41 @@ pub struct ChunkMap<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> {
     pub thread_safe_chunks: ThreadSafeChunks<W>,
     pub dead_chunks: Queue<AxolotlChunk<W>>,
     pub load_queue: Queue<ChunkUpdate<W>>,
+    pub tickets: ChunkTickets,
+    /// Shared by every chunk created by the map
+    pub pool: Arc<SectionPool<W>>,
+    /// Block changes are recorded if set
+    pub recorder: Option<EventRecorder>,
+    /// Recent block changes. Used by [ChunkMap::rollback]
+    pub journal: ChunkJournal<W>,
+    /// Consulted before players modify the world
+    pub protection: Option<Box<dyn ProtectionProvider>>,
+    /// The claims of the world. Consulted before [ChunkMap::protection] and saved by [ChunkMap::save_all]
+    pub claims: Option<Arc<ChunkClaims>>,
+    /// The height of the dimension. Every chunk is created with these sections
+    pub height: WorldHeight,
+    /// Deferred work for block behaviors, commands and plugins. See [crate::world::scheduler]
+    pub scheduler: TickScheduler<ChunkMap<W, V>>,
+    /// Chunk lifecycle transitions are traced if set. See [crate::world::chunk::trace]
+    pub tracer: Option<ChunkTracer>,
+    /// Every block change waits here until [crate::world::updates::WorldChannels] sends it
+    pub changes: UpdateCoalescer<W>,
+    /// Queued by every block change. Handled by [ChunkMap::run_block_updates] on the next full tick
+    pub block_updates: BlockUpdateQueue,
+    /// The settings of the world. Worlds opened with `ChunkMap::open` use the settings of the accessor
+    pub settings: WorldSettings,
+    /// Seeds the random of every tick and limits the threads of pre-generation. See [crate::world::simulation]
+    pub simulation: Mutex<Simulation>,
+    /// The `level.dat` of the world. The difficulty and game rules are read from it
+    pub level: RwLock<LevelDat>,
+    /// Saved by [ChunkMap::save_all]
+    pub players: WorldPlayers,
+    /// The movement of the players. Decides who sees who and which chunks they keep loaded. Built from [ChunkMap::settings]
+    pub tracker: PlayerTracker,
+    /// The players in bed. Ticked by [crate::world::updates::WorldChannels::tick]
+    pub sleep: SleepManager,
+    /// Raids and the villages they start in. Ticked by [crate::world::updates::WorldChannels::tick] and saved by [ChunkMap::save_all]
+    pub events: Option<WorldEvents>,
     pub accessor: V,
 }
 
@@ -42,11 +100,109 @@ where
             thread_safe_chunks: ThreadSafeChunks::default(),
             dead_chunks: Queue::default(),
             load_queue: Queue::default(),
+            tickets: ChunkTickets::default(),
+            pool: Arc::new(SectionPool::default()),
+            recorder: None,
+            journal: ChunkJournal::default(),
+            protection: None,
+            claims: None,
+            height: WorldHeight::OVERWORLD,
+            scheduler: TickScheduler::default(),
+            tracer: None,
+            changes: UpdateCoalescer::default(),
+            block_updates: BlockUpdateQueue::default(),
+            settings: WorldSettings::default(),
+            simulation: Mutex::default(),
+            level: RwLock::default(),
+            players: WorldPlayers::default(),
+            tracker: PlayerTracker::from(&WorldSettings::default()),
+            sleep: SleepManager::default(),
+            events: None,
             accessor,
         }
     }
+    pub fn with_height(mut self, height: WorldHeight) -> Self {
+        self.height = height;
+        self
+    }
+    pub fn with_recorder(mut self, recorder: EventRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+    pub fn with_protection(mut self, protection: impl ProtectionProvider + 'static) -> Self {
+        self.protection = Some(Box::new(protection));
+        self
+    }
+    pub fn with_claims(mut self, claims: ChunkClaims) -> Self {
+        self.claims = Some(Arc::new(claims));
+        self
+    }
+    pub fn with_tracer(mut self, tracer: ChunkTracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+    pub fn with_settings(mut self, settings: WorldSettings) -> Self {
+        self.tracker = PlayerTracker::from(&settings);
+        self.settings = settings;
+        self
+    }
+    pub fn with_simulation(mut self, simulation: Simulation) -> Self {
+        self.simulation = Mutex::new(simulation);
+        self
+    }
+    pub fn with_level(mut self, level: LevelDat) -> Self {
+        self.level = RwLock::new(level);
+        self
+    }
+    pub fn with_player_access(mut self, access: impl PlayerAccess + 'static) -> Self {
+        self.players = WorldPlayers::new(Arc::new(access));
+        self
+    }
+    pub fn with_events(mut self, events: WorldEvents) -> Self {
+        self.events = Some(events);
+        self
+    }
+    /// Moves the [ChunkMap::journal] and [ChunkMap::recorder] to the tick. Called by the world at the start of every full tick
+    pub fn set_tick(&self, tick: u64) {
+        self.journal.set_tick(tick);
+        if let Some(recorder) = &self.recorder {
+            recorder.set_tick(tick);
+        }
+    }
+    /// Traces the transition at the tick of the [ChunkMap::scheduler]
+    #[inline]
+    fn trace(&self, chunk: ChunkPos, event: ChunkEvent) {
+        if let Some(tracer) = &self.tracer {
+            // Illegal transitions are logged by the tracer
+            let _ = tracer.record(chunk, event, self.scheduler.current_tick());
+        }
+    }
+    /// Asks the [ChunkMap::claims] then the [ChunkMap::protection]. Allowed if both pass
+    pub fn can_perform(
+        &self,
+        player: &Uuid,
+        position: &BlockPosition,
+        action: ProtectedAction,
+    ) -> bool {
+        self.claims
+            .as_ref()
+            .map_or(Decision::Pass, |claims| {
+                claims.can_perform(player, position, action)
+            })
+            .or(|| {
+                self.protection
+                    .as_ref()
+                    .map_or(Decision::Pass, |protection| {
+                        protection.can_perform(player, position, action)
+                    })
+            })
+            .is_allowed()
+    }
     #[inline]
     pub fn push_chunk_update(&self, update: ChunkUpdate<W>) {
+        if let ChunkUpdate::Load { x, z, .. } = &update {
+            self.trace(ChunkPos::new(*x, *z), ChunkEvent::Queued);
+        }
         self.load_queue.lock().push_back(update);
     }
 
@@ -74,14 +230,16 @@ where
         }
         Ok(())
     }
+    /// Chunks with a ticket are not unloaded
     #[inline(always)]
     pub fn unload_chunk(&self, x: i32, z: i32) -> Result<(), Error> {
         let chunk_pos = ChunkPos::new(x, z);
+        if self.tickets.has_ticket(&chunk_pos) {
+            debug!("Not unloading chunk {:?} it has a ticket", chunk_pos);
+            return Ok(());
+        }
 
-        let mut pos = self.thread_safe_chunks.write();
-        let removed = pos.remove(&chunk_pos);
-        drop(pos);
-        if let Some(value) = removed {
+        if let Some(value) = self.thread_safe_chunks.remove(&chunk_pos) {
             self.unload_inner(chunk_pos, value)?;
         };
         Ok(())
@@ -94,6 +252,7 @@ where
         chunk_pos: ChunkPos,
         value: Arc<InnerChunkHandle<W>>,
     ) -> Result<(), Error> {
+        let modified = value.is_modified();
         let chunk = match Arc::try_unwrap(value) {
             Ok(chunk) => chunk.value.into_inner(),
             Err(e) => {
@@ -104,7 +263,12 @@ where
                 (guard.deref().clone())
             }
         };
+        self.trace(chunk_pos, ChunkEvent::Unloaded);
+        if !modified && !self.accessor.saves_unmodified() {
+            return Ok(());
+        }
         self.accessor.save_chunk(chunk_pos, chunk)?;
+        self.trace(chunk_pos, ChunkEvent::Saved);
         Ok(())
     }
     /// Will run the update before putting chunk in map
@@ -117,21 +281,15 @@ where
     ) -> Result<(), Error> {
         let pos = ChunkPos::new(x, z);
         info!("Loading chunk at {:?}", pos);
-        let mut lock = self.thread_safe_chunks.write();
-        info!("Got lock");
-        let handle = if let Some(chunk) = lock.get(&pos) {
+        let (handle, created) = self
+            .thread_safe_chunks
+            .get_or_insert_with(pos, || self.create_chunk(pos));
+        if !created {
             info!("Chunk handle already exists");
-            if !chunk.safe_to_load() {
+            if !handle.safe_to_load() {
                 return Ok(());
             }
-            chunk.clone()
-        } else {
-            info!("Creating new chunk at {:?}", pos);
-            let handle = Self::create_chunk(&self.dead_chunks, pos);
-            lock.insert(pos, handle.clone());
-            drop(lock);
-            handle
-        };
+        }
         info!("Loading chunk at {:?} with handle {:?}", pos, handle);
         handle.mark_loading();
         let mut chunk = handle.value.write();
@@ -141,67 +299,420 @@ where
             chunk_ref.chunk_pos = pos;
             debug!("Generating chunk at {:?}", pos);
             self.generator.generate_chunk_into(chunk_ref);
+            self.trace(pos, ChunkEvent::Generated);
         }
 
         if let Some((pos, block)) = update {
             chunk_ref.set_block(pos, block);
+            handle.mark_modified();
         }
+        handle.snapshots.invalidate_all();
         drop(chunk);
 
         handle.mark_loaded();
+        self.trace(pos, ChunkEvent::Loaded);
         info!("Loaded chunk at {:?}", pos);
 
         Ok(())
     }
-    fn create_chunk(dead_chunks: &Queue<AxolotlChunk<W>>, pos: ChunkPos) -> ChunkHandle<W> {
-        let mut dead_chunks = dead_chunks.lock();
+    fn create_chunk(&self, pos: ChunkPos) -> ChunkHandle<W> {
+        let mut dead_chunks = self.dead_chunks.lock();
         let chunk = if let Some(mut dead) = dead_chunks.pop_front() {
             dead.chunk_pos = pos;
             dead
         } else {
-            AxolotlChunk::new(pos)
+            let mut chunk = AxolotlChunk::with_height(pos, self.height);
+            chunk.pool = Some(self.pool.clone());
+            chunk
         };
 
         InnerChunkHandle::new(chunk).into()
     }
     // TODO How should errors be handled?
     pub fn save_all(&self) -> Result<(), Error> {
-        let mut lock = self.thread_safe_chunks.write();
-
-        for (chunk_pos, data) in lock.drain() {
+        for (chunk_pos, data) in self.thread_safe_chunks.drain() {
             if let Err(e) = self.unload_inner(chunk_pos, data) {
                 warn!("Error saving chunk: {:?}", e);
             }
         }
+        self.save_claims();
+        self.save_players();
+        self.save_events();
         Ok(())
     }
+    /// Saves the modified chunks without unloading them. Returns the number of chunks saved
+    pub fn save_modified(&self) -> Result<usize, Error> {
+        let mut saved = 0;
+        for chunk_pos in self.thread_safe_chunks.positions() {
+            let Some(handle) = self.thread_safe_chunks.get(&chunk_pos) else {
+                continue;
+            };
+            if !handle.modified.swap(false, Ordering::Relaxed) {
+                continue;
+            }
+            let chunk = handle.value.read().clone();
+            self.accessor.save_chunk(chunk_pos, chunk)?;
+            self.trace(chunk_pos, ChunkEvent::Saved);
+            saved += 1;
+        }
+        Ok(saved)
+    }
+    /// Saves the modified chunks and the claims every [WorldSettings::autosave_interval] ticks of the [ChunkMap::scheduler].
+    ///
+    /// Called by [crate::world::updates::WorldChannels::tick] after the scheduler runs
+    pub fn autosave(&self) {
+        let interval = self.settings.autosave_interval;
+        if interval == 0 || self.scheduler.current_tick() % interval != 0 {
+            return;
+        }
+        match self.save_modified() {
+            Ok(saved) => debug!("Autosaved {} chunks", saved),
+            Err(e) => warn!("Error autosaving: {:?}", e),
+        }
+        self.save_claims();
+        self.save_events();
+    }
+    fn save_claims(&self) {
+        if let Some(claims) = &self.claims {
+            match claims.save() {
+                Ok(()) | Err(Error::ReadOnly) => {}
+                Err(e) => warn!("Error saving claims: {:?}", e),
+            }
+        }
+    }
 
     pub fn load_chunk(&self, handle: ChunkHandle<W>) -> Result<(), Error> {
         handle.mark_loading();
         let mut chunk = handle.value.write();
         let chunk_ref = chunk.deref_mut();
-        debug!("Loading chunk at {:?}", chunk_ref.chunk_pos);
-        if !self
-            .accessor
-            .get_chunk_into(&chunk_ref.chunk_pos.clone(), chunk_ref)?
-        {
+        let pos = chunk_ref.chunk_pos;
+        debug!("Loading chunk at {:?}", pos);
+        if !self.accessor.get_chunk_into(&pos, chunk_ref)? {
             self.generator.generate_chunk_into(chunk_ref);
+            self.trace(pos, ChunkEvent::Generated);
         }
+        handle.snapshots.invalidate_all();
         drop(chunk);
+        self.trace(pos, ChunkEvent::Loaded);
 
         Ok(())
     }
+    pub fn is_chunk_loaded(&self, pos: &ChunkPos) -> bool {
+        self.thread_safe_chunks
+            .get(pos)
+            .is_some_and(|handle| handle.is_loaded())
+    }
+    /// Returns the block if the chunk is loaded. Air is returned as None
+    ///
+    /// Reads through [InnerChunkHandle::get_block] so readers do not wait on writers of other sections
+    pub fn get_block(&self, mut pos: BlockPosition) -> Option<PlacedBlock<W>> {
+        let chunk_pos = pos.chunk();
+        let handle = self.thread_safe_chunks.get(&chunk_pos)?;
+        if !handle.is_loaded() {
+            return None;
+        }
+        handle.get_block(pos)
+    }
+    /// A [ChunkDump] of the loaded chunk with its queued block updates. None if the chunk is not loaded
+    pub fn dump_chunk(&self, pos: ChunkPos) -> Option<ChunkDump> {
+        let handle = self.thread_safe_chunks.get(&pos)?;
+        if !handle.is_loaded() {
+            return None;
+        }
+        let dump = ChunkDump::from_chunk(&handle.value.read());
+        Some(dump.with_block_updates(&self.block_updates))
+    }
+    /// Sets the block if the chunk is loaded. The change is queued in [ChunkMap::changes]
+    ///
+    /// Returns None if the chunk is not loaded. Otherwise the previous block, None being air
+    pub fn set_block(
+        &self,
+        position: BlockPosition,
+        block: PlacedBlock<W>,
+        actor: Option<Uuid>,
+    ) -> Option<Option<PlacedBlock<W>>> {
+        let mut pos = position;
+        let chunk_pos = pos.chunk();
+        let handle = self.thread_safe_chunks.get(&chunk_pos)?;
+        if !handle.is_loaded() {
+            return None;
+        }
+        let mut chunk = handle.value.write();
+        let old = chunk.get_block(pos).cloned();
+        self.record_change(chunk_pos, position, old.clone(), &block, actor);
+        self.changes
+            .push(position, (!block.is_air()).then(|| block.clone()));
+        chunk.set_block(pos, block);
+        handle.snapshots.invalidate((pos.y >> 4) as i32);
+        handle.mark_modified();
+        self.block_updates.state_changed(position);
+        self.trace(chunk_pos, ChunkEvent::Modified);
+        Some(old)
+    }
+    /// Sets many blocks taking the lock of each chunk once. Blocks in chunks that are not loaded are skipped
+    ///
+    /// Returns the number of blocks set
+    pub fn set_blocks(
+        &self,
+        blocks: impl IntoIterator<Item = (BlockPosition, PlacedBlock<W>)>,
+        actor: Option<Uuid>,
+    ) -> usize {
+        let mut set = 0;
+        for (chunk_pos, blocks) in Self::group_by_chunk(blocks) {
+            set += self.set_chunk_blocks(chunk_pos, blocks, Some(actor));
+        }
+        set
+    }
+    /// Places the blocks for a player. Nothing is placed if any position is protected
+    ///
+    /// Returns None if the placement was denied. Otherwise the number of blocks set
+    pub fn place_blocks(
+        &self,
+        player: Uuid,
+        blocks: Vec<(BlockPosition, PlacedBlock<W>)>,
+    ) -> Option<usize> {
+        if !blocks
+            .iter()
+            .all(|(position, _)| self.can_perform(&player, position, ProtectedAction::PlaceBlock))
+        {
+            return None;
+        }
+        Some(self.set_blocks(blocks, Some(player)))
+    }
+    /// Breaks the block for a player. See [PlacedBlock::break_remains]
+    ///
+    /// Returns the broken block. None if the position is protected, air or not loaded
+    pub fn break_block(
+        &self,
+        player: Uuid,
+        position: BlockPosition,
+        game: &AxolotlGame<W>,
+    ) -> Option<PlacedBlock<W>> {
+        if !self.can_perform(&player, &position, ProtectedAction::BreakBlock) {
+            return None;
+        }
+        let block = self.get_block(position).filter(|block| !block.is_air())?;
+        let remains = block.break_remains(game)?;
+        self.set_block(position, remains, Some(player))?
+    }
+    /// The block of the container a player opens
+    ///
+    /// Returns None if the position is protected, air or not loaded
+    pub fn open_container(&self, player: Uuid, position: BlockPosition) -> Option<PlacedBlock<W>> {
+        if !self.can_perform(&player, &position, ProtectedAction::OpenContainer) {
+            return None;
+        }
+        self.get_block(position).filter(|block| !block.is_air())
+    }
+    /// A player uses the block. Note blocks move to their next note.
+    ///
+    /// Tuning changes the block so it is protected like a placement. Returns the new block. None if nothing changed
+    pub fn use_block(&self, player: Uuid, position: BlockPosition) -> Option<PlacedBlock<W>> {
+        if !self.can_perform(&player, &position, ProtectedAction::PlaceBlock) {
+            return None;
+        }
+        let tuned = cycle_note_block(&self.get_block(position)?)?;
+        self.set_block(position, tuned.clone(), Some(player))?;
+        Some(tuned)
+    }
+    /// Handles the updates queued before the call. Updates queued while running wait for the next call.
+    ///
+    /// - Observers watching a changed block fire. The block behind them gets a neighbor update
+    /// - Blocks that need support break when the block below them is gone
+    ///
+    /// Returns the number of updates handled
+    pub fn run_block_updates(&self, game: &AxolotlGame<W>) -> usize {
+        let updates = self.block_updates.take();
+        let handled = updates.len();
+        for update in updates {
+            match update {
+                BlockUpdate::StateChange { position } => {
+                    self.update_meeting_point(position);
+                    for observer in self.observers_of(position) {
+                        // Observers face the block they watch. So the back is the opposite side
+                        let back = BlockPosition::new(
+                            observer.x * 2 - position.x,
+                            observer.y * 2 - position.y,
+                            observer.z * 2 - position.z,
+                        );
+                        self.block_updates.push(BlockUpdate::Neighbor {
+                            position: back,
+                            source: observer,
+                        });
+                        self.block_updates.neighbor_changed(back);
+                    }
+                }
+                BlockUpdate::Neighbor { position, source } => {
+                    if source != position.relative(BlockFace::Down) {
+                        continue;
+                    }
+                    let Some(block) = self.get_block(position) else {
+                        continue;
+                    };
+                    if !requires_support(block.block.key()) {
+                        continue;
+                    }
+                    if self.get_block(source).is_none_or(|below| below.is_air()) {
+                        if let Some(remains) = block.break_remains(game) {
+                            self.set_block(position, remains, None);
+                        }
+                    }
+                }
+            }
+        }
+        handled
+    }
+    /// Reverts the journaled changes inside the region and tick range made by the matching actors.
+    ///
+    /// Each block goes back to the state before the oldest matching change. Chunks that are not loaded are loaded first.
+    /// The rollback itself is not journaled. Returns the number of blocks restored
+    pub fn rollback(
+        &self,
+        region: &BlockRegion,
+        ticks: Range<u64>,
+        actors: &ActorFilter,
+        game: &AxolotlGame<W>,
+    ) -> Result<usize, Error> {
+        let Some(air) = game
+            .registries
+            .blocks
+            .get_by_namespace("minecraft:air")
+            .map(|block| PlacedBlock::from(block.clone()))
+        else {
+            warn!("Can not rollback without an air block");
+            return Ok(0);
+        };
+        let mut by_chunk: AHashMap<ChunkPos, Vec<_>> = AHashMap::new();
+        for (chunk_pos, entry) in self.journal.take_matching(region, &ticks, actors) {
+            by_chunk.entry(chunk_pos).or_default().push(entry);
+        }
+        let mut restored = 0;
+        for (chunk_pos, entries) in by_chunk {
+            // Newest first. So the oldest state of each position is the one kept
+            let mut restore = AHashMap::new();
+            for entry in &entries {
+                let old = entry.old.clone().unwrap_or_else(|| air.clone());
+                restore.insert(entry.position, old);
+            }
+            if !self.is_chunk_loaded(&chunk_pos) {
+                if let Err(error) = self.load_chunk_task(chunk_pos.x(), chunk_pos.z(), None) {
+                    warn!(
+                        "Failed to load chunk {:?} for rollback: {:?}",
+                        chunk_pos, error
+                    );
+                }
+            }
+            let set = self.set_chunk_blocks(chunk_pos, restore.into_iter().collect(), None);
+            if set == 0 {
+                // The chunk could not be loaded. Keep the entries so the rollback can be retried
+                self.journal.restore(chunk_pos, entries);
+            }
+            restored += set;
+        }
+        info!("Rolled back {} blocks", restored);
+        Ok(restored)
+    }
+    fn group_by_chunk(
+        blocks: impl IntoIterator<Item = (BlockPosition, PlacedBlock<W>)>,
+    ) -> AHashMap<ChunkPos, Vec<(BlockPosition, PlacedBlock<W>)>> {
+        let mut by_chunk: AHashMap<ChunkPos, Vec<_>> = AHashMap::new();
+        for (position, block) in blocks {
+            let mut pos = position;
+            by_chunk
+                .entry(pos.chunk())
+                .or_default()
+                .push((position, block));
+        }
+        by_chunk
+    }
+    /// `journal` is the actor to record the changes under. None to not record them
+    fn set_chunk_blocks(
+        &self,
+        chunk_pos: ChunkPos,
+        blocks: Vec<(BlockPosition, PlacedBlock<W>)>,
+        journal: Option<Option<Uuid>>,
+    ) -> usize {
+        let Some(handle) = self.thread_safe_chunks.get(&chunk_pos) else {
+            return 0;
+        };
+        if !handle.is_loaded() {
+            return 0;
+        }
+        let mut chunk = handle.value.write();
+        let set = blocks.len();
+        for (position, block) in blocks {
+            let mut pos = position;
+            pos.chunk();
+            if let Some(actor) = journal {
+                let old = chunk.get_block(pos).cloned();
+                self.record_change(chunk_pos, position, old, &block, actor);
+            }
+            self.changes
+                .push(position, (!block.is_air()).then(|| block.clone()));
+            chunk.set_block(pos, block);
+            handle.snapshots.invalidate((pos.y >> 4) as i32);
+            self.block_updates.state_changed(position);
+        }
+        if set > 0 {
+            handle.mark_modified();
+            self.trace(chunk_pos, ChunkEvent::Modified);
+        }
+        set
+    }
+    fn record_change(
+        &self,
+        chunk_pos: ChunkPos,
+        position: BlockPosition,
+        old: Option<PlacedBlock<W>>,
+        new: &PlacedBlock<W>,
+        actor: Option<Uuid>,
+    ) {
+        if let Some(recorder) = &self.recorder {
+            if let Err(error) = recorder.record_block_change(position, old.as_ref(), new, actor) {
+                warn!("Failed to record block change: {}", error);
+            }
+        }
+        self.journal.record(chunk_pos, position, old, new, actor);
+    }
+    /// Records the event if the world has a [ChunkMap::recorder]. Failures are logged
+    pub fn record_event(&self, event: WorldEvent) {
+        if let Some(recorder) = &self.recorder {
+            if let Err(error) = recorder.record(event) {
+                warn!("Failed to record event: {}", error);
+            }
+        }
+    }
+    /// Observers that fire because the block at the position changed state
+    pub fn observers_of(&self, changed: BlockPosition) -> Vec<BlockPosition> {
+        crate::world::block_update::observers_of(changed, |pos| self.get_block(pos))
+    }
     /// Will return a ChunkHandle this may or may not be loaded
     pub fn get_chunk(&self, pos: ChunkPos) -> ChunkHandle<W> {
-        let lock = self.thread_safe_chunks.read();
-        if let Some(chunk) = lock.get(&pos) {
-            chunk.clone()
-        } else {
-            drop(lock);
-            let mut lock = self.thread_safe_chunks.write();
-            let handle = Self::create_chunk(&self.dead_chunks, pos);
-            lock.insert(pos, handle.clone());
-            handle
-        }
+        self.thread_safe_chunks
+            .get_or_insert_with(pos, || self.create_chunk(pos))
+            .0
+    }
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> PlacementWorld for ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    fn is_solid(&self, position: &BlockPosition) -> bool {
+        self.get_block(*position)
+            .map(|block| !block.is_air() && block.block.key() != "water")
+            .unwrap_or(false)
+    }
+
+    fn is_replaceable(&self, position: &BlockPosition) -> bool {
+        self.get_block(*position)
+            .map(|block| block.is_air() || block.block.key() == "water")
+            .unwrap_or(true)
+    }
+    /// Water-logged blocks count as a source so placing next to them keeps the water
+    fn is_water_source(&self, position: &BlockPosition) -> bool {
+        self.get_block(*position)
+            .map(|block| block.is_water_source())
+            .unwrap_or(false)
     }
 }
@@ -12,59 +12,131 @@ use parking_lot::RwLock;
 
 use axolotl_api::world::{BlockPosition, World};
 use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::world_gen::dimension::WorldHeight;
 use axolotl_api::world_gen::noise::ChunkGenerator;
 use axolotl_api::OwnedNameSpaceKey;
 use axolotl_world::chunk::{ChunkSection, RawChunk};
 use axolotl_world::entity::RawEntities;
 use placed_block::PlacedBlock;
 
+use crate::world::chunk::pool::SectionPool;
 use crate::world::chunk::sections::Sections;
+use crate::world::chunk::snapshot::SectionSnapshots;
 use crate::world::level::accessor::{IntoRawChunk, LevelReader, LevelWriter};
 use crate::AxolotlGame;
 
 pub mod consts;
+pub mod dump;
+pub mod freeze_dried;
+pub mod journal;
 mod map;
 pub mod network;
 pub mod placed_block;
-mod sections;
+pub mod pool;
+pub mod pregen;
+pub(crate) mod sections;
+mod shards;
+mod snapshot;
+pub mod tickets;
+pub mod trace;
 
 pub use map::ChunkMap;
+pub use shards::ChunkShards;
 #[derive(Debug)]
 pub struct AxolotlChunk<W: World> {
     pub chunk_pos: ChunkPos,
     pub sections: Sections<W>,
+    /// Section storage is taken from and returned to this pool
+    pub pool: Option<Arc<SectionPool<W>>>,
 }
 impl<W: World> Clone for AxolotlChunk<W> {
     fn clone(&self) -> Self {
         Self {
             chunk_pos: self.chunk_pos,
             sections: self.sections.clone(),
+            pool: self.pool.clone(),
+        }
+    }
+}
+impl<W: World> Drop for AxolotlChunk<W> {
+    fn drop(&mut self) {
+        if let Some(pool) = &self.pool {
+            for section in self.sections.as_mut() {
+                pool.recycle_section(section);
+            }
         }
     }
 }
 impl<W: World> AxolotlChunk<W> {
     pub fn new(chunk_pos: ChunkPos) -> Self {
+        Self::with_height(chunk_pos, WorldHeight::OVERWORLD)
+    }
+    /// A chunk with the sections needed for the dimension height
+    pub fn with_height(chunk_pos: ChunkPos, height: WorldHeight) -> Self {
         Self {
             chunk_pos,
-            sections: Sections::default(),
+            sections: Sections::new(height),
+            pool: None,
         }
     }
+    pub fn with_pool(chunk_pos: ChunkPos, pool: Arc<SectionPool<W>>) -> Self {
+        let mut chunk = Self::new(chunk_pos);
+        chunk.pool = Some(pool);
+        chunk
+    }
+    pub fn height(&self) -> WorldHeight {
+        self.sections.height()
+    }
     pub fn set_block(&mut self, mut pos: BlockPosition, block: PlacedBlock<W>) {
-        let id = pos.section();
-        if id >= self.sections.len() {
+        let section_y = pos.section() as i32;
+        let Some(section) = self.sections.get_mut(section_y) else {
             warn!("Tried to set block out of bounds");
             return;
+        };
+        section
+            .blocks
+            .set_block_pooled(pos, block, self.pool.as_deref());
+    }
+    /// Returns None if the block is air or out of bounds
+    pub fn get_block(&self, mut pos: BlockPosition) -> Option<&PlacedBlock<W>> {
+        let section_y = pos.section() as i32;
+        self.sections.get(section_y)?.blocks.get_block(pos)
+    }
+    /// Replaces the block with what it leaves behind. See [PlacedBlock::break_remains]
+    ///
+    /// Returns the broken block. None if nothing was broken
+    pub fn break_block(
+        &mut self,
+        pos: BlockPosition,
+        game: &AxolotlGame<W>,
+    ) -> Option<PlacedBlock<W>> {
+        let block = self.get_block(pos).filter(|block| !block.is_air())?.clone();
+        let remains = block.break_remains(game)?;
+        self.set_block(pos, remains);
+        Some(block)
+    }
+    /// The highest block in the column that is not air. `x` and `z` are relative to the chunk
+    pub fn highest_block(&self, x: i64, z: i64) -> Option<(i32, &PlacedBlock<W>)> {
+        for section in self.sections.as_ref().iter().rev() {
+            if section.blocks.is_empty() {
+                continue;
+            }
+            for y in (0..16).rev() {
+                let y = section.y as i32 * 16 + y;
+                let block = section.blocks.get_block(BlockPosition::new(x, y as i16, z));
+                if let Some(block) = block.filter(|block| !block.is_air()) {
+                    return Some((y, block));
+                }
+            }
         }
-        let section = &mut self.sections.as_mut()[id];
-        section.blocks.set_block(pos, block);
+        None
     }
     pub fn set_biome(&mut self, mut pos: BlockPosition, biome: OwnedNameSpaceKey) {
-        let id = pos.section();
-        if id >= self.sections.len() {
+        let section_y = pos.section() as i32;
+        let Some(section) = self.sections.get_mut(section_y) else {
             warn!("Tried to set biome out of bounds");
             return;
-        }
-        let section = &mut self.sections.as_mut()[id];
+        };
         section.biomes.set_biome(pos, biome);
     }
 }
@@ -75,46 +147,50 @@ impl<W: World> IntoRawChunk<W> for AxolotlChunk<W> {
         chunk: &mut RawChunk,
         _entities: Option<&mut RawEntities>,
     ) {
-        for (index, raw_section) in chunk.sections.iter_mut().enumerate() {
-            let section = if raw_section.y_pos != self.sections.0[index].y {
-                &mut self.sections.0[index]
-            } else {
-                // They should be in the same order BUT just in case
-                if let Some(value) = self
-                    .sections
-                    .0
-                    .iter_mut()
-                    .find(|x| x.y == raw_section.y_pos)
-                {
-                    value
-                } else {
-                    warn!("Tried to load chunk with invalid section");
-                    continue;
-                }
+        for raw_section in chunk.sections.iter_mut() {
+            let Some(section) = self.sections.get_mut(raw_section.y_pos as i32) else {
+                warn!(
+                    "Tried to load section {} outside of the world height",
+                    raw_section.y_pos
+                );
+                continue;
             };
             if let Some(blocks_section) = raw_section.block_states.as_mut() {
                 if let Err(e) = section.blocks.load(game.as_ref(), blocks_section) {
                     warn!("Failed to load blocks section: {}", e);
                 }
             } else {
-                *section = Default::default();
+                section.blocks = Default::default();
             }
 
             if let Some(_biome_section) = raw_section.biomes.as_mut() {
                 warn!("Biome section not implemented");
-            } else {
-                *section = Default::default();
             }
         }
     }
 
-    fn into_raw_chunk(self) -> RawChunk {
-        let sections: Vec<ChunkSection> = self.sections.0.into_iter().map(|x| x.into()).collect();
+    /// Without a pool the section storage moves into the raw chunk.
+    /// With one the sections are copied so the storage is recycled when the chunk drops
+    fn into_raw_chunk(mut self) -> RawChunk {
+        let min_section = self.sections.height().min_section();
+        let sections: Vec<ChunkSection> = if self.pool.is_some() {
+            self.sections
+                .as_ref()
+                .iter()
+                .cloned()
+                .map(|x| x.into())
+                .collect()
+        } else {
+            std::mem::take(&mut self.sections.sections)
+                .into_iter()
+                .map(|x| x.into())
+                .collect()
+        };
 
         RawChunk {
             data_version: consts::DATA_VERSION,
             x_pos: self.chunk_pos.0,
-            y_pos: -4,
+            y_pos: min_section,
             z_pos: self.chunk_pos.1,
             last_update: 0,
             sections,
@@ -140,6 +216,10 @@ pub enum LoadState {
 pub struct InnerChunkHandle<W: World> {
     pub value: RwLock<AxolotlChunk<W>>,
     pub loaded: AtomicLoadState,
+    /// Set when a block changes after the chunk was loaded
+    pub modified: AtomicBool,
+    /// Read by [InnerChunkHandle::get_block] instead of [InnerChunkHandle::value]
+    pub(crate) snapshots: SectionSnapshots<W>,
 }
 
 pub struct ChunkFuture<W: World>(ChunkHandle<W>);
@@ -159,8 +239,10 @@ impl<W: World> Future for ChunkFuture<W> {
 impl<W: World> InnerChunkHandle<W> {
     pub fn new(value: AxolotlChunk<W>) -> Self {
         Self {
+            snapshots: SectionSnapshots::new(value.height()),
             value: RwLock::new(value),
             loaded: AtomicLoadState::new(LoadState::Unloaded),
+            modified: AtomicBool::new(false),
         }
     }
     pub fn wait_for_load(chunk: Arc<Self>) -> ChunkFuture<W> {
@@ -178,6 +260,53 @@ impl<W: World> InnerChunkHandle<W> {
     pub fn is_loaded(&self) -> bool {
         self.loaded.load(Ordering::Relaxed) == LoadState::Loaded
     }
+    pub fn mark_modified(&self) {
+        self.modified.store(true, Ordering::Relaxed);
+    }
+    pub fn is_modified(&self) -> bool {
+        self.modified.load(Ordering::Relaxed)
+    }
+    /// Reads the block from the copy of its section. Only takes the chunk lock if the section changed since the last read
+    ///
+    /// Returns None if the block is air or out of bounds
+    pub fn get_block(&self, mut pos: BlockPosition) -> Option<PlacedBlock<W>> {
+        let section_y = pos.section() as i32;
+        let blocks = match self.snapshots.get(section_y) {
+            Some(blocks) => blocks,
+            None => self.snapshots.take(&self.value.read(), section_y)?,
+        };
+        blocks.get_block(pos).cloned()
+    }
+    /// Sets the block and drops the copy of its section. Returns the previous block
+    pub fn set_block(&self, pos: BlockPosition, block: PlacedBlock<W>) -> Option<PlacedBlock<W>> {
+        let mut chunk = self.value.write();
+        let old = chunk.get_block(pos).cloned();
+        chunk.set_block(pos, block);
+        self.snapshots.invalidate((pos.y >> 4) as i32);
+        old
+    }
 }
 
 pub type ChunkHandle<W> = Arc<InnerChunkHandle<W>>;
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::AxolotlChunk;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+
+    #[test]
+    pub fn test_break_block() {
+        let game = test_game(&["stone"]);
+        let stone = test_block(&game, "stone");
+        let mut chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+        let position = BlockPosition::new(1, 64, 1);
+        chunk.set_block(position, stone.clone());
+        assert_eq!(chunk.break_block(position, &game), Some(stone));
+        assert!(chunk.get_block(position).is_none_or(|block| block.is_air()));
+        // Nothing left to break
+        assert_eq!(chunk.break_block(position, &game), None);
+    }
+}
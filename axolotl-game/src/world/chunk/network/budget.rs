@@ -0,0 +1,156 @@
+use std::time::Instant;
+
+use ahash::AHashSet;
+
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::world::level::configs::WorldSettings;
+
+/// Limits how fast chunks are sent to a single player.
+///
+/// Keeps a joining player from taking the whole tick and from filling a slow connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSendLimits {
+    pub max_chunks_per_tick: u32,
+    /// None for no limit
+    pub max_bytes_per_second: Option<u64>,
+}
+impl From<&WorldSettings> for ChunkSendLimits {
+    fn from(settings: &WorldSettings) -> Self {
+        Self {
+            max_chunks_per_tick: settings.max_chunks_per_tick,
+            max_bytes_per_second: settings.max_chunk_bytes_per_second,
+        }
+    }
+}
+
+/// Lower is sent first. The distance from the player, doubled for chunks behind them.
+///
+/// `yaw` is in degrees. 0 faces south (+Z) like vanilla
+pub fn send_priority(center: ChunkPos, chunk: ChunkPos, yaw: f32) -> f32 {
+    let dx = (chunk.x() - center.x()) as f32;
+    let dz = (chunk.z() - center.z()) as f32;
+    let distance = (dx * dx + dz * dz).sqrt();
+    if distance == 0.0 {
+        return 0.0;
+    }
+    let (sin, cos) = yaw.to_radians().sin_cos();
+    let facing = (-sin * dx + cos * dz) / distance;
+    // 1 in front of the player, 2 behind
+    distance * (1.5 - facing * 0.5)
+}
+
+/// The chunks waiting to be sent to one player
+#[derive(Debug, Clone)]
+pub struct ChunkSendQueue {
+    pub limits: ChunkSendLimits,
+    pending: AHashSet<ChunkPos>,
+    sent_this_tick: u32,
+    /// Bytes that may be sent before the limit is hit. Refilled every tick. Negative after a large chunk
+    bytes_available: f64,
+    last_refill: Option<Instant>,
+}
+impl ChunkSendQueue {
+    pub fn new(limits: ChunkSendLimits) -> Self {
+        Self {
+            limits,
+            pending: AHashSet::new(),
+            sent_this_tick: 0,
+            bytes_available: limits.max_bytes_per_second.unwrap_or_default() as f64,
+            last_refill: None,
+        }
+    }
+    pub fn request(&mut self, chunks: impl IntoIterator<Item = ChunkPos>) {
+        self.pending.extend(chunks);
+    }
+    /// The chunk left the view distance before it was sent
+    pub fn cancel(&mut self, chunk: &ChunkPos) -> bool {
+        self.pending.remove(chunk)
+    }
+    pub fn is_pending(&self, chunk: &ChunkPos) -> bool {
+        self.pending.contains(chunk)
+    }
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+    /// Resets the chunk limit and refills the byte limit for the time passed. At most one second is saved up
+    pub fn begin_tick(&mut self, now: Instant) {
+        self.sent_this_tick = 0;
+        let Some(max_bytes) = self.limits.max_bytes_per_second else {
+            return;
+        };
+        if let Some(last_refill) = self.last_refill {
+            let elapsed = now.saturating_duration_since(last_refill).as_secs_f64();
+            self.bytes_available =
+                (self.bytes_available + elapsed * max_bytes as f64).min(max_bytes as f64);
+        }
+        self.last_refill = Some(now);
+    }
+    /// If another chunk may be sent this tick
+    pub fn can_send(&self) -> bool {
+        self.sent_this_tick < self.limits.max_chunks_per_tick
+            && (self.limits.max_bytes_per_second.is_none() || self.bytes_available > 0.0)
+    }
+    /// Removes the pending chunk with the lowest [send_priority]. None if empty or the limits are hit
+    pub fn next_chunk(&mut self, center: ChunkPos, yaw: f32) -> Option<ChunkPos> {
+        if !self.can_send() {
+            return None;
+        }
+        let next = self.pending.iter().copied().min_by(|a, b| {
+            send_priority(center, *a, yaw).total_cmp(&send_priority(center, *b, yaw))
+        })?;
+        self.pending.remove(&next);
+        self.sent_this_tick += 1;
+        Some(next)
+    }
+    /// Called with the size of the chunk packet after it is written
+    pub fn record_sent(&mut self, bytes: usize) {
+        if self.limits.max_bytes_per_second.is_some() {
+            self.bytes_available -= bytes as f64;
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::{Duration, Instant};
+
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::network::budget::{send_priority, ChunkSendLimits, ChunkSendQueue};
+
+    #[test]
+    pub fn test_budget() {
+        let center = ChunkPos::new(0, 0);
+        // Facing south. The chunk in front is sent before the one behind
+        assert!(
+            send_priority(center, ChunkPos::new(0, 2), 0.0)
+                < send_priority(center, ChunkPos::new(0, -2), 0.0)
+        );
+
+        let mut queue = ChunkSendQueue::new(ChunkSendLimits {
+            max_chunks_per_tick: 2,
+            max_bytes_per_second: Some(1000),
+        });
+        queue.request([ChunkPos::new(0, -1), ChunkPos::new(0, 1), center]);
+        let now = Instant::now();
+        queue.begin_tick(now);
+        assert_eq!(queue.next_chunk(center, 0.0), Some(center));
+        assert_eq!(queue.next_chunk(center, 0.0), Some(ChunkPos::new(0, 1)));
+        assert_eq!(queue.next_chunk(center, 0.0), None);
+
+        queue.begin_tick(now);
+        queue.record_sent(1500);
+        assert_eq!(queue.next_chunk(center, 0.0), None);
+        queue.begin_tick(now + Duration::from_secs(1));
+        assert_eq!(queue.next_chunk(center, 0.0), Some(ChunkPos::new(0, -1)));
+    }
+}
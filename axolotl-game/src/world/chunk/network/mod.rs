@@ -8,6 +8,8 @@ use minecraft_protocol::packets::play::client::chunk::GetVanillaId;
 use minecraft_protocol::PacketWriteError;
 use std::io::Write;
 
+pub mod budget;
+
 pub trait NetworkChunk<W: World> {
     fn write_chunk<Writer: Write>(
         chunk: &AxolotlChunk<W>,
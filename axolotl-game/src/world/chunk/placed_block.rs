@@ -1,8 +1,12 @@
 use minecraft_protocol::packets::play::client::chunk::GetVanillaId;
 
-use axolotl_api::world::World;
+use axolotl_api::game::Registry;
+use axolotl_api::item::block::{Block, BlockState, BlockStateValue};
+use axolotl_api::item::metadata::BlockMetadata;
+use axolotl_api::item::placement::PlacementContext;
+use axolotl_api::world::{BlockPosition, World};
 use axolotl_api::{NamespacedId, NumericId, OwnedNameSpaceKey};
-use axolotl_items::blocks::generic_block::VanillaStateIdOrValue;
+use axolotl_items::blocks::generic_block::{VanillaState, VanillaStateIdOrValue};
 use axolotl_items::blocks::{InnerMinecraftBlock, MinecraftBlock};
 use axolotl_world::chunk::PaletteItem;
 
@@ -66,4 +70,95 @@ impl<W: World> PlacedBlock<W> {
     pub fn id(&self) -> usize {
         self.block.id()
     }
+    /// Hardness, resistance, light and other static properties of the block
+    pub fn metadata(&self) -> &BlockMetadata {
+        <InnerMinecraftBlock<AxolotlGame<W>> as Block<AxolotlGame<W>>>::metadata(&self.block)
+    }
+    /// Gets a block state property.
+    ///
+    /// State ids can only be resolved for generic blocks
+    pub fn property(&self, name: &str) -> Option<BlockStateValue> {
+        self.vanilla_state()
+            .and_then(|state| state.values.get(name).cloned())
+    }
+    /// The full state. Only resolved for generic blocks when stored as an id
+    pub fn vanilla_state(&self) -> Option<&VanillaState> {
+        match &self.state {
+            VanillaStateIdOrValue::Value(state) => Some(state),
+            VanillaStateIdOrValue::Id(id) => match self.block.as_ref() {
+                InnerMinecraftBlock::GenericBlock(block) => block.get_state(*id),
+                _ => None,
+            },
+        }
+    }
+    pub fn is_waterlogged(&self) -> bool {
+        self.vanilla_state()
+            .map(|state| state.is_waterlogged())
+            .unwrap_or(false)
+    }
+    /// Water of any level or a water-logged block
+    pub fn is_water(&self) -> bool {
+        self.block.key() == "water" || self.is_waterlogged()
+    }
+    /// Water with a level of 0 or a water-logged block.
+    ///
+    /// Fluid ticking treats both as a source
+    pub fn is_water_source(&self) -> bool {
+        if self.block.key() == "water" {
+            self.property("level")
+                .map(|level| level == BlockStateValue::String("0".to_string()))
+                .unwrap_or(true)
+        } else {
+            self.is_waterlogged()
+        }
+    }
+    /// Returns a copy of the block with the waterlogged property set.
+    ///
+    /// None if the block can not be water-logged
+    pub fn waterlogged(&self, waterlogged: bool) -> Option<Self> {
+        let InnerMinecraftBlock::GenericBlock(block) = self.block.as_ref() else {
+            return None;
+        };
+        let state = block.set_waterlogged(self.vanilla_state()?, waterlogged)?;
+        Some(Self::with_state(self.block.clone(), state))
+    }
+    /// The block left behind when this block is broken.
+    ///
+    /// Water-logged blocks leave a water source. Everything else leaves air
+    pub fn break_remains(&self, game: &AxolotlGame<W>) -> Option<Self> {
+        let key = if self.is_waterlogged() {
+            "minecraft:water"
+        } else {
+            "minecraft:air"
+        };
+        game.registries
+            .blocks
+            .get_by_namespace(key)
+            .map(|block| PlacedBlock::from(block.clone()))
+    }
+    /// Creates the blocks placed when a player places `block`.
+    ///
+    /// The first value is the clicked position. Returns None if the placement rules deny it
+    pub fn from_placement(
+        block: MinecraftBlock<AxolotlGame<W>>,
+        context: &PlacementContext,
+    ) -> Option<Vec<(BlockPosition, PlacedBlock<W>)>> {
+        let state = block.get_placement_state(context)?;
+        let additional = block.get_additional_placements(context, &state);
+        let mut blocks = Vec::with_capacity(additional.len() + 1);
+        blocks.push((
+            context.placement_position(),
+            Self::with_state(block.clone(), state),
+        ));
+        for (position, state) in additional {
+            blocks.push((position, Self::with_state(block.clone(), state)));
+        }
+        Some(blocks)
+    }
+    pub fn with_state(block: MinecraftBlock<AxolotlGame<W>>, state: VanillaState) -> Self {
+        PlacedBlock {
+            state: VanillaStateIdOrValue::Id(state.state_id),
+            block,
+        }
+    }
 }
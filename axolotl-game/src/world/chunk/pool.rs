@@ -0,0 +1,231 @@
+//! Reuses the storage of chunk sections.
+//!
+//! Mass generation turns thousands of sections from a single block into a full palette.
+//! Chunks created by a [crate::world::chunk::ChunkMap] take that storage from the pool and give it back when they are dropped
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+
+use axolotl_api::world::World;
+use axolotl_api::OwnedNameSpaceKey;
+use axolotl_world::chunk::compact_array::CompactArray;
+
+use crate::world::chunk::consts::{BITS_PER_BLOCK, SECTION_SIZE};
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
+use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+use crate::world::chunk::sections::AxolotlChunkSection;
+
+/// The default number of each kind of storage kept
+pub const DEFAULT_POOL_SIZE: usize = 4096;
+
+#[derive(Debug, Default)]
+pub struct PoolMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    recycled: AtomicU64,
+    /// Returned while the pool was full
+    discarded: AtomicU64,
+}
+impl PoolMetrics {
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+    pub fn recycled(&self) -> u64 {
+        self.recycled.load(Ordering::Relaxed)
+    }
+    pub fn discarded(&self) -> u64 {
+        self.discarded.load(Ordering::Relaxed)
+    }
+    /// Between 0 and 1. 0 if nothing was taken yet
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.hits();
+        let total = hits + self.misses();
+        if total == 0 {
+            return 0.0;
+        }
+        hits as f64 / total as f64
+    }
+    fn hit(&self, hit: bool) {
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SectionPool<W: World> {
+    block_storage: Mutex<Vec<Vec<u64>>>,
+    block_palettes: Mutex<Vec<Vec<PlacedBlock<W>>>>,
+    biome_storage: Mutex<Vec<Vec<u64>>>,
+    biome_palettes: Mutex<Vec<Vec<OwnedNameSpaceKey>>>,
+    /// The number of each kind of storage kept
+    pub max_pooled: usize,
+    pub metrics: PoolMetrics,
+}
+impl<W: World> Default for SectionPool<W> {
+    fn default() -> Self {
+        Self::new(DEFAULT_POOL_SIZE)
+    }
+}
+impl<W: World> SectionPool<W> {
+    pub fn new(max_pooled: usize) -> Self {
+        Self {
+            block_storage: Mutex::new(Vec::new()),
+            block_palettes: Mutex::new(Vec::new()),
+            biome_storage: Mutex::new(Vec::new()),
+            biome_palettes: Mutex::new(Vec::new()),
+            max_pooled,
+            metrics: PoolMetrics::default(),
+        }
+    }
+    fn take<T>(&self, pool: &Mutex<Vec<T>>) -> Option<T> {
+        let value = pool.lock().pop();
+        self.metrics.hit(value.is_some());
+        value
+    }
+    fn give<T>(&self, pool: &Mutex<Vec<T>>, value: T) {
+        let mut pool = pool.lock();
+        if pool.len() >= self.max_pooled {
+            self.metrics.discarded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        pool.push(value);
+        self.metrics.recycled.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Zeroed storage for a full block section
+    pub fn block_storage(&self) -> CompactArray {
+        let Some(mut data) = self.take(&self.block_storage) else {
+            return CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE);
+        };
+        let longs = SECTION_SIZE / CompactArray::calc_values_per_u64(BITS_PER_BLOCK);
+        data.clear();
+        data.resize(longs, 0);
+        CompactArray::new_from_vec(BITS_PER_BLOCK, data, SECTION_SIZE)
+    }
+    /// An empty palette
+    pub fn block_palette(&self) -> Vec<PlacedBlock<W>> {
+        self.take(&self.block_palettes).unwrap_or_default()
+    }
+    /// Zeroed storage for a full biome section
+    pub fn biome_storage(&self, bits_per_biome: usize, length: usize) -> CompactArray {
+        let Some(mut data) = self.take(&self.biome_storage) else {
+            return CompactArray::new(bits_per_biome, length);
+        };
+        data.clear();
+        data.resize(
+            length.div_ceil(CompactArray::calc_values_per_u64(bits_per_biome)),
+            0,
+        );
+        CompactArray::new_from_vec(bits_per_biome, data, length)
+    }
+    pub fn biome_palette(&self) -> Vec<OwnedNameSpaceKey> {
+        self.take(&self.biome_palettes).unwrap_or_default()
+    }
+    /// Takes the storage of the section and leaves it empty
+    pub fn recycle_section(&self, section: &mut AxolotlChunkSection<W>) {
+        if let AxolotlBlockSection::Full {
+            blocks,
+            mut block_palette,
+        } = std::mem::take(&mut section.blocks)
+        {
+            self.give(&self.block_storage, blocks.into());
+            block_palette.clear();
+            self.give(&self.block_palettes, block_palette);
+        }
+        if let AxolotlBiomeSection::Full { .. } = &section.biomes {
+            let empty =
+                AxolotlBiomeSection::new(OwnedNameSpaceKey::new(String::new(), String::new()));
+            if let AxolotlBiomeSection::Full {
+                mut biome_palette,
+                biomes,
+            } = std::mem::replace(&mut section.biomes, empty)
+            {
+                self.give(&self.biome_storage, biomes.into());
+                biome_palette.clear();
+                self.give(&self.biome_palettes, biome_palette);
+            }
+        }
+    }
+    /// The number of block storages waiting to be reused
+    pub fn pooled_blocks(&self) -> usize {
+        self.block_storage.lock().len()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_world::chunk::compact_array::CompactArray;
+
+    use crate::world::chunk::consts::{BITS_PER_BLOCK, SECTION_SIZE};
+    use crate::world::chunk::pool::SectionPool;
+    use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+    use crate::world::chunk::sections::AxolotlChunkSection;
+    use crate::world::chunk::ChunkMap;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+
+    #[test]
+    pub fn test_recycle() {
+        let pool = SectionPool::<TestWorld>::new(1);
+        assert_eq!(
+            pool.block_storage(),
+            CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE)
+        );
+        assert_eq!(pool.metrics.misses(), 1);
+
+        let mut storage = CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE);
+        storage.set(5u64, 3);
+        let mut section = AxolotlChunkSection::<TestWorld>::new(0);
+        section.blocks = AxolotlBlockSection::Full {
+            blocks: storage,
+            block_palette: vec![],
+        };
+        pool.recycle_section(&mut section);
+        assert_eq!(section.blocks, AxolotlBlockSection::Empty);
+        assert_eq!(pool.pooled_blocks(), 1);
+
+        let reused = pool.block_storage();
+        assert_eq!(reused.get(5u64), Some(0));
+        assert_eq!(pool.metrics.hits(), 1);
+        assert_eq!(pool.metrics.hit_rate(), 0.5);
+    }
+
+    #[test]
+    pub fn test_recycle_on_unload() {
+        let game = Arc::new(test_game(&["stone", "dirt"]));
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone());
+        map.load_chunk_task(0, 0, None).unwrap();
+        map.set_block(
+            BlockPosition::new(1, 64, 1),
+            test_block(&game, "stone"),
+            None,
+        );
+        map.set_block(
+            BlockPosition::new(2, 64, 1),
+            test_block(&game, "dirt"),
+            None,
+        );
+
+        let recycled = map.pool.metrics.recycled();
+        map.unload_chunk(0, 0).unwrap();
+        assert!(map.pool.metrics.recycled() > recycled);
+        assert_eq!(map.pool.pooled_blocks(), 1);
+        // The saved copy still has the blocks
+        assert!(map.accessor.contains_chunk(&ChunkPos::new(0, 0)));
+        map.load_chunk_task(0, 0, None).unwrap();
+        assert_eq!(
+            map.get_block(BlockPosition::new(2, 64, 1)),
+            Some(test_block(&game, "dirt"))
+        );
+    }
+}
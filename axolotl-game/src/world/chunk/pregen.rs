@@ -0,0 +1,249 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+
+use axolotl_api::world::World;
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::world::chunk::tickets::TicketType;
+use crate::world::chunk::ChunkMap;
+use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::level::configs::WorldSettings;
+use crate::Error;
+
+/// Reported while chunks are generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationProgress {
+    pub completed: usize,
+    pub total: usize,
+}
+impl GenerationProgress {
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.completed as f32 / self.total as f32 * 100.0
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.completed >= self.total
+    }
+}
+
+/// Limits how hard [ChunkMap::pregen] works the server
+#[derive(Debug, Clone, Copy)]
+pub struct PregenThrottle {
+    /// The number of generation threads
+    pub threads: usize,
+    /// None for no limit
+    pub max_chunks_per_second: Option<u32>,
+    /// Generated chunks are saved and unloaded after this many chunks. Bounds the memory used
+    pub save_interval: usize,
+}
+impl Default for PregenThrottle {
+    fn default() -> Self {
+        Self {
+            threads: std::thread::available_parallelism()
+                .map(|value| value.get())
+                .unwrap_or(1),
+            max_chunks_per_second: None,
+            save_interval: 256,
+        }
+    }
+}
+impl From<&WorldSettings> for PregenThrottle {
+    fn from(settings: &WorldSettings) -> Self {
+        Self {
+            threads: settings.generation_threads,
+            ..Self::default()
+        }
+    }
+}
+#[derive(Debug, Clone, Copy)]
+pub struct PregenProgress {
+    pub progress: GenerationProgress,
+    pub elapsed: Duration,
+    /// None until the first batch is done
+    pub eta: Option<Duration>,
+}
+impl PregenProgress {
+    pub fn chunks_per_second(&self) -> f32 {
+        let seconds = self.elapsed.as_secs_f32();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.progress.completed as f32 / seconds
+    }
+}
+
+/// The number of chunks within a square radius
+#[inline]
+pub fn chunks_in_radius(radius: u32) -> usize {
+    let width = radius as usize * 2 + 1;
+    width * width
+}
+
+/// Every chunk in a square radius. Ordered from the center outwards, one ring at a time
+pub fn spiral(center: ChunkPos, radius: u32) -> impl Iterator<Item = ChunkPos> {
+    let radius = radius as i32;
+    std::iter::once(center).chain((1..=radius).flat_map(move |ring| {
+        let top = (-ring..=ring).map(move |x| (x, -ring));
+        let right = (-ring + 1..=ring).map(move |z| (ring, z));
+        let bottom = (-ring..ring).rev().map(move |x| (x, ring));
+        let left = (-ring + 1..ring).rev().map(move |z| (-ring, z));
+        top.chain(right)
+            .chain(bottom)
+            .chain(left)
+            .map(move |(x, z)| ChunkPos::new(center.x() + x, center.z() + z))
+    }))
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    /// Loads or generates the chunks around spawn and keeps them loaded with a [TicketType::Spawn].
+    ///
+    /// `progress` is called after every chunk so startup can show how far along it is
+    pub fn load_spawn_chunks(
+        &self,
+        spawn: ChunkPos,
+        radius: u32,
+        mut progress: impl FnMut(GenerationProgress),
+    ) -> Result<(), Error> {
+        let total = chunks_in_radius(radius);
+        info!("Preparing {} spawn chunks around {:?}", total, spawn);
+        for (index, pos) in spiral(spawn, radius).enumerate() {
+            self.tickets.add(pos, TicketType::Spawn);
+            self.load_chunk_task(pos.x(), pos.z(), None)?;
+            progress(GenerationProgress {
+                completed: index + 1,
+                total,
+            });
+        }
+        Ok(())
+    }
+    /// Generates every chunk within the radius of center. Starting at the center and spiraling out.
+    ///
+    /// Chunks already loaded are skipped. Every [PregenThrottle::save_interval] chunks the new chunks are saved and unloaded.
+    /// A deterministic [ChunkMap::simulation] limits the throttle to one thread
+    pub fn pregen(
+        &self,
+        center: ChunkPos,
+        radius: u32,
+        throttle: PregenThrottle,
+        mut progress: impl FnMut(PregenProgress),
+    ) -> Result<GenerationProgress, Error>
+    where
+        Self: Sync,
+        Error: Send,
+    {
+        let throttle = self.simulation.lock().pregen_throttle(throttle);
+        let total = chunks_in_radius(radius);
+        info!(
+            "Pre-generating {} chunks around {:?} with {} threads",
+            total, center, throttle.threads
+        );
+        let start = Instant::now();
+        let mut completed = 0;
+        let mut positions = spiral(center, radius);
+        loop {
+            let batch: Vec<ChunkPos> = positions
+                .by_ref()
+                .take(throttle.save_interval.max(1))
+                .collect();
+            if batch.is_empty() {
+                break;
+            }
+            for pos in self.generate_batch(&batch, throttle.threads.max(1))? {
+                self.unload_chunk(pos.x(), pos.z())?;
+            }
+            completed += batch.len();
+
+            let elapsed = start.elapsed();
+            if let Some(limit) = throttle.max_chunks_per_second {
+                let expected = Duration::from_secs_f64(completed as f64 / limit.max(1) as f64);
+                if expected > elapsed {
+                    std::thread::sleep(expected - elapsed);
+                }
+            }
+            let elapsed = start.elapsed();
+            let remaining = total - completed;
+            progress(PregenProgress {
+                progress: GenerationProgress { completed, total },
+                elapsed,
+                eta: Some(elapsed.mul_f64(remaining as f64 / completed as f64)),
+            });
+        }
+        info!("Pre-generated {} chunks in {:?}", total, start.elapsed());
+        Ok(GenerationProgress { completed, total })
+    }
+    /// Loads the chunks on `threads` threads. Returns the chunks that were not loaded before
+    fn generate_batch(&self, batch: &[ChunkPos], threads: usize) -> Result<Vec<ChunkPos>, Error>
+    where
+        Self: Sync,
+        Error: Send,
+    {
+        let next = AtomicUsize::new(0);
+        let generated = Mutex::new(Vec::with_capacity(batch.len()));
+        let error = Mutex::new(None);
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    while let Some(pos) = batch.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if self.thread_safe_chunks.contains(pos) {
+                            continue;
+                        }
+                        if let Err(e) = self.load_chunk_task(pos.x(), pos.z(), None) {
+                            warn!("Failed to generate chunk {:?}: {}", pos, e);
+                            *error.lock() = Some(e);
+                            // Stop the other threads
+                            next.store(batch.len(), Ordering::Relaxed);
+                            return;
+                        }
+                        generated.lock().push(*pos);
+                    }
+                });
+            }
+        });
+        if let Some(error) = error.into_inner() {
+            return Err(error);
+        }
+        Ok(generated.into_inner())
+    }
+    /// Removes the spawn tickets. Used when the spawn moves
+    ///
+    /// The released chunks are unloaded
+    pub fn release_spawn_chunks(&self) -> Result<(), Error> {
+        for pos in self.tickets.remove_all(TicketType::Spawn) {
+            debug!("Releasing spawn chunk {:?}", pos);
+            self.unload_chunk(pos.x(), pos.z())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ahash::AHashSet;
+
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::pregen::{chunks_in_radius, spiral};
+
+    #[test]
+    pub fn test_spiral() {
+        let center = ChunkPos::new(-3, 7);
+        let chunks: Vec<ChunkPos> = spiral(center, 3).collect();
+        assert_eq!(chunks.len(), chunks_in_radius(3));
+        assert_eq!(chunks[0], center);
+        let unique: AHashSet<ChunkPos> = chunks.iter().copied().collect();
+        assert_eq!(unique.len(), chunks.len());
+        assert!(chunks
+            .iter()
+            .all(|pos| (pos.x() - center.x()).abs() <= 3 && (pos.z() - center.z()).abs() <= 3));
+    }
+}
@@ -13,10 +13,10 @@ use axolotl_world::chunk::compact_array::CompactArray;
 use axolotl_world::chunk::{BlockStates, PaletteItem};
 
 use crate::world::chunk::consts::{
-    BITS_PER_BLOCK, LONGS_PER_BLOC_SECTION, SECTION_SIZE, SECTION_X_SIZE, SECTION_Y_SIZE,
-    SECTION_Z_SIZE,
+    BITS_PER_BLOCK, SECTION_SIZE, SECTION_X_SIZE, SECTION_Y_SIZE, SECTION_Z_SIZE,
 };
 use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::pool::SectionPool;
 use crate::world::chunk::sections::{InvalidChunkSection, SectionPosIndex};
 use crate::AxolotlGame;
 
@@ -93,7 +93,7 @@ where
     Iter: IntoIterator<Item = (Pos, Block)>,
 {
     fn from(iter: Iter) -> Self {
-        let mut blocks = CompactArray::new(BITS_PER_BLOCK, LONGS_PER_BLOC_SECTION);
+        let mut blocks = CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE);
         let mut block_palette = Vec::new();
 
         for (pos, block) in iter {
@@ -151,6 +151,15 @@ impl<W: World> AxolotlBlockSection<W> {
         count
     }
     pub fn set_block(&mut self, pos: impl Into<SectionPosIndex>, block: PlacedBlock<W>) {
+        self.set_block_pooled(pos, block, None);
+    }
+    /// Takes the storage from the pool when the section becomes full
+    pub fn set_block_pooled(
+        &mut self,
+        pos: impl Into<SectionPosIndex>,
+        block: PlacedBlock<W>,
+        pool: Option<&SectionPool<W>>,
+    ) {
         let pos = pos.into();
 
         match self {
@@ -177,25 +186,30 @@ impl<W: World> AxolotlBlockSection<W> {
             _ => {}
         }
         let (loc_x, loc_y, loc_z) = <SectionPosIndex as Into<(u64, u64, u64)>>::into(pos);
-        let mut compact = CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE);
+        let mut compact = pool
+            .map(SectionPool::block_storage)
+            .unwrap_or_else(|| CompactArray::new(BITS_PER_BLOCK, SECTION_SIZE));
         for x in 0..SECTION_X_SIZE as u64 {
             for y in 0..SECTION_Y_SIZE as u64 {
                 for z in 0..SECTION_Z_SIZE as u64 {
                     let pos_index = SectionPosIndex::from((x, y, z));
 
+                    // The new block is first in the palette and the old block second
                     if x == loc_x && y == loc_y && z == loc_z {
-                        compact.set(pos_index, 1);
-                    } else {
                         compact.set(pos_index, 0);
+                    } else {
+                        compact.set(pos_index, 1);
                     }
                 }
             }
         }
+        let mut block_palette = pool.map(SectionPool::block_palette).unwrap_or_default();
+        block_palette.push(block);
         let replace = mem::replace(
             self,
             AxolotlBlockSection::Full {
                 blocks: compact,
-                block_palette: vec![block],
+                block_palette,
             },
         );
         if let AxolotlBlockSection::SingleBlock(block) = replace {
@@ -255,12 +269,10 @@ impl<W: World> AxolotlBlockSection<W> {
                     block_palette,
                 } => {
                     blocks.replace_inner(data);
-                    if block_palette.len() > section.palette.len() {
-                        block_palette.truncate(section.palette.len());
-                    }
-                    for (i, block) in section.palette.iter().enumerate() {
+                    block_palette.clear();
+                    for block in section.palette.iter() {
                         let mc_block = game.get_block(&block.name);
-                        block_palette[i] = if let Some(block) = mc_block {
+                        block_palette.push(if let Some(block) = mc_block {
                             PlacedBlock::from(block.clone())
                         } else {
                             warn!("Invalid block: {}", block.name);
@@ -269,7 +281,7 @@ impl<W: World> AxolotlBlockSection<W> {
                                     .unwrap()
                                     .clone(),
                             )
-                        };
+                        });
                     }
                 }
                 v => {
@@ -284,11 +296,7 @@ impl<W: World> AxolotlBlockSection<W> {
                     }
 
                     *v = AxolotlBlockSection::Full {
-                        blocks: CompactArray::new_from_vec(
-                            BITS_PER_BLOCK,
-                            data,
-                            LONGS_PER_BLOC_SECTION,
-                        ),
+                        blocks: CompactArray::new_from_vec(BITS_PER_BLOCK, data, SECTION_SIZE),
                         block_palette: placed_blocks,
                     };
                 }
@@ -5,52 +5,79 @@ use bytemuck::{Pod, Zeroable};
 use thiserror::Error;
 
 use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::dimension::WorldHeight;
 use axolotl_api::OwnedNameSpaceKey;
 use axolotl_world::chunk::compact_array::CompactArrayIndex;
 use axolotl_world::chunk::ChunkSection;
 
-use crate::world::chunk::consts;
-use crate::world::chunk::consts::{SECTION_X_SIZE, SECTION_Y_SIZE, SECTION_Z_SIZE};
 use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
 use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
 
 pub mod biome_section;
 pub mod blocks_section;
 
-type InnerSections<W> = [AxolotlChunkSection<W>; (consts::Y_SIZE / consts::SECTION_Y_SIZE)];
-
 #[derive(Debug)]
-#[repr(transparent)]
-pub struct Sections<W: World>(pub(crate) InnerSections<W>);
+pub struct Sections<W: World> {
+    pub(crate) sections: Vec<AxolotlChunkSection<W>>,
+    pub(crate) height: WorldHeight,
+}
 impl<W: World> Clone for Sections<W> {
     fn clone(&self) -> Self {
-        Sections(self.0.clone())
+        Sections {
+            sections: self.sections.clone(),
+            height: self.height,
+        }
     }
 }
 
 impl<W: World> Default for Sections<W> {
     fn default() -> Self {
-        let mut sections: InnerSections<W> = Default::default();
-        for index in consts::MIN_Y_SECTION..consts::MAX_Y_SECTION {
-            let section = &mut sections[(index + 4) as usize];
-            section.y = index;
-        }
-        Sections(sections)
+        Self::new(WorldHeight::OVERWORLD)
     }
 }
-impl<W: World> AsMut<InnerSections<W>> for Sections<W> {
-    fn as_mut(&mut self) -> &mut InnerSections<W> {
-        &mut self.0
+impl<W: World> AsMut<[AxolotlChunkSection<W>]> for Sections<W> {
+    fn as_mut(&mut self) -> &mut [AxolotlChunkSection<W>] {
+        &mut self.sections
     }
 }
-impl<W: World> AsRef<InnerSections<W>> for Sections<W> {
-    fn as_ref(&self) -> &InnerSections<W> {
-        &self.0
+impl<W: World> AsRef<[AxolotlChunkSection<W>]> for Sections<W> {
+    fn as_ref(&self) -> &[AxolotlChunkSection<W>] {
+        &self.sections
     }
 }
 impl<W: World> Sections<W> {
+    /// One section for every 16 blocks of the height. Ordered from the bottom up
+    pub fn new(height: WorldHeight) -> Self {
+        let min_section = height.min_section();
+        let sections = (0..height.section_count())
+            .map(|index| AxolotlChunkSection::new((min_section + index as i32) as i8))
+            .collect();
+        Self { sections, height }
+    }
     pub fn len(&self) -> usize {
-        consts::Y_SIZE / consts::SECTION_Y_SIZE
+        self.sections.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+    pub fn height(&self) -> WorldHeight {
+        self.height
+    }
+    /// The index of the section with the section y. None if it is outside of the world
+    #[inline]
+    pub fn section_index(&self, section_y: i32) -> Option<usize> {
+        let index = section_y - self.height.min_section();
+        if index < 0 || index as usize >= self.sections.len() {
+            return None;
+        }
+        Some(index as usize)
+    }
+    pub fn get(&self, section_y: i32) -> Option<&AxolotlChunkSection<W>> {
+        self.sections.get(self.section_index(section_y)?)
+    }
+    pub fn get_mut(&mut self, section_y: i32) -> Option<&mut AxolotlChunkSection<W>> {
+        let index = self.section_index(section_y)?;
+        self.sections.get_mut(index)
     }
 }
 
@@ -98,10 +125,8 @@ impl<T: From<u64>> From<SectionPosIndex> for (T, T, T) {
 }
 impl From<BlockPosition> for SectionPosIndex {
     fn from(pos: BlockPosition) -> Self {
-        let x = ((pos.x.unsigned_abs() as usize) % SECTION_X_SIZE) as u64;
-        let y = ((pos.y.unsigned_abs() as usize) % SECTION_Y_SIZE) as u64;
-        let z = ((pos.z.unsigned_abs() as usize) % SECTION_Z_SIZE) as u64;
-        SectionPosIndex::from((x, y, z))
+        let (x, y, z) = pos.section_coords();
+        SectionPosIndex::from((x as u64, y as u64, z as u64))
     }
 }
 
@@ -157,3 +182,110 @@ impl<W: World> AxolotlChunkSection<W> {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_api::world_gen::dimension::WorldHeight;
+    use axolotl_world::chunk::RawChunk;
+
+    use crate::world::chunk::sections::{SectionPosIndex, Sections};
+    use crate::world::chunk::AxolotlChunk;
+    use crate::world::level::accessor::IntoRawChunk;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+
+    #[test]
+    pub fn test_negative_y() {
+        let mut bottom = BlockPosition::new(0, -64, 0);
+        assert_eq!(bottom.section(), -4);
+        assert_eq!(bottom.y, 0);
+        let mut below_zero = BlockPosition::new(0, -1, 0);
+        assert_eq!(below_zero.section(), -1);
+        assert_eq!(below_zero.y, 15);
+        let mut top = BlockPosition::new(0, 319, 0);
+        assert_eq!(top.section(), 19);
+        assert_eq!(top.y, 15);
+
+        assert_eq!(
+            SectionPosIndex::from(BlockPosition::new(0, -1, 0)),
+            SectionPosIndex::from((0u64, 15, 0))
+        );
+        assert_eq!(
+            SectionPosIndex::from(BlockPosition::new(0, -64, 0)),
+            SectionPosIndex::from((0u64, 0, 0))
+        );
+        assert_eq!(
+            SectionPosIndex::from(BlockPosition::new(-1, 5, -16)),
+            SectionPosIndex::from((15u64, 5, 0))
+        );
+        assert_eq!(
+            SectionPosIndex::from(BlockPosition::new(-17, 5, 33)),
+            SectionPosIndex::from((15u64, 5, 1))
+        );
+    }
+    #[test]
+    pub fn test_sections_for_height() {
+        let sections = Sections::<TestWorld>::new(WorldHeight::OVERWORLD);
+        assert_eq!(sections.len(), 24);
+        assert_eq!(sections.get(-4).unwrap().y, -4);
+        assert_eq!(sections.get(19).unwrap().y, 19);
+        assert!(sections.get(20).is_none());
+        assert!(sections.get(-5).is_none());
+
+        let legacy = Sections::<TestWorld>::new(WorldHeight::LEGACY);
+        assert_eq!(legacy.len(), 16);
+        assert_eq!(legacy.get(0).unwrap().y, 0);
+        assert_eq!(legacy.get(15).unwrap().y, 15);
+        assert!(legacy.get(-1).is_none());
+    }
+    #[test]
+    pub fn test_raw_chunk_sections() {
+        let chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+        let raw: RawChunk = chunk.into_raw_chunk();
+        assert_eq!(raw.y_pos, -4);
+        let y: Vec<i8> = raw.sections.iter().map(|section| section.y_pos).collect();
+        assert_eq!(y, (-4..=19).collect::<Vec<i8>>());
+
+        let chunk =
+            AxolotlChunk::<TestWorld>::with_height(ChunkPos::new(0, 0), WorldHeight::LEGACY);
+        let raw: RawChunk = chunk.into_raw_chunk();
+        assert_eq!(raw.y_pos, 0);
+        assert_eq!(raw.sections.len(), 16);
+        assert_eq!(raw.sections.last().unwrap().y_pos, 15);
+    }
+    #[test]
+    pub fn test_round_trip_height_limits() {
+        let game = Arc::new(test_game(&["stone", "dirt"]));
+        let stone = test_block(&game, "stone");
+        let dirt = test_block(&game, "dirt");
+        let mut chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+        // Two blocks in each section so they are stored as full sections
+        let blocks = [
+            (BlockPosition::new(0, -64, 0), &stone),
+            (BlockPosition::new(15, -64, 15), &dirt),
+            (BlockPosition::new(0, 319, 0), &stone),
+            (BlockPosition::new(15, 319, 15), &dirt),
+        ];
+        for (position, block) in blocks {
+            chunk.set_block(position, block.clone());
+        }
+        let mut raw: RawChunk = chunk.into_raw_chunk();
+
+        let mut loaded = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+        loaded.load_from_chunk(game.clone(), &mut raw, None);
+        for (position, block) in blocks {
+            assert_eq!(loaded.get_block(position), Some(block), "{:?}", position);
+        }
+        assert_eq!(
+            loaded.get_block(BlockPosition::new(7, 319, 3)),
+            Some(&stone)
+        );
+        // Empty sections come back as a single air block
+        assert!(loaded
+            .get_block(BlockPosition::new(0, 0, 0))
+            .is_none_or(|block| block.is_air()));
+    }
+}
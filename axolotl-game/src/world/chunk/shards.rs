@@ -0,0 +1,143 @@
+use ahash::AHashMap;
+use parking_lot::RwLock;
+
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+/// The default number of shards. Must be a power of two
+pub const DEFAULT_SHARD_COUNT: usize = 32;
+
+type Shard<T> = RwLock<AHashMap<ChunkPos, T>>;
+
+/// A map of chunks split into independently locked shards.
+///
+/// Lookups, inserts and removals only contend with others that land in the same shard.
+/// Reads of the chunks themselves go through the section copies of [InnerChunkHandle::get_block](super::InnerChunkHandle::get_block)
+#[derive(Debug)]
+pub struct ChunkShards<T> {
+    shards: Box<[Shard<T>]>,
+    mask: usize,
+}
+
+impl<T> Default for ChunkShards<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHARD_COUNT)
+    }
+}
+
+impl<T> ChunkShards<T> {
+    /// Creates the map with `shard_count` shards. The count is rounded up to the next power of two
+    pub fn new(shard_count: usize) -> Self {
+        let shard_count = shard_count.max(1).next_power_of_two();
+        let shards = (0..shard_count)
+            .map(|_| RwLock::new(AHashMap::new()))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            shards,
+            mask: shard_count - 1,
+        }
+    }
+    #[inline(always)]
+    fn shard_index(&self, pos: &ChunkPos) -> usize {
+        // Fibonacci hashing so neighbouring chunks land in different shards
+        let value: u64 = (*pos).into();
+        (value.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize & self.mask
+    }
+    #[inline(always)]
+    fn shard(&self, pos: &ChunkPos) -> &Shard<T> {
+        &self.shards[self.shard_index(pos)]
+    }
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn contains(&self, pos: &ChunkPos) -> bool {
+        self.shard(pos).read().contains_key(pos)
+    }
+
+    pub fn insert(&self, pos: ChunkPos, value: T) -> Option<T> {
+        self.shard(&pos).write().insert(pos, value)
+    }
+
+    pub fn remove(&self, pos: &ChunkPos) -> Option<T> {
+        self.shard(pos).write().remove(pos)
+    }
+    /// The number of chunks across all shards.
+    ///
+    /// Each shard is locked one after another so the value may already be outdated.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.read().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.read().is_empty())
+    }
+    /// Removes every chunk from the map
+    pub fn drain(&self) -> Vec<(ChunkPos, T)> {
+        let mut values = Vec::new();
+        for shard in self.shards.iter() {
+            values.extend(shard.write().drain());
+        }
+        values
+    }
+}
+impl<T: Clone> ChunkShards<T> {
+    pub fn get(&self, pos: &ChunkPos) -> Option<T> {
+        self.shard(pos).read().get(pos).cloned()
+    }
+    /// Returns the value at the position or inserts the value returned by `create`.
+    ///
+    /// The bool is true if the value was created
+    pub fn get_or_insert_with(&self, pos: ChunkPos, create: impl FnOnce() -> T) -> (T, bool) {
+        let shard = self.shard(&pos);
+        if let Some(value) = shard.read().get(&pos) {
+            return (value.clone(), false);
+        }
+        let mut guard = shard.write();
+        // Another thread could have inserted it between the read and write lock
+        if let Some(value) = guard.get(&pos) {
+            return (value.clone(), false);
+        }
+        let value = create();
+        guard.insert(pos, value.clone());
+        (value, true)
+    }
+    /// Copies the positions of all chunks
+    pub fn positions(&self) -> Vec<ChunkPos> {
+        let mut values = Vec::new();
+        for shard in self.shards.iter() {
+            values.extend(shard.read().keys().copied());
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::shards::ChunkShards;
+
+    #[test]
+    pub fn test_shards() {
+        let shards = ChunkShards::new(5);
+        assert_eq!(shards.shard_count(), 8);
+        for x in -8..8 {
+            for z in -8..8 {
+                shards.insert(ChunkPos::new(x, z), x * z);
+            }
+        }
+        assert_eq!(shards.len(), 256);
+        assert_eq!(shards.get(&ChunkPos::new(-3, 4)), Some(-12));
+
+        let (value, created) = shards.get_or_insert_with(ChunkPos::new(-3, 4), || 0);
+        assert!(!created);
+        assert_eq!(value, -12);
+        let (value, created) = shards.get_or_insert_with(ChunkPos::new(100, 4), || 7);
+        assert!(created);
+        assert_eq!(value, 7);
+
+        assert_eq!(shards.drain().len(), 257);
+        assert!(shards.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use axolotl_api::world::World;
+use axolotl_api::world_gen::dimension::WorldHeight;
+
+use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+use crate::world::chunk::AxolotlChunk;
+
+type Snapshot<W> = RwLock<Option<Arc<AxolotlBlockSection<W>>>>;
+
+/// A copy of the blocks of every section of a chunk, each behind its own lock.
+///
+/// Readers clone the copy of the section instead of taking the lock of the whole chunk.
+/// Writers drop the copy of the sections they change while holding the chunk write lock.
+/// The next reader takes a new copy under the chunk read lock,
+/// so readers of a hot chunk (spawn) only wait on its writers once per change of a section
+#[derive(Debug)]
+pub struct SectionSnapshots<W: World> {
+    sections: Box<[Snapshot<W>]>,
+    min_section: i32,
+}
+
+impl<W: World> SectionSnapshots<W> {
+    pub fn new(height: WorldHeight) -> Self {
+        let sections = (0..height.section_count())
+            .map(|_| RwLock::new(None))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            sections,
+            min_section: height.min_section(),
+        }
+    }
+    #[inline(always)]
+    fn section(&self, section_y: i32) -> Option<&Snapshot<W>> {
+        let index = section_y - self.min_section;
+        if index < 0 {
+            return None;
+        }
+        self.sections.get(index as usize)
+    }
+    /// The copy of the section. None if it changed since the last copy was taken
+    pub fn get(&self, section_y: i32) -> Option<Arc<AxolotlBlockSection<W>>> {
+        self.section(section_y)?.read().clone()
+    }
+    /// Copies the section out of the chunk.
+    ///
+    /// The chunk must be read locked so no writer can change it until the copy is stored
+    pub fn take(
+        &self,
+        chunk: &AxolotlChunk<W>,
+        section_y: i32,
+    ) -> Option<Arc<AxolotlBlockSection<W>>> {
+        let snapshot = self.section(section_y)?;
+        let blocks = Arc::new(chunk.sections.get(section_y)?.blocks.clone());
+        *snapshot.write() = Some(blocks.clone());
+        Some(blocks)
+    }
+    /// Drops the copy of the section. Called with the chunk write locked
+    pub fn invalidate(&self, section_y: i32) {
+        if let Some(snapshot) = self.section(section_y) {
+            *snapshot.write() = None;
+        }
+    }
+    /// Drops the copy of every section. Called with the chunk write locked after it was loaded or generated
+    pub fn invalidate_all(&self) {
+        for snapshot in self.sections.iter() {
+            *snapshot.write() = None;
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::{AxolotlChunk, InnerChunkHandle};
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+
+    #[test]
+    pub fn test_snapshots() {
+        let game = test_game(&["stone", "dirt"]);
+        let stone = test_block(&game, "stone");
+        let dirt = test_block(&game, "dirt");
+        let position = BlockPosition::new(1, 70, 1);
+        let mut chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(0, 0));
+        chunk.set_block(position, stone.clone());
+        let handle = InnerChunkHandle::new(chunk);
+
+        assert!(handle.snapshots.get(4).is_none());
+        assert_eq!(handle.get_block(position), Some(stone.clone()));
+        let snapshot = handle.snapshots.get(4).unwrap();
+        // Reads share the copy until the section changes
+        assert!(Arc::ptr_eq(&snapshot, &handle.snapshots.get(4).unwrap()));
+
+        assert_eq!(
+            handle.set_block(position, dirt.clone()),
+            Some(stone.clone())
+        );
+        assert!(handle.snapshots.get(4).is_none());
+        assert_eq!(handle.get_block(position), Some(dirt));
+        // The old copy is unchanged
+        assert_eq!(snapshot.get_block(position), Some(&stone));
+        // Out of the world
+        assert_eq!(handle.get_block(BlockPosition::new(1, 400, 1)), None);
+    }
+}
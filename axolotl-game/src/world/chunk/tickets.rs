@@ -0,0 +1,75 @@
+use ahash::{AHashMap, AHashSet};
+use parking_lot::RwLock;
+
+use uuid::Uuid;
+
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+/// The reason a chunk is kept loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TicketType {
+    /// The area around the world spawn
+    Spawn,
+    /// Forced by a command or plugin
+    Forced,
+    /// Around a player that simulates chunks. See [MovementMode::simulates_chunks](crate::world::entity::movement::MovementMode::simulates_chunks)
+    Player(Uuid),
+}
+
+/// Chunks with a ticket are never unloaded by chunk updates
+#[derive(Debug, Default)]
+pub struct ChunkTickets {
+    tickets: RwLock<AHashMap<ChunkPos, AHashSet<TicketType>>>,
+}
+impl ChunkTickets {
+    pub fn add(&self, pos: ChunkPos, ticket: TicketType) {
+        self.tickets.write().entry(pos).or_default().insert(ticket);
+    }
+    /// Returns true if the chunk has no tickets left
+    pub fn remove(&self, pos: &ChunkPos, ticket: TicketType) -> bool {
+        let mut tickets = self.tickets.write();
+        if let Some(value) = tickets.get_mut(pos) {
+            value.remove(&ticket);
+            if value.is_empty() {
+                tickets.remove(pos);
+                return true;
+            }
+            return false;
+        }
+        true
+    }
+    /// Removes the ticket from every chunk. Returns the chunks that no longer have a ticket
+    pub fn remove_all(&self, ticket: TicketType) -> Vec<ChunkPos> {
+        let mut released = Vec::new();
+        self.tickets.write().retain(|pos, value| {
+            value.remove(&ticket);
+            if value.is_empty() {
+                released.push(*pos);
+                false
+            } else {
+                true
+            }
+        });
+        released
+    }
+    /// Every chunk holding the ticket
+    pub fn chunks_with(&self, ticket: TicketType) -> Vec<ChunkPos> {
+        self.tickets
+            .read()
+            .iter()
+            .filter(|(_, value)| value.contains(&ticket))
+            .map(|(pos, _)| *pos)
+            .collect()
+    }
+    pub fn has_ticket(&self, pos: &ChunkPos) -> bool {
+        self.tickets.read().contains_key(pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tickets.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tickets.read().is_empty()
+    }
+}
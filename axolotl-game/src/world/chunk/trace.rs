@@ -0,0 +1,212 @@
+//! Records the lifecycle of chunks for debugging.
+//!
+//! Only used when [ChunkMap::tracer](crate::world::chunk::ChunkMap::tracer) is set. The transitions of all chunks share one ring buffer
+//! so the memory used does not grow with the number of chunks. In [TraceMode::Assert] an illegal transition panics with the history of the chunk
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+use ahash::AHashSet;
+use log::warn;
+use parking_lot::Mutex;
+
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+pub const DEFAULT_TRACE_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkEvent {
+    /// A load was requested
+    Queued,
+    /// The chunk was not saved so it was generated
+    Generated,
+    Loaded,
+    /// Blocks were set
+    Modified,
+    Saved,
+    /// Removed from the map
+    Unloaded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkTransition {
+    pub chunk: ChunkPos,
+    pub event: ChunkEvent,
+    pub tick: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IllegalTransition {
+    pub transition: ChunkTransition,
+    pub reason: &'static str,
+}
+impl Display for IllegalTransition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} of chunk {:?} at tick {}: {}",
+            self.transition.event, self.transition.chunk, self.transition.tick, self.reason
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceMode {
+    /// Illegal transitions are logged and kept
+    #[default]
+    Record,
+    /// Illegal transitions panic
+    Assert,
+}
+
+#[derive(Debug)]
+struct TraceState {
+    transitions: VecDeque<ChunkTransition>,
+    loaded: AHashSet<ChunkPos>,
+    illegal: Vec<IllegalTransition>,
+}
+
+#[derive(Debug)]
+pub struct ChunkTracer {
+    state: Mutex<TraceState>,
+    capacity: usize,
+    pub mode: TraceMode,
+}
+impl Default for ChunkTracer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRACE_CAPACITY, TraceMode::Record)
+    }
+}
+impl ChunkTracer {
+    /// Keeps the last `capacity` transitions
+    pub fn new(capacity: usize, mode: TraceMode) -> Self {
+        Self {
+            state: Mutex::new(TraceState {
+                transitions: VecDeque::with_capacity(capacity),
+                loaded: AHashSet::new(),
+                illegal: Vec::new(),
+            }),
+            capacity: capacity.max(1),
+            mode,
+        }
+    }
+    /// Records the transition. Returns an error if the chunk could not make it
+    ///
+    /// # Panics
+    /// In [TraceMode::Assert] if the transition is illegal
+    pub fn record(
+        &self,
+        chunk: ChunkPos,
+        event: ChunkEvent,
+        tick: u64,
+    ) -> Result<(), IllegalTransition> {
+        let transition = ChunkTransition { chunk, event, tick };
+        let mut state = self.state.lock();
+        let loaded = state.loaded.contains(&chunk);
+        let reason = match event {
+            ChunkEvent::Generated if loaded => Some("generated while loaded"),
+            ChunkEvent::Loaded if loaded => Some("loaded twice"),
+            ChunkEvent::Modified if !loaded => Some("modified while not loaded"),
+            ChunkEvent::Unloaded if !loaded => Some("unloaded while not loaded"),
+            _ => None,
+        };
+        match event {
+            ChunkEvent::Loaded => {
+                state.loaded.insert(chunk);
+            }
+            ChunkEvent::Unloaded => {
+                state.loaded.remove(&chunk);
+            }
+            _ => {}
+        }
+        if state.transitions.len() >= self.capacity {
+            state.transitions.pop_front();
+        }
+        state.transitions.push_back(transition);
+        let Some(reason) = reason else {
+            return Ok(());
+        };
+        let illegal = IllegalTransition { transition, reason };
+        if self.mode == TraceMode::Assert {
+            let history: Vec<_> = state
+                .transitions
+                .iter()
+                .filter(|recorded| recorded.chunk == chunk)
+                .map(|recorded| (recorded.tick, recorded.event))
+                .collect();
+            drop(state);
+            panic!(
+                "Illegal chunk transition {}. History {:?}",
+                illegal, history
+            );
+        }
+        warn!("Illegal chunk transition {}", illegal);
+        state.illegal.push(illegal.clone());
+        Err(illegal)
+    }
+    /// The transitions of the chunk still in the buffer. Oldest first
+    pub fn history(&self, chunk: &ChunkPos) -> Vec<ChunkTransition> {
+        self.state
+            .lock()
+            .transitions
+            .iter()
+            .filter(|transition| transition.chunk == *chunk)
+            .copied()
+            .collect()
+    }
+    pub fn last(&self, chunk: &ChunkPos) -> Option<ChunkTransition> {
+        self.state
+            .lock()
+            .transitions
+            .iter()
+            .rev()
+            .find(|transition| transition.chunk == *chunk)
+            .copied()
+    }
+    /// Every illegal transition seen in [TraceMode::Record]
+    pub fn illegal_transitions(&self) -> Vec<IllegalTransition> {
+        self.state.lock().illegal.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().transitions.is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::trace::{ChunkEvent, ChunkTracer, TraceMode};
+
+    #[test]
+    pub fn test_lifecycle() {
+        let tracer = ChunkTracer::new(4, TraceMode::Record);
+        let chunk = ChunkPos::new(1, -1);
+        let other = ChunkPos::new(0, 0);
+        tracer.record(chunk, ChunkEvent::Queued, 0).unwrap();
+        tracer.record(chunk, ChunkEvent::Generated, 1).unwrap();
+        tracer.record(chunk, ChunkEvent::Loaded, 1).unwrap();
+        tracer.record(other, ChunkEvent::Queued, 2).unwrap();
+        tracer.record(chunk, ChunkEvent::Modified, 3).unwrap();
+        // The oldest transition was dropped
+        assert_eq!(tracer.len(), 4);
+        assert_eq!(tracer.history(&chunk).len(), 3);
+        assert_eq!(tracer.last(&other).unwrap().event, ChunkEvent::Queued);
+
+        tracer.record(chunk, ChunkEvent::Unloaded, 4).unwrap();
+        tracer.record(chunk, ChunkEvent::Saved, 4).unwrap();
+        let illegal = tracer.record(chunk, ChunkEvent::Modified, 5).unwrap_err();
+        assert_eq!(illegal.transition.tick, 5);
+        assert_eq!(tracer.illegal_transitions().len(), 1);
+    }
+    #[test]
+    #[should_panic]
+    pub fn test_assert_mode() {
+        let tracer = ChunkTracer::new(16, TraceMode::Assert);
+        let _ = tracer.record(ChunkPos::new(0, 0), ChunkEvent::Modified, 0);
+    }
+}
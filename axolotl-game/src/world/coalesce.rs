@@ -0,0 +1,244 @@
+//! Merges the block changes made during a tick.
+//!
+//! Pistons, explosions and world edits change many blocks in the same sections.
+//! Instead of one [ServerUpdateOut::BlockChanged] per change the world sends one update per section once per tick
+use std::mem;
+
+use ahash::AHashMap;
+use parking_lot::Mutex;
+
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::channel::{Disconnected, UpdateSender};
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::updates::ServerUpdateOut;
+
+/// A chunk and the y of a section within it
+pub type SectionPos = (ChunkPos, i16);
+
+type PendingSection<W> = AHashMap<BlockPosition, Option<PlacedBlock<W>>>;
+
+#[derive(Debug)]
+pub struct UpdateCoalescer<W: World> {
+    pending: Mutex<AHashMap<SectionPos, PendingSection<W>>>,
+}
+impl<W: World> Default for UpdateCoalescer<W> {
+    fn default() -> Self {
+        Self {
+            pending: Mutex::default(),
+        }
+    }
+}
+impl<W: World> UpdateCoalescer<W> {
+    /// Queues a change. Replaces any earlier change to the same block this tick
+    pub fn push(&self, position: BlockPosition, block: Option<PlacedBlock<W>>) {
+        let section = (position.chunk_pos(), position.y >> 4);
+        self.pending
+            .lock()
+            .entry(section)
+            .or_default()
+            .insert(position, block);
+    }
+    /// The number of blocks waiting to be sent
+    pub fn len(&self) -> usize {
+        self.pending
+            .lock()
+            .values()
+            .map(|blocks| blocks.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.lock().is_empty()
+    }
+    /// Takes the pending changes.
+    ///
+    /// A section with a single change becomes a [ServerUpdateOut::BlockChanged]. Every other section becomes a [ServerUpdateOut::SectionChanged]
+    pub fn take(&self) -> Vec<ServerUpdateOut<W>> {
+        let pending = mem::take(&mut *self.pending.lock());
+        Self::into_updates(pending.into_iter().collect())
+    }
+    /// Takes the pending changes of one chunk. See [UpdateCoalescer::take]
+    pub fn take_chunk(&self, chunk: ChunkPos) -> Vec<ServerUpdateOut<W>> {
+        let mut pending = self.pending.lock();
+        let (sections, others): (AHashMap<_, _>, _) = mem::take(&mut *pending)
+            .into_iter()
+            .partition(|((section_chunk, _), _)| *section_chunk == chunk);
+        *pending = others;
+        drop(pending);
+        Self::into_updates(sections.into_iter().collect())
+    }
+    fn into_updates(mut sections: Vec<(SectionPos, PendingSection<W>)>) -> Vec<ServerUpdateOut<W>> {
+        sections.sort_unstable_by_key(|((chunk, section_y), _)| (*chunk, *section_y));
+        sections
+            .into_iter()
+            .map(|((chunk, section_y), blocks)| {
+                let mut blocks: Vec<_> = blocks.into_iter().collect();
+                if blocks.len() == 1 {
+                    let (position, block) = blocks.pop().unwrap();
+                    return ServerUpdateOut::BlockChanged { position, block };
+                }
+                blocks.sort_unstable_by_key(|(position, _)| (position.y, position.z, position.x));
+                ServerUpdateOut::SectionChanged {
+                    chunk,
+                    section_y,
+                    blocks,
+                }
+            })
+            .collect()
+    }
+    /// Sends the pending changes. Called once per tick
+    ///
+    /// Returns the number of updates sent
+    pub fn flush(
+        &self,
+        outgoing: &impl UpdateSender<ServerUpdateOut<W>>,
+    ) -> Result<usize, Disconnected> {
+        Self::send_all(self.take(), outgoing)
+    }
+    /// Sends the pending changes of one chunk. See [UpdateCoalescer::flush]
+    pub fn flush_chunk(
+        &self,
+        chunk: ChunkPos,
+        outgoing: &impl UpdateSender<ServerUpdateOut<W>>,
+    ) -> Result<usize, Disconnected> {
+        Self::send_all(self.take_chunk(chunk), outgoing)
+    }
+    fn send_all(
+        updates: Vec<ServerUpdateOut<W>>,
+        outgoing: &impl UpdateSender<ServerUpdateOut<W>>,
+    ) -> Result<usize, Disconnected> {
+        let sent = updates.len();
+        for update in updates {
+            outgoing.send(update)?;
+        }
+        Ok(sent)
+    }
+}
+
+/// Holds back block changes in the [UpdateCoalescer]. Everything else goes straight to `outgoing`.
+///
+/// The changes of a chunk are sent before it is unloaded and every change is sent before a save so neither arrives out of order
+#[derive(Debug)]
+pub struct CoalescingSender<'a, W: World, S> {
+    pub coalescer: &'a UpdateCoalescer<W>,
+    pub outgoing: &'a S,
+}
+impl<W: World, S> Clone for CoalescingSender<'_, W, S> {
+    fn clone(&self) -> Self {
+        Self {
+            coalescer: self.coalescer,
+            outgoing: self.outgoing,
+        }
+    }
+}
+impl<W: World, S> UpdateSender<ServerUpdateOut<W>> for CoalescingSender<'_, W, S>
+where
+    S: UpdateSender<ServerUpdateOut<W>>,
+    UpdateCoalescer<W>: Sync,
+{
+    fn send(&self, value: ServerUpdateOut<W>) -> Result<(), Disconnected> {
+        match value {
+            ServerUpdateOut::BlockChanged { position, block } => {
+                self.coalescer.push(position, block);
+                Ok(())
+            }
+            ServerUpdateOut::ChunkUnloaded(chunk) => {
+                self.coalescer.flush_chunk(chunk, self.outgoing)?;
+                self.outgoing.send(ServerUpdateOut::ChunkUnloaded(chunk))
+            }
+            ServerUpdateOut::Saved => {
+                self.coalescer.flush(self.outgoing)?;
+                self.outgoing.send(ServerUpdateOut::Saved)
+            }
+            value => self.outgoing.send(value),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::channel::{UpdateReceiver, UpdateSender};
+    use crate::world::coalesce::{CoalescingSender, UpdateCoalescer};
+    use crate::world::test_world::TestWorld;
+    use crate::world::updates::ServerUpdateOut;
+
+    #[test]
+    pub fn test_coalesce() {
+        let coalescer = UpdateCoalescer::<TestWorld>::default();
+        // Superseded by the second change
+        coalescer.push(BlockPosition::new(1, 64, 1), None);
+        coalescer.push(BlockPosition::new(1, 64, 1), None);
+        coalescer.push(BlockPosition::new(2, 65, 1), None);
+        // A different section
+        coalescer.push(BlockPosition::new(-1, -3, 0), None);
+        assert_eq!(coalescer.len(), 3);
+
+        let updates = coalescer.take();
+        assert!(coalescer.is_empty());
+        assert_eq!(updates.len(), 2);
+        match &updates[0] {
+            ServerUpdateOut::BlockChanged { position, .. } => {
+                assert_eq!(*position, BlockPosition::new(-1, -3, 0))
+            }
+            other => panic!("Expected a block change {:?}", other),
+        }
+        match &updates[1] {
+            ServerUpdateOut::SectionChanged {
+                chunk,
+                section_y,
+                blocks,
+            } => {
+                assert_eq!(*chunk, ChunkPos::new(0, 0));
+                assert_eq!(*section_y, 4);
+                assert_eq!(blocks.len(), 2);
+            }
+            other => panic!("Expected a section change {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn test_order() {
+        let coalescer = UpdateCoalescer::<TestWorld>::default();
+        let (outgoing, mut receiver) = flume::unbounded();
+        let sender = CoalescingSender {
+            coalescer: &coalescer,
+            outgoing: &outgoing,
+        };
+        let changed = |x: i64| ServerUpdateOut::BlockChanged {
+            position: BlockPosition::new(x, 64, 0),
+            block: None,
+        };
+        sender.send(changed(1)).unwrap();
+        sender.send(changed(2)).unwrap();
+        sender.send(changed(17)).unwrap();
+        assert!(receiver.drain().is_empty());
+
+        sender
+            .send(ServerUpdateOut::ChunkUnloaded(ChunkPos::new(0, 0)))
+            .unwrap();
+        let sent = receiver.drain();
+        assert_eq!(sent.len(), 2);
+        assert!(matches!(
+            sent[0],
+            ServerUpdateOut::SectionChanged { chunk, .. } if chunk == ChunkPos::new(0, 0)
+        ));
+        assert!(matches!(sent[1], ServerUpdateOut::ChunkUnloaded(_)));
+        // The other chunk is still held back
+        assert_eq!(coalescer.len(), 1);
+
+        sender.send(ServerUpdateOut::Saved).unwrap();
+        let sent = receiver.drain();
+        assert_eq!(sent.len(), 2);
+        assert!(matches!(
+            sent[0],
+            ServerUpdateOut::BlockChanged { position, .. } if position.x == 17
+        ));
+        assert!(matches!(sent[1], ServerUpdateOut::Saved));
+        assert!(coalescer.is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+//! Hunger, saturation and breath. Matches the vanilla formulas
+use minecraft_protocol::packets::play::client::Difficulty;
+use rand::Rng;
+
+use axolotl_world::entity::player::PlayerData;
+
+use crate::world::entity::properties::{AirLevel, Food, Health};
+
+pub const MAX_FOOD_LEVEL: i32 = 20;
+pub const MAX_EXHAUSTION: f32 = 40.0;
+/// Exhaustion is turned into a point of saturation or food at this level
+pub const EXHAUSTION_PER_FOOD: f32 = 4.0;
+/// In ticks
+pub const MAX_AIR: i32 = 300;
+pub const DROWNING_DAMAGE: f32 = 2.0;
+pub const STARVATION_DAMAGE: f32 = 1.0;
+
+/// Exhaustion added by an action
+pub mod exhaustion {
+    /// Per meter
+    pub const SPRINT: f32 = 0.1;
+    /// Per meter
+    pub const SWIM: f32 = 0.01;
+    pub const JUMP: f32 = 0.05;
+    pub const SPRINT_JUMP: f32 = 0.2;
+    pub const ATTACK: f32 = 0.1;
+    pub const DAMAGE: f32 = 0.1;
+    pub const BREAK_BLOCK: f32 = 0.005;
+    /// Added for each point of health regenerated
+    pub const REGENERATION: f32 = 6.0;
+}
+
+/// The food values of an item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoodProperties {
+    pub nutrition: i32,
+    pub saturation_modifier: f32,
+    /// Can be eaten with a full food bar. Such as golden apples
+    pub always_edible: bool,
+}
+impl FoodProperties {
+    pub const fn new(nutrition: i32, saturation_modifier: f32) -> Self {
+        Self {
+            nutrition,
+            saturation_modifier,
+            always_edible: false,
+        }
+    }
+    /// The food values of a vanilla item. None if the item can not be eaten
+    pub fn vanilla(key: &str) -> Option<Self> {
+        let key = key.strip_prefix("minecraft:").unwrap_or(key);
+        let food = match key {
+            "apple" => Self::new(4, 0.3),
+            "baked_potato" => Self::new(5, 0.6),
+            "beef" | "porkchop" => Self::new(3, 0.3),
+            "bread" => Self::new(5, 0.6),
+            "carrot" => Self::new(3, 0.6),
+            "chicken" => Self::new(2, 0.3),
+            "cooked_beef" | "cooked_porkchop" => Self::new(8, 0.8),
+            "cooked_chicken" => Self::new(6, 0.6),
+            "cooked_cod" => Self::new(5, 0.6),
+            "cooked_mutton" => Self::new(6, 0.8),
+            "cooked_salmon" => Self::new(6, 0.8),
+            "cookie" => Self::new(2, 0.1),
+            "golden_apple" => Self {
+                always_edible: true,
+                ..Self::new(4, 1.2)
+            },
+            "enchanted_golden_apple" => Self {
+                always_edible: true,
+                ..Self::new(4, 1.2)
+            },
+            "golden_carrot" => Self::new(6, 1.2),
+            "melon_slice" => Self::new(2, 0.3),
+            "potato" => Self::new(1, 0.3),
+            "pumpkin_pie" => Self::new(8, 0.3),
+            "rotten_flesh" => Self::new(4, 0.1),
+            "sweet_berries" => Self::new(2, 0.1),
+            _ => return None,
+        };
+        Some(food)
+    }
+}
+
+/// The food bar of a player
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HungerState {
+    pub food_level: i32,
+    pub saturation: f32,
+    pub exhaustion: f32,
+    /// Counts ticks between regeneration and starvation
+    pub tick_timer: i32,
+}
+impl Default for HungerState {
+    fn default() -> Self {
+        Self {
+            food_level: MAX_FOOD_LEVEL,
+            saturation: 5.0,
+            exhaustion: 0.0,
+            tick_timer: 0,
+        }
+    }
+}
+impl HungerState {
+    pub fn add_exhaustion(&mut self, amount: f32) {
+        self.exhaustion = (self.exhaustion + amount).min(MAX_EXHAUSTION);
+    }
+    pub fn can_eat(&self, food: &FoodProperties) -> bool {
+        food.always_edible || self.food_level < MAX_FOOD_LEVEL
+    }
+    /// Returns false if the player is not hungry
+    pub fn eat(&mut self, food: &FoodProperties) -> bool {
+        if !self.can_eat(food) {
+            return false;
+        }
+        self.food_level = (self.food_level + food.nutrition).min(MAX_FOOD_LEVEL);
+        self.saturation = (self.saturation
+            + food.nutrition as f32 * food.saturation_modifier * 2.0)
+            .min(self.food_level as f32);
+        true
+    }
+    /// Eats the vanilla food item. Returns false if the item is not food or the player is not hungry
+    pub fn eat_item(&mut self, key: &str) -> bool {
+        FoodProperties::vanilla(key)
+            .map(|food| self.eat(&food))
+            .unwrap_or(false)
+    }
+    /// Called every tick. Regenerates or starves the player.
+    ///
+    /// Returns the change in health. `natural_regeneration` is the game rule
+    pub fn tick(
+        &mut self,
+        health: &mut Health,
+        max_health: f32,
+        difficulty: &Difficulty,
+        natural_regeneration: bool,
+    ) -> f32 {
+        if self.exhaustion > EXHAUSTION_PER_FOOD {
+            self.exhaustion -= EXHAUSTION_PER_FOOD;
+            if self.saturation > 0.0 {
+                self.saturation = (self.saturation - 1.0).max(0.0);
+            } else if *difficulty != Difficulty::Peaceful {
+                self.food_level = (self.food_level - 1).max(0);
+            }
+        }
+        let before = health.0;
+        let hurt = health.0 > 0.0 && health.0 < max_health;
+        if natural_regeneration && self.saturation > 0.0 && hurt && self.food_level >= 20 {
+            self.tick_timer += 1;
+            if self.tick_timer >= 10 {
+                let amount = self.saturation.min(6.0);
+                health.0 = (health.0 + amount / 6.0).min(max_health);
+                self.add_exhaustion(amount);
+                self.tick_timer = 0;
+            }
+        } else if natural_regeneration && self.food_level >= 18 && hurt {
+            self.tick_timer += 1;
+            if self.tick_timer >= 80 {
+                health.0 = (health.0 + 1.0).min(max_health);
+                self.add_exhaustion(exhaustion::REGENERATION);
+                self.tick_timer = 0;
+            }
+        } else if self.food_level <= 0 {
+            self.tick_timer += 1;
+            if self.tick_timer >= 80 {
+                let starves = match difficulty {
+                    Difficulty::Hard => true,
+                    Difficulty::Normal => health.0 > 1.0,
+                    _ => health.0 > 10.0,
+                };
+                if starves {
+                    health.0 -= STARVATION_DAMAGE;
+                }
+                self.tick_timer = 0;
+            }
+        } else {
+            self.tick_timer = 0;
+        }
+        health.0 - before
+    }
+    pub fn load(data: &PlayerData) -> Self {
+        Self {
+            food_level: data.food_level,
+            saturation: data.food_saturation_level,
+            exhaustion: data.food_exhaustion_level,
+            tick_timer: data.food_tick_timer,
+        }
+    }
+    pub fn save(&self, data: &mut PlayerData) {
+        data.food_level = self.food_level;
+        data.food_saturation_level = self.saturation;
+        data.food_exhaustion_level = self.exhaustion;
+        data.food_tick_timer = self.tick_timer;
+    }
+}
+impl From<HungerState> for Food {
+    fn from(value: HungerState) -> Self {
+        Food(value.food_level as f32)
+    }
+}
+
+impl AirLevel {
+    /// Called every tick. Returns the drowning damage to deal.
+    ///
+    /// `respiration` is the level of the helmet enchantment. Each level gives a chance to keep the breath
+    pub fn tick(
+        &mut self,
+        eyes_in_water: bool,
+        water_breathing: bool,
+        respiration: u32,
+        random: &mut impl Rng,
+    ) -> Option<f32> {
+        if !eyes_in_water || water_breathing {
+            self.0 = (self.0 + 4.0).min(MAX_AIR as f32);
+            return None;
+        }
+        if respiration > 0 && random.gen_range(0..=respiration) > 0 {
+            return None;
+        }
+        self.0 -= 1.0;
+        if self.0 <= -20.0 {
+            self.0 = 0.0;
+            return Some(DROWNING_DAMAGE);
+        }
+        None
+    }
+    pub fn load(data: &PlayerData) -> Self {
+        Self(data.air as f32)
+    }
+    pub fn save(&self, data: &mut PlayerData) {
+        data.air = self.0 as i16;
+    }
+}
+impl Health {
+    pub fn load(data: &PlayerData) -> Self {
+        Self(data.health)
+    }
+    pub fn save(&self, data: &mut PlayerData) {
+        data.health = self.0;
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use minecraft_protocol::packets::play::client::Difficulty;
+
+    use crate::world::entity::hunger::{HungerState, MAX_AIR};
+    use crate::world::entity::properties::{AirLevel, Health};
+
+    #[test]
+    pub fn test_hunger() {
+        let mut hunger = HungerState {
+            food_level: 10,
+            saturation: 0.0,
+            ..Default::default()
+        };
+        assert!(hunger.eat_item("minecraft:cooked_beef"));
+        assert_eq!(hunger.food_level, 18);
+        assert_eq!(hunger.saturation, 12.8);
+
+        let mut health = Health(10.0);
+        hunger.saturation = 0.0;
+        for _ in 0..80 {
+            hunger.tick(&mut health, 20.0, &Difficulty::Normal, true);
+        }
+        assert_eq!(health.0, 11.0);
+        assert_eq!(hunger.exhaustion, 6.0);
+        hunger.tick(&mut health, 20.0, &Difficulty::Normal, true);
+        assert_eq!(hunger.food_level, 17);
+    }
+    #[test]
+    pub fn test_drowning() {
+        let mut random = rand::thread_rng();
+        let mut air = AirLevel::default();
+        let damage: f32 = (0..MAX_AIR + 20)
+            .filter_map(|_| air.tick(true, false, 0, &mut random))
+            .sum();
+        assert_eq!(damage, 2.0);
+        air.tick(false, false, 0, &mut random);
+        assert_eq!(air.0, 4.0);
+    }
+}
@@ -1,4 +1,15 @@
+use axolotl_api::OwnedNameSpaceKey;
+
+pub mod hunger;
+pub mod movement;
 pub mod properties;
+pub mod tracker;
 
 #[derive(Debug)]
 pub enum MinecraftEntity {}
+impl MinecraftEntity {
+    /// The key of the entity type. Such as `minecraft:pig`
+    pub fn entity_type(&self) -> OwnedNameSpaceKey {
+        match *self {}
+    }
+}
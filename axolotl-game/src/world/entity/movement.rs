@@ -0,0 +1,195 @@
+//! Player movement modes and the server side checks of moves sent by the client
+use axolotl_api::world::BlockPosition;
+use axolotl_world::entity::player::PlayerData;
+
+use crate::world::entity::properties::Location;
+
+/// The squared distance a player may move in one tick before the move is rejected. Matches vanilla
+pub const MAX_MOVE_DISTANCE_SQUARED: f64 = 100.0;
+/// Elytra flight is allowed to go further
+pub const MAX_ELYTRA_MOVE_DISTANCE_SQUARED: f64 = 300.0;
+
+/// How a player moves through the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MovementMode {
+    #[default]
+    Walking,
+    /// Creative flight. Still collides with blocks
+    Flying,
+    /// Flies through blocks. Invisible to non spectators, ignored by mobs and does not keep chunks ticking
+    Spectator,
+}
+impl MovementMode {
+    pub fn has_collision(&self) -> bool {
+        !matches!(self, MovementMode::Spectator)
+    }
+    pub fn can_fly(&self) -> bool {
+        !matches!(self, MovementMode::Walking)
+    }
+    /// If mobs can target the player
+    pub fn is_targetable(&self) -> bool {
+        !matches!(self, MovementMode::Spectator)
+    }
+    /// If the chunks around the player are ticked because of them
+    pub fn simulates_chunks(&self) -> bool {
+        !matches!(self, MovementMode::Spectator)
+    }
+    /// If the entity tracker sends a player in this mode to a viewer.
+    ///
+    /// Spectators are only seen by other spectators
+    pub fn is_visible_to(&self, viewer: MovementMode) -> bool {
+        !matches!(self, MovementMode::Spectator) || matches!(viewer, MovementMode::Spectator)
+    }
+}
+
+impl MovementMode {
+    /// The vanilla game type saved in [PlayerData]. Flight is toggled by the client so everything but spectator walks
+    pub fn from_game_type(game_type: i32) -> Self {
+        match game_type {
+            3 => MovementMode::Spectator,
+            _ => MovementMode::Walking,
+        }
+    }
+}
+
+/// The movement component of a player
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayerMovement {
+    pub mode: MovementMode,
+    pub location: Location,
+    /// Gliding with an elytra
+    pub elytra: bool,
+}
+impl PlayerMovement {
+    /// Moves the player if [validate_move] accepts it. Otherwise the player should be teleported back to [Self::location]
+    pub fn try_move(
+        &mut self,
+        to: Location,
+        is_solid: impl Fn(BlockPosition) -> bool,
+    ) -> Result<(), InvalidMove> {
+        validate_move(self.mode, &self.location, &to, self.elytra, is_solid)?;
+        self.location = to;
+        Ok(())
+    }
+    pub fn load(data: &PlayerData) -> Self {
+        let mut location = Location::default();
+        if let [x, y, z] = data.pos[..] {
+            location.x = x;
+            location.y = y;
+            location.z = z;
+        }
+        if let [yaw, pitch] = data.rotation[..] {
+            location.yaw = yaw;
+            location.pitch = pitch;
+        }
+        Self {
+            mode: MovementMode::from_game_type(data.player_game_type),
+            location,
+            elytra: false,
+        }
+    }
+    pub fn save(&self, data: &mut PlayerData) {
+        data.pos = vec![self.location.x, self.location.y, self.location.z];
+        data.rotation = vec![self.location.yaw, self.location.pitch];
+    }
+}
+
+/// Why a move was rejected. The player should be teleported back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidMove {
+    #[error("Moved too quickly")]
+    TooFast,
+    #[error("Moved into a solid block")]
+    Collision,
+}
+
+/// Checks a move sent by a player. `is_solid` is only called when the mode has collision
+pub fn validate_move(
+    mode: MovementMode,
+    from: &Location,
+    to: &Location,
+    elytra: bool,
+    is_solid: impl Fn(BlockPosition) -> bool,
+) -> Result<(), InvalidMove> {
+    let distance = (to.x - from.x).powi(2) + (to.y - from.y).powi(2) + (to.z - from.z).powi(2);
+    let max = if elytra {
+        MAX_ELYTRA_MOVE_DISTANCE_SQUARED
+    } else {
+        MAX_MOVE_DISTANCE_SQUARED
+    };
+    if distance > max {
+        return Err(InvalidMove::TooFast);
+    }
+    if !mode.has_collision() {
+        return Ok(());
+    }
+    // The feet and head of the player
+    let feet = BlockPosition::new(
+        to.x.floor() as i64,
+        to.y.floor() as i16,
+        to.z.floor() as i64,
+    );
+    let head = BlockPosition::new(feet.x, feet.y + 1, feet.z);
+    if is_solid(feet) || is_solid(head) {
+        return Err(InvalidMove::Collision);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_world::entity::player::PlayerData;
+
+    use crate::world::entity::movement::{
+        validate_move, InvalidMove, MovementMode, PlayerMovement,
+    };
+    use crate::world::entity::properties::Location;
+
+    #[test]
+    pub fn test_no_clip() {
+        let from = Location::new(0.5, 64.0, 0.5, 0.0, 0.0);
+        let to = Location::new(1.5, 64.0, 0.5, 0.0, 0.0);
+        let solid = |pos: axolotl_api::world::BlockPosition| pos.x == 1;
+        assert_eq!(
+            validate_move(MovementMode::Walking, &from, &to, false, solid),
+            Err(InvalidMove::Collision)
+        );
+        assert_eq!(
+            validate_move(MovementMode::Spectator, &from, &to, false, solid),
+            Ok(())
+        );
+        let far = Location::new(50.0, 64.0, 0.5, 0.0, 0.0);
+        assert_eq!(
+            validate_move(MovementMode::Spectator, &from, &far, false, solid),
+            Err(InvalidMove::TooFast)
+        );
+        assert!(!MovementMode::Spectator.is_visible_to(MovementMode::Walking));
+        assert!(MovementMode::Spectator.is_visible_to(MovementMode::Spectator));
+    }
+
+    #[test]
+    pub fn test_player_movement() {
+        let mut data = PlayerData {
+            player_game_type: 3,
+            pos: vec![0.5, 64.0, 0.5],
+            ..PlayerData::default()
+        };
+        let mut movement = PlayerMovement::load(&data);
+        assert_eq!(movement.mode, MovementMode::Spectator);
+        let to = Location::new(1.5, 64.0, 0.5, 90.0, 0.0);
+        assert_eq!(movement.try_move(to, |_| true), Ok(()));
+        assert_eq!(movement.location, to);
+
+        movement.mode = MovementMode::Walking;
+        let back = Location::new(0.5, 64.0, 0.5, 0.0, 0.0);
+        assert_eq!(
+            movement.try_move(back, |_| true),
+            Err(InvalidMove::Collision)
+        );
+        assert_eq!(movement.location, to);
+
+        movement.save(&mut data);
+        assert_eq!(data.pos, vec![1.5, 64.0, 0.5]);
+        assert_eq!(data.rotation, vec![90.0, 0.0]);
+    }
+}
@@ -17,6 +17,11 @@ impl Default for Health {
 
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct AirLevel(pub f32);
+impl Default for AirLevel {
+    fn default() -> Self {
+        Self(crate::world::entity::hunger::MAX_AIR as f32)
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Location {
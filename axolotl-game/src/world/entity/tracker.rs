@@ -0,0 +1,277 @@
+//! Tracks the players in a world. Decides who can see who, what mobs can target and which chunks players keep ticking
+use std::fmt::Debug;
+
+use ahash::{AHashMap, AHashSet};
+use log::{debug, warn};
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+use axolotl_api::item::placement::PlacementWorld;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::world::chunk::pregen::spiral;
+use crate::world::chunk::tickets::TicketType;
+use crate::world::chunk::ChunkMap;
+use crate::world::entity::movement::{InvalidMove, MovementMode, PlayerMovement};
+use crate::world::entity::properties::Location;
+use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::level::configs::WorldSettings;
+use crate::Error;
+
+#[derive(Debug)]
+pub struct PlayerTracker {
+    players: RwLock<AHashMap<Uuid, PlayerMovement>>,
+    /// In blocks. Players further apart are not sent to each other
+    view_distance: f64,
+}
+impl PlayerTracker {
+    pub fn new(view_distance: f64) -> Self {
+        Self {
+            players: RwLock::new(AHashMap::new()),
+            view_distance,
+        }
+    }
+    pub fn join(&self, player: Uuid, movement: PlayerMovement) {
+        self.players.write().insert(player, movement);
+    }
+    pub fn leave(&self, player: &Uuid) -> Option<PlayerMovement> {
+        self.players.write().remove(player)
+    }
+    pub fn get(&self, player: &Uuid) -> Option<PlayerMovement> {
+        self.players.read().get(player).copied()
+    }
+    pub fn set_mode(&self, player: &Uuid, mode: MovementMode) {
+        if let Some(movement) = self.players.write().get_mut(player) {
+            movement.mode = mode;
+        }
+    }
+    /// Checks and applies a move sent by the player. None if the player is not tracked
+    pub fn move_player(
+        &self,
+        player: &Uuid,
+        to: Location,
+        is_solid: impl Fn(BlockPosition) -> bool,
+    ) -> Option<Result<(), InvalidMove>> {
+        self.players
+            .write()
+            .get_mut(player)
+            .map(|movement| movement.try_move(to, is_solid))
+    }
+    /// The players that should be sent the player
+    pub fn viewers(&self, player: &Uuid) -> Vec<Uuid> {
+        let players = self.players.read();
+        let Some(movement) = players.get(player) else {
+            return Vec::new();
+        };
+        players
+            .iter()
+            .filter(|(viewer, viewer_movement)| {
+                *viewer != player
+                    && movement.mode.is_visible_to(viewer_movement.mode)
+                    && distance_squared(&movement.location, &viewer_movement.location)
+                        <= self.view_distance * self.view_distance
+            })
+            .map(|(viewer, _)| *viewer)
+            .collect()
+    }
+    /// The players that are not spectators. They count towards skipping the night
+    pub fn active_count(&self) -> usize {
+        self.players
+            .read()
+            .values()
+            .filter(|movement| movement.mode != MovementMode::Spectator)
+            .count()
+    }
+    /// The players a mob at the location can target
+    pub fn targets_near(&self, location: &Location, radius: f64) -> Vec<Uuid> {
+        self.players
+            .read()
+            .iter()
+            .filter(|(_, movement)| {
+                movement.mode.is_targetable()
+                    && distance_squared(location, &movement.location) <= radius * radius
+            })
+            .map(|(player, _)| *player)
+            .collect()
+    }
+}
+
+impl From<&WorldSettings> for PlayerTracker {
+    fn from(settings: &WorldSettings) -> Self {
+        Self::new(settings.view_distance as f64 * 16.0)
+    }
+}
+
+fn distance_squared(a: &Location, b: &Location) -> f64 {
+    (a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)
+}
+
+fn chunk_of(location: &Location) -> ChunkPos {
+    ChunkPos::new(
+        (location.x.floor() as i32) >> 4,
+        (location.z.floor() as i32) >> 4,
+    )
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    /// Moves the [TicketType::Player] tickets of a player to the chunks within [WorldSettings::simulation_distance].
+    ///
+    /// Players whose mode does not simulate chunks hold no tickets. New chunks are loaded and released chunks are unloaded
+    pub fn update_player_tickets(
+        &self,
+        player: Uuid,
+        movement: &PlayerMovement,
+    ) -> Result<(), Error> {
+        let ticket = TicketType::Player(player);
+        let radius = self.settings.simulation_distance as u32;
+        let wanted: AHashSet<ChunkPos> = if movement.mode.simulates_chunks() {
+            spiral(chunk_of(&movement.location), radius).collect()
+        } else {
+            AHashSet::new()
+        };
+        let held: AHashSet<ChunkPos> = self.tickets.chunks_with(ticket).into_iter().collect();
+        for pos in held.difference(&wanted) {
+            if self.tickets.remove(pos, ticket) {
+                debug!("Releasing chunk {:?} held by {}", pos, player);
+                self.unload_chunk(pos.x(), pos.z())?;
+            }
+        }
+        for pos in wanted.difference(&held) {
+            self.tickets.add(*pos, ticket);
+            self.load_chunk_task(pos.x(), pos.z(), None)?;
+        }
+        Ok(())
+    }
+    /// Removes every ticket held by the player. Called when they leave
+    pub fn release_player_chunks(&self, player: Uuid) -> Result<(), Error> {
+        for pos in self.tickets.remove_all(TicketType::Player(player)) {
+            self.unload_chunk(pos.x(), pos.z())?;
+        }
+        Ok(())
+    }
+    /// Adds a player of [ChunkMap::players] to the [ChunkMap::tracker] at their saved location and loads the chunks around them.
+    ///
+    /// None if the player is not in the world
+    pub fn track_player(&self, player: Uuid) -> Result<Option<PlayerMovement>, Error> {
+        let Some(movement) = self
+            .players
+            .update(&player, |joined| PlayerMovement::load(&joined.data))
+        else {
+            return Ok(None);
+        };
+        self.tracker.join(player, movement);
+        self.update_player_tickets(player, &movement)?;
+        Ok(Some(movement))
+    }
+    /// Checks a move sent by the player against the blocks of the world.
+    ///
+    /// An accepted move is written into the data of the player and moves their tickets. None if the player is not tracked
+    pub fn move_player(&self, player: Uuid, to: Location) -> Option<Result<(), InvalidMove>> {
+        let moved = self
+            .tracker
+            .move_player(&player, to, |position| self.is_solid(&position))?;
+        if moved.is_ok() {
+            let movement = self.tracker.get(&player)?;
+            self.players
+                .update(&player, |joined| movement.save(&mut joined.data));
+            if let Err(error) = self.update_player_tickets(player, &movement) {
+                warn!("Error moving the chunks of {}: {:?}", player, error);
+            }
+        }
+        Some(moved)
+    }
+    /// Removes the player from the [ChunkMap::tracker]. Their location is written into their data and their chunks are released
+    pub fn untrack_player(&self, player: Uuid) -> Result<(), Error> {
+        if let Some(movement) = self.tracker.leave(&player) {
+            self.players
+                .update(&player, |left| movement.save(&mut left.data));
+        }
+        self.release_player_chunks(player)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::ChunkMap;
+    use crate::world::entity::movement::{MovementMode, PlayerMovement};
+    use crate::world::entity::properties::Location;
+    use crate::world::entity::tracker::PlayerTracker;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::level::configs::WorldSettings;
+    use crate::world::test_world::test_game;
+
+    fn player_at(mode: MovementMode, x: f64) -> PlayerMovement {
+        PlayerMovement {
+            mode,
+            location: Location::new(x, 64.0, 0.0, 0.0, 0.0),
+            elytra: false,
+        }
+    }
+
+    #[test]
+    pub fn test_visibility() {
+        // 4 chunks
+        let tracker = PlayerTracker::from(&WorldSettings {
+            view_distance: 4,
+            ..WorldSettings::default()
+        });
+        let walker = Uuid::new_v4();
+        let spectator = Uuid::new_v4();
+        let far = Uuid::new_v4();
+        tracker.join(walker, player_at(MovementMode::Walking, 0.0));
+        tracker.join(spectator, player_at(MovementMode::Spectator, 10.0));
+        tracker.join(far, player_at(MovementMode::Walking, 1000.0));
+
+        assert_eq!(tracker.viewers(&walker), vec![spectator]);
+        assert!(tracker.viewers(&spectator).is_empty());
+        let near = Location::new(5.0, 64.0, 0.0, 0.0, 0.0);
+        assert_eq!(tracker.targets_near(&near, 16.0), vec![walker]);
+
+        tracker.set_mode(&spectator, MovementMode::Walking);
+        assert_eq!(tracker.viewers(&spectator), vec![walker]);
+        assert_eq!(
+            tracker.move_player(&far, Location::new(1001.0, 64.0, 0.0, 0.0, 0.0), |_| false),
+            Some(Ok(()))
+        );
+        assert_eq!(tracker.get(&far).unwrap().location.x, 1001.0);
+    }
+
+    #[test]
+    pub fn test_player_tickets() {
+        let game = Arc::new(test_game(&["stone"]));
+        let map =
+            ChunkMap::in_memory(AxolotlGenerator::Debug(), game).with_settings(WorldSettings {
+                simulation_distance: 1,
+                ..WorldSettings::default()
+            });
+        let player = Uuid::new_v4();
+
+        map.update_player_tickets(player, &player_at(MovementMode::Walking, 0.0))
+            .unwrap();
+        assert_eq!(map.tickets.len(), 9);
+        assert!(map.thread_safe_chunks.contains(&ChunkPos::new(-1, -1)));
+
+        // One chunk over. The column at x -1 is released
+        map.update_player_tickets(player, &player_at(MovementMode::Walking, 16.0))
+            .unwrap();
+        assert_eq!(map.tickets.len(), 9);
+        assert!(!map.thread_safe_chunks.contains(&ChunkPos::new(-1, 0)));
+        assert!(map.thread_safe_chunks.contains(&ChunkPos::new(2, 0)));
+
+        // Spectators do not keep chunks ticking
+        map.update_player_tickets(player, &player_at(MovementMode::Spectator, 16.0))
+            .unwrap();
+        assert!(map.tickets.is_empty());
+        assert!(map.thread_safe_chunks.is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+//! Events that run over many ticks in an area of the world. Such as raids
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use minecraft_protocol::packets::play::client::Difficulty;
+use parking_lot::Mutex;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::world::{BlockPosition, World};
+
+use crate::world::chunk::ChunkMap;
+use crate::world::events::raid::{Raid, RAID_RADIUS, VILLAGE_RADIUS};
+use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::level::rules;
+use crate::world::level::session_lock::OpenMode;
+use crate::Error;
+
+pub mod raid;
+
+/// The file within the world folder
+pub const EVENTS_FILE: &str = "data/axolotl_events.json";
+
+/// The color of a boss bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BarColor {
+    Pink,
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White,
+}
+/// The boss bar shown to the players near an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventBar {
+    pub title: String,
+    /// 0 to 1
+    pub progress: f32,
+    pub color: BarColor,
+}
+
+/// What the world must do for an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EventAction {
+    /// The world spawns the entities and reports them back to the event
+    SpawnEntities {
+        position: BlockPosition,
+        entities: Vec<String>,
+    },
+    BarChanged(EventBar),
+    Finished(EventOutcome),
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventOutcome {
+    Victory {
+        /// The players that took part. Raids give them Hero of the Village
+        heroes: Vec<Uuid>,
+    },
+    Defeat,
+    /// Ended without a winner. Such as timing out or the area being unloaded
+    Stopped,
+}
+
+/// An event in progress
+pub trait WorldEvent: Debug {
+    fn name(&self) -> &str;
+    /// The center of the event
+    fn position(&self) -> BlockPosition;
+    /// Called every tick
+    fn tick(&mut self, random: &mut dyn RngCore) -> Vec<EventAction>;
+    fn bar(&self) -> Option<EventBar>;
+    fn is_finished(&self) -> bool;
+}
+
+/// Every kind of event. Saved with the world
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ActiveEvent {
+    Raid(Raid),
+}
+impl WorldEvent for ActiveEvent {
+    fn name(&self) -> &str {
+        match self {
+            ActiveEvent::Raid(raid) => raid.name(),
+        }
+    }
+
+    fn position(&self) -> BlockPosition {
+        match self {
+            ActiveEvent::Raid(raid) => raid.position(),
+        }
+    }
+
+    fn tick(&mut self, random: &mut dyn RngCore) -> Vec<EventAction> {
+        match self {
+            ActiveEvent::Raid(raid) => raid.tick(random),
+        }
+    }
+
+    fn bar(&self) -> Option<EventBar> {
+        match self {
+            ActiveEvent::Raid(raid) => raid.bar(),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        match self {
+            ActiveEvent::Raid(raid) => raid.is_finished(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedEvents {
+    next_id: u32,
+    events: Vec<(u32, ActiveEvent)>,
+    /// The meeting points (bells) of the villages
+    #[serde(default)]
+    villages: Vec<BlockPosition>,
+}
+
+/// The events running in a world
+#[derive(Debug)]
+pub struct WorldEvents {
+    path: PathBuf,
+    events: Mutex<SavedEvents>,
+    /// [WorldEvents::save] returns [Error::ReadOnly]
+    read_only: bool,
+}
+impl WorldEvents {
+    /// Loads the events of the world. None if the file does not exist yet
+    pub fn open(world_folder: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::open_with_mode(world_folder, OpenMode::ReadWrite)
+    }
+    /// Events opened [OpenMode::ReadOnly] still tick but are never saved
+    pub fn open_with_mode(world_folder: impl AsRef<Path>, mode: OpenMode) -> Result<Self, Error> {
+        let path = world_folder.as_ref().join(EVENTS_FILE);
+        let events = if path.exists() {
+            serde_json::from_slice(&fs::read(&path)?)?
+        } else {
+            SavedEvents {
+                next_id: 0,
+                events: vec![],
+                villages: vec![],
+            }
+        };
+        Ok(Self {
+            path,
+            events: Mutex::new(events),
+            read_only: mode == OpenMode::ReadOnly,
+        })
+    }
+    pub fn save(&self) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&*self.events.lock())?)?;
+        Ok(())
+    }
+    /// Returns the id of the event
+    pub fn start(&self, event: ActiveEvent) -> u32 {
+        let mut events = self.events.lock();
+        let id = events.next_id;
+        events.next_id = events.next_id.wrapping_add(1);
+        events.events.push((id, event));
+        id
+    }
+    /// Runs the function on the event. None if the event has finished
+    pub fn with_event<R>(&self, id: u32, f: impl FnOnce(&mut ActiveEvent) -> R) -> Option<R> {
+        let mut events = self.events.lock();
+        events
+            .events
+            .iter_mut()
+            .find(|(event_id, _)| *event_id == id)
+            .map(|(_, event)| f(event))
+    }
+    /// The closest event within the distance of the position
+    pub fn find_near(&self, position: BlockPosition, distance: i64) -> Option<u32> {
+        let events = self.events.lock();
+        events
+            .events
+            .iter()
+            .map(|(id, event)| (*id, distance_squared(event.position(), position)))
+            .filter(|(_, value)| *value <= distance * distance)
+            .min_by_key(|(_, value)| *value)
+            .map(|(id, _)| id)
+    }
+    /// Adds or removes the meeting point of a village
+    pub fn set_meeting_point(&self, position: BlockPosition, is_meeting_point: bool) {
+        let mut events = self.events.lock();
+        let known = events.villages.contains(&position);
+        if is_meeting_point && !known {
+            events.villages.push(position);
+        } else if !is_meeting_point && known {
+            events.villages.retain(|village| *village != position);
+        }
+    }
+    /// The closest meeting point within the distance of the position
+    pub fn find_village(&self, position: BlockPosition, distance: i64) -> Option<BlockPosition> {
+        let events = self.events.lock();
+        events
+            .villages
+            .iter()
+            .map(|village| (*village, distance_squared(*village, position)))
+            .filter(|(_, value)| *value <= distance * distance)
+            .min_by_key(|(_, value)| *value)
+            .map(|(village, _)| village)
+    }
+    /// Bad Omen entered the village. Joins the raid near the village or starts one.
+    ///
+    /// Returns the id of the raid
+    pub fn bad_omen(&self, village: BlockPosition, level: u8, difficulty: Difficulty) -> u32 {
+        if let Some(id) = self.find_near(village, RAID_RADIUS) {
+            self.with_event(id, |event| match event {
+                ActiveEvent::Raid(raid) => raid.absorb_bad_omen(level),
+            });
+            return id;
+        }
+        self.start(ActiveEvent::Raid(Raid::new(village, level, difficulty)))
+    }
+    /// Ticks every event. Finished events are removed after their last actions
+    pub fn tick(&self, random: &mut dyn RngCore) -> Vec<(u32, EventAction)> {
+        let mut events = self.events.lock();
+        let mut actions = Vec::new();
+        for (id, event) in events.events.iter_mut() {
+            actions.extend(event.tick(random).into_iter().map(|action| (*id, action)));
+        }
+        events.events.retain(|(_, event)| !event.is_finished());
+        actions
+    }
+    pub fn len(&self) -> usize {
+        self.events.lock().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.lock().events.is_empty()
+    }
+}
+fn distance_squared(a: BlockPosition, b: BlockPosition) -> i64 {
+    let x = a.x - b.x;
+    let y = (a.y - b.y) as i64;
+    let z = a.z - b.z;
+    x * x + y * y + z * z
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    /// Starts a raid for every player with Bad Omen in a village, then ticks the [ChunkMap::events] with the tick random.
+    ///
+    /// No raids start in peaceful or with the `disableRaids` rule of [ChunkMap::level]. Returns the actions of the events
+    pub fn tick_events(&self, random: &mut dyn RngCore) -> Vec<(u32, EventAction)> {
+        let Some(events) = &self.events else {
+            return vec![];
+        };
+        let level = self.level.read();
+        if rules::difficulty(&level) != Difficulty::Peaceful
+            && !rules::bool_rule(&level, "disableRaids")
+        {
+            self.players.trigger_bad_omen(|player, bad_omen| {
+                let Some(village) = player
+                    .position()
+                    .and_then(|position| events.find_village(position, VILLAGE_RADIUS))
+                else {
+                    return false;
+                };
+                events.bad_omen(village, bad_omen, rules::difficulty(&level));
+                true
+            });
+        }
+        drop(level);
+        events.tick(random)
+    }
+    /// Bells are the meeting points of villages. Called for every block that changed
+    pub(crate) fn update_meeting_point(&self, position: BlockPosition) {
+        if let Some(events) = &self.events {
+            let is_bell = self
+                .get_block(position)
+                .is_some_and(|block| block.block.key() == "bell");
+            events.set_meeting_point(position, is_bell);
+        }
+    }
+    /// Saves the events. Read only worlds are skipped
+    pub(crate) fn save_events(&self) {
+        if let Some(events) = &self.events {
+            match events.save() {
+                Ok(()) | Err(Error::ReadOnly) => {}
+                Err(e) => warn!("Error saving events: {:?}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Raids. Started when a player with Bad Omen enters a village
+use minecraft_protocol::packets::play::client::Difficulty;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::world::BlockPosition;
+
+use crate::world::events::{BarColor, EventAction, EventBar, EventOutcome, WorldEvent};
+
+/// Bad Omen within this distance of a raid joins it instead of starting another
+pub const RAID_RADIUS: i64 = 96;
+/// A player this close to a bell is in its village
+pub const VILLAGE_RADIUS: i64 = 32;
+pub const MAX_BAD_OMEN_LEVEL: u8 = 5;
+/// The delay before each wave
+pub const WAVE_COUNTDOWN_TICKS: u32 = 300;
+/// How long the bar stays after the raid ends
+pub const CELEBRATION_TICKS: u32 = 600;
+/// Raids stop after this long
+pub const MAX_RAID_TICKS: u64 = 48000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RaiderType {
+    Vindicator,
+    Evoker,
+    Pillager,
+    Witch,
+    Ravager,
+}
+impl RaiderType {
+    pub const ALL: [RaiderType; 5] = [
+        RaiderType::Vindicator,
+        RaiderType::Evoker,
+        RaiderType::Pillager,
+        RaiderType::Witch,
+        RaiderType::Ravager,
+    ];
+    pub fn key(&self) -> &'static str {
+        match self {
+            RaiderType::Vindicator => "minecraft:vindicator",
+            RaiderType::Evoker => "minecraft:evoker",
+            RaiderType::Pillager => "minecraft:pillager",
+            RaiderType::Witch => "minecraft:witch",
+            RaiderType::Ravager => "minecraft:ravager",
+        }
+    }
+    pub fn max_health(&self) -> f32 {
+        match self {
+            RaiderType::Witch => 26.0,
+            RaiderType::Ravager => 100.0,
+            _ => 24.0,
+        }
+    }
+    /// The vanilla number spawned in each wave before the random bonus. Indexed by wave
+    pub fn spawns_per_wave(&self) -> [u32; 8] {
+        match self {
+            RaiderType::Vindicator => [0, 0, 2, 0, 1, 4, 2, 5],
+            RaiderType::Evoker => [0, 0, 0, 0, 0, 1, 1, 2],
+            RaiderType::Pillager => [0, 4, 3, 3, 4, 4, 4, 2],
+            RaiderType::Witch => [0, 0, 0, 0, 3, 0, 0, 1],
+            RaiderType::Ravager => [0, 0, 0, 1, 0, 1, 0, 2],
+        }
+    }
+}
+
+/// The number of waves without the bonus wave
+pub fn total_waves(difficulty: &Difficulty) -> u32 {
+    match difficulty {
+        Difficulty::Peaceful => 0,
+        Difficulty::Easy => 3,
+        Difficulty::Normal => 5,
+        Difficulty::Hard => 7,
+    }
+}
+/// The raiders of a wave. `wave` starts at 1. Matches vanilla
+pub fn wave_composition(
+    wave: u32,
+    difficulty: &Difficulty,
+    bonus_wave: bool,
+    random: &mut dyn RngCore,
+) -> Vec<RaiderType> {
+    let index = if bonus_wave {
+        total_waves(difficulty)
+    } else {
+        wave
+    }
+    .min(7) as usize;
+    let easy = *difficulty == Difficulty::Easy;
+    let mut raiders = Vec::new();
+    for raider in RaiderType::ALL {
+        let bonus = match raider {
+            RaiderType::Witch if !easy && wave > 2 && wave != 4 => 1,
+            RaiderType::Pillager | RaiderType::Vindicator if easy => random.gen_range(0..2),
+            RaiderType::Pillager | RaiderType::Vindicator if *difficulty == Difficulty::Normal => 1,
+            RaiderType::Pillager | RaiderType::Vindicator => 2,
+            RaiderType::Ravager if !easy && bonus_wave => 1,
+            _ => 0,
+        };
+        let bonus = if bonus > 0 {
+            random.gen_range(0..=bonus)
+        } else {
+            0
+        };
+        let count = raider.spawns_per_wave()[index] + bonus;
+        raiders.extend(std::iter::repeat(raider).take(count as usize));
+    }
+    raiders
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RaidStatus {
+    Ongoing,
+    Victory,
+    Loss,
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Raider {
+    pub id: Uuid,
+    pub kind: RaiderType,
+    pub health: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Raid {
+    pub center: BlockPosition,
+    pub bad_omen_level: u8,
+    pub difficulty: Difficulty,
+    pub waves_spawned: u32,
+    pub raiders: Vec<Raider>,
+    /// Players that fought in the raid
+    pub heroes: Vec<Uuid>,
+    pub status: RaidStatus,
+    pub ticks_active: u64,
+    countdown: u32,
+    celebration: u32,
+    /// The total health of the current wave when it spawned
+    wave_health: f32,
+    finished: bool,
+    #[serde(skip)]
+    last_bar: Option<EventBar>,
+}
+impl Raid {
+    pub fn new(center: BlockPosition, bad_omen_level: u8, difficulty: Difficulty) -> Self {
+        Self {
+            center,
+            bad_omen_level: bad_omen_level.clamp(1, MAX_BAD_OMEN_LEVEL),
+            difficulty,
+            waves_spawned: 0,
+            raiders: vec![],
+            heroes: vec![],
+            status: RaidStatus::Ongoing,
+            ticks_active: 0,
+            countdown: WAVE_COUNTDOWN_TICKS,
+            celebration: 0,
+            wave_health: 0.0,
+            finished: false,
+            last_bar: None,
+        }
+    }
+    /// Bad Omen above level 1 adds a final bonus wave
+    pub fn has_bonus_wave(&self) -> bool {
+        self.bad_omen_level > 1
+    }
+    pub fn total_waves(&self) -> u32 {
+        total_waves(&self.difficulty) + self.has_bonus_wave() as u32
+    }
+    /// Another player with Bad Omen entered the raid
+    pub fn absorb_bad_omen(&mut self, level: u8) {
+        self.bad_omen_level = (self.bad_omen_level + level).min(MAX_BAD_OMEN_LEVEL);
+    }
+    /// Called after the world spawns a raider of the wave
+    pub fn add_raider(&mut self, id: Uuid, kind: RaiderType) {
+        self.wave_health += kind.max_health();
+        self.raiders.push(Raider {
+            id,
+            kind,
+            health: kind.max_health(),
+        });
+    }
+    pub fn raider_damaged(&mut self, id: &Uuid, health: f32) {
+        if let Some(raider) = self.raiders.iter_mut().find(|raider| raider.id == *id) {
+            raider.health = health.max(0.0);
+        }
+    }
+    /// The raider died or left the raid
+    pub fn remove_raider(&mut self, id: &Uuid) {
+        self.raiders.retain(|raider| raider.id != *id);
+    }
+    pub fn add_hero(&mut self, player: Uuid) {
+        if !self.heroes.contains(&player) {
+            self.heroes.push(player);
+        }
+    }
+    /// Every villager died. The raid is lost
+    pub fn village_lost(&mut self) {
+        if self.status == RaidStatus::Ongoing {
+            self.status = RaidStatus::Loss;
+        }
+    }
+    pub fn stop(&mut self) {
+        self.status = RaidStatus::Stopped;
+    }
+    fn end(&mut self) -> EventAction {
+        self.finished = true;
+        EventAction::Finished(match self.status {
+            RaidStatus::Victory => EventOutcome::Victory {
+                heroes: self.heroes.clone(),
+            },
+            RaidStatus::Loss => EventOutcome::Defeat,
+            _ => EventOutcome::Stopped,
+        })
+    }
+}
+impl WorldEvent for Raid {
+    fn name(&self) -> &str {
+        "raid"
+    }
+
+    fn position(&self) -> BlockPosition {
+        self.center
+    }
+
+    fn tick(&mut self, random: &mut dyn RngCore) -> Vec<EventAction> {
+        if self.finished {
+            return vec![];
+        }
+        self.ticks_active += 1;
+        let mut actions = Vec::new();
+        match self.status {
+            RaidStatus::Stopped => return vec![self.end()],
+            RaidStatus::Ongoing if self.ticks_active >= MAX_RAID_TICKS => {
+                self.status = RaidStatus::Stopped;
+                return vec![self.end()];
+            }
+            RaidStatus::Ongoing if self.raiders.is_empty() => {
+                if self.waves_spawned >= self.total_waves() {
+                    self.status = RaidStatus::Victory;
+                } else if self.countdown > 0 {
+                    self.countdown -= 1;
+                } else {
+                    self.waves_spawned += 1;
+                    let bonus_wave = self.waves_spawned > total_waves(&self.difficulty);
+                    let raiders =
+                        wave_composition(self.waves_spawned, &self.difficulty, bonus_wave, random);
+                    self.countdown = WAVE_COUNTDOWN_TICKS;
+                    self.wave_health = 0.0;
+                    actions.push(EventAction::SpawnEntities {
+                        position: self.center,
+                        entities: raiders
+                            .iter()
+                            .map(|raider| raider.key().to_string())
+                            .collect(),
+                    });
+                }
+            }
+            RaidStatus::Ongoing => {}
+            RaidStatus::Victory | RaidStatus::Loss => {
+                self.celebration += 1;
+                if self.celebration >= CELEBRATION_TICKS {
+                    actions.push(self.end());
+                    return actions;
+                }
+            }
+        }
+        let bar = self.bar();
+        if bar != self.last_bar {
+            if let Some(bar) = &bar {
+                actions.push(EventAction::BarChanged(bar.clone()));
+            }
+            self.last_bar = bar;
+        }
+        actions
+    }
+
+    fn bar(&self) -> Option<EventBar> {
+        let (title, progress) = match self.status {
+            RaidStatus::Stopped => return None,
+            RaidStatus::Victory => ("Raid - Victory".to_string(), 0.0),
+            RaidStatus::Loss => ("Raid - Defeat".to_string(), 0.0),
+            RaidStatus::Ongoing if self.raiders.is_empty() => (
+                "Raid".to_string(),
+                1.0 - self.countdown as f32 / WAVE_COUNTDOWN_TICKS as f32,
+            ),
+            RaidStatus::Ongoing => {
+                let health: f32 = self.raiders.iter().map(|raider| raider.health).sum();
+                let title = if self.raiders.len() <= 2 {
+                    format!("Raid - Raiders Remaining: {}", self.raiders.len())
+                } else {
+                    "Raid".to_string()
+                };
+                (title, (health / self.wave_health.max(1.0)).clamp(0.0, 1.0))
+            }
+        };
+        Some(EventBar {
+            title,
+            progress,
+            color: BarColor::Red,
+        })
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use minecraft_protocol::packets::play::client::Difficulty;
+    use uuid::Uuid;
+
+    use axolotl_api::world::BlockPosition;
+
+    use crate::world::events::raid::{
+        wave_composition, Raid, RaidStatus, RaiderType, CELEBRATION_TICKS, WAVE_COUNTDOWN_TICKS,
+    };
+    use crate::world::events::{EventAction, EventOutcome, WorldEvent};
+
+    #[test]
+    pub fn test_wave_composition() {
+        let mut random = rand::thread_rng();
+        let wave = wave_composition(1, &Difficulty::Normal, false, &mut random);
+        let pillagers = wave
+            .iter()
+            .filter(|raider| **raider == RaiderType::Pillager)
+            .count();
+        assert!((4..=5).contains(&pillagers));
+        assert!(!wave.contains(&RaiderType::Ravager));
+    }
+    #[test]
+    pub fn test_raid() {
+        let mut random = rand::thread_rng();
+        let mut raid = Raid::new(BlockPosition::new(0, 64, 0), 1, Difficulty::Easy);
+        assert_eq!(raid.total_waves(), 3);
+        let hero = Uuid::new_v4();
+        raid.add_hero(hero);
+        for wave in 1..=3 {
+            let actions: Vec<EventAction> = (0..=WAVE_COUNTDOWN_TICKS)
+                .flat_map(|_| raid.tick(&mut random))
+                .collect();
+            assert!(actions
+                .iter()
+                .any(|action| matches!(action, EventAction::SpawnEntities { .. })));
+            assert_eq!(raid.waves_spawned, wave);
+            let raider = Uuid::new_v4();
+            raid.add_raider(raider, RaiderType::Pillager);
+            raid.tick(&mut random);
+            raid.remove_raider(&raider);
+        }
+        raid.tick(&mut random);
+        assert_eq!(raid.status, RaidStatus::Victory);
+        let actions: Vec<EventAction> = (0..CELEBRATION_TICKS)
+            .flat_map(|_| raid.tick(&mut random))
+            .collect();
+        assert_eq!(
+            actions.last(),
+            Some(&EventAction::Finished(EventOutcome::Victory {
+                heroes: vec![hero]
+            }))
+        );
+        assert!(raid.is_finished());
+    }
+}
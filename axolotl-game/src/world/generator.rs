@@ -10,10 +10,12 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use axolotl_api::game::{Game, Registry};
 use axolotl_api::world::World;
+use axolotl_api::world_gen::chunk::ChunkPos;
 use axolotl_api::world_gen::noise::density::loading::{DensityLoader, FunctionArgument};
 use axolotl_api::world_gen::noise::density::perlin::Perlin;
-use axolotl_api::world_gen::noise::density::{DensityState, Function};
+use axolotl_api::world_gen::noise::density::{BuildDefResult, DensityState, Function};
 use axolotl_api::world_gen::noise::{ChunkGenerator, NameSpaceKeyOrType, Noise, NoiseSetting};
+use axolotl_api::world_gen::seed::WorldSeed;
 use axolotl_api::OwnedNameSpaceKey;
 
 use crate::registry::SimpleRegistry;
@@ -31,21 +33,33 @@ pub enum AxolotlGenerator<W: World> {
     Debug(),
 }
 
+impl<W: World> AxolotlGenerator<W> {
+    /// The temperature of the biome at the position. Includes the drop in temperature with height.
+    ///
+    /// None if the biome is not known
+    pub fn temperature_at(&self, x: i64, y: i32, z: i64) -> Option<f32> {
+        match self {
+            AxolotlGenerator::Flat(flat) => flat.temperature_at(x, y, z),
+            AxolotlGenerator::Noise(noise) => noise.temperature_at(x, y, z),
+            AxolotlGenerator::Debug() => None,
+        }
+    }
+}
 impl<W: World> ChunkGenerator for AxolotlGenerator<W> {
     type PerlinNoise = GameNoise;
     type ChunkSettings = ChunkSettings;
     type Chunk = AxolotlChunk<W>;
     type GameTy = AxolotlGame<W>;
 
-    fn new(game: Arc<Self::GameTy>, chunk_settings: Self::ChunkSettings) -> Self {
+    fn new(game: Arc<Self::GameTy>, chunk_settings: Self::ChunkSettings, seed: WorldSeed) -> Self {
         match chunk_settings {
             ChunkSettings::Flat { settings } => {
-                AxolotlGenerator::Flat(FlatGenerator::new(game, settings))
+                AxolotlGenerator::Flat(FlatGenerator::new(game, settings, seed))
             }
             ChunkSettings::Noise {
                 settings,
                 biome_source,
-            } => AxolotlGenerator::Noise(NoiseGenerator::new(game, (biome_source, settings))),
+            } => AxolotlGenerator::Noise(NoiseGenerator::new(game, (biome_source, settings), seed)),
             _ => unimplemented!(),
         }
     }
@@ -54,7 +68,7 @@ impl<W: World> ChunkGenerator for AxolotlGenerator<W> {
         match self {
             AxolotlGenerator::Flat(v) => v.generate_chunk(chunk_x, chunk_z),
             AxolotlGenerator::Noise(noise) => noise.generate_chunk(chunk_x, chunk_z),
-            AxolotlGenerator::Debug() => todo!(),
+            AxolotlGenerator::Debug() => AxolotlChunk::new(ChunkPos::new(chunk_x, chunk_z)),
         }
     }
 
@@ -101,7 +115,7 @@ impl DensityLoader for AxolotlDensityLoader {
         &self,
         _game: &G,
         _def: FunctionArgument,
-    ) -> Function<P> {
+    ) -> Result<Function<P>, BuildDefResult> {
         todo!()
     }
 
@@ -109,7 +123,7 @@ impl DensityLoader for AxolotlDensityLoader {
         &self,
         _game: &G,
         _def: NameSpaceKeyOrType<FunctionArgument>,
-    ) -> Function<P> {
+    ) -> Result<Function<P>, BuildDefResult> {
         todo!()
     }
 }
@@ -142,7 +156,7 @@ impl<'function, 'game> DensityState for AxolotlDensityState<'function, 'game> {
         &self,
         _game: &G,
         _def: FunctionArgument,
-    ) -> Function<P> {
+    ) -> Result<Function<P>, BuildDefResult> {
         todo!()
     }
 
@@ -150,7 +164,7 @@ impl<'function, 'game> DensityState for AxolotlDensityState<'function, 'game> {
         &self,
         _game: &G,
         _def: NameSpaceKeyOrType<FunctionArgument>,
-    ) -> Function<P> {
+    ) -> Result<Function<P>, BuildDefResult> {
         todo!()
     }
 }
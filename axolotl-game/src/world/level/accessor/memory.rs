@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+use axolotl_api::world::World;
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_world::entity::player::PlayerData;
+
+use crate::world::chunk::ChunkMap;
+use crate::world::generator::AxolotlGenerator;
+use crate::world::level::accessor::{
+    IntoRawChunk, LevelReader, LevelWriter, PlayerAccess, RawChunk,
+};
+use crate::{AxolotlGame, Error};
+
+/// A world that only lives in memory. No region files are read or written.
+///
+/// Used by tests and benchmarks so they do not have to create and clean up a world folder
+#[derive(Debug)]
+pub struct MemoryWorldAccessor<W: World> {
+    pub chunks: RwLock<AHashMap<ChunkPos, RawChunk>>,
+    pub players: MemoryPlayerAccess,
+    pub game: Arc<AxolotlGame<W>>,
+}
+impl<W: World> MemoryWorldAccessor<W> {
+    pub fn new(game: Arc<AxolotlGame<W>>) -> Self {
+        Self {
+            chunks: RwLock::new(AHashMap::new()),
+            players: MemoryPlayerAccess::default(),
+            game,
+        }
+    }
+    /// Starts with the chunks already saved
+    pub fn with_chunks(
+        game: Arc<AxolotlGame<W>>,
+        chunks: impl IntoIterator<Item = (ChunkPos, RawChunk)>,
+    ) -> Self {
+        let accessor = Self::new(game);
+        accessor.chunks.write().extend(chunks);
+        accessor
+    }
+    pub fn contains_chunk(&self, chunk_pos: &ChunkPos) -> bool {
+        self.chunks.read().contains_key(chunk_pos)
+    }
+    /// The number of saved chunks
+    pub fn len(&self) -> usize {
+        self.chunks.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.read().is_empty()
+    }
+}
+impl<W: World> ChunkMap<W, MemoryWorldAccessor<W>> {
+    /// A chunk map that never touches the disk
+    pub fn in_memory(generator: AxolotlGenerator<W>, game: Arc<AxolotlGame<W>>) -> Self {
+        Self::new(generator, MemoryWorldAccessor::new(game))
+    }
+}
+impl<W: World> LevelReader<W> for MemoryWorldAccessor<W> {
+    type Error = Error;
+
+    fn get_chunk_into(
+        &self,
+        chunk_pos: &ChunkPos,
+        chunk: &mut impl IntoRawChunk<W>,
+    ) -> Result<bool, Self::Error> {
+        let Some(mut raw_chunk) = self.chunks.read().get(chunk_pos).cloned() else {
+            return Ok(false);
+        };
+        chunk.load_from_chunk(self.game.clone(), &mut raw_chunk, None);
+        Ok(true)
+    }
+
+    fn get_chunk(&self, chunk_pos: &ChunkPos) -> Result<Option<RawChunk>, Self::Error> {
+        Ok(self.chunks.read().get(chunk_pos).cloned())
+    }
+}
+impl<W: World> LevelWriter<W> for MemoryWorldAccessor<W> {
+    type Error = Error;
+
+    fn save_chunk(
+        &self,
+        chunk_pos: ChunkPos,
+        chunk: impl IntoRawChunk<W>,
+    ) -> Result<(), Self::Error> {
+        let raw_chunk = chunk.into_raw_chunk();
+        self.chunks.write().insert(chunk_pos, raw_chunk);
+        Ok(())
+    }
+
+    fn save_chunks(
+        &self,
+        chunks: impl Iterator<Item = (ChunkPos, RawChunk)>,
+    ) -> Result<(), Self::Error> {
+        self.chunks.write().extend(chunks);
+        Ok(())
+    }
+}
+
+/// The in memory version of [crate::world::level::accessor::v_19::player::Minecraft19PlayerAccess]
+#[derive(Debug, Default)]
+pub struct MemoryPlayerAccess {
+    // Key is Player UUID and Value is a hash of the world's name
+    pub loaded_players: RwLock<AHashMap<Uuid, u64>>,
+    pub players: RwLock<AHashMap<Uuid, PlayerData>>,
+}
+impl MemoryPlayerAccess {
+    pub fn save_player(&self, uuid: Uuid, player: &PlayerData) -> Result<(), Error> {
+        self.loaded_players.write().remove(&uuid);
+        self.players.write().insert(uuid, player.clone());
+        Ok(())
+    }
+    /// Returns None if the player is already loaded by another world
+    pub fn get_player(&self, uuid: Uuid, source_world: u64) -> Result<Option<PlayerData>, Error> {
+        let mut loaded_players = self.loaded_players.write();
+        if loaded_players.contains_key(&uuid) {
+            return Ok(None);
+        }
+        loaded_players.insert(uuid, source_world);
+        drop(loaded_players);
+        Ok(Some(
+            self.players.read().get(&uuid).cloned().unwrap_or_default(),
+        ))
+    }
+}
+impl PlayerAccess for MemoryPlayerAccess {
+    fn get_player(&self, uuid: Uuid, source_world: u64) -> Result<Option<PlayerData>, Error> {
+        MemoryPlayerAccess::get_player(self, uuid, source_world)
+    }
+
+    fn save_player(&self, uuid: Uuid, player: &PlayerData) -> Result<(), Error> {
+        MemoryPlayerAccess::save_player(self, uuid, player)
+    }
+}
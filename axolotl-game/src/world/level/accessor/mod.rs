@@ -1,13 +1,18 @@
 use std::fmt::Debug;
 use std::sync::Arc;
 
+use uuid::Uuid;
+
 use axolotl_api::world::World;
 use axolotl_api::world_gen::chunk::ChunkPos;
 use axolotl_world::chunk::RawChunk;
+use axolotl_world::entity::player::PlayerData;
 use axolotl_world::entity::RawEntities;
 
 use crate::AxolotlGame;
 
+pub mod memory;
+pub mod template;
 pub mod v_19;
 
 #[derive(Debug)]
@@ -49,6 +54,19 @@ pub trait LevelWriter<W: World> {
         &self,
         chunks: impl Iterator<Item = (ChunkPos, RawChunk)>,
     ) -> Result<(), Self::Error>;
+    /// If chunks that were never changed after loading are saved when they unload
+    fn saves_unmodified(&self) -> bool {
+        true
+    }
+}
+
+/// Where a world loads and saves the [PlayerData] of its players
+pub trait PlayerAccess: Debug + Send + Sync {
+    /// `source_world` is a hash of the world loading the player. None if another world already loaded them
+    fn get_player(&self, uuid: Uuid, source_world: u64)
+        -> Result<Option<PlayerData>, crate::Error>;
+
+    fn save_player(&self, uuid: Uuid, player: &PlayerData) -> Result<(), crate::Error>;
 }
 
 pub trait IntoRawChunk<W: World> {
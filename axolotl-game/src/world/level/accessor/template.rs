@@ -0,0 +1,185 @@
+//! Copy on write worlds on top of a read only template. See [TemplateWorldAccessor]
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use log::debug;
+use parking_lot::RwLock;
+
+use axolotl_api::world::World;
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::world::chunk::ChunkMap;
+use crate::world::generator::AxolotlGenerator;
+use crate::world::level::accessor::{IntoRawChunk, LevelReader, LevelWriter, RawChunk};
+use crate::{AxolotlGame, Error};
+
+/// Serves a world from a template without ever writing to it.
+///
+/// Saved chunks go into an in memory overlay that is read before the template.
+/// Many accessors can share one template. Used by minigames that reset the same arena every match
+#[derive(Debug)]
+pub struct TemplateWorldAccessor<W: World, V: LevelReader<W>> {
+    pub template: Arc<V>,
+    pub overlay: RwLock<AHashMap<ChunkPos, RawChunk>>,
+    pub game: Arc<AxolotlGame<W>>,
+}
+impl<W: World, V: LevelReader<W>> TemplateWorldAccessor<W, V> {
+    pub fn new(template: Arc<V>, game: Arc<AxolotlGame<W>>) -> Self {
+        Self {
+            template,
+            overlay: RwLock::new(AHashMap::new()),
+            game,
+        }
+    }
+    /// Another accessor on the same template with an empty overlay
+    pub fn new_instance(&self) -> Self {
+        Self::new(self.template.clone(), self.game.clone())
+    }
+    /// If the chunk differs from the template
+    pub fn is_modified(&self, chunk_pos: &ChunkPos) -> bool {
+        self.overlay.read().contains_key(chunk_pos)
+    }
+    /// The number of chunks that differ from the template
+    pub fn modified_chunks(&self) -> usize {
+        self.overlay.read().len()
+    }
+    /// Drops every change. The next read comes from the template again
+    pub fn reset(&self) {
+        self.overlay.write().clear();
+    }
+}
+impl<W: World, V: LevelReader<W> + Debug> ChunkMap<W, TemplateWorldAccessor<W, V>>
+where
+    Error: From<<V as LevelReader<W>>::Error>,
+{
+    /// A chunk map that reads from the template and never writes to it
+    pub fn from_template(
+        generator: AxolotlGenerator<W>,
+        template: Arc<V>,
+        game: Arc<AxolotlGame<W>>,
+    ) -> Self {
+        Self::new(generator, TemplateWorldAccessor::new(template, game))
+    }
+    /// Drops the loaded chunks without saving them and clears the overlay. Scheduled tasks are dropped
+    pub fn reset(&self) {
+        let dropped = self.thread_safe_chunks.drain().len();
+        self.accessor.reset();
+        self.scheduler.clear();
+        debug!(
+            "Reset the world to its template. Dropped {} chunks",
+            dropped
+        );
+    }
+}
+impl<W: World, V: LevelReader<W>> LevelReader<W> for TemplateWorldAccessor<W, V>
+where
+    Error: From<<V as LevelReader<W>>::Error>,
+{
+    type Error = Error;
+
+    fn get_chunk_into(
+        &self,
+        chunk_pos: &ChunkPos,
+        chunk: &mut impl IntoRawChunk<W>,
+    ) -> Result<bool, Self::Error> {
+        let overlay = self.overlay.read().get(chunk_pos).cloned();
+        if let Some(mut raw_chunk) = overlay {
+            chunk.load_from_chunk(self.game.clone(), &mut raw_chunk, None);
+            return Ok(true);
+        }
+        Ok(self.template.get_chunk_into(chunk_pos, chunk)?)
+    }
+
+    fn get_chunk(&self, chunk_pos: &ChunkPos) -> Result<Option<RawChunk>, Self::Error> {
+        if let Some(raw_chunk) = self.overlay.read().get(chunk_pos) {
+            return Ok(Some(raw_chunk.clone()));
+        }
+        Ok(self.template.get_chunk(chunk_pos)?)
+    }
+}
+/// Writes only go to the overlay
+impl<W: World, V: LevelReader<W>> LevelWriter<W> for TemplateWorldAccessor<W, V>
+where
+    Error: From<<V as LevelReader<W>>::Error>,
+{
+    type Error = Error;
+
+    fn save_chunk(
+        &self,
+        chunk_pos: ChunkPos,
+        chunk: impl IntoRawChunk<W>,
+    ) -> Result<(), Self::Error> {
+        let raw_chunk = chunk.into_raw_chunk();
+        self.overlay.write().insert(chunk_pos, raw_chunk);
+        Ok(())
+    }
+
+    fn save_chunks(
+        &self,
+        chunks: impl Iterator<Item = (ChunkPos, RawChunk)>,
+    ) -> Result<(), Self::Error> {
+        self.overlay.write().extend(chunks);
+        Ok(())
+    }
+
+    /// Unchanged chunks are read from the template again
+    fn saves_unmodified(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::ChunkMap;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::level::accessor::memory::MemoryWorldAccessor;
+    use crate::world::test_world::{test_block, test_game};
+
+    #[test]
+    pub fn test_only_changed_chunks_are_kept() {
+        let game = Arc::new(test_game(&["stone"]));
+        let stone = test_block(&game, "stone");
+        let template = Arc::new(MemoryWorldAccessor::new(game.clone()));
+        let map = ChunkMap::from_template(AxolotlGenerator::Debug(), template.clone(), game);
+        map.load_chunk_task(0, 0, None).unwrap();
+        map.load_chunk_task(1, 0, None).unwrap();
+        let position = BlockPosition::new(1, 64, 1);
+        map.set_block(position, stone.clone(), None).unwrap();
+
+        map.unload_chunk(0, 0).unwrap();
+        map.unload_chunk(1, 0).unwrap();
+        assert_eq!(map.accessor.modified_chunks(), 1);
+        assert!(map.accessor.is_modified(&ChunkPos::new(0, 0)));
+        assert!(!map.accessor.is_modified(&ChunkPos::new(1, 0)));
+        assert!(template.is_empty());
+
+        map.load_chunk_task(0, 0, None).unwrap();
+        assert_eq!(map.get_block(position), Some(stone));
+    }
+
+    #[test]
+    pub fn test_reset() {
+        let game = Arc::new(test_game(&["stone"]));
+        let template = Arc::new(MemoryWorldAccessor::new(game.clone()));
+        let map = ChunkMap::from_template(AxolotlGenerator::Debug(), template, game.clone());
+        let position = BlockPosition::new(1, 64, 1);
+        map.load_chunk_task(0, 0, None).unwrap();
+        map.set_block(position, test_block(&game, "stone"), None)
+            .unwrap();
+        map.save_all().unwrap();
+        assert_eq!(map.accessor.modified_chunks(), 1);
+
+        map.load_chunk_task(0, 0, None).unwrap();
+        map.reset();
+        assert_eq!(map.accessor.modified_chunks(), 0);
+        assert!(!map.is_chunk_loaded(&ChunkPos::new(0, 0)));
+        map.load_chunk_task(0, 0, None).unwrap();
+        assert_eq!(map.get_block(position), None);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::VecDeque;
 use std::fs::OpenOptions;
 use std::io::BufReader;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use ahash::AHashMap;
@@ -14,15 +14,25 @@ use parking_lot::{Mutex, RawRwLock, RwLock};
 
 use axolotl_api::world::World;
 use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::world_gen::seed::WorldSeed;
 use axolotl_world::entity::RawEntities;
 use axolotl_world::level;
 use axolotl_world::level::{DataPacks, LevelDat, MinecraftVersion, RootWrapper, WorldGenSettings};
 use axolotl_world::region::file::{RegionFile, RegionFileType};
 use axolotl_world::region::RegionHeader;
+use axolotl_world::validate::{ValidateOptions, ValidationReport};
 use axolotl_world::world::axolotl::AxolotlWorld as RawWorld;
 use axolotl_world::world::World as RawWorldTrait;
 
+use crate::world::chunk::pregen::chunks_in_radius;
+use crate::world::chunk::ChunkMap;
+use crate::world::events::WorldEvents;
+use crate::world::generator::AxolotlGenerator;
+use crate::world::level::accessor::v_19::player::Minecraft19PlayerAccess;
 use crate::world::level::accessor::{IntoRawChunk, LevelReader, LevelWriter, RawChunk};
+use crate::world::level::configs::{WorldConfig, WorldSettings, WorldSettingsOverrides};
+use crate::world::level::session_lock::{OpenMode, SessionLock};
+use crate::world::protection::ChunkClaims;
 use crate::{AxolotlGame, Error};
 
 pub mod player;
@@ -41,34 +51,86 @@ pub struct Minecraft19WorldAccessor<W: World> {
     pub dead_chunks: Mutex<VecDeque<RawChunk>>,
     pub dead_regions: Mutex<VecDeque<(RegionHeader, Vec<u8>)>>,
     pub game: Arc<AxolotlGame<W>>,
+    pub settings: WorldSettings,
+    /// Nothing is written to the world folder. Unloaded chunks are dropped and explicit saves return [Error::ReadOnly]
+    pub read_only: bool,
+    /// None if the world is read only
+    pub session_lock: Option<SessionLock>,
 }
 
 impl<W: World> Minecraft19WorldAccessor<W> {
-    fn new(game: Arc<AxolotlGame<W>>, world: RawWorld) -> Self {
+    fn new(game: Arc<AxolotlGame<W>>, world: RawWorld, settings: WorldSettings) -> Self {
         Self {
             active_regions: RwLock::new(AHashMap::with_capacity(MAX_NUMBER_OPEN_REGIONS)),
             world,
             dead_chunks: Mutex::new(VecDeque::with_capacity(8)),
             dead_regions: Mutex::new(VecDeque::with_capacity(8)),
             game,
+            settings,
+            read_only: false,
+            session_lock: None,
         }
     }
-    pub fn load(game: Arc<AxolotlGame<W>>, path: PathBuf) -> Result<Self, Error> {
+    /// Loads the world. The overrides inside the world folder are applied on top of `settings`
+    pub fn load(
+        game: Arc<AxolotlGame<W>>,
+        path: PathBuf,
+        settings: WorldSettings,
+    ) -> Result<Self, Error> {
+        Self::load_with_mode(game, path, settings, OpenMode::ReadWrite)
+    }
+    /// Loads the world even if another process holds its `session.lock`
+    pub fn load_forced(
+        game: Arc<AxolotlGame<W>>,
+        path: PathBuf,
+        settings: WorldSettings,
+    ) -> Result<Self, Error> {
+        Self::load_with_mode(game, path, settings, OpenMode::Force)
+    }
+    /// Loads the world without ever writing to it.
+    ///
+    /// For tools that inspect or render a world another server may be running
+    pub fn load_read_only(
+        game: Arc<AxolotlGame<W>>,
+        path: PathBuf,
+        settings: WorldSettings,
+    ) -> Result<Self, Error> {
+        Self::load_with_mode(game, path, settings, OpenMode::ReadOnly)
+    }
+    /// Loads the world. Unless read only, `session.lock` is taken first
+    pub fn load_with_mode(
+        game: Arc<AxolotlGame<W>>,
+        path: PathBuf,
+        mut settings: WorldSettings,
+        mode: OpenMode,
+    ) -> Result<Self, Error> {
         let level_dat_file = path.join("level.dat");
         if !level_dat_file.exists() {
             return Err(Error::WorldError(axolotl_world::Error::WorldDoesNotExist));
         }
+        let session_lock = match mode {
+            OpenMode::ReadWrite => Some(SessionLock::acquire(&path, false)?),
+            OpenMode::Force => Some(SessionLock::acquire(&path, true)?),
+            OpenMode::ReadOnly => None,
+        };
         let mut file =
             std::fs::File::open(level_dat_file).map(|r| BufReader::new(GzDecoder::new(r)))?;
         let level_dat: RootWrapper = serde_impl::from_buf_reader_binary(file)?;
+        if let Some(overrides) = WorldSettingsOverrides::load(&path)? {
+            overrides.apply(&mut settings);
+        }
         let world = RawWorld::load(path, level_dat.data)?;
-        Ok(Self::new(game, world))
+        let mut accessor = Self::new(game, world, settings);
+        accessor.read_only = mode == OpenMode::ReadOnly;
+        accessor.session_lock = session_lock;
+        Ok(accessor)
     }
     pub fn create(
         game: Arc<AxolotlGame<W>>,
         world_gen: impl Into<WorldGenSettings>,
         path: PathBuf,
         name: String,
+        settings: WorldSettings,
     ) -> Result<Self, Error> {
         let world = RawWorld::create(
             path,
@@ -94,7 +156,60 @@ impl<W: World> Minecraft19WorldAccessor<W> {
                 ..Default::default()
             },
         )?;
-        Ok(Self::new(game, world))
+        let session_lock = SessionLock::acquire(&world.world_folder, false)?;
+        let mut accessor = Self::new(game, world, settings);
+        accessor.session_lock = Some(session_lock);
+        Ok(accessor)
+    }
+    /// Loads the world of the config. If it does not exist yet it is created with [WorldConfig::world_seed]
+    ///
+    /// `root` is the folder [WorldConfig::path] is relative to
+    pub fn open_config(
+        game: Arc<AxolotlGame<W>>,
+        config: &WorldConfig,
+        root: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let root = root.as_ref();
+        let path = root.join(&config.path);
+        if path.join("level.dat").exists() {
+            // Loading applies the overrides in the world folder
+            return Self::load(game, path, config.settings.clone());
+        }
+        let world_gen = WorldGenSettings {
+            seed: config.world_seed().0,
+            ..WorldGenSettings::default()
+        };
+        let settings = config.resolve_settings(root)?;
+        Self::create(game, world_gen, path, config.name.clone(), settings)
+    }
+    /// The seed saved in `level.dat`. Passed to [ChunkGenerator::new](axolotl_api::world_gen::noise::ChunkGenerator::new)
+    pub fn seed(&self) -> WorldSeed {
+        WorldSeed::new(self.world.level_dat.world_gen_settings.seed)
+    }
+    /// [OpenMode::ReadOnly] if the world was opened read only. Otherwise [OpenMode::ReadWrite]
+    pub fn mode(&self) -> OpenMode {
+        if self.read_only {
+            OpenMode::ReadOnly
+        } else {
+            OpenMode::ReadWrite
+        }
+    }
+    /// The player files of the world. Read only if the world is
+    pub fn player_access(&self) -> Minecraft19PlayerAccess {
+        let folder = self.world.player_folder.clone();
+        if self.read_only {
+            Minecraft19PlayerAccess::new_read_only(folder)
+        } else {
+            Minecraft19PlayerAccess::new(folder)
+        }
+    }
+    /// The claims of the world. Read only if the world is
+    pub fn open_claims(&self) -> Result<ChunkClaims, Error> {
+        ChunkClaims::open_with_mode(&self.world.world_folder, self.mode())
+    }
+    /// The events of the world. Read only if the world is
+    pub fn open_events(&self) -> Result<WorldEvents, Error> {
+        WorldEvents::open_with_mode(&self.world.world_folder, self.mode())
     }
     pub fn clean(&self) {
         let mut guard = self.dead_regions.lock();
@@ -120,6 +235,9 @@ impl<W: World> Minecraft19WorldAccessor<W> {
                 Buffer::new().format(chunk_pos.0),
                 Buffer::new().format(chunk_pos.1)
             ));
+        if self.read_only {
+            return Ok(RegionFile::open_read_only(buf)?);
+        }
         return if buf.exists() {
             let mut file = OpenOptions::new().read(true).write(true).open(&buf)?;
             if let Some((mut header, buffer)) = self.dead_regions.lock().pop_front() {
@@ -161,6 +279,10 @@ impl<W: World> Minecraft19WorldAccessor<W> {
         };
     }
     fn close_inner(&self, region_loc: &(i32, i32), mut region: ActiveRegion) {
+        if self.read_only {
+            debug!("Closed read only region {:?}", region_loc);
+            return;
+        }
         if let Err(e) = region.entities.save() {
             warn!("Failed to save entities for region {:?}: {}", region_loc, e);
         }
@@ -197,7 +319,7 @@ impl<W: World> Minecraft19WorldAccessor<W> {
     where
         After: FnOnce(&mut ActiveRegion) -> Result<R, Error>,
     {
-        let region_loc = (pos.0 / 32, pos.1 / 32);
+        let region_loc = (pos.0 >> 5, pos.1 >> 5);
         let guard = self.active_regions.read();
         if let Some(region) = guard.get(&region_loc).cloned() {
             drop(guard);
@@ -252,6 +374,20 @@ impl<W: World> Minecraft19WorldAccessor<W> {
             }
         }
     }
+    /// Closes all regions and validates the world against the game's registries.
+    ///
+    /// Unknown blocks and biomes in saved palettes are reported and replaced if `options.repair` is set
+    pub fn validate(&self, options: ValidateOptions) -> Result<ValidationReport, Error> {
+        if self.read_only && options.repair {
+            return Err(Error::ReadOnly);
+        }
+        self.force_close_all();
+        let report = self.world.validate(options, Some(self.game.as_ref()))?;
+        for problem in report.unrepaired() {
+            warn!("{}", problem);
+        }
+        Ok(report)
+    }
     pub fn force_close_all(&self) {
         let mut guard = self.active_regions.write();
         for (loc, region) in guard
@@ -330,6 +466,36 @@ impl<W: World> LevelReader<W> for Minecraft19WorldAccessor<W> {
         })
     }
 }
+impl<W: World> ChunkMap<W, Minecraft19WorldAccessor<W>> {
+    /// A chunk map for the world with its claims, settings, `level.dat`, players and events. The claims, players and events are saved with the chunks
+    ///
+    /// Returns once the chunks within [WorldSettings::spawn_chunk_radius] of the world spawn are loaded
+    pub fn open(
+        generator: AxolotlGenerator<W>,
+        accessor: Minecraft19WorldAccessor<W>,
+    ) -> Result<Self, Error> {
+        let claims = accessor.open_claims()?;
+        let events = accessor.open_events()?;
+        let players = accessor.player_access();
+        let level_dat = accessor.world.level_dat.clone();
+        let spawn = ChunkPos::new(level_dat.spawn_x >> 4, level_dat.spawn_z >> 4);
+        let settings = accessor.settings.clone();
+        let radius = settings.spawn_chunk_radius as u32;
+        let map = Self::new(generator, accessor)
+            .with_claims(claims)
+            .with_settings(settings)
+            .with_level(level_dat)
+            .with_player_access(players)
+            .with_events(events);
+        let step = (chunks_in_radius(radius) / 10).max(1);
+        map.load_spawn_chunks(spawn, radius, |progress| {
+            if progress.completed % step == 0 {
+                info!("Preparing spawn area: {:.0}%", progress.percent());
+            }
+        })?;
+        Ok(map)
+    }
+}
 impl<W: World> LevelWriter<W> for Minecraft19WorldAccessor<W> {
     type Error = crate::Error;
 
@@ -338,6 +504,10 @@ impl<W: World> LevelWriter<W> for Minecraft19WorldAccessor<W> {
         chunk_pos: ChunkPos,
         chunk: impl IntoRawChunk<W>,
     ) -> Result<(), Self::Error> {
+        if self.read_only {
+            debug!("Dropped chunk {:?} of a read only world", chunk_pos);
+            return Ok(());
+        }
         self.region(&chunk_pos, |region| {
             let index = RegionHeader::get_index(chunk_pos) as usize;
             if let Some(region_loc) = region.chunks.region_header.locations.get(index) {
@@ -362,6 +532,123 @@ impl<W: World> LevelWriter<W> for Minecraft19WorldAccessor<W> {
         &self,
         _chunks: impl Iterator<Item = (ChunkPos, RawChunk)>,
     ) -> Result<(), Self::Error> {
+        if self.read_only {
+            return Ok(());
+        }
         todo!()
     }
+
+    fn saves_unmodified(&self) -> bool {
+        !self.read_only
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_api::world_gen::seed::WorldSeed;
+    use axolotl_world::level::WorldGenSettings;
+
+    use crate::world::chunk::ChunkMap;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::level::accessor::v_19::Minecraft19WorldAccessor;
+    use crate::world::level::configs::{WorldConfig, WorldSettings, WORLD_SETTINGS_FILE};
+    use crate::world::test_world::{test_block, test_game};
+    use crate::Error;
+
+    #[test]
+    pub fn test_read_only() {
+        let game = Arc::new(test_game(&["stone"]));
+        let folder = std::env::temp_dir().join(format!("axolotl_read_only_{}", Uuid::new_v4()));
+        drop(
+            Minecraft19WorldAccessor::create(
+                game.clone(),
+                WorldGenSettings {
+                    seed: 42,
+                    ..WorldGenSettings::default()
+                },
+                folder.clone(),
+                "test".to_string(),
+                WorldSettings::default(),
+            )
+            .unwrap(),
+        );
+        let accessor = Minecraft19WorldAccessor::load_read_only(
+            game.clone(),
+            folder.clone(),
+            WorldSettings {
+                spawn_chunk_radius: 1,
+                ..WorldSettings::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(accessor.seed(), WorldSeed::new(42));
+        assert!(matches!(
+            accessor
+                .player_access()
+                .save_player(Uuid::new_v4(), &Default::default()),
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(
+            accessor.open_claims().unwrap().save(),
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(
+            accessor.open_events().unwrap().save(),
+            Err(Error::ReadOnly)
+        ));
+
+        let map = ChunkMap::open(AxolotlGenerator::Debug(), accessor).unwrap();
+        // The spawn chunks are loaded on open
+        assert_eq!(map.tickets.len(), 9);
+        assert!(map.thread_safe_chunks.contains(&ChunkPos::new(-1, -1)));
+        map.set_block(
+            BlockPosition::new(1, 64, 1),
+            test_block(&game, "stone"),
+            None,
+        )
+        .unwrap();
+        // Dropped without an error
+        map.release_spawn_chunks().unwrap();
+        map.accessor.force_close_all();
+        assert!(!folder.join("region").join("r.0.0.mca").exists());
+        assert!(!folder.join("data").exists());
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn test_open_config() {
+        let game = Arc::new(test_game(&[]));
+        let root = std::env::temp_dir().join(format!("axolotl_config_{}", Uuid::new_v4()));
+        let config: WorldConfig = serde_json::from_str(
+            r#"{
+                "name": "flat",
+                "path": "flat",
+                "world_type": "minecraft:overworld",
+                "generator": {"type": "minecraft:flat", "settings": {"biome": "minecraft:plains", "features": false, "lakes": false, "layers": [], "structure_overrides": []}},
+                "seed": "axolotl"
+            }"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("flat")).unwrap();
+        std::fs::write(
+            root.join("flat").join(WORLD_SETTINGS_FILE),
+            r#"{"spawn_chunk_radius": 2}"#,
+        )
+        .unwrap();
+
+        let created = Minecraft19WorldAccessor::open_config(game.clone(), &config, &root).unwrap();
+        assert_eq!(created.seed(), WorldSeed::from_string("axolotl"));
+        assert_eq!(created.settings.spawn_chunk_radius, 2);
+        drop(created);
+        let loaded = Minecraft19WorldAccessor::open_config(game, &config, &root).unwrap();
+        assert_eq!(loaded.seed(), WorldSeed::from_string("axolotl"));
+        assert_eq!(loaded.settings.spawn_chunk_radius, 2);
+        drop(loaded);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -1,38 +1,154 @@
 use std::fs::File;
+use std::io::BufReader;
 use std::path::PathBuf;
 
 use ahash::AHashMap;
 use axolotl_nbt::serde_impl;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use log::{debug, warn};
 use parking_lot::RwLock;
 use uuid::Uuid;
 
+use axolotl_api::player::offline::is_offline_uuid;
+use axolotl_api::player::profile::GameProfile;
 use axolotl_world::entity::player::PlayerData;
 
+use crate::profile::{IdentityMap, ProfileCache};
+use crate::world::level::accessor::PlayerAccess;
 use crate::Error;
 
+/// Which uuids the player files are renamed to by [Minecraft19PlayerAccess::migrate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationDirection {
+    /// The server is switching to offline mode
+    ToOffline,
+    /// The server is switching to online mode
+    ToOnline,
+}
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    /// (from, to)
+    pub renamed: Vec<(Uuid, Uuid)>,
+    /// Players the [IdentityMap] does not know. Left untouched
+    pub unmapped: Vec<Uuid>,
+    /// A file already exists for the new uuid. Left untouched
+    pub conflicts: Vec<Uuid>,
+}
+
 #[derive(Debug)]
 pub struct Minecraft19PlayerAccess {
     // Key is Player UUID and Value is a hash of the world's name
     pub loaded_players: RwLock<AHashMap<Uuid, u64>>,
     pub player_folder: PathBuf,
+    /// Saving and migrating return [Error::ReadOnly]
+    pub read_only: bool,
 }
 impl Minecraft19PlayerAccess {
     pub fn new(player_folder: PathBuf) -> Self {
         Self {
             loaded_players: RwLock::new(AHashMap::new()),
             player_folder,
+            read_only: false,
+        }
+    }
+    /// Players can be read but nothing is written to `player_folder`
+    pub fn new_read_only(player_folder: PathBuf) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(player_folder)
+        }
+    }
+    pub fn player_file(&self, uuid: Uuid) -> PathBuf {
+        self.player_folder
+            .join(format!("{}.dat", uuid.hyphenated()))
+    }
+    /// Every player with a saved file
+    pub fn saved_players(&self) -> Result<Vec<Uuid>, Error> {
+        let mut players = Vec::new();
+        for entry in std::fs::read_dir(&self.player_folder)? {
+            let path = entry?.path();
+            if path.extension().and_then(|value| value.to_str()) != Some("dat") {
+                continue;
+            }
+            if let Some(uuid) = path
+                .file_stem()
+                .and_then(|value| value.to_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+            {
+                players.push(uuid);
+            }
         }
+        Ok(players)
     }
+    /// Renames the player files when the server switches between online and offline mode.
+    ///
+    /// Run before any player joins. Files already in the target mode are skipped
+    pub fn migrate(
+        &self,
+        map: &IdentityMap,
+        direction: MigrationDirection,
+    ) -> Result<MigrationReport, Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        let mut report = MigrationReport::default();
+        for uuid in self.saved_players()? {
+            let target = match direction {
+                MigrationDirection::ToOffline if !is_offline_uuid(&uuid) => map.offline(&uuid),
+                MigrationDirection::ToOnline if is_offline_uuid(&uuid) => map.online(&uuid),
+                _ => continue,
+            };
+            let Some(target) = target else {
+                report.unmapped.push(uuid);
+                continue;
+            };
+            let target_file = self.player_file(target);
+            if target_file.exists() {
+                warn!("Not migrating {} because {:?} exists", uuid, target_file);
+                report.conflicts.push(uuid);
+                continue;
+            }
+            std::fs::rename(self.player_file(uuid), target_file)?;
+            // The backup vanilla keeps of the previous save
+            let old = self.player_file(uuid).with_extension("dat_old");
+            if old.exists() {
+                std::fs::rename(old, self.player_file(target).with_extension("dat_old"))?;
+            }
+            debug!("Migrated player {} to {}", uuid, target);
+            report.renamed.push((uuid, target));
+        }
+        Ok(report)
+    }
+    /// Gzipped like vanilla
     pub fn save_player(&self, uuid: Uuid, player: &PlayerData) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
         let mut guard = self.loaded_players.write();
         guard.remove(&uuid);
-        let mut file = File::create(
-            self.player_folder
-                .join(format!("{}.dat", uuid.hyphenated())),
-        )?;
+        let mut file = GzEncoder::new(
+            File::create(self.player_file(uuid))?,
+            flate2::Compression::default(),
+        );
         serde_impl::to_writer(&mut file, player)?;
+        file.finish()?;
         Ok(())
     }
+    /// Loads the player that is joining. The profile gets its skin from the cache. See [ProfileCache::on_join]
+    ///
+    /// None if the player is already loaded
+    pub fn join(
+        &self,
+        profile: GameProfile,
+        profiles: &ProfileCache,
+        source_world: u64,
+    ) -> Result<Option<(GameProfile, PlayerData)>, Error> {
+        let profile = profiles.on_join(profile);
+        Ok(self
+            .get_player(profile.id, source_world)?
+            .map(|data| (profile, data)))
+    }
     pub fn get_player(
         &self,
         uuid: Uuid,
@@ -49,13 +165,96 @@ impl Minecraft19PlayerAccess {
         }
         loaded_players.insert(uuid, source_world);
         drop(loaded_players);
-        let player_data = self
-            .player_folder
-            .join(format!("{}.dat", uuid.hyphenated()));
+        let player_data = self.player_file(uuid);
         if !player_data.exists() {
             return Ok(Some(PlayerData::default()));
         }
-        let data: PlayerData = serde_impl::from_reader_binary(File::open(player_data)?)?;
+        let data: PlayerData = serde_impl::from_buf_reader_binary(BufReader::new(GzDecoder::new(
+            File::open(player_data)?,
+        )))?;
         Ok(Some(data))
     }
 }
+impl PlayerAccess for Minecraft19PlayerAccess {
+    fn get_player(&self, uuid: Uuid, source_world: u64) -> Result<Option<PlayerData>, Error> {
+        Minecraft19PlayerAccess::get_player(self, uuid, source_world)
+    }
+
+    fn save_player(&self, uuid: Uuid, player: &PlayerData) -> Result<(), Error> {
+        Minecraft19PlayerAccess::save_player(self, uuid, player)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use axolotl_api::player::offline::offline_uuid;
+    use axolotl_api::player::profile::{GameProfile, ProfileProperty, TEXTURES_PROPERTY};
+    use axolotl_world::validate::{validate_player_folder, ValidationReport};
+
+    use crate::profile::{IdentityMap, ProfileCache};
+    use crate::world::level::accessor::v_19::player::{
+        MigrationDirection, Minecraft19PlayerAccess,
+    };
+
+    #[test]
+    pub fn test_migrate() {
+        let folder = std::env::temp_dir().join(format!("axolotl_players_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let access = Minecraft19PlayerAccess::new(folder.clone());
+        let online = Uuid::new_v4();
+        let unknown = Uuid::new_v4();
+        std::fs::write(access.player_file(online), []).unwrap();
+        std::fs::write(access.player_file(unknown), []).unwrap();
+
+        let mut map = IdentityMap::default();
+        map.insert(online, "Player");
+        let report = access.migrate(&map, MigrationDirection::ToOffline).unwrap();
+        assert_eq!(report.renamed, vec![(online, offline_uuid("Player"))]);
+        assert_eq!(report.unmapped, vec![unknown]);
+        assert!(access.player_file(offline_uuid("Player")).exists());
+
+        let report = access.migrate(&map, MigrationDirection::ToOnline).unwrap();
+        assert_eq!(report.renamed, vec![(offline_uuid("Player"), online)]);
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn test_join() {
+        let folder = std::env::temp_dir().join(format!("axolotl_join_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let access = Minecraft19PlayerAccess::new(folder.clone());
+        let profiles = ProfileCache::default();
+        let mut online = GameProfile::new(Uuid::new_v4(), "Player");
+        online.set_property(ProfileProperty {
+            name: TEXTURES_PROPERTY.to_string(),
+            value: "e30=".to_string(),
+            signature: None,
+        });
+        profiles.insert(online.clone());
+
+        let offline = GameProfile::new(offline_uuid("Player"), "Player");
+        let (profile, _data) = access.join(offline.clone(), &profiles, 0).unwrap().unwrap();
+        assert_eq!(profile.textures(), online.textures());
+        // Already loaded
+        assert!(access.join(offline, &profiles, 0).unwrap().is_none());
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn test_save_gzipped() {
+        let folder = std::env::temp_dir().join(format!("axolotl_save_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let access = Minecraft19PlayerAccess::new(folder.clone());
+        let uuid = Uuid::new_v4();
+        access.save_player(uuid, &Default::default()).unwrap();
+        let file = std::fs::read(access.player_file(uuid)).unwrap();
+        assert_eq!(file[..2], [0x1f, 0x8b]);
+
+        let mut report = ValidationReport::default();
+        validate_player_folder(&folder, &mut report).unwrap();
+        assert_eq!(report.player_files_checked, 1);
+        assert!(report.is_valid());
+        assert!(access.get_player(uuid, 0).unwrap().is_some());
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
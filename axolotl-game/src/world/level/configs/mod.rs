@@ -1,5 +1,7 @@
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use serde::de::MapAccess;
@@ -7,12 +9,16 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use axolotl_api::world::WorldLocationID;
+use axolotl_api::world_gen::seed::WorldSeed;
 use axolotl_api::OwnedNameSpaceKey;
 
 use crate::world::generator::ChunkSettings;
+use crate::Error;
 
 /// This is the default config for worlds. This is to make a vanilla like feel to the game.
 pub static DEFAULT_VANILLA_CONFIG: &str = include_str!("vanilla.worldconfig.json");
+/// The file inside a world folder that overrides the [WorldSettings] from the [WorldConfig]
+pub const WORLD_SETTINGS_FILE: &str = "axolotl.world.json";
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldsConfig {
     pub groups: Vec<WorldGrouping>,
@@ -40,10 +46,121 @@ pub struct WorldConfig {
     pub path: PathBuf,
     pub world_type: OwnedNameSpaceKey,
     pub generator: ChunkSettings,
+    /// A number or a string. Strings are hashed the same way as the vanilla client
     pub seed: Option<Value>,
     /// By default they will use the grouping resource pool however, This will force them to use a different one
     #[serde(default)]
     pub use_own_resource_pool: bool,
+    #[serde(default)]
+    pub settings: WorldSettings,
+}
+impl WorldConfig {
+    /// The seed of the world. Random if the seed is not set
+    pub fn world_seed(&self) -> WorldSeed {
+        match &self.seed {
+            Some(Value::Number(number)) => number
+                .as_i64()
+                .map(WorldSeed::new)
+                .unwrap_or_else(|| WorldSeed::from_string(&number.to_string())),
+            Some(Value::String(seed)) => WorldSeed::from_string(seed),
+            _ => WorldSeed::random(),
+        }
+    }
+    /// The settings with the overrides from the world folder applied
+    ///
+    /// `root` is the folder the world path is relative to
+    pub fn resolve_settings(&self, root: impl AsRef<Path>) -> Result<WorldSettings, Error> {
+        let mut settings = self.settings.clone();
+        if let Some(overrides) = WorldSettingsOverrides::load(root.as_ref().join(&self.path))? {
+            overrides.apply(&mut settings);
+        }
+        Ok(settings)
+    }
+}
+/// Settings that can be different for each world
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldSettings {
+    /// In chunks
+    pub view_distance: u8,
+    /// In chunks
+    pub simulation_distance: u8,
+    /// In ticks. 0 disables autosave
+    pub autosave_interval: u64,
+    /// The radius of chunks around spawn that are always loaded
+    pub spawn_chunk_radius: u8,
+    pub generation_threads: usize,
+    /// The most chunks sent to a single player each tick
+    pub max_chunks_per_tick: u32,
+    /// The chunk data sent to a single player each second. None for no limit
+    pub max_chunk_bytes_per_second: Option<u64>,
+}
+impl Default for WorldSettings {
+    fn default() -> Self {
+        Self {
+            view_distance: 8,
+            simulation_distance: 8,
+            autosave_interval: 6000,
+            spawn_chunk_radius: 11,
+            generation_threads: std::thread::available_parallelism()
+                .map(|value| value.get())
+                .unwrap_or(1),
+            max_chunks_per_tick: 8,
+            max_chunk_bytes_per_second: None,
+        }
+    }
+}
+/// The format of [WORLD_SETTINGS_FILE]. Only the values set replace the config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldSettingsOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_distance: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulation_distance: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autosave_interval: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawn_chunk_radius: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation_threads: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chunks_per_tick: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chunk_bytes_per_second: Option<u64>,
+}
+impl WorldSettingsOverrides {
+    /// Loads the overrides from the world folder. None if the file does not exist
+    pub fn load(world_folder: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        let path = world_folder.as_ref().join(WORLD_SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let overrides = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        Ok(Some(overrides))
+    }
+    pub fn apply(&self, settings: &mut WorldSettings) {
+        if let Some(value) = self.view_distance {
+            settings.view_distance = value;
+        }
+        if let Some(value) = self.simulation_distance {
+            settings.simulation_distance = value;
+        }
+        if let Some(value) = self.autosave_interval {
+            settings.autosave_interval = value;
+        }
+        if let Some(value) = self.spawn_chunk_radius {
+            settings.spawn_chunk_radius = value;
+        }
+        if let Some(value) = self.generation_threads {
+            settings.generation_threads = value;
+        }
+        if let Some(value) = self.max_chunks_per_tick {
+            settings.max_chunks_per_tick = value;
+        }
+        if let Some(value) = self.max_chunk_bytes_per_second {
+            settings.max_chunk_bytes_per_second = Some(value);
+        }
+    }
 }
 pub trait WorldGroupAccessor {
     fn world_config(&self) -> &WorldConfig;
@@ -51,11 +168,23 @@ pub trait WorldGroupAccessor {
 
 #[cfg(test)]
 mod tests {
-    use crate::world::level::configs::DEFAULT_VANILLA_CONFIG;
+    use crate::world::level::configs::{
+        WorldSettings, WorldSettingsOverrides, DEFAULT_VANILLA_CONFIG,
+    };
 
     #[test]
     pub fn test_load() {
         let config: super::WorldsConfig = serde_json::from_str(DEFAULT_VANILLA_CONFIG).unwrap();
         println!("{:#?}", config);
     }
+    #[test]
+    pub fn test_overrides() {
+        let overrides: WorldSettingsOverrides =
+            serde_json::from_str(r#"{"view_distance": 12, "autosave_interval": 500}"#).unwrap();
+        let mut settings = WorldSettings::default();
+        overrides.apply(&mut settings);
+        assert_eq!(settings.view_distance, 12);
+        assert_eq!(settings.autosave_interval, 500);
+        assert_eq!(settings.simulation_distance, 8);
+    }
 }
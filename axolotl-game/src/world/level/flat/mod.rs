@@ -1,26 +1,114 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use log::warn;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use axolotl_api::game::{Game, Registry};
 use axolotl_api::world::{BlockPosition, World};
 use axolotl_api::world_gen::chunk::ChunkPos;
 use axolotl_api::world_gen::noise::ChunkGenerator;
+use axolotl_api::world_gen::seed::WorldSeed;
+use axolotl_api::OwnedNameSpaceKey;
 use axolotl_items::blocks::MinecraftBlock;
 
 use crate::world::chunk::placed_block::PlacedBlock;
 use crate::world::chunk::AxolotlChunk;
+use crate::world::level::surface::{BiomeClimate, SurfaceStage};
 use crate::world::perlin::GameNoise;
 use crate::AxolotlGame;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The tallest a dimension can be. The layers of a preset can not be taller
+pub const MAX_HEIGHT: i64 = 4064;
+pub const DEFAULT_BIOME: &str = "minecraft:plains";
+/// The presets from the create world screen. The first is the default
+pub const PRESETS: [(&str, &str); 9] = [
+    (
+        "classic_flat",
+        "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains;minecraft:villages",
+    ),
+    (
+        "tunnelers_dream",
+        "minecraft:bedrock,230*minecraft:stone,5*minecraft:dirt,minecraft:grass_block;minecraft:windswept_hills;minecraft:mineshafts,minecraft:strongholds",
+    ),
+    (
+        "water_world",
+        "minecraft:bedrock,64*minecraft:deepslate,5*minecraft:stone,5*minecraft:dirt,5*minecraft:sand,90*minecraft:water;minecraft:deep_ocean;minecraft:ocean_ruins,minecraft:shipwrecks,minecraft:ocean_monuments",
+    ),
+    (
+        "overworld",
+        "minecraft:bedrock,59*minecraft:stone,3*minecraft:dirt,minecraft:grass_block;minecraft:plains;minecraft:villages,minecraft:mineshafts,minecraft:pillager_outposts,minecraft:ruined_portals,minecraft:strongholds",
+    ),
+    (
+        "snowy_kingdom",
+        "minecraft:bedrock,59*minecraft:stone,3*minecraft:dirt,minecraft:grass_block,minecraft:snow;minecraft:snowy_plains;minecraft:villages,minecraft:igloos",
+    ),
+    (
+        "bottomless_pit",
+        "2*minecraft:cobblestone,3*minecraft:dirt,minecraft:grass_block;minecraft:plains;minecraft:villages",
+    ),
+    (
+        "desert",
+        "minecraft:bedrock,3*minecraft:stone,52*minecraft:sandstone,8*minecraft:sand;minecraft:desert;minecraft:villages,minecraft:desert_pyramids,minecraft:mineshafts,minecraft:strongholds",
+    ),
+    (
+        "redstone_ready",
+        "minecraft:bedrock,3*minecraft:stone,116*minecraft:sandstone;minecraft:desert",
+    ),
+    ("the_void", "minecraft:air;minecraft:the_void"),
+];
+
+/// Why a preset string could not be read
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FlatPresetError {
+    #[error("Invalid layer {0}")]
+    InvalidLayer(String),
+    #[error("The layers are taller than {} blocks", MAX_HEIGHT)]
+    TooTall,
+    #[error("Unknown preset {0}")]
+    UnknownPreset(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Layer {
     pub block: String,
     pub height: i64,
 }
+impl FromStr for Layer {
+    type Err = FlatPresetError;
+    /// `[height*]block`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FlatPresetError::InvalidLayer(s.to_string());
+        let (height, block) = match s.split_once('*') {
+            Some((height, block)) => (height.trim().parse().map_err(|_| invalid())?, block),
+            None => (1, s),
+        };
+        let block = block.trim();
+        if !(1..=MAX_HEIGHT).contains(&height) || block.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            block: with_namespace(block),
+            height,
+        })
+    }
+}
+impl Display for Layer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.height == 1 {
+            write!(f, "{}", self.block)
+        } else {
+            write!(f, "{}*{}", self.height, self.block)
+        }
+    }
+}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Can be read from and written as a vanilla superflat preset string. `layers;biome;structure_overrides`.
+///
+/// Layers are listed from the bottom up. The biome and structure overrides are optional
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct FlatSettings {
     pub biome: String,
     pub features: bool,
@@ -28,6 +116,88 @@ pub struct FlatSettings {
     pub layers: Vec<Layer>,
     pub structure_overrides: Vec<String>,
 }
+impl FlatSettings {
+    /// One of [PRESETS] by name. The `minecraft` namespace is optional
+    pub fn preset(name: &str) -> Result<Self, FlatPresetError> {
+        let key = name.strip_prefix("minecraft:").unwrap_or(name);
+        PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == key)
+            .ok_or_else(|| FlatPresetError::UnknownPreset(name.to_string()))
+            .and_then(|(_, preset)| preset.parse())
+    }
+}
+impl Default for FlatSettings {
+    fn default() -> Self {
+        PRESETS[0].1.parse().expect("The default preset is valid")
+    }
+}
+impl FromStr for FlatSettings {
+    type Err = FlatPresetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';');
+        let layers = parts
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|layer| !layer.trim().is_empty())
+            .map(Layer::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        layers
+            .iter()
+            .try_fold(0i64, |total, layer| {
+                total
+                    .checked_add(layer.height)
+                    .filter(|total| *total <= MAX_HEIGHT)
+            })
+            .ok_or(FlatPresetError::TooTall)?;
+        let biome = parts
+            .next()
+            .map(str::trim)
+            .filter(|biome| !biome.is_empty())
+            .map_or_else(|| DEFAULT_BIOME.to_string(), with_namespace);
+        let structure_overrides = parts
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|structure| !structure.is_empty())
+            .map(with_namespace)
+            .collect();
+        Ok(Self {
+            biome,
+            features: false,
+            lakes: false,
+            layers,
+            structure_overrides,
+        })
+    }
+}
+impl Display for FlatSettings {
+    /// The preset string. [FlatSettings::features] and [FlatSettings::lakes] are not part of it
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, layer) in self.layers.iter().enumerate() {
+            if index != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", layer)?;
+        }
+        write!(f, ";{}", self.biome)?;
+        if !self.structure_overrides.is_empty() {
+            write!(f, ";{}", self.structure_overrides.join(","))?;
+        }
+        Ok(())
+    }
+}
+fn with_namespace(key: &str) -> String {
+    if key.contains(':') {
+        key.to_string()
+    } else {
+        format!("minecraft:{}", key)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LoadedLayer<W: World> {
     pub block: MinecraftBlock<AxolotlGame<W>>,
@@ -39,6 +209,15 @@ pub struct FlatGenerator<W: World> {
     pub settings: FlatSettings,
     pub layers: Vec<LoadedLayer<W>>,
     pub game: Arc<AxolotlGame<W>>,
+    pub surface: SurfaceStage<W>,
+    /// The climate of [FlatSettings::biome]
+    pub climate: Option<BiomeClimate>,
+}
+impl<W: World> FlatGenerator<W> {
+    /// The temperature at the position. None if the biome is unknown
+    pub fn temperature_at(&self, x: i64, y: i32, z: i64) -> Option<f32> {
+        Some(self.surface.temperature_at(self.climate?, x, y, z))
+    }
 }
 
 impl<W: World> ChunkGenerator for FlatGenerator<W> {
@@ -47,7 +226,8 @@ impl<W: World> ChunkGenerator for FlatGenerator<W> {
     type Chunk = AxolotlChunk<W>;
     type GameTy = AxolotlGame<W>;
 
-    fn new(game: Arc<AxolotlGame<W>>, settings: FlatSettings) -> Self {
+    /// Flat worlds have no randomness so the seed is not used
+    fn new(game: Arc<AxolotlGame<W>>, settings: FlatSettings, _seed: WorldSeed) -> Self {
         let mut layers = Vec::new();
         for layer in settings.layers.iter() {
             let block = game
@@ -69,7 +249,15 @@ impl<W: World> ChunkGenerator for FlatGenerator<W> {
                 height: layer.height as i16,
             });
         }
+        let climate = OwnedNameSpaceKey::from_str(&settings.biome)
+            .ok()
+            .and_then(|biome| game.biome_climate(biome));
+        if climate.is_none() {
+            warn!("Biome {} not found", settings.biome);
+        }
         Self {
+            surface: SurfaceStage::new(&game),
+            climate,
             settings,
             layers,
             game,
@@ -83,11 +271,12 @@ impl<W: World> ChunkGenerator for FlatGenerator<W> {
     }
 
     fn generate_chunk_into(&self, chunk: &mut Self::Chunk) {
-        for (y, layer) in self.layers.iter().enumerate() {
-            for x in 0..16 {
-                for z in 0..16 {
-                    for y_v in 0..=layer.height {
-                        let y = y as i16 + y_v;
+        // Layers start at the bottom of the world and are stacked on top of each other
+        let mut bottom = chunk.height().min_y as i16;
+        for layer in self.layers.iter() {
+            for y in bottom..bottom + layer.height {
+                for x in 0..16 {
+                    for z in 0..16 {
                         chunk.set_block(
                             BlockPosition::new(x, y, z),
                             PlacedBlock::from(layer.block.clone()),
@@ -95,23 +284,90 @@ impl<W: World> ChunkGenerator for FlatGenerator<W> {
                     }
                 }
             }
+            bottom += layer.height;
         }
-        let air = self
-            .game
-            .registries
-            .blocks
-            .get_by_namespace("minecraft:air")
-            .expect("minecraft:air is missing");
-
-        for y in self.layers.len()..16 {
-            for x in 0..16 {
-                for z in 0..16 {
-                    chunk.set_block(
-                        BlockPosition::new(x, y as i16, z),
-                        PlacedBlock::from(air.clone()),
-                    );
-                }
+        self.surface.apply(chunk, |_, _| self.climate);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::noise::ChunkGenerator;
+    use axolotl_api::world_gen::seed::WorldSeed;
+    use axolotl_api::NamespacedId;
+
+    use crate::world::level::flat::{FlatGenerator, FlatPresetError, FlatSettings, Layer, PRESETS};
+    use crate::world::test_world::test_game;
+
+    #[test]
+    pub fn test_preset_string() {
+        let settings: FlatSettings =
+            "minecraft:bedrock,2*dirt,minecraft:grass_block;minecraft:plains"
+                .parse()
+                .unwrap();
+        assert_eq!(settings.layers.len(), 3);
+        assert_eq!(
+            settings.layers[1],
+            Layer {
+                block: "minecraft:dirt".to_string(),
+                height: 2
             }
+        );
+        assert_eq!(
+            settings.to_string(),
+            "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains"
+        );
+        assert_eq!(
+            "minecraft:stone".parse::<FlatSettings>().unwrap().biome,
+            "minecraft:plains"
+        );
+        assert_eq!(
+            "0*minecraft:stone".parse::<FlatSettings>().unwrap_err(),
+            FlatPresetError::InvalidLayer("0*minecraft:stone".to_string())
+        );
+        assert_eq!(
+            "4064*minecraft:stone,minecraft:dirt"
+                .parse::<FlatSettings>()
+                .unwrap_err(),
+            FlatPresetError::TooTall
+        );
+        assert_eq!(
+            "9223372036854775807*stone,stone"
+                .parse::<FlatSettings>()
+                .unwrap_err(),
+            FlatPresetError::InvalidLayer("9223372036854775807*stone".to_string())
+        );
+
+        for (name, preset) in PRESETS {
+            assert_eq!(FlatSettings::preset(name).unwrap().to_string(), preset);
+        }
+        let tunnelers = FlatSettings::preset("minecraft:tunnelers_dream").unwrap();
+        assert_eq!(tunnelers.layers[1].height, 230);
+        assert!(FlatSettings::preset("nether").is_err());
+    }
+    #[test]
+    pub fn test_generate_preset() {
+        let game = Arc::new(test_game(&["bedrock", "stone", "dirt", "grass_block"]));
+        let settings = FlatSettings::preset("tunnelers_dream").unwrap();
+        let generator = FlatGenerator::new(game, settings, WorldSeed::new(0));
+        let chunk = generator.generate_chunk(0, 0);
+        let min_y = chunk.height().min_y;
+        // 1 bedrock, 230 stone, 5 dirt and 1 grass block
+        for (x, z) in [(0, 0), (15, 15)] {
+            let (y, top) = chunk.highest_block(x, z).unwrap();
+            assert_eq!(y, min_y + 236);
+            assert_eq!(top.block.key(), "grass_block");
         }
+        let block_at = |y: i32| {
+            chunk
+                .get_block(BlockPosition::new(0, y as i16, 0))
+                .map(|block| block.block.key().to_string())
+        };
+        assert_eq!(block_at(min_y).as_deref(), Some("bedrock"));
+        assert_eq!(block_at(min_y + 230).as_deref(), Some("stone"));
+        assert_eq!(block_at(min_y + 231).as_deref(), Some("dirt"));
     }
 }
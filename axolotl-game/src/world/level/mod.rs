@@ -4,3 +4,6 @@ pub mod configs;
 pub mod flat;
 pub mod level_gen;
 pub mod noise;
+pub mod rules;
+pub mod session_lock;
+pub mod surface;
@@ -3,15 +3,28 @@ use std::sync::Arc;
 use log::warn;
 
 use axolotl_api::game::{DataRegistries, Game, Registry};
-use axolotl_api::world::World;
+use axolotl_api::world::{BlockPosition, World};
 use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::world_gen::noise::density::perlin::Perlin;
 use axolotl_api::world_gen::noise::density::DensityContext;
-use axolotl_api::world_gen::noise::{ChunkGenerator, NameSpaceKeyOrType, NoiseSetting};
+use axolotl_api::world_gen::noise::router::{DensityBuffer, NoiseRouter};
+use axolotl_api::world_gen::noise::{
+    ChunkGenerator, NameSpaceKeyAndProperties, NameSpaceKeyOrType, Noise, NoiseSetting,
+};
+use axolotl_api::world_gen::seed::{java_string_hash, WorldSeed};
+use axolotl_items::blocks::MinecraftBlock;
 
+use crate::world::chunk::placed_block::PlacedBlock;
 use crate::world::chunk::AxolotlChunk;
 use crate::world::level::biome_source::BiomeSourceSettings;
+use crate::world::level::surface::{BiomeClimate, SurfaceStage};
 use crate::{AxolotlGame, GameNoise};
 
+/// Blocks above sea level at which the terrain noise no longer makes land
+const TERRAIN_FALLOFF: f64 = 32.0;
+/// How far [NoiseGenerator::offset] moves the surface up or down in blocks
+const OFFSET_HEIGHT: f64 = 24.0;
+
 pub struct ChunkContext {
     pub chunk_x: i32,
     pub chunk_z: i32,
@@ -35,11 +48,69 @@ pub struct Settings {
     pub noise: NoiseSetting,
     pub biome_source: BiomeSourceSettings,
 }
+/// Fills chunks through a [NoiseRouter].
+///
+/// The `final_density` of the noise settings can not be built yet, see [crate::world::generator::AxolotlDensityState].
+/// Until it can, the density is a 3D terrain noise falling off above sea level, raised and lowered per 4x4 columns by a 2D offset noise
 #[derive(Debug)]
 pub struct NoiseGenerator<W: World> {
     game: Arc<AxolotlGame<W>>,
     noise: NoiseSetting,
     biome_source: BiomeSourceSettings,
+    surface: SurfaceStage<W>,
+    seed: WorldSeed,
+    router: NoiseRouter,
+    terrain: GameNoise,
+    /// Only depends on x and z. Sampled once per 4x4 columns like `flat_cache`
+    offset: GameNoise,
+    default_block: Option<MinecraftBlock<AxolotlGame<W>>>,
+    default_fluid: Option<MinecraftBlock<AxolotlGame<W>>>,
+    /// The climate of a fixed biome source
+    climate: Option<BiomeClimate>,
+}
+impl<W: World> NoiseGenerator<W> {
+    pub fn seed(&self) -> WorldSeed {
+        self.seed
+    }
+    /// The noise for the key. Each key gets its own seed forked from the world seed
+    pub fn create_noise(&self, key: &str, noise: Noise) -> GameNoise {
+        fork_noise(self.seed, key, noise)
+    }
+    pub fn router(&self) -> &NoiseRouter {
+        &self.router
+    }
+    /// The density of every block in the chunk. Positive is solid
+    pub fn density(&self, chunk: ChunkPos) -> DensityBuffer {
+        let noise_chunk = self.router.for_chunk(chunk);
+        let offsets = noise_chunk.flat(|column| self.offset_at(column.x, column.z));
+        let mut density = noise_chunk.interpolate_noise(&self.terrain, |corner, noise| {
+            noise + self.falloff(corner.y as i32)
+        });
+        // Values are ordered y, z, x. So the column of a value repeats every 256 values
+        for (index, value) in density.values.iter_mut().enumerate() {
+            *value += offsets.0[index % 256];
+        }
+        density
+    }
+    /// The density of a single block without interpolation. Matches [NoiseGenerator::density] on the cell corners
+    pub fn density_at(&self, x: i32, y: i32, z: i32) -> f64 {
+        self.terrain.get(x as f64, y as f64, z as f64) + self.falloff(y) + self.offset_at(x, z)
+    }
+    fn falloff(&self, y: i32) -> f64 {
+        (self.noise.sea_level - y) as f64 / TERRAIN_FALLOFF
+    }
+    fn offset_at(&self, x: i32, z: i32) -> f64 {
+        let (x, z) = (x & !3, z & !3);
+        self.offset.get(x as f64, 0.0, z as f64) * OFFSET_HEIGHT / TERRAIN_FALLOFF
+    }
+    /// The temperature at the position. Only known for fixed biome sources
+    pub fn temperature_at(&self, x: i64, y: i32, z: i64) -> Option<f32> {
+        let BiomeSourceSettings::Fixed { biome } = &self.biome_source else {
+            return None;
+        };
+        let climate = self.game.biome_climate(biome.clone())?;
+        Some(self.surface.temperature_at(climate, x, y, z))
+    }
 }
 
 impl<W: World> ChunkGenerator for NoiseGenerator<W> {
@@ -48,7 +119,11 @@ impl<W: World> ChunkGenerator for NoiseGenerator<W> {
     type Chunk = AxolotlChunk<W>;
     type GameTy = AxolotlGame<W>;
 
-    fn new(game: Arc<AxolotlGame<W>>, chunk_settings: Self::ChunkSettings) -> Self {
+    fn new(
+        game: Arc<AxolotlGame<W>>,
+        chunk_settings: Self::ChunkSettings,
+        seed: WorldSeed,
+    ) -> Self {
         let (biome_source, settings) = chunk_settings;
         let settings = match settings {
             NameSpaceKeyOrType::NameSpaceKey(key) => game
@@ -60,10 +135,26 @@ impl<W: World> ChunkGenerator for NoiseGenerator<W> {
             NameSpaceKeyOrType::Type(ty) => ty,
         };
 
+        let climate = match &biome_source {
+            BiomeSourceSettings::Fixed { biome } => game.biome_climate(biome.clone()),
+            _ => None,
+        };
         Self {
+            surface: SurfaceStage::new(&game),
+            router: NoiseRouter::new(&settings.noise),
+            terrain: fork_noise(
+                seed,
+                "axolotl:terrain",
+                Noise::from((vec![1.0, 1.0, 0.5], -7)),
+            ),
+            offset: fork_noise(seed, "axolotl:offset", Noise::from((vec![1.0, 1.0], -9))),
+            default_block: find_block(&game, &settings.default_block),
+            default_fluid: find_block(&game, &settings.default_fluid),
+            climate,
             game,
             noise: settings,
             biome_source,
+            seed,
         }
     }
 
@@ -73,7 +164,123 @@ impl<W: World> ChunkGenerator for NoiseGenerator<W> {
         chunk
     }
 
-    fn generate_chunk_into(&self, _chunk: &mut Self::Chunk) {
-        warn!("Unimplemented chunk generation");
+    fn generate_chunk_into(&self, chunk: &mut Self::Chunk) {
+        let (Some(block), Some(fluid)) = (&self.default_block, &self.default_fluid) else {
+            return;
+        };
+        let density = self.density(chunk.chunk_pos);
+        let height = chunk.height();
+        let min_y = self.router.min_y.max(height.min_y);
+        let max_y = (self.router.min_y + self.router.height).min(height.max_y() + 1);
+        for y in min_y..max_y {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let block = if density.get(x, y, z) > 0.0 {
+                        block
+                    } else if y < self.noise.sea_level {
+                        fluid
+                    } else {
+                        continue;
+                    };
+                    chunk.set_block(
+                        BlockPosition::new(x as i64, y as i16, z as i64),
+                        PlacedBlock::from(block.clone()),
+                    );
+                }
+            }
+        }
+        self.surface.apply(chunk, |_, _| self.climate);
+    }
+}
+
+fn fork_noise(seed: WorldSeed, key: &str, noise: Noise) -> GameNoise {
+    let seed = seed.fork(java_string_hash(key) as u32 as u64);
+    GameNoise::new(seed.xoroshiro_seed(), noise)
+}
+
+fn find_block<W: World>(
+    game: &AxolotlGame<W>,
+    block: &NameSpaceKeyAndProperties,
+) -> Option<MinecraftBlock<AxolotlGame<W>>> {
+    let found = game
+        .registries
+        .blocks
+        .get_by_namespace(block.name.to_string())
+        .cloned();
+    if found.is_none() {
+        warn!(
+            "Block {} not found. Chunks will not be generated",
+            block.name
+        );
+    }
+    found
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::Cell;
+    use std::sync::Arc;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_api::world_gen::noise::density::cache::flat::FlatCache;
+    use axolotl_api::world_gen::noise::density::cache::CacheFunctions;
+    use axolotl_api::world_gen::noise::density::Function;
+    use axolotl_api::world_gen::noise::ChunkGenerator;
+    use axolotl_api::world_gen::seed::WorldSeed;
+    use axolotl_api::NamespacedId;
+
+    use crate::world::level::noise::NoiseGenerator;
+    use crate::world::perlin::GameNoise;
+    use crate::world::test_world::{test_game, test_noise_generator, TestWorld};
+
+    fn generator() -> NoiseGenerator<TestWorld> {
+        test_noise_generator(Arc::new(test_game(&["stone", "water"])), WorldSeed::new(42))
+    }
+
+    #[test]
+    pub fn test_generate_chunk() {
+        let generator = generator();
+        let chunk = generator.generate_chunk(2, -3);
+        let block_at = |y: i16| {
+            chunk
+                .get_block(BlockPosition::new(7, y, 7))
+                .map(|block| block.block.key().to_string())
+        };
+        assert_eq!(block_at(-64).as_deref(), Some("stone"));
+        // Water fills up to sea level and the land falls off long before the top of the world
+        let (top, _) = chunk.highest_block(7, 7).unwrap();
+        assert!((62..300).contains(&top));
+
+        // The interpolated density is exact on the cell corners
+        let density = generator.density(ChunkPos::new(2, -3));
+        for (x, y, z) in [(0, -64, 0), (4, 0, 8), (12, 312, 4)] {
+            let exact = generator.density_at(32 + x as i32, y, -48 + z as i32);
+            assert!((density.get(x, y, z) - exact).abs() < 1e-9);
+        }
+        // The same seed generates the same density
+        assert_eq!(generator().density(ChunkPos::new(2, -3)), density);
+    }
+
+    #[test]
+    pub fn test_flat_cache_per_chunk() {
+        let function: Function<GameNoise> =
+            Function::Cached(Box::new(CacheFunctions::FlatCache(FlatCache {
+                function: Function::Constant(2.0),
+                cache: Cell::new(None),
+            })));
+        let router = *generator().router();
+        let density = router
+            .for_chunk(ChunkPos::new(0, 0))
+            .interpolate_function(&function);
+        assert_eq!(density.get(3, 10, 5), 2.0);
+        // Each chunk computed on its own clone. The shared function was never written to
+        let Function::Cached(cache) = &function else {
+            unreachable!()
+        };
+        let CacheFunctions::FlatCache(flat) = cache.as_ref() else {
+            unreachable!()
+        };
+        assert_eq!(flat.cache.get(), None);
     }
 }
@@ -0,0 +1,73 @@
+//! Reads the difficulty and game rules saved in `level.dat`
+use minecraft_protocol::packets::play::client::Difficulty;
+use serde_json::Value;
+
+use axolotl_world::level::{default_game_rules, LevelDat};
+
+/// Unknown values are treated as normal
+pub fn difficulty(level: &LevelDat) -> Difficulty {
+    match level.difficulty {
+        0 => Difficulty::Peaceful,
+        1 => Difficulty::Easy,
+        3 => Difficulty::Hard,
+        _ => Difficulty::Normal,
+    }
+}
+/// Rules missing from `level.dat` have their vanilla default
+fn game_rule(level: &LevelDat, name: &str) -> Option<Value> {
+    level
+        .game_rules
+        .get(name)
+        .cloned()
+        .or_else(|| default_game_rules().remove(name))
+}
+/// Vanilla saves the game rules as strings. Both strings and booleans are read
+pub fn bool_rule(level: &LevelDat, name: &str) -> bool {
+    match game_rule(level, name) {
+        Some(Value::Bool(value)) => value,
+        Some(Value::String(value)) => value == "true",
+        _ => false,
+    }
+}
+/// Vanilla saves the game rules as strings. Both strings and numbers are read
+pub fn int_rule(level: &LevelDat, name: &str) -> Option<i64> {
+    match game_rule(level, name)? {
+        Value::Number(value) => value.as_i64(),
+        Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use minecraft_protocol::packets::play::client::Difficulty;
+    use serde_json::Value;
+
+    use axolotl_world::level::LevelDat;
+
+    use crate::world::level::rules::{bool_rule, difficulty, int_rule};
+
+    #[test]
+    pub fn test_rules() {
+        let mut level = LevelDat {
+            difficulty: 3,
+            ..LevelDat::default()
+        };
+        assert_eq!(difficulty(&level), Difficulty::Hard);
+        // The vanilla defaults
+        assert!(bool_rule(&level, "naturalRegeneration"));
+        assert_eq!(int_rule(&level, "playersSleepingPercentage"), Some(100));
+
+        level.game_rules.insert(
+            "naturalRegeneration".to_string(),
+            Value::String("false".to_string()),
+        );
+        level.game_rules.insert(
+            "playersSleepingPercentage".to_string(),
+            Value::String("50".to_string()),
+        );
+        assert!(!bool_rule(&level, "naturalRegeneration"));
+        assert_eq!(int_rule(&level, "playersSleepingPercentage"), Some(50));
+        assert!(!bool_rule(&level, "unknownRule"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+
+use crate::Error;
+
+pub const SESSION_LOCK_FILE: &str = "session.lock";
+
+/// How a world folder is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenMode {
+    /// Fails with [Error::WorldLocked] if another process holds the lock
+    #[default]
+    ReadWrite,
+    /// Opens the world even if another process holds the lock. Can corrupt the world
+    Force,
+    /// Never takes the lock and never writes
+    ReadOnly,
+}
+
+/// The `session.lock` of a world. Matches vanilla: the file contains a snowman and is locked by the OS.
+///
+/// The OS releases the lock when the process dies. So a lock file left behind by a crash is stale and taken over
+#[derive(Debug)]
+pub struct SessionLock {
+    pub path: PathBuf,
+    /// None if the lock was forced while another process held it
+    file: Option<File>,
+}
+impl SessionLock {
+    pub fn acquire(world_folder: impl AsRef<Path>, force: bool) -> Result<Self, Error> {
+        let path = world_folder.as_ref().join(SESSION_LOCK_FILE);
+        let existed = path.exists();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {
+                if existed {
+                    debug!("Taking over stale lock {:?}", path);
+                }
+                file.set_len(0)?;
+                file.seek(SeekFrom::Start(0))?;
+                file.write_all("☃".as_bytes())?;
+                file.flush()?;
+                Ok(Self {
+                    path,
+                    file: Some(file),
+                })
+            }
+            Err(TryLockError::WouldBlock) if force => {
+                warn!(
+                    "{:?} is held by another process. Opening the world anyway",
+                    path
+                );
+                Ok(Self { path, file: None })
+            }
+            Err(TryLockError::WouldBlock) => Err(Error::WorldLocked(path)),
+            Err(TryLockError::Error(e)) => Err(Error::IO(e)),
+        }
+    }
+    /// False if the lock was forced
+    pub fn is_held(&self) -> bool {
+        self.file.is_some()
+    }
+}
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            if let Err(e) = file.unlock() {
+                warn!("Failed to release {:?}: {}", self.path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::world::level::session_lock::SessionLock;
+    use crate::Error;
+
+    #[test]
+    pub fn test_session_lock() {
+        let folder = std::env::temp_dir().join(format!("axolotl_lock_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let lock = SessionLock::acquire(&folder, false).unwrap();
+        assert!(lock.is_held());
+        assert!(matches!(
+            SessionLock::acquire(&folder, false),
+            Err(Error::WorldLocked(_))
+        ));
+        assert!(!SessionLock::acquire(&folder, true).unwrap().is_held());
+        drop(lock);
+        // The file is left behind but no longer locked
+        assert!(SessionLock::acquire(&folder, false).unwrap().is_held());
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
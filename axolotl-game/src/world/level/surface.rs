@@ -0,0 +1,191 @@
+//! The last generation stage. Covers cold biomes in snow and freezes their water
+use log::warn;
+
+use axolotl_api::game::Registry;
+use axolotl_api::item::block::Block;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::biome::climate::{
+    cold_enough_to_snow, ClimateNoise, TemperatureModifier,
+};
+use axolotl_api::NamespacedId;
+use axolotl_items::blocks::{InnerMinecraftBlock, MinecraftBlock};
+
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+use crate::world::chunk::AxolotlChunk;
+use crate::AxolotlGame;
+
+/// Snow is not placed on these
+const NO_SNOW_ON: [&str; 7] = [
+    "snow",
+    "ice",
+    "packed_ice",
+    "blue_ice",
+    "barrier",
+    "lava",
+    "water",
+];
+/// Snow stays on these even though their top is not a full face
+const SNOW_ALWAYS_ON: [&str; 2] = ["honey_block", "soul_sand"];
+
+/// Like vanilla's `SnowLayerBlock.canSurvive`. Snow needs a full face below it.
+///
+/// Opaque blocks count as full. Leaves are full but let light through
+fn supports_snow<W: World>(block: &PlacedBlock<W>) -> bool {
+    let key = block.block.key();
+    if NO_SNOW_ON.contains(&key) {
+        return false;
+    }
+    SNOW_ALWAYS_ON.contains(&key)
+        || key.ends_with("_leaves")
+        || <InnerMinecraftBlock<AxolotlGame<W>> as Block<AxolotlGame<W>>>::opacity(&block.block)
+            == 15
+}
+
+/// The base temperature and modifier of a biome
+pub type BiomeClimate = (f32, TemperatureModifier);
+
+#[derive(Debug, Clone)]
+pub struct SurfaceStage<W: World> {
+    pub climate: ClimateNoise,
+    snow: Option<MinecraftBlock<AxolotlGame<W>>>,
+    ice: Option<MinecraftBlock<AxolotlGame<W>>>,
+    air: Option<MinecraftBlock<AxolotlGame<W>>>,
+}
+impl<W: World> SurfaceStage<W> {
+    pub fn new(game: &AxolotlGame<W>) -> Self {
+        let snow = game.registries.blocks.get_by_namespace("minecraft:snow");
+        let ice = game.registries.blocks.get_by_namespace("minecraft:ice");
+        if snow.is_none() || ice.is_none() {
+            warn!("Snow or ice is missing. Cold biomes will not be covered");
+        }
+        Self {
+            climate: ClimateNoise::default(),
+            snow: snow.cloned(),
+            ice: ice.cloned(),
+            air: game
+                .registries
+                .blocks
+                .get_by_namespace("minecraft:air")
+                .cloned(),
+        }
+    }
+    /// The temperature of the biome at the position
+    pub fn temperature_at(&self, biome: BiomeClimate, x: i64, y: i32, z: i64) -> f32 {
+        self.climate.temperature_at(biome.0, biome.1, x, y, z)
+    }
+    /// Freezes the top water and places snow on the top block of each column that is cold enough.
+    ///
+    /// `biome_at` gives the climate of the biome of a column. Columns without a biome are skipped
+    pub fn apply(
+        &self,
+        chunk: &mut AxolotlChunk<W>,
+        biome_at: impl Fn(i64, i64) -> Option<BiomeClimate>,
+    ) {
+        let (Some(snow), Some(ice)) = (&self.snow, &self.ice) else {
+            return;
+        };
+        let max_y = chunk.height().max_y();
+        let (chunk_x, chunk_z) = (
+            chunk.chunk_pos.x() as i64 * 16,
+            chunk.chunk_pos.z() as i64 * 16,
+        );
+        for x in 0..16 {
+            for z in 0..16 {
+                let Some(biome) = biome_at(chunk_x + x, chunk_z + z) else {
+                    continue;
+                };
+                let Some((y, top)) = chunk
+                    .highest_block(x, z)
+                    .map(|(y, block)| (y, block.clone()))
+                else {
+                    continue;
+                };
+                let key = top.block.key();
+                if key == "water" && top.is_water_source() {
+                    if cold_enough_to_snow(self.temperature_at(biome, chunk_x + x, y, chunk_z + z))
+                    {
+                        chunk.set_block(
+                            BlockPosition::new(x, y as i16, z),
+                            PlacedBlock::from(ice.clone()),
+                        );
+                    }
+                    continue;
+                }
+                if !supports_snow(&top) || y + 1 > max_y {
+                    continue;
+                }
+                if cold_enough_to_snow(self.temperature_at(biome, chunk_x + x, y + 1, chunk_z + z))
+                {
+                    self.place(chunk, BlockPosition::new(x, (y + 1) as i16, z), snow);
+                }
+            }
+        }
+    }
+    /// A block set in an empty section fills the whole section. The section is filled with air first
+    fn place(
+        &self,
+        chunk: &mut AxolotlChunk<W>,
+        position: BlockPosition,
+        block: &MinecraftBlock<AxolotlGame<W>>,
+    ) {
+        if let (Some(air), Some(section)) =
+            (&self.air, chunk.sections.get_mut(position.y as i32 >> 4))
+        {
+            if section.blocks == AxolotlBlockSection::Empty {
+                section.blocks = AxolotlBlockSection::SingleBlock(PlacedBlock::from(air.clone()));
+            }
+        }
+        chunk.set_block(position, PlacedBlock::from(block.clone()));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::biome::climate::TemperatureModifier;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+    use crate::world::chunk::AxolotlChunk;
+    use crate::world::level::surface::{BiomeClimate, SurfaceStage};
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+
+    /// Stone from y 64 to 79 with water at 1, 1 and a torch at 2, 2 on top
+    fn surface_chunk(climate: BiomeClimate) -> AxolotlChunk<TestWorld> {
+        let game = test_game(&["stone", "snow", "ice", "water", "torch"]);
+        let mut chunk = AxolotlChunk::new(ChunkPos::new(0, 0));
+        chunk.sections.get_mut(4).unwrap().blocks =
+            AxolotlBlockSection::SingleBlock(test_block(&game, "stone"));
+        chunk.set_block(BlockPosition::new(1, 79, 1), test_block(&game, "water"));
+        chunk.set_block(BlockPosition::new(2, 79, 2), test_block(&game, "torch"));
+        SurfaceStage::new(&game).apply(&mut chunk, |_, _| Some(climate));
+        chunk
+    }
+    fn key_at(chunk: &AxolotlChunk<TestWorld>, x: i64, y: i16, z: i64) -> Option<String> {
+        chunk
+            .get_block(BlockPosition::new(x, y, z))
+            .filter(|block| !block.is_air())
+            .map(|block| block.block.key().to_string())
+    }
+
+    #[test]
+    pub fn test_cold_surface() {
+        let chunk = surface_chunk((-0.5, TemperatureModifier::None));
+        assert_eq!(key_at(&chunk, 0, 80, 0).as_deref(), Some("snow"));
+        assert_eq!(key_at(&chunk, 15, 80, 15).as_deref(), Some("snow"));
+        // Only the top of each column
+        assert_eq!(key_at(&chunk, 0, 81, 0), None);
+        assert_eq!(key_at(&chunk, 1, 79, 1).as_deref(), Some("ice"));
+        assert_eq!(key_at(&chunk, 1, 80, 1), None);
+        // A torch can not hold snow
+        assert_eq!(key_at(&chunk, 2, 80, 2), None);
+    }
+
+    #[test]
+    pub fn test_warm_surface() {
+        let chunk = surface_chunk((0.8, TemperatureModifier::None));
+        assert_eq!(key_at(&chunk, 0, 80, 0), None);
+        assert_eq!(key_at(&chunk, 1, 79, 1).as_deref(), Some("water"));
+    }
+}
@@ -2,11 +2,27 @@ use axolotl_api::world::{BlockPosition, World};
 
 use crate::world::chunk::placed_block::PlacedBlock;
 
+pub mod block_update;
 pub mod chunk;
+pub mod coalesce;
 pub mod entity;
+pub mod events;
 pub mod generator;
 pub mod level;
 pub mod perlin;
+pub mod players;
+pub mod protection;
+pub mod recorder;
+pub mod render;
+pub mod scheduler;
+pub mod simulation;
+pub mod sleep;
+#[cfg(test)]
+pub mod test_world;
+pub mod tick_rate;
+pub mod updates;
+pub mod view;
+pub mod wire;
 #[derive(Debug)]
 pub enum ChunkUpdate<W: World> {
     Unload {
@@ -0,0 +1,343 @@
+//! The players in a world.
+//!
+//! Their state is loaded from their [PlayerData] when they join and written back when they leave or the world saves
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use log::warn;
+use minecraft_protocol::packets::play::client::Difficulty;
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_world::entity::player::PlayerData;
+
+use crate::world::chunk::ChunkMap;
+use crate::world::entity::hunger::{exhaustion, HungerState};
+use crate::world::entity::properties::{AirLevel, Health};
+use crate::world::events::raid::MAX_BAD_OMEN_LEVEL;
+use crate::world::level::accessor::{LevelReader, LevelWriter, PlayerAccess};
+use crate::world::level::rules;
+use crate::Error;
+
+/// The height of the eyes of a standing player
+pub const EYE_HEIGHT: f64 = 1.62;
+pub const MAX_HEALTH: f32 = 20.0;
+
+/// What the client shows in its health, food and air bars
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStatus {
+    pub health: f32,
+    pub food_level: i32,
+    pub saturation: f32,
+    /// In ticks
+    pub air: i32,
+}
+
+/// The state a world keeps for a player
+#[derive(Debug, Clone)]
+pub struct WorldPlayer {
+    /// Loaded from the player file. The state below is written back into it by [WorldPlayer::save]
+    pub data: PlayerData,
+    pub hunger: HungerState,
+    pub air: AirLevel,
+    pub health: Health,
+    /// The level of Bad Omen. A raid starts once the player enters a village
+    pub bad_omen: Option<u8>,
+}
+impl WorldPlayer {
+    pub fn load(data: PlayerData) -> Self {
+        Self {
+            hunger: HungerState::load(&data),
+            air: AirLevel::load(&data),
+            health: Health::load(&data),
+            bad_omen: None,
+            data,
+        }
+    }
+    /// Writes the state into [WorldPlayer::data]
+    pub fn save(&mut self) -> &PlayerData {
+        self.hunger.save(&mut self.data);
+        self.air.save(&mut self.data);
+        self.health.save(&mut self.data);
+        &self.data
+    }
+    pub fn status(&self) -> PlayerStatus {
+        PlayerStatus {
+            health: self.health.0,
+            food_level: self.hunger.food_level,
+            saturation: self.hunger.saturation,
+            air: self.air.0 as i32,
+        }
+    }
+    /// The block the feet of the player are in. None for a new player without a position
+    pub fn position(&self) -> Option<BlockPosition> {
+        let [x, y, z] = self.data.pos[..] else {
+            return None;
+        };
+        Some(BlockPosition::new(
+            x.floor() as i64,
+            y.floor() as i16,
+            z.floor() as i64,
+        ))
+    }
+    /// Levels of Bad Omen add up to [MAX_BAD_OMEN_LEVEL]
+    pub fn add_bad_omen(&mut self, level: u8) {
+        let level = self.bad_omen.unwrap_or(0).saturating_add(level);
+        self.bad_omen = Some(level.min(MAX_BAD_OMEN_LEVEL));
+    }
+    /// The block the eyes of the player are in. None for a new player without a position
+    pub fn eyes(&self) -> Option<BlockPosition> {
+        let [x, y, z] = self.data.pos[..] else {
+            return None;
+        };
+        Some(BlockPosition::new(
+            x.floor() as i64,
+            (y + EYE_HEIGHT).floor() as i16,
+            z.floor() as i64,
+        ))
+    }
+    /// Runs the hunger and breath of the player for one tick
+    pub fn tick(
+        &mut self,
+        difficulty: &Difficulty,
+        natural_regeneration: bool,
+        eyes_in_water: bool,
+        random: &mut impl Rng,
+    ) {
+        self.hunger.tick(
+            &mut self.health,
+            MAX_HEALTH,
+            difficulty,
+            natural_regeneration,
+        );
+        if let Some(damage) = self.air.tick(eyes_in_water, false, 0, random) {
+            self.health.0 = (self.health.0 - damage).max(0.0);
+            self.hunger.add_exhaustion(exhaustion::DAMAGE);
+        }
+    }
+}
+
+/// The players of a world. Held by [ChunkMap::players]
+///
+/// Ordered by uuid so every tick consumes the tick random in the same order
+#[derive(Debug, Default)]
+pub struct WorldPlayers {
+    players: Mutex<BTreeMap<Uuid, WorldPlayer>>,
+    /// Players only live in memory if None
+    access: Option<Arc<dyn PlayerAccess>>,
+}
+impl WorldPlayers {
+    pub fn new(access: Arc<dyn PlayerAccess>) -> Self {
+        Self {
+            players: Mutex::default(),
+            access: Some(access),
+        }
+    }
+    /// Loads the player. None if another world already loaded them
+    pub fn join(&self, player: Uuid, source_world: u64) -> Result<Option<PlayerStatus>, Error> {
+        let data = match &self.access {
+            Some(access) => match access.get_player(player, source_world)? {
+                Some(data) => data,
+                None => return Ok(None),
+            },
+            None => PlayerData::default(),
+        };
+        let joined = WorldPlayer::load(data);
+        let status = joined.status();
+        self.players.lock().insert(player, joined);
+        Ok(Some(status))
+    }
+    /// Saves and removes the player. None if they are not in the world
+    pub fn leave(&self, player: &Uuid) -> Result<Option<WorldPlayer>, Error> {
+        let Some(mut left) = self.players.lock().remove(player) else {
+            return Ok(None);
+        };
+        if let Some(access) = &self.access {
+            access.save_player(*player, left.save())?;
+        }
+        Ok(Some(left))
+    }
+    /// Saves every player. They stay in the world
+    pub fn save_all(&self) -> Result<(), Error> {
+        let Some(access) = &self.access else {
+            return Ok(());
+        };
+        for (uuid, player) in self.players.lock().iter_mut() {
+            access.save_player(*uuid, player.save())?;
+        }
+        Ok(())
+    }
+    pub fn get(&self, player: &Uuid) -> Option<WorldPlayer> {
+        self.players.lock().get(player).cloned()
+    }
+    /// Changes the player. None if they are not in the world
+    pub fn update<T>(
+        &self,
+        player: &Uuid,
+        update: impl FnOnce(&mut WorldPlayer) -> T,
+    ) -> Option<T> {
+        self.players.lock().get_mut(player).map(update)
+    }
+    pub fn contains(&self, player: &Uuid) -> bool {
+        self.players.lock().contains_key(player)
+    }
+    pub fn len(&self) -> usize {
+        self.players.lock().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.players.lock().is_empty()
+    }
+    /// Eats the vanilla food item.
+    ///
+    /// Returns the new status. None if the player is not in the world, the item is not food or they are not hungry
+    pub fn eat(&self, player: &Uuid, item: &str) -> Option<PlayerStatus> {
+        let mut players = self.players.lock();
+        let player = players.get_mut(player)?;
+        player.hunger.eat_item(item).then(|| player.status())
+    }
+    /// Removes Bad Omen from every player `trigger` returns true for. Called with the level of Bad Omen
+    pub fn trigger_bad_omen(&self, mut trigger: impl FnMut(&WorldPlayer, u8) -> bool) {
+        for player in self.players.lock().values_mut() {
+            let Some(level) = player.bad_omen else {
+                continue;
+            };
+            if trigger(player, level) {
+                player.bad_omen = None;
+            }
+        }
+    }
+    /// Ticks every player. Returns the players whose status changed
+    pub fn tick(
+        &self,
+        difficulty: &Difficulty,
+        natural_regeneration: bool,
+        eyes_in_water: impl Fn(&WorldPlayer) -> bool,
+        random: &mut impl Rng,
+    ) -> Vec<(Uuid, PlayerStatus)> {
+        let mut changed = Vec::new();
+        for (uuid, player) in self.players.lock().iter_mut() {
+            let before = player.status();
+            let in_water = eyes_in_water(player);
+            player.tick(difficulty, natural_regeneration, in_water, random);
+            let status = player.status();
+            if status != before {
+                changed.push((*uuid, status));
+            }
+        }
+        changed
+    }
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    /// Ticks the hunger and breath of every player with the difficulty and `naturalRegeneration` rule of [ChunkMap::level].
+    ///
+    /// Returns the players whose status changed
+    pub fn tick_players(&self, random: &mut impl Rng) -> Vec<(Uuid, PlayerStatus)> {
+        let level = self.level.read();
+        let difficulty = rules::difficulty(&level);
+        let natural_regeneration = rules::bool_rule(&level, "naturalRegeneration");
+        drop(level);
+        self.players.tick(
+            &difficulty,
+            natural_regeneration,
+            |player| {
+                player
+                    .eyes()
+                    .is_some_and(|eyes| self.get_block(eyes).is_some_and(|block| block.is_water()))
+            },
+            random,
+        )
+    }
+    /// Saves the players. Read only worlds are skipped
+    pub(crate) fn save_players(&self) {
+        match self.players.save_all() {
+            Ok(()) | Err(Error::ReadOnly) => {}
+            Err(e) => warn!("Error saving players: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_world::entity::player::PlayerData;
+
+    use crate::world::chunk::ChunkMap;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::level::accessor::memory::MemoryPlayerAccess;
+    use crate::world::players::{WorldPlayer, WorldPlayers};
+    use crate::world::test_world::{test_block, test_game};
+
+    #[test]
+    pub fn test_join_and_leave() {
+        let access = Arc::new(MemoryPlayerAccess::default());
+        let player = Uuid::new_v4();
+        access
+            .save_player(
+                player,
+                &PlayerData {
+                    food_level: 10,
+                    ..PlayerData::default()
+                },
+            )
+            .unwrap();
+        let players = WorldPlayers::new(access.clone());
+        let status = players.join(player, 1).unwrap().unwrap();
+        assert_eq!(status.food_level, 10);
+        // Loaded by this world
+        assert_eq!(players.join(player, 2).unwrap(), None);
+
+        assert_eq!(
+            players.eat(&player, "minecraft:bread").unwrap().food_level,
+            15
+        );
+        assert_eq!(players.eat(&player, "minecraft:stone"), None);
+        players.leave(&player).unwrap().unwrap();
+        assert!(players.is_empty());
+        assert_eq!(access.players.read()[&player].food_level, 15);
+    }
+
+    #[test]
+    pub fn test_drowning() {
+        let game = Arc::new(test_game(&["water"]));
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone());
+        map.load_chunk_task(0, 0, None).unwrap();
+        map.set_block(
+            BlockPosition::new(1, 65, 1),
+            test_block(&game, "water"),
+            None,
+        );
+        let player = Uuid::new_v4();
+        map.players.players.lock().insert(
+            player,
+            WorldPlayer::load(PlayerData {
+                pos: vec![1.5, 64.0, 1.5],
+                ..PlayerData::default()
+            }),
+        );
+
+        let mut random = rand::thread_rng();
+        for _ in 0..300 {
+            map.tick_players(&mut random);
+        }
+        assert_eq!(map.players.get(&player).unwrap().health.0, 20.0);
+        // Out of air. Drowning starts 20 ticks later
+        let changed = map.tick_players(&mut random);
+        assert_eq!(changed[0].1.air, -1);
+        for _ in 0..19 {
+            map.tick_players(&mut random);
+        }
+        assert_eq!(map.players.get(&player).unwrap().health.0, 18.0);
+    }
+}
@@ -0,0 +1,194 @@
+//! A built-in [ProtectionProvider] that lets players claim whole chunks
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ahash::AHashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::world::protection::{Decision, ProtectionProvider};
+use axolotl_api::world::BlockPosition;
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::world::level::session_lock::OpenMode;
+use crate::Error;
+
+/// The file within the world folder
+pub const CLAIMS_FILE: &str = "data/axolotl_claims.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkClaim {
+    pub owner: Uuid,
+    /// Players allowed to modify the chunk besides the owner
+    #[serde(default)]
+    pub trusted: Vec<Uuid>,
+}
+impl ChunkClaim {
+    pub fn is_member(&self, player: &Uuid) -> bool {
+        self.owner == *player || self.trusted.contains(player)
+    }
+}
+#[derive(Serialize, Deserialize)]
+struct SavedClaim {
+    x: i32,
+    z: i32,
+    #[serde(flatten)]
+    claim: ChunkClaim,
+}
+
+/// Unclaimed chunks pass. Claimed chunks allow the owner and the trusted players and deny everyone else
+#[derive(Debug)]
+pub struct ChunkClaims {
+    path: PathBuf,
+    claims: RwLock<AHashMap<ChunkPos, ChunkClaim>>,
+    /// [ChunkClaims::save] returns [Error::ReadOnly]
+    read_only: bool,
+}
+impl ChunkClaims {
+    /// Loads the claims of the world. No claims if the file does not exist yet
+    pub fn open(world_folder: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::open_with_mode(world_folder, OpenMode::ReadWrite)
+    }
+    /// Claims opened [OpenMode::ReadOnly] can change in memory but are never saved
+    pub fn open_with_mode(world_folder: impl AsRef<Path>, mode: OpenMode) -> Result<Self, Error> {
+        let path = world_folder.as_ref().join(CLAIMS_FILE);
+        let mut claims = AHashMap::new();
+        if path.exists() {
+            let saved: Vec<SavedClaim> = serde_json::from_slice(&fs::read(&path)?)?;
+            for saved in saved {
+                claims.insert(ChunkPos::new(saved.x, saved.z), saved.claim);
+            }
+        }
+        Ok(Self {
+            path,
+            claims: RwLock::new(claims),
+            read_only: mode == OpenMode::ReadOnly,
+        })
+    }
+    pub fn save(&self) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        let saved: Vec<SavedClaim> = self
+            .claims
+            .read()
+            .iter()
+            .map(|(pos, claim)| SavedClaim {
+                x: pos.x(),
+                z: pos.z(),
+                claim: claim.clone(),
+            })
+            .collect();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&saved)?)?;
+        Ok(())
+    }
+    pub fn get(&self, chunk: &ChunkPos) -> Option<ChunkClaim> {
+        self.claims.read().get(chunk).cloned()
+    }
+    /// Returns false if another player owns the chunk
+    pub fn claim(&self, chunk: ChunkPos, owner: Uuid) -> bool {
+        let mut claims = self.claims.write();
+        match claims.get(&chunk) {
+            Some(claim) if claim.owner != owner => false,
+            Some(_) => true,
+            None => {
+                claims.insert(
+                    chunk,
+                    ChunkClaim {
+                        owner,
+                        trusted: Vec::new(),
+                    },
+                );
+                true
+            }
+        }
+    }
+    /// Only the owner can remove a claim
+    pub fn unclaim(&self, chunk: &ChunkPos, owner: &Uuid) -> bool {
+        let mut claims = self.claims.write();
+        if claims
+            .get(chunk)
+            .is_some_and(|claim| claim.owner == *owner)
+        {
+            claims.remove(chunk);
+            return true;
+        }
+        false
+    }
+    /// Returns false if the chunk is not claimed
+    pub fn trust(&self, chunk: &ChunkPos, player: Uuid) -> bool {
+        let mut claims = self.claims.write();
+        let Some(claim) = claims.get_mut(chunk) else {
+            return false;
+        };
+        if !claim.is_member(&player) {
+            claim.trusted.push(player);
+        }
+        true
+    }
+    pub fn untrust(&self, chunk: &ChunkPos, player: &Uuid) {
+        if let Some(claim) = self.claims.write().get_mut(chunk) {
+            claim.trusted.retain(|trusted| trusted != player);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.claims.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.claims.read().is_empty()
+    }
+}
+impl ProtectionProvider for ChunkClaims {
+    fn can_modify(&self, player: &Uuid, position: &BlockPosition) -> Decision {
+        let mut position = *position;
+        match self.claims.read().get(&position.chunk()) {
+            None => Decision::Pass,
+            Some(claim) if claim.is_member(player) => Decision::Allow,
+            Some(_) => Decision::Deny,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::protection::{Decision, ProtectionProvider};
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use uuid::Uuid;
+
+    use crate::world::protection::ChunkClaims;
+
+    #[test]
+    pub fn test_claims() {
+        let folder = std::env::temp_dir().join(format!("axolotl_claims_{}", Uuid::new_v4()));
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let claims = ChunkClaims::open(&folder).unwrap();
+        assert!(claims.claim(ChunkPos::new(1, 0), owner));
+        assert!(!claims.claim(ChunkPos::new(1, 0), other));
+
+        let inside = BlockPosition::new(20, 64, 3);
+        assert_eq!(claims.can_modify(&owner, &inside), Decision::Allow);
+        assert_eq!(claims.can_modify(&other, &inside), Decision::Deny);
+        assert_eq!(
+            claims.can_modify(&other, &BlockPosition::new(40, 64, 3)),
+            Decision::Pass
+        );
+        claims.trust(&ChunkPos::new(1, 0), other);
+        assert_eq!(claims.can_modify(&other, &inside), Decision::Allow);
+
+        claims.save().unwrap();
+        let loaded = ChunkClaims::open(&folder).unwrap();
+        assert_eq!(
+            loaded.get(&ChunkPos::new(1, 0)),
+            claims.get(&ChunkPos::new(1, 0))
+        );
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+//! Records the events that change a world to an append-only log.
+//!
+//! The log is JSON lines. One [RecordedEvent] per line so a crash only loses the last line
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ahash::AHashMap;
+use log::warn;
+use minecraft_protocol::packets::play::client::chunk::GetVanillaId;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::NamespacedId;
+
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::Error;
+
+/// A block as it is stored in the log. The state id is the vanilla id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedBlock {
+    pub key: String,
+    pub state: i32,
+}
+impl<W: World> From<&PlacedBlock<W>> for RecordedBlock {
+    fn from(block: &PlacedBlock<W>) -> Self {
+        Self {
+            key: format!("{}:{}", block.block.namespace(), block.block.key()),
+            state: block.get_vanilla_id(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorldEvent {
+    /// None is air
+    BlockChange {
+        position: BlockPosition,
+        old: Option<RecordedBlock>,
+        new: Option<RecordedBlock>,
+        /// The player or entity that made the change
+        actor: Option<Uuid>,
+    },
+    /// Recorded by [crate::world::view::AxolotlWorldView] when a plugin spawns an entity
+    EntitySpawn {
+        uuid: Uuid,
+        entity_type: String,
+        position: [f64; 3],
+    },
+    /// A message sent to the world with [crate::world::updates::ServerUpdateIn::Chat]
+    Chat {
+        sender: Option<Uuid>,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub tick: u64,
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: WorldEvent,
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Appends events to the log file
+#[derive(Debug)]
+pub struct EventRecorder {
+    pub path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+    tick: AtomicU64,
+}
+impl EventRecorder {
+    /// Opens the log. New events are added to the end of an existing log
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(BufWriter::new(file)),
+            tick: AtomicU64::new(0),
+        })
+    }
+    /// Called through [crate::world::chunk::ChunkMap::set_tick] at the start of every full tick
+    pub fn set_tick(&self, tick: u64) {
+        self.tick.store(tick, Ordering::Relaxed);
+    }
+    pub fn record(&self, event: WorldEvent) -> Result<(), Error> {
+        let event = RecordedEvent {
+            tick: self.tick.load(Ordering::Relaxed),
+            timestamp: now_millis(),
+            event,
+        };
+        let mut writer = self.writer.lock();
+        serde_json::to_writer(&mut *writer, &event)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+    pub fn record_block_change<W: World>(
+        &self,
+        position: BlockPosition,
+        old: Option<&PlacedBlock<W>>,
+        new: &PlacedBlock<W>,
+        actor: Option<Uuid>,
+    ) -> Result<(), Error> {
+        let new = if new.is_air() {
+            None
+        } else {
+            Some(RecordedBlock::from(new))
+        };
+        self.record(WorldEvent::BlockChange {
+            position,
+            old: old.map(RecordedBlock::from),
+            new,
+            actor,
+        })
+    }
+    pub fn flush(&self) -> Result<(), Error> {
+        self.writer.lock().flush()?;
+        Ok(())
+    }
+}
+impl Drop for EventRecorder {
+    fn drop(&mut self) {
+        if let Err(error) = self.writer.get_mut().flush() {
+            warn!("Failed to flush the event log {:?}: {}", self.path, error);
+        }
+    }
+}
+
+/// A log read back for replay
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    pub events: Vec<RecordedEvent>,
+}
+impl EventLog {
+    /// Reads the log. A partially written last line is skipped
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let reader = BufReader::new(File::open(path.as_ref())?);
+        let mut events = Vec::new();
+        let mut lines = reader.lines().peekable();
+        while let Some(line) = lines.next() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(event) => events.push(event),
+                Err(error) if lines.peek().is_none() => {
+                    warn!("Skipping incomplete event at the end of the log: {}", error);
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(Self { events })
+    }
+    /// Events with a timestamp within the range. Inclusive
+    pub fn between(&self, from: u64, to: u64) -> impl Iterator<Item = &RecordedEvent> {
+        self.events
+            .iter()
+            .filter(move |event| event.timestamp >= from && event.timestamp <= to)
+    }
+    /// The recorded state of every changed block at the timestamp. Feed this to a replay viewer or apply it to a world
+    pub fn block_states_at(
+        &self,
+        timestamp: u64,
+    ) -> AHashMap<BlockPosition, Option<RecordedBlock>> {
+        let mut blocks = AHashMap::new();
+        for event in self
+            .events
+            .iter()
+            .take_while(|event| event.timestamp <= timestamp)
+        {
+            if let WorldEvent::BlockChange { position, new, .. } = &event.event {
+                blocks.insert(*position, new.clone());
+            }
+        }
+        blocks
+    }
+    /// The blocks to place to undo every change made after the timestamp
+    ///
+    /// Each position goes back to the state before its first change after the timestamp
+    pub fn undo_since(&self, timestamp: u64) -> AHashMap<BlockPosition, Option<RecordedBlock>> {
+        let mut blocks = AHashMap::new();
+        for event in self
+            .events
+            .iter()
+            .filter(|event| event.timestamp > timestamp)
+        {
+            if let WorldEvent::BlockChange { position, old, .. } = &event.event {
+                blocks.entry(*position).or_insert_with(|| old.clone());
+            }
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_api::world::BlockPosition;
+    use uuid::Uuid;
+
+    use crate::world::recorder::{EventLog, EventRecorder, RecordedBlock, WorldEvent};
+
+    fn block(key: &str) -> Option<RecordedBlock> {
+        Some(RecordedBlock {
+            key: key.to_string(),
+            state: 1,
+        })
+    }
+
+    #[test]
+    pub fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("axolotl_recorder_{}.jsonl", Uuid::new_v4()));
+        let recorder = EventRecorder::open(&path).unwrap();
+        let position = BlockPosition::new(1, 64, -3);
+        for (tick, (old, new)) in [(None, block("stone")), (block("stone"), block("dirt"))]
+            .into_iter()
+            .enumerate()
+        {
+            recorder.set_tick(tick as u64 + 1);
+            recorder
+                .record(WorldEvent::BlockChange {
+                    position,
+                    old,
+                    new,
+                    actor: None,
+                })
+                .unwrap();
+        }
+        drop(recorder);
+
+        let log = EventLog::read(&path).unwrap();
+        assert_eq!(log.events.len(), 2);
+        assert_eq!(log.events[1].tick, 2);
+        assert_eq!(log.block_states_at(u64::MAX)[&position], block("dirt"));
+        assert_eq!(log.undo_since(0)[&position], None);
+        std::fs::remove_file(path).ok();
+    }
+    #[test]
+    pub fn test_other_events() {
+        let path = std::env::temp_dir().join(format!("axolotl_recorder_{}.jsonl", Uuid::new_v4()));
+        let recorder = EventRecorder::open(&path).unwrap();
+        let events = [
+            WorldEvent::EntitySpawn {
+                uuid: Uuid::new_v4(),
+                entity_type: "minecraft:pig".to_string(),
+                position: [0.5, 64.0, -3.5],
+            },
+            WorldEvent::Chat {
+                sender: None,
+                message: "Hello".to_string(),
+            },
+        ];
+        for event in events.iter() {
+            recorder.record(event.clone()).unwrap();
+        }
+        drop(recorder);
+
+        let log = EventLog::read(&path).unwrap();
+        let read: Vec<_> = log.events.into_iter().map(|event| event.event).collect();
+        assert_eq!(read, events);
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,421 @@
+//! Renders saved regions to top-down PNG tiles. One tile per region file.
+//!
+//! Reads the region files directly so it can run against a world another server has open
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ahash::{AHashMap, AHashSet};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_world::chunk::{BlockStates, RawChunk};
+use axolotl_world::region::file::RegionFile;
+use axolotl_world::validate::parse_region_file_name;
+
+use crate::Error;
+
+/// The width of a tile in pixels. One pixel per block
+pub const TILE_SIZE: usize = 512;
+/// Read from the data dump folder if present. Maps block keys to `#rrggbb`
+pub const BLOCK_COLORS_FILE: &str = "block_colors.json";
+/// Remembers when each tile was rendered. Stored in the output folder
+pub const RENDER_STATE_FILE: &str = "render_state.json";
+
+pub type Rgb = [u8; 3];
+
+/// The color of every block on the map
+#[derive(Debug, Clone)]
+pub struct BlockColors {
+    colors: AHashMap<String, Rgb>,
+    /// Used for blocks without a color
+    pub fallback: Rgb,
+}
+impl BlockColors {
+    /// The vanilla map colors of common blocks. Other blocks are matched by name
+    pub fn vanilla() -> Self {
+        let colors = [
+            ("minecraft:grass_block", [127, 178, 56]),
+            ("minecraft:dirt", [151, 109, 77]),
+            ("minecraft:coarse_dirt", [151, 109, 77]),
+            ("minecraft:podzol", [129, 86, 49]),
+            ("minecraft:mycelium", [127, 63, 178]),
+            ("minecraft:stone", [112, 112, 112]),
+            ("minecraft:deepslate", [100, 100, 100]),
+            ("minecraft:bedrock", [112, 112, 112]),
+            ("minecraft:gravel", [112, 112, 112]),
+            ("minecraft:sand", [247, 233, 163]),
+            ("minecraft:sandstone", [247, 233, 163]),
+            ("minecraft:red_sand", [216, 127, 51]),
+            ("minecraft:clay", [164, 168, 184]),
+            ("minecraft:water", [64, 64, 255]),
+            ("minecraft:lava", [255, 0, 0]),
+            ("minecraft:ice", [160, 160, 255]),
+            ("minecraft:packed_ice", [160, 160, 255]),
+            ("minecraft:snow", [255, 255, 255]),
+            ("minecraft:snow_block", [255, 255, 255]),
+            ("minecraft:netherrack", [112, 2, 0]),
+            ("minecraft:end_stone", [247, 233, 163]),
+            ("minecraft:obsidian", [25, 25, 25]),
+        ];
+        Self {
+            colors: colors
+                .into_iter()
+                .map(|(key, color)| (key.to_string(), color))
+                .collect(),
+            fallback: [112, 112, 112],
+        }
+    }
+    /// [BlockColors::vanilla] with the colors in [BLOCK_COLORS_FILE] on top
+    pub fn load(data_dump: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut colors = Self::vanilla();
+        let path = data_dump.as_ref().join(BLOCK_COLORS_FILE);
+        if path.exists() {
+            let values: HashMap<String, String> = serde_json::from_reader(File::open(&path)?)?;
+            for (key, value) in values {
+                match parse_hex_color(&value) {
+                    Some(color) => colors.insert(key, color),
+                    None => warn!("Invalid color {} for {}", value, key),
+                }
+            }
+        }
+        Ok(colors)
+    }
+    pub fn insert(&mut self, key: impl Into<String>, color: Rgb) {
+        self.colors.insert(key.into(), color);
+    }
+    /// None if the block is not drawn. Such as air
+    pub fn get(&self, key: &str) -> Option<Rgb> {
+        if is_transparent(key) {
+            return None;
+        }
+        if let Some(color) = self.colors.get(key) {
+            return Some(*color);
+        }
+        let name = key.rsplit(':').next().unwrap_or(key);
+        let color = if name.contains("leaves") || name.ends_with("grass") || name == "vine" {
+            [0, 124, 0]
+        } else if name.ends_with("_log") || name.ends_with("_planks") || name.ends_with("_wood") {
+            [143, 119, 72]
+        } else if name.contains("water") || name == "kelp" || name == "seagrass" {
+            [64, 64, 255]
+        } else {
+            self.fallback
+        };
+        Some(color)
+    }
+}
+impl Default for BlockColors {
+    fn default() -> Self {
+        Self::vanilla()
+    }
+}
+fn is_transparent(key: &str) -> bool {
+    matches!(
+        key,
+        "minecraft:air" | "minecraft:cave_air" | "minecraft:void_air" | "minecraft:barrier"
+    )
+}
+fn parse_hex_color(value: &str) -> Option<Rgb> {
+    let value = value.strip_prefix('#').unwrap_or(value);
+    if value.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(value, 16).ok()?;
+    Some([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+}
+
+/// The highest drawn block of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnTop {
+    pub height: i32,
+    pub color: Rgb,
+}
+
+/// The palette index of a block inside a saved section. Entries never span two longs
+fn palette_index(block_states: &BlockStates, index: usize) -> usize {
+    let Some(data) = &block_states.data else {
+        return 0;
+    };
+    let bits =
+        (usize::BITS - (block_states.palette.len().max(1) - 1).leading_zeros()).max(4) as usize;
+    let per_long = 64 / bits;
+    let value = data.get(index / per_long).copied().unwrap_or_default();
+    ((value >> ((index % per_long) * bits)) & ((1 << bits) - 1)) as usize
+}
+
+/// The highest drawn block of every column. Indexed by `z * 16 + x`
+pub fn column_tops(chunk: &RawChunk, colors: &BlockColors) -> [Option<ColumnTop>; 256] {
+    let mut tops = [None; 256];
+    let mut sections: Vec<_> = chunk
+        .sections
+        .iter()
+        .filter_map(|section| Some((section.y_pos, section.block_states.as_ref()?)))
+        .collect();
+    sections.sort_unstable_by_key(|(y, _)| std::cmp::Reverse(*y));
+    let mut remaining = 256;
+    for (section_y, block_states) in sections {
+        let palette: Vec<Option<Rgb>> = block_states
+            .palette
+            .iter()
+            .map(|item| colors.get(&item.name.to_string()))
+            .collect();
+        if palette.iter().all(Option::is_none) {
+            continue;
+        }
+        for y in (0..16).rev() {
+            for column in 0..256 {
+                if tops[column].is_some() {
+                    continue;
+                }
+                let index = palette_index(block_states, y * 256 + column);
+                if let Some(Some(color)) = palette.get(index) {
+                    tops[column] = Some(ColumnTop {
+                        height: section_y as i32 * 16 + y as i32,
+                        color: *color,
+                    });
+                    remaining -= 1;
+                }
+            }
+            if remaining == 0 {
+                return tops;
+            }
+        }
+    }
+    tops
+}
+
+/// The RGBA pixels of a region
+#[derive(Debug, Clone)]
+pub struct Tile {
+    pub pixels: Vec<u8>,
+}
+impl Tile {
+    pub fn new() -> Self {
+        Self {
+            pixels: vec![0; TILE_SIZE * TILE_SIZE * 4],
+        }
+    }
+    /// Draws a chunk. `north` is the bottom row of heights of the chunk to the north. Used for shading
+    pub fn draw_chunk(
+        &mut self,
+        local: (usize, usize),
+        tops: &[Option<ColumnTop>; 256],
+        mut north: [Option<i32>; 16],
+    ) {
+        for z in 0..16 {
+            for x in 0..16 {
+                let Some(top) = tops[z * 16 + x] else {
+                    north[x] = None;
+                    continue;
+                };
+                let color = shade(top.color, top.height, north[x]);
+                north[x] = Some(top.height);
+                let pixel_x = local.0 * 16 + x;
+                let pixel_z = local.1 * 16 + z;
+                let offset = (pixel_z * TILE_SIZE + pixel_x) * 4;
+                self.pixels[offset..offset + 4]
+                    .copy_from_slice(&[color[0], color[1], color[2], 255]);
+            }
+        }
+    }
+    pub fn write_png(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, TILE_SIZE as u32, TILE_SIZE as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+impl Default for Tile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+/// Vanilla map shading. Brighter if higher than the block to the north, darker if lower
+pub fn shade(color: Rgb, height: i32, north: Option<i32>) -> Rgb {
+    let brightness = match north {
+        Some(north) if height > north => 255,
+        Some(north) if height < north => 180,
+        _ => 220,
+    };
+    color.map(|value| (value as u32 * brightness / 255) as u8)
+}
+
+/// Renders the regions of a world into an output folder. Only changed regions are rendered again
+#[derive(Debug)]
+pub struct MapRenderer {
+    pub region_folder: PathBuf,
+    pub output_folder: PathBuf,
+    pub colors: BlockColors,
+    /// When each tile was last rendered
+    rendered: AHashMap<String, SystemTime>,
+    /// Regions marked by [MapRenderer::mark_dirty]
+    dirty: AHashSet<(i32, i32)>,
+}
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RenderState {
+    rendered: HashMap<String, SystemTime>,
+}
+impl MapRenderer {
+    pub fn new(
+        world_folder: impl AsRef<Path>,
+        output_folder: impl Into<PathBuf>,
+        colors: BlockColors,
+    ) -> Result<Self, Error> {
+        let output_folder = output_folder.into();
+        std::fs::create_dir_all(&output_folder)?;
+        let state_file = output_folder.join(RENDER_STATE_FILE);
+        let state: RenderState = if state_file.exists() {
+            serde_json::from_reader(File::open(state_file)?)?
+        } else {
+            RenderState::default()
+        };
+        Ok(Self {
+            region_folder: world_folder.as_ref().join("region"),
+            output_folder,
+            colors,
+            rendered: state.rendered.into_iter().collect(),
+            dirty: AHashSet::new(),
+        })
+    }
+    pub fn tile_path(&self, region: (i32, i32)) -> PathBuf {
+        self.output_folder
+            .join(format!("r.{}.{}.png", region.0, region.1))
+    }
+    fn tile_name(region: (i32, i32)) -> String {
+        format!("{}.{}", region.0, region.1)
+    }
+    /// Forces the region containing the chunk to render on the next [MapRenderer::render_dirty]
+    pub fn mark_dirty(&mut self, chunk: ChunkPos) {
+        self.dirty
+            .insert((chunk.x().div_euclid(32), chunk.z().div_euclid(32)));
+    }
+    /// Renders a single region. Missing chunks are transparent
+    pub fn render_region(&self, region: (i32, i32)) -> Result<Tile, Error> {
+        let path = self
+            .region_folder
+            .join(format!("r.{}.{}.mca", region.0, region.1));
+        let mut tile = Tile::new();
+        if !path.exists() {
+            return Ok(tile);
+        }
+        let mut file = RegionFile::open_read_only(path)?;
+        for local_x in 0..32 {
+            // The bottom row of heights of the chunk to the north
+            let mut north = [None; 16];
+            for local_z in 0..32 {
+                let index = local_z * 32 + local_x;
+                let location = file.region_header.locations[index];
+                let chunk = match file.read_chunk::<RawChunk>(&location) {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        warn!("Skipping unreadable chunk {} in {:?}: {}", index, region, e);
+                        None
+                    }
+                };
+                let Some((_, chunk)) = chunk else {
+                    north = [None; 16];
+                    continue;
+                };
+                let tops = column_tops(&chunk, &self.colors);
+                tile.draw_chunk((local_x, local_z), &tops, north);
+                north = std::array::from_fn(|x| tops[15 * 16 + x].map(|top| top.height));
+            }
+        }
+        Ok(tile)
+    }
+    /// Regions changed since they were rendered, regions without a tile and regions marked dirty
+    pub fn dirty_regions(&self) -> Result<Vec<(i32, i32)>, Error> {
+        let mut regions: AHashSet<(i32, i32)> = self.dirty.clone();
+        if self.region_folder.exists() {
+            for entry in std::fs::read_dir(&self.region_folder)? {
+                let entry = entry?;
+                let Some(region) = parse_region_file_name(&entry.path()) else {
+                    continue;
+                };
+                let modified = entry.metadata()?.modified()?;
+                let up_to_date = self
+                    .rendered
+                    .get(&Self::tile_name(region))
+                    .map(|rendered| *rendered >= modified)
+                    .unwrap_or(false);
+                if !up_to_date || !self.tile_path(region).exists() {
+                    regions.insert(region);
+                }
+            }
+        }
+        Ok(regions.into_iter().collect())
+    }
+    /// Renders every dirty region and saves the state. Returns the rendered regions
+    pub fn render_dirty(&mut self) -> Result<Vec<(i32, i32)>, Error> {
+        let regions = self.dirty_regions()?;
+        for region in regions.iter().copied() {
+            let started = SystemTime::now();
+            self.render_region(region)?
+                .write_png(self.tile_path(region))?;
+            self.rendered.insert(Self::tile_name(region), started);
+            self.dirty.remove(&region);
+            debug!("Rendered region {:?}", region);
+        }
+        self.save_state()?;
+        Ok(regions)
+    }
+    pub fn save_state(&self) -> Result<(), Error> {
+        let state = RenderState {
+            rendered: self
+                .rendered
+                .iter()
+                .map(|(key, value)| (key.clone(), *value))
+                .collect(),
+        };
+        serde_json::to_writer(
+            BufWriter::new(File::create(self.output_folder.join(RENDER_STATE_FILE))?),
+            &state,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use axolotl_world::chunk::{BlockStates, ChunkSection, RawChunk};
+
+    use crate::world::render::{column_tops, shade, BlockColors};
+
+    #[test]
+    pub fn test_column_tops() {
+        let mut data = vec![0u64; 256];
+        // Index 0 is air and 1 is stone. y = 3 of the section at x = 0, z = 0
+        data[3 * 256 / 16] = 1;
+        let chunk = RawChunk {
+            sections: vec![ChunkSection {
+                y_pos: -1,
+                block_states: Some(BlockStates {
+                    data: Some(data),
+                    palette: vec![
+                        "minecraft:air".parse().unwrap(),
+                        "minecraft:stone".parse().unwrap(),
+                    ],
+                }),
+                biomes: None,
+            }],
+            ..Default::default()
+        };
+        let colors = BlockColors::vanilla();
+        let tops = column_tops(&chunk, &colors);
+        assert_eq!(tops[0].unwrap().height, -13);
+        assert_eq!(
+            tops[0].unwrap().color,
+            colors.get("minecraft:stone").unwrap()
+        );
+        assert!(tops[1].is_none());
+        assert_eq!(shade([255, 255, 255], 5, Some(4)), [255, 255, 255]);
+        assert_eq!(shade([255, 255, 255], 5, Some(6)), [180, 180, 180]);
+    }
+}
@@ -0,0 +1,217 @@
+//! Work deferred to a later tick.
+//!
+//! Tasks run from [TickScheduler::run_tick]. The world calls it during full ticks after the incoming updates are handled
+//! and before block changes are flushed. Frozen ticks do not run tasks.
+//!
+//! Tasks are transient. They are never saved and are dropped with the world
+use std::fmt::{Debug, Formatter};
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+pub type OnceTask<T> = Box<dyn FnOnce(&T) + Send>;
+pub type RepeatingTask<T> = Box<dyn FnMut(&T) + Send>;
+
+/// Cancels a scheduled task. Dropping the handle does not cancel the task
+#[derive(Debug, Clone)]
+pub struct TaskHandle {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+impl TaskHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    /// The task will not run again. A task that is already running finishes
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+enum TaskKind<T: ?Sized> {
+    Once(OnceTask<T>),
+    Repeating { period: u64, task: RepeatingTask<T> },
+}
+
+struct ScheduledTask<T: ?Sized> {
+    /// The tick the task runs on
+    due: u64,
+    cancelled: Arc<AtomicBool>,
+    kind: TaskKind<T>,
+}
+
+/// Runs tasks after a number of ticks. `T` is what the tasks get access to when they run
+pub struct TickScheduler<T: ?Sized> {
+    tick: AtomicU64,
+    next_id: AtomicU64,
+    tasks: Mutex<Vec<ScheduledTask<T>>>,
+}
+impl<T: ?Sized> Default for TickScheduler<T> {
+    fn default() -> Self {
+        Self {
+            tick: AtomicU64::new(0),
+            next_id: AtomicU64::new(0),
+            tasks: Mutex::default(),
+        }
+    }
+}
+impl<T: ?Sized> Debug for TickScheduler<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TickScheduler")
+            .field("tick", &self.current_tick())
+            .field("tasks", &self.len())
+            .finish()
+    }
+}
+impl<T: ?Sized> TickScheduler<T> {
+    /// The number of ticks run
+    pub fn current_tick(&self) -> u64 {
+        self.tick.load(Ordering::Relaxed)
+    }
+    /// Runs the task once after `delay_ticks`. A delay of 0 runs it on the next tick
+    pub fn schedule(&self, delay_ticks: u32, task: impl FnOnce(&T) + Send + 'static) -> TaskHandle {
+        self.schedule_boxed(delay_ticks, Box::new(task))
+    }
+
+    pub fn schedule_boxed(&self, delay_ticks: u32, task: OnceTask<T>) -> TaskHandle {
+        self.insert(delay_ticks, TaskKind::Once(task))
+    }
+    /// Runs the task after `delay_ticks` and then every `period_ticks` until it is cancelled
+    pub fn schedule_repeating(
+        &self,
+        delay_ticks: u32,
+        period_ticks: u32,
+        task: impl FnMut(&T) + Send + 'static,
+    ) -> TaskHandle {
+        self.insert(
+            delay_ticks,
+            TaskKind::Repeating {
+                period: period_ticks.max(1) as u64,
+                task: Box::new(task),
+            },
+        )
+    }
+    fn insert(&self, delay_ticks: u32, kind: TaskKind<T>) -> TaskHandle {
+        let handle = TaskHandle {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        // Tasks scheduled during a tick never run in the same tick
+        let due = self.current_tick() + delay_ticks.max(1) as u64;
+        self.tasks.lock().push(ScheduledTask {
+            due,
+            cancelled: handle.cancelled.clone(),
+            kind,
+        });
+        handle
+    }
+    /// Advances one tick and runs the tasks that are due. Returns the number of tasks run
+    ///
+    /// The lock is not held while the tasks run so they can schedule more tasks
+    pub fn run_tick(&self, context: &T) -> usize {
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed) + 1;
+        let due = {
+            let mut tasks = self.tasks.lock();
+            tasks.retain(|task| !task.cancelled.load(Ordering::Relaxed));
+            let (due, waiting) = mem::take(&mut *tasks)
+                .into_iter()
+                .partition::<Vec<_>, _>(|task| task.due <= tick);
+            *tasks = waiting;
+            due
+        };
+        let mut ran = 0;
+        let mut repeat = Vec::new();
+        for task in due {
+            if task.cancelled.load(Ordering::Relaxed) {
+                continue;
+            }
+            ran += 1;
+            match task.kind {
+                TaskKind::Once(run) => run(context),
+                TaskKind::Repeating {
+                    period,
+                    task: mut run,
+                } => {
+                    run(context);
+                    repeat.push(ScheduledTask {
+                        due: tick + period,
+                        cancelled: task.cancelled,
+                        kind: TaskKind::Repeating { period, task: run },
+                    });
+                }
+            }
+        }
+        self.tasks.lock().extend(repeat);
+        ran
+    }
+    /// Drops every task. Used when the world is unloaded
+    pub fn clear(&self) {
+        self.tasks.lock().clear();
+    }
+    /// The number of tasks waiting. Includes cancelled tasks until the next tick
+    pub fn len(&self) -> usize {
+        self.tasks.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use crate::world::scheduler::TickScheduler;
+
+    #[test]
+    pub fn test_scheduler() {
+        let scheduler = TickScheduler::<AtomicU32>::default();
+        let counter = AtomicU32::new(0);
+        scheduler.schedule(2, |counter: &AtomicU32| {
+            counter.fetch_add(100, Ordering::Relaxed);
+        });
+        let repeating = scheduler.schedule_repeating(1, 2, |counter: &AtomicU32| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let cancelled = scheduler.schedule(1, |counter: &AtomicU32| {
+            counter.fetch_add(1000, Ordering::Relaxed);
+        });
+        cancelled.cancel();
+
+        assert_eq!(scheduler.run_tick(&counter), 1);
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+        assert_eq!(scheduler.run_tick(&counter), 1);
+        assert_eq!(counter.load(Ordering::Relaxed), 101);
+        assert_eq!(scheduler.run_tick(&counter), 1);
+        assert_eq!(counter.load(Ordering::Relaxed), 102);
+
+        repeating.cancel();
+        scheduler.run_tick(&counter);
+        scheduler.run_tick(&counter);
+        assert_eq!(counter.load(Ordering::Relaxed), 102);
+        assert!(scheduler.is_empty());
+    }
+    #[test]
+    pub fn test_schedule_from_task() {
+        let scheduler = Arc::new(TickScheduler::<AtomicU32>::default());
+        let inner = scheduler.clone();
+        scheduler.schedule(0, move |_: &AtomicU32| {
+            inner.schedule(0, |counter: &AtomicU32| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+        });
+        let counter = AtomicU32::new(0);
+        scheduler.run_tick(&counter);
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        scheduler.run_tick(&counter);
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+    }
+}
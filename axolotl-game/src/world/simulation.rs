@@ -0,0 +1,165 @@
+use axolotl_noise::minecraft::random::xoroshiro::rand_xoshiro::Xoroshiro128PlusPlus;
+use rand::SeedableRng;
+
+use axolotl_api::world_gen::seed::WorldSeed;
+
+use crate::world::chunk::pregen::PregenThrottle;
+
+/// How the world runs its systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationMode {
+    /// Systems may run on multiple threads and the random is seeded from the OS
+    #[default]
+    Parallel,
+    /// Everything runs on one thread in a fixed order and the random of each tick is derived from the seed.
+    ///
+    /// The same seed and inputs always give the same world. Used for golden tests and reproducing bug reports
+    Deterministic { seed: WorldSeed },
+}
+
+/// Counts the ticks of a world and hands out the random used by each tick.
+///
+/// Held by [crate::world::chunk::ChunkMap::simulation]. [crate::world::updates::WorldChannels::tick] runs every tick through it
+#[derive(Debug, Clone, Default)]
+pub struct Simulation {
+    pub mode: SimulationMode,
+    tick: u64,
+}
+impl Simulation {
+    pub fn new(mode: SimulationMode) -> Self {
+        Self { mode, tick: 0 }
+    }
+    pub fn deterministic(seed: WorldSeed) -> Self {
+        Self::new(SimulationMode::Deterministic { seed })
+    }
+    pub fn is_deterministic(&self) -> bool {
+        matches!(self.mode, SimulationMode::Deterministic { .. })
+    }
+    /// The number of ticks run so far
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+    /// The number of threads a system may use. Always 1 in deterministic mode
+    pub fn threads(&self, requested: usize) -> usize {
+        if self.is_deterministic() {
+            1
+        } else {
+            requested.max(1)
+        }
+    }
+    /// Pre-generation is limited to one thread in deterministic mode so chunks generate in spiral order
+    pub fn pregen_throttle(&self, throttle: PregenThrottle) -> PregenThrottle {
+        PregenThrottle {
+            threads: self.threads(throttle.threads),
+            ..throttle
+        }
+    }
+    /// The random for the current tick. See [WorldSeed::fork]
+    pub fn tick_random(&self) -> Xoroshiro128PlusPlus {
+        match self.mode {
+            SimulationMode::Parallel => Xoroshiro128PlusPlus::from_entropy(),
+            SimulationMode::Deterministic { seed } => {
+                Xoroshiro128PlusPlus::from_seed(seed.fork(self.tick).xoroshiro_seed())
+            }
+        }
+    }
+    /// Runs a single tick
+    pub fn tick(&mut self, system: impl FnOnce(u64, &mut Xoroshiro128PlusPlus)) {
+        let mut random = self.tick_random();
+        system(self.tick, &mut random);
+        self.tick += 1;
+    }
+    /// Runs `ticks` ticks one after another
+    pub fn tick_n(&mut self, ticks: u64, mut system: impl FnMut(u64, &mut Xoroshiro128PlusPlus)) {
+        for _ in 0..ticks {
+            self.tick(&mut system);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use rand::RngCore;
+
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_api::world_gen::seed::WorldSeed;
+
+    use crate::channel::UpdateReceiver;
+    use crate::world::chunk::dump::ChunkDump;
+    use crate::world::chunk::pregen::PregenThrottle;
+    use crate::world::chunk::ChunkMap;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::simulation::Simulation;
+    use crate::world::test_world::{test_block, test_game, test_noise_generator};
+    use crate::world::tick_rate::TickManager;
+    use crate::world::updates::{flume_channels, ServerUpdateIn};
+
+    fn run(seed: i64) -> Vec<u64> {
+        let mut simulation = Simulation::deterministic(WorldSeed::new(seed));
+        let mut values = Vec::new();
+        simulation.tick_n(10, |_, random| values.push(random.next_u64()));
+        values
+    }
+
+    #[test]
+    pub fn test_deterministic() {
+        let first = run(42);
+        assert_eq!(first, run(42));
+        assert_ne!(first, run(43));
+        assert_ne!(first[0], first[1]);
+        // Seed 43 must not replay seed 42 one tick later
+        assert_ne!(first[1..], run(43)[..9]);
+    }
+
+    /// Generates a world, breaks the support of a torch and ticks. Returns the chunks and everything sent
+    fn run_world(seed: i64) -> (Vec<ChunkDump>, Vec<String>) {
+        let game = Arc::new(test_game(&["stone", "water", "torch"]));
+        let seed = WorldSeed::new(seed);
+        let generator = AxolotlGenerator::Noise(test_noise_generator(game.clone(), seed));
+        let map = ChunkMap::in_memory(generator, game.clone())
+            .with_simulation(Simulation::deterministic(seed));
+        let center = ChunkPos::new(0, 0);
+        map.pregen(center, 1, PregenThrottle::default(), |_| {})
+            .unwrap();
+        map.load_spawn_chunks(center, 1, |_| {}).unwrap();
+
+        let (mut channels, (incoming, mut outgoing)) = flume_channels();
+        let ticks = TickManager::default();
+        let support = BlockPosition::new(3, 300, 3);
+        let torch = BlockPosition::new(3, 301, 3);
+        let set_block = |position, key| ServerUpdateIn::SetBlock {
+            position,
+            block: test_block(&game, key),
+            actor: None,
+        };
+        incoming.send(set_block(support, "stone")).unwrap();
+        incoming.send(set_block(torch, "torch")).unwrap();
+        channels.tick_n(5, &map, &ticks, &game).unwrap();
+        incoming.send(set_block(support, "air")).unwrap();
+        channels.tick_n(5, &map, &ticks, &game).unwrap();
+        assert_eq!(map.simulation.lock().current_tick(), 10);
+        assert!(map.get_block(torch).is_none_or(|block| block.is_air()));
+
+        let chunks = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |z| ChunkPos::new(x, z)))
+            .map(|pos| map.dump_chunk(pos).unwrap())
+            .collect();
+        let sent = outgoing
+            .drain()
+            .iter()
+            .map(|update| format!("{:?}", update))
+            .collect();
+        (chunks, sent)
+    }
+
+    #[test]
+    pub fn test_golden_world() {
+        let (chunks, sent) = run_world(42);
+        assert_eq!((chunks.clone(), sent.clone()), run_world(42));
+        assert!(!sent.is_empty());
+        assert_ne!(chunks, run_world(43).0);
+    }
+}
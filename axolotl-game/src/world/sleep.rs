@@ -0,0 +1,317 @@
+//! Beds and skipping the night
+use std::fmt::Debug;
+
+use ahash::AHashMap;
+use parking_lot::Mutex;
+use thiserror::Error;
+use uuid::Uuid;
+
+use axolotl_api::world::{BlockFace, BlockPosition, World};
+use axolotl_world::entity::player::PlayerData;
+use axolotl_world::level::LevelDat;
+
+use crate::world::chunk::ChunkMap;
+use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::level::rules;
+use crate::Error;
+
+pub const TICKS_PER_DAY: i64 = 24000;
+/// Players must sleep this long before the night can be skipped
+pub const FULLY_ASLEEP_TICKS: u32 = 100;
+/// The game rule with the percentage of players that must sleep
+pub const PLAYERS_SLEEPING_PERCENTAGE: &str = "playersSleepingPercentage";
+/// The first and last tick of the day a bed can be used in clear weather
+pub const SLEEP_START: i64 = 12542;
+pub const SLEEP_END: i64 = 23459;
+/// During a thunderstorm beds work earlier and later
+pub const THUNDER_SLEEP_START: i64 = 12010;
+pub const THUNDER_SLEEP_END: i64 = 23991;
+/// Beds only work in the overworld. The respawn point is always set in it
+pub const BED_DIMENSION: &str = "minecraft:overworld";
+
+/// Why a player could not sleep. The messages match vanilla
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum SleepError {
+    #[error("You can sleep only at night or during thunderstorms")]
+    NotPossibleNow,
+    #[error("You may not rest now; the bed is too far away")]
+    TooFarAway,
+    #[error("This bed is obstructed")]
+    Obstructed,
+    #[error("You may not rest now; there are monsters nearby")]
+    NotSafe,
+}
+
+/// If beds can be used at the time of day
+pub fn can_sleep_at(day_time: i64, thundering: bool) -> bool {
+    let time = day_time.rem_euclid(TICKS_PER_DAY);
+    if thundering {
+        (THUNDER_SLEEP_START..=THUNDER_SLEEP_END).contains(&time)
+    } else {
+        (SLEEP_START..=SLEEP_END).contains(&time)
+    }
+}
+/// The time of the next morning
+pub fn next_morning(day_time: i64) -> i64 {
+    day_time + TICKS_PER_DAY - day_time.rem_euclid(TICKS_PER_DAY)
+}
+/// The number of sleeping players needed to skip the night. At least one
+pub fn required_sleepers(active_players: usize, percentage: u32) -> usize {
+    ((active_players as u64 * percentage as u64).div_ceil(100) as usize).max(1)
+}
+
+/// If the feet of the player are within three blocks horizontally and two vertically of the bottom center of the bed
+pub fn bed_in_reach(position: &[f64], bed: BlockPosition) -> bool {
+    let [x, y, z] = position[..] else {
+        return false;
+    };
+    (x - (bed.x as f64 + 0.5)).abs() <= 3.0
+        && (y - bed.y as f64).abs() <= 2.0
+        && (z - (bed.z as f64 + 0.5)).abs() <= 3.0
+}
+/// The `playersSleepingPercentage` game rule. Above 100 the night is never skipped
+pub fn sleeping_percentage(level: &LevelDat) -> u32 {
+    rules::int_rule(level, PLAYERS_SLEEPING_PERCENTAGE)
+        .unwrap_or(100)
+        .clamp(0, u32::MAX as i64) as u32
+}
+
+/// Makes the bed the respawn point of the player
+pub fn set_respawn_point(data: &mut PlayerData, bed: BlockPosition, dimension: impl Into<String>) {
+    data.spawn_x = Some(bed.x as i32);
+    data.spawn_y = Some(bed.y as i32);
+    data.spawn_z = Some(bed.z as i32);
+    data.spawn_dimension = Some(dimension.into());
+}
+
+/// The checks done before a player gets into bed
+#[derive(Debug, Clone, Copy)]
+pub struct SleepAttempt {
+    pub day_time: i64,
+    pub thundering: bool,
+    /// Within three blocks horizontally and two vertically
+    pub in_reach: bool,
+    pub obstructed: bool,
+    pub monsters_nearby: bool,
+}
+impl SleepAttempt {
+    pub fn check(&self) -> Result<(), SleepError> {
+        if !can_sleep_at(self.day_time, self.thundering) {
+            return Err(SleepError::NotPossibleNow);
+        }
+        if !self.in_reach {
+            return Err(SleepError::TooFarAway);
+        }
+        if self.obstructed {
+            return Err(SleepError::Obstructed);
+        }
+        if self.monsters_nearby {
+            return Err(SleepError::NotSafe);
+        }
+        Ok(())
+    }
+}
+
+/// Sent to the players of the world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SleepUpdate {
+    /// Shown on the action bar. Sent when a player gets in or out of bed
+    SleepingPlayers {
+        sleeping: usize,
+        required: usize,
+    },
+    WokeUp {
+        player: Uuid,
+        bed: BlockPosition,
+    },
+    NightSkipped {
+        day_time: i64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sleeper {
+    pub bed: BlockPosition,
+    pub ticks: u32,
+}
+
+/// The sleeping players of a world
+#[derive(Debug, Default)]
+pub struct SleepManager {
+    sleeping: Mutex<AHashMap<Uuid, Sleeper>>,
+}
+impl SleepManager {
+    /// Puts the player in bed. Call [set_respawn_point] if it succeeds.
+    ///
+    /// Returns the update to send to the players
+    pub fn start_sleeping(
+        &self,
+        player: Uuid,
+        bed: BlockPosition,
+        attempt: &SleepAttempt,
+        active_players: usize,
+        percentage: u32,
+    ) -> Result<SleepUpdate, SleepError> {
+        attempt.check()?;
+        let mut sleeping = self.sleeping.lock();
+        sleeping.insert(player, Sleeper { bed, ticks: 0 });
+        Ok(SleepUpdate::SleepingPlayers {
+            sleeping: sleeping.len(),
+            required: required_sleepers(active_players, percentage),
+        })
+    }
+    /// The player left the bed or the server
+    pub fn wake_up(&self, player: &Uuid) -> Option<SleepUpdate> {
+        let sleeper = self.sleeping.lock().remove(player)?;
+        Some(SleepUpdate::WokeUp {
+            player: *player,
+            bed: sleeper.bed,
+        })
+    }
+    pub fn is_sleeping(&self, player: &Uuid) -> bool {
+        self.sleeping.lock().contains_key(player)
+    }
+    pub fn len(&self) -> usize {
+        self.sleeping.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sleeping.lock().is_empty()
+    }
+    /// Called every tick. Skips the night once enough players are fully asleep.
+    ///
+    /// `day_time` is moved to the next morning and every player is woken up.
+    /// `active_players` does not count spectators
+    pub fn tick(
+        &self,
+        day_time: &mut i64,
+        active_players: usize,
+        percentage: u32,
+    ) -> Vec<SleepUpdate> {
+        let mut sleeping = self.sleeping.lock();
+        if sleeping.is_empty() {
+            return vec![];
+        }
+        let mut asleep = 0;
+        for sleeper in sleeping.values_mut() {
+            sleeper.ticks = sleeper.ticks.saturating_add(1);
+            if sleeper.ticks >= FULLY_ASLEEP_TICKS {
+                asleep += 1;
+            }
+        }
+        if asleep < required_sleepers(active_players, percentage) {
+            return vec![];
+        }
+        *day_time = next_morning(*day_time);
+        let mut updates = vec![SleepUpdate::NightSkipped {
+            day_time: *day_time,
+        }];
+        updates.extend(
+            sleeping
+                .drain()
+                .map(|(player, sleeper)| SleepUpdate::WokeUp {
+                    player,
+                    bed: sleeper.bed,
+                }),
+        );
+        updates
+    }
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    /// Puts the player in the bed with the time and weather of [ChunkMap::level]. The bed becomes their respawn point.
+    ///
+    /// Spectators in the [ChunkMap::tracker] do not count towards the players needed
+    ///
+    /// None if the player is not in the world
+    pub fn enter_bed(
+        &self,
+        player: Uuid,
+        bed: BlockPosition,
+    ) -> Option<Result<SleepUpdate, SleepError>> {
+        let position = self.players.get(&player)?.data.pos;
+        let level = self.level.read();
+        let attempt = SleepAttempt {
+            day_time: level.day_time,
+            thundering: level.thundering,
+            in_reach: bed_in_reach(&position, bed),
+            obstructed: self
+                .get_block(bed.relative(BlockFace::Up))
+                .is_some_and(|block| !block.is_air()),
+            // The world has no mobs yet
+            monsters_nearby: false,
+        };
+        let percentage = sleeping_percentage(&level);
+        drop(level);
+        let update = self.sleep.start_sleeping(
+            player,
+            bed,
+            &attempt,
+            self.tracker.active_count(),
+            percentage,
+        );
+        if update.is_ok() {
+            self.players.update(&player, |sleeper| {
+                set_respawn_point(&mut sleeper.data, bed, BED_DIMENSION)
+            });
+        }
+        Some(update)
+    }
+    /// Ticks the [ChunkMap::sleep] with the day time and `playersSleepingPercentage` rule of [ChunkMap::level].
+    ///
+    /// A skipped night moves the day time of the level to the next morning
+    pub fn tick_sleep(&self) -> Vec<SleepUpdate> {
+        let mut level = self.level.write();
+        let percentage = sleeping_percentage(&level);
+        self.sleep
+            .tick(&mut level.day_time, self.tracker.active_count(), percentage)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use axolotl_api::world::BlockPosition;
+
+    use crate::world::sleep::{
+        required_sleepers, SleepAttempt, SleepError, SleepManager, SleepUpdate, FULLY_ASLEEP_TICKS,
+    };
+
+    #[test]
+    pub fn test_skip_night() {
+        assert_eq!(required_sleepers(3, 50), 2);
+        assert_eq!(required_sleepers(0, 100), 1);
+
+        let manager = SleepManager::default();
+        let mut attempt = SleepAttempt {
+            day_time: 1000,
+            thundering: false,
+            in_reach: true,
+            obstructed: false,
+            monsters_nearby: false,
+        };
+        let player = Uuid::new_v4();
+        let bed = BlockPosition::new(0, 64, 0);
+        assert_eq!(
+            manager.start_sleeping(player, bed, &attempt, 2, 50),
+            Err(SleepError::NotPossibleNow)
+        );
+        attempt.day_time = 13000;
+        manager
+            .start_sleeping(player, bed, &attempt, 2, 50)
+            .unwrap();
+
+        let mut day_time = 13000;
+        for _ in 1..FULLY_ASLEEP_TICKS {
+            assert!(manager.tick(&mut day_time, 2, 50).is_empty());
+        }
+        let updates = manager.tick(&mut day_time, 2, 50);
+        assert_eq!(day_time, 24000);
+        assert_eq!(updates[0], SleepUpdate::NightSkipped { day_time: 24000 });
+        assert!(manager.is_empty());
+    }
+}
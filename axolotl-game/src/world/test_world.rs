@@ -0,0 +1,186 @@
+//! A [World] for unit tests that never touches the world itself.
+//!
+//! [test_game] builds a game without the Minecraft data so tests can place real blocks
+use std::sync::Arc;
+
+use axolotl_api::events::{EventHandler, NoError};
+use axolotl_api::game::Registry;
+use axolotl_api::item::block::{Block, BlockPlaceEvent};
+use axolotl_api::item::metadata::BlockMetadata;
+use axolotl_api::item::ItemType;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+use axolotl_api::world_gen::noise::{ChunkGenerator, NameSpaceKeyOrType};
+use axolotl_api::world_gen::seed::WorldSeed;
+use axolotl_api::{NamespacedId, NumericId, OwnedNameSpaceKey};
+use axolotl_items::blocks::generic_block::VanillaState;
+use axolotl_items::blocks::InnerMinecraftBlock;
+
+use crate::registry::SimpleRegistry;
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::AxolotlChunk;
+use crate::world::generator::{AxolotlDensityLoader, AxolotlGenerator};
+use crate::world::level::biome_source::BiomeSourceSettings;
+use crate::world::level::noise::NoiseGenerator;
+use crate::{AxolotlDataRegistries, AxolotlGame, AxolotlRegistries};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct TestWorld {}
+impl World for TestWorld {
+    type Chunk = AxolotlChunk<Self>;
+    type WorldBlock = PlacedBlock<Self>;
+    type NoiseGenerator = AxolotlGenerator<Self>;
+
+    fn get_name(&self) -> &str {
+        "test"
+    }
+
+    fn tick(&mut self) {}
+
+    /// Generates empty chunks
+    fn generator(&self) -> &Self::NoiseGenerator {
+        &AxolotlGenerator::Debug()
+    }
+
+    fn set_block(
+        &self,
+        _location: BlockPosition,
+        _block: Self::WorldBlock,
+        _require_loaded: bool,
+    ) -> bool {
+        false
+    }
+
+    fn set_blocks(
+        &self,
+        _chunk_pos: ChunkPos,
+        _blocks: impl Iterator<Item = (BlockPosition, Self::WorldBlock)>,
+    ) {
+    }
+}
+
+/// A full block. The default state id is the block id
+#[derive(Debug)]
+pub struct TestBlock {
+    pub id: usize,
+    pub key: String,
+    pub metadata: BlockMetadata,
+}
+impl ItemType for TestBlock {}
+impl NamespacedId for TestBlock {
+    fn namespace(&self) -> &str {
+        "minecraft"
+    }
+
+    fn key(&self) -> &str {
+        &self.key
+    }
+}
+impl NumericId for TestBlock {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+impl EventHandler<BlockPlaceEvent<'_, AxolotlGame<TestWorld>>> for TestBlock {
+    fn handle(&self, _event: BlockPlaceEvent<AxolotlGame<TestWorld>>) -> Result<bool, NoError> {
+        Ok(true)
+    }
+}
+impl Block<AxolotlGame<TestWorld>> for TestBlock {
+    type State = VanillaState;
+
+    fn create_default_state(&self) -> Self::State {
+        VanillaState {
+            state_id: self.id,
+            default: true,
+            ..Default::default()
+        }
+    }
+
+    fn is_air(&self) -> bool {
+        false
+    }
+
+    fn metadata(&self) -> &BlockMetadata {
+        &self.metadata
+    }
+}
+
+/// A game with `minecraft:air` as id 0 followed by a [TestBlock] for each key. Nothing else is registered.
+///
+/// Every block is opaque except `torch`
+pub fn test_game(blocks: &[&str]) -> AxolotlGame<TestWorld> {
+    let mut block_registry = SimpleRegistry::new();
+    block_registry.register(
+        "minecraft:air",
+        Arc::new(InnerMinecraftBlock::Air {
+            id: 0,
+            key: "air".to_string(),
+        }),
+    );
+    for (index, key) in blocks.iter().enumerate() {
+        block_registry.register(
+            format!("minecraft:{}", key),
+            Arc::new(InnerMinecraftBlock::DynamicBlock(Box::new(TestBlock {
+                id: index + 1,
+                key: key.to_string(),
+                metadata: BlockMetadata {
+                    opacity: if *key == "torch" { 0 } else { 15 },
+                    ..Default::default()
+                },
+            }))),
+        );
+    }
+    AxolotlGame {
+        data_registries: AxolotlDataRegistries {
+            noises: SimpleRegistry::new(),
+            noise_settings: SimpleRegistry::new(),
+            dimensions: SimpleRegistry::new(),
+        },
+        registries: AxolotlRegistries {
+            biomes: SimpleRegistry::new(),
+            blocks: block_registry,
+            items: SimpleRegistry::new(),
+            chat_types: SimpleRegistry::new(),
+        },
+        density_loader: AxolotlDensityLoader(SimpleRegistry::new()),
+        minecraft_version: serde_json::from_str(crate::MINECRAFT_VERSION).unwrap(),
+        axolotl_version: serde_json::from_str(crate::AXOLOTL_VERSION).unwrap(),
+    }
+}
+/// The default state of a block registered by [test_game]
+pub fn test_block(game: &AxolotlGame<TestWorld>, key: &str) -> PlacedBlock<TestWorld> {
+    PlacedBlock::from(
+        game.registries
+            .blocks
+            .get_by_namespace(format!("minecraft:{}", key))
+            .unwrap_or_else(|| panic!("{} is not a test block", key))
+            .clone(),
+    )
+}
+/// An overworld shaped noise generator in plains. `game` needs stone and water
+pub fn test_noise_generator(
+    game: Arc<AxolotlGame<TestWorld>>,
+    seed: WorldSeed,
+) -> NoiseGenerator<TestWorld> {
+    let settings = serde_json::from_value(serde_json::json!({
+        "sea_level": 63,
+        "disable_mob_generation": false,
+        "ore_veins_enabled": false,
+        "default_block": { "Name": "minecraft:stone" },
+        "default_fluid": { "Name": "minecraft:water" },
+        "legacy_random_source": false,
+        "noise": { "height": 384, "min_y": -64, "size_horizontal": 1, "size_vertical": 2 },
+        "spawn_target": []
+    }))
+    .unwrap();
+    let plains = OwnedNameSpaceKey::new("minecraft".to_string(), "plains".to_string());
+    NoiseGenerator::new(
+        game,
+        (
+            BiomeSourceSettings::Fixed { biome: plains },
+            NameSpaceKeyOrType::Type(settings),
+        ),
+        seed,
+    )
+}
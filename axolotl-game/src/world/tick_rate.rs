@@ -0,0 +1,186 @@
+//! Controls how fast the world ticks. Backs the `/tick` command
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+/// Vanilla runs 20 ticks a second
+pub const DEFAULT_TICK_RATE: f32 = 20.0;
+pub const MIN_TICK_RATE: f32 = 1.0;
+pub const MAX_TICK_RATE: f32 = 10000.0;
+
+/// A change requested through [crate::world::updates::ServerUpdateIn::Tick]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TickCommand {
+    Freeze,
+    Unfreeze,
+    /// Runs the ticks while frozen
+    Step(u32),
+    /// Runs the ticks as fast as possible
+    Sprint(u64),
+    StopSprint,
+    /// Ticks per second
+    Rate(f32),
+}
+
+/// The state reported back after a [TickCommand]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TickStatus {
+    pub tick_rate: f32,
+    pub frozen: bool,
+    pub steps_remaining: u32,
+    pub sprint_remaining: u64,
+}
+impl Default for TickStatus {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+            frozen: false,
+            steps_remaining: 0,
+            sprint_remaining: 0,
+        }
+    }
+}
+
+/// What the world should run this tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickKind {
+    /// Everything ticks
+    Full,
+    /// Only players tick. The world is frozen
+    PlayersOnly,
+}
+
+#[derive(Debug, Default)]
+pub struct TickManager {
+    status: Mutex<TickStatus>,
+}
+impl TickManager {
+    pub fn status(&self) -> TickStatus {
+        *self.status.lock()
+    }
+    /// Stops the world from ticking. Players still tick
+    pub fn freeze(&self) {
+        let mut status = self.status.lock();
+        status.frozen = true;
+        status.steps_remaining = 0;
+    }
+    pub fn unfreeze(&self) {
+        let mut status = self.status.lock();
+        status.frozen = false;
+        status.steps_remaining = 0;
+    }
+    /// Runs `ticks` full ticks. Returns false if the world is not frozen
+    pub fn step(&self, ticks: u32) -> bool {
+        let mut status = self.status.lock();
+        if !status.frozen {
+            return false;
+        }
+        status.steps_remaining = status.steps_remaining.saturating_add(ticks);
+        true
+    }
+    /// Runs `ticks` full ticks without waiting between them. Replaces any running sprint
+    pub fn sprint(&self, ticks: u64) {
+        self.status.lock().sprint_remaining = ticks;
+    }
+    /// Returns false if no sprint was running
+    pub fn stop_sprint(&self) -> bool {
+        let mut status = self.status.lock();
+        let sprinting = status.sprint_remaining > 0;
+        status.sprint_remaining = 0;
+        sprinting
+    }
+    /// Clamped between [MIN_TICK_RATE] and [MAX_TICK_RATE]. Returns false and keeps the current rate if the rate is NaN or infinite
+    pub fn set_tick_rate(&self, rate: f32) -> bool {
+        if !rate.is_finite() {
+            return false;
+        }
+        self.status.lock().tick_rate = rate.clamp(MIN_TICK_RATE, MAX_TICK_RATE);
+        true
+    }
+    pub fn is_frozen(&self) -> bool {
+        self.status.lock().frozen
+    }
+    pub fn is_sprinting(&self) -> bool {
+        self.status.lock().sprint_remaining > 0
+    }
+    /// Applies the command and returns the new status
+    pub fn apply(&self, command: TickCommand) -> TickStatus {
+        match command {
+            TickCommand::Freeze => self.freeze(),
+            TickCommand::Unfreeze => self.unfreeze(),
+            TickCommand::Step(ticks) => {
+                self.step(ticks);
+            }
+            TickCommand::Sprint(ticks) => self.sprint(ticks),
+            TickCommand::StopSprint => {
+                self.stop_sprint();
+            }
+            TickCommand::Rate(rate) => {
+                self.set_tick_rate(rate);
+            }
+        }
+        self.status()
+    }
+    /// How long to wait before the next tick. Zero while sprinting
+    pub fn tick_duration(&self) -> Duration {
+        let status = self.status.lock();
+        if status.sprint_remaining > 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(1.0 / status.tick_rate as f64)
+    }
+    /// Called at the start of every tick. Uses up the steps and the sprint
+    pub fn begin_tick(&self) -> TickKind {
+        let mut status = self.status.lock();
+        status.sprint_remaining = status.sprint_remaining.saturating_sub(1);
+        if !status.frozen {
+            return TickKind::Full;
+        }
+        if status.steps_remaining > 0 {
+            status.steps_remaining -= 1;
+            TickKind::Full
+        } else {
+            TickKind::PlayersOnly
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use crate::world::tick_rate::{TickCommand, TickKind, TickManager, DEFAULT_TICK_RATE};
+
+    #[test]
+    pub fn test_freeze_and_step() {
+        let ticks = TickManager::default();
+        assert!(!ticks.step(1));
+        assert_eq!(ticks.begin_tick(), TickKind::Full);
+        ticks.freeze();
+        assert_eq!(ticks.begin_tick(), TickKind::PlayersOnly);
+        assert!(ticks.step(2));
+        assert_eq!(ticks.begin_tick(), TickKind::Full);
+        assert_eq!(ticks.begin_tick(), TickKind::Full);
+        assert_eq!(ticks.begin_tick(), TickKind::PlayersOnly);
+
+        ticks.sprint(1);
+        assert_eq!(ticks.tick_duration(), Duration::ZERO);
+        ticks.begin_tick();
+        assert_eq!(ticks.tick_duration(), Duration::from_millis(50));
+    }
+
+    #[test]
+    pub fn test_non_finite_rate() {
+        let ticks = TickManager::default();
+        for rate in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(!ticks.set_tick_rate(rate));
+            assert_eq!(
+                ticks.apply(TickCommand::Rate(rate)).tick_rate,
+                DEFAULT_TICK_RATE
+            );
+            assert_eq!(ticks.tick_duration(), Duration::from_millis(50));
+        }
+        assert!(ticks.set_tick_rate(0.0));
+        assert_eq!(ticks.tick_duration(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,965 @@
+//! The messages sent between a world and the server
+use std::fmt::Debug;
+
+use uuid::Uuid;
+
+use axolotl_api::world::protection::ProtectedAction;
+use axolotl_api::world::view::Sound;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::world_gen::chunk::ChunkPos;
+
+use crate::channel::{Disconnected, UpdateReceiver, UpdateSender};
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::ChunkMap;
+use crate::world::coalesce::CoalescingSender;
+use crate::world::entity::movement::InvalidMove;
+use crate::world::entity::properties::Location;
+use crate::world::events::EventAction;
+use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::players::PlayerStatus;
+use crate::world::recorder::WorldEvent;
+use crate::world::sleep::{SleepError, SleepUpdate};
+use crate::world::tick_rate::{TickCommand, TickKind, TickManager, TickStatus};
+use crate::world::ChunkUpdate;
+use crate::{AxolotlGame, Error};
+
+/// Sent to the world
+#[derive(Debug)]
+pub enum ServerUpdateIn<W: World> {
+    Chunk(ChunkUpdate<W>),
+    /// Checked against the protection of the world if an actor is set
+    SetBlock {
+        position: BlockPosition,
+        block: PlacedBlock<W>,
+        actor: Option<Uuid>,
+    },
+    OpenContainer {
+        player: Uuid,
+        position: BlockPosition,
+    },
+    /// A player used a block. See [ChunkMap::use_block]
+    UseBlock {
+        player: Uuid,
+        position: BlockPosition,
+    },
+    /// A chat message said in the world. Recorded by the [ChunkMap::recorder]
+    Chat {
+        sender: Option<Uuid>,
+        message: String,
+    },
+    /// A player entered the world. Their data is loaded into [ChunkMap::players] and they are tracked by [ChunkMap::tracker]
+    PlayerJoin {
+        player: Uuid,
+        /// A hash of the world name. See [crate::world::level::accessor::PlayerAccess::get_player]
+        source_world: u64,
+    },
+    /// The player is saved and removed from [ChunkMap::players]
+    PlayerLeave {
+        player: Uuid,
+    },
+    /// A move sent by the client. See [ChunkMap::move_player]
+    PlayerMove {
+        player: Uuid,
+        location: Location,
+    },
+    /// A player used the item in their hand. Food is eaten
+    UseItem {
+        player: Uuid,
+        item: String,
+    },
+    /// A player used a bed. See [ChunkMap::enter_bed]
+    EnterBed {
+        player: Uuid,
+        bed: BlockPosition,
+    },
+    /// A player got out of bed before the night was skipped
+    LeaveBed {
+        player: Uuid,
+    },
+    /// A player got Bad Omen, such as by killing a raid captain. A raid starts once they enter a village
+    BadOmen {
+        player: Uuid,
+        level: u8,
+    },
+    SaveAll,
+    /// Handled by the [TickManager]
+    Tick(TickCommand),
+}
+/// Sent by the world
+#[derive(Debug, Clone)]
+pub enum ServerUpdateOut<W: World> {
+    /// None is air
+    BlockChanged {
+        position: BlockPosition,
+        block: Option<PlacedBlock<W>>,
+    },
+    /// Many blocks in one section changed during the tick. Sent by the [UpdateCoalescer]
+    SectionChanged {
+        chunk: ChunkPos,
+        section_y: i16,
+        /// None is air
+        blocks: Vec<(BlockPosition, Option<PlacedBlock<W>>)>,
+    },
+    ContainerOpened {
+        player: Uuid,
+        position: BlockPosition,
+        block: PlacedBlock<W>,
+    },
+    /// The protection of the world stopped a player
+    Denied {
+        player: Uuid,
+        position: BlockPosition,
+        action: ProtectedAction,
+    },
+    ChunkLoaded(ChunkPos),
+    ChunkUnloaded(ChunkPos),
+    Saved,
+    TickChanged(TickStatus),
+    Sleep(SleepUpdate),
+    /// The player could not get into bed
+    SleepFailed {
+        player: Uuid,
+        error: SleepError,
+    },
+    Sound(Sound),
+    /// The health, food or air of a player changed
+    PlayerStatus {
+        player: Uuid,
+        status: PlayerStatus,
+    },
+    /// A player joined or moved. Only sent to the players that can see them
+    PlayerMoved {
+        player: Uuid,
+        location: Location,
+        viewers: Vec<Uuid>,
+    },
+    /// The move was invalid. The player is teleported back to the location
+    MoveRejected {
+        player: Uuid,
+        location: Location,
+        error: InvalidMove,
+    },
+    /// An event of [ChunkMap::events] needs the world. Such as a raid spawning a wave
+    Event {
+        id: u32,
+        action: EventAction,
+    },
+}
+
+/// Both ends a world uses. Generic so any [UpdateSender] and [UpdateReceiver] can be used
+#[derive(Debug)]
+pub struct WorldChannels<W: World, S, R>
+where
+    S: UpdateSender<ServerUpdateOut<W>>,
+    R: UpdateReceiver<ServerUpdateIn<W>>,
+{
+    pub outgoing: S,
+    pub incoming: R,
+    _world: std::marker::PhantomData<W>,
+}
+impl<W: World, S, R> WorldChannels<W, S, R>
+where
+    S: UpdateSender<ServerUpdateOut<W>>,
+    R: UpdateReceiver<ServerUpdateIn<W>>,
+{
+    pub fn new(outgoing: S, incoming: R) -> Self {
+        Self {
+            outgoing,
+            incoming,
+            _world: Default::default(),
+        }
+    }
+    /// Handles everything waiting on [WorldChannels::incoming]
+    ///
+    /// The block changes in [ChunkMap::changes] are sent at the end
+    pub fn handle_incoming<V>(
+        &mut self,
+        chunks: &ChunkMap<W, V>,
+        ticks: &TickManager,
+    ) -> Result<(), Disconnected>
+    where
+        V: LevelReader<W> + LevelWriter<W> + Debug,
+        Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+    {
+        self.receive(chunks, ticks)?;
+        chunks.changes.flush(&self.outgoing)?;
+        Ok(())
+    }
+    /// Runs one tick of the world. Called once per tick
+    ///
+    /// 1. The incoming updates are handled
+    /// 2. The tick runs through the [ChunkMap::simulation]. On a full tick the block updates and the tasks of the [ChunkMap::scheduler] run.
+    ///    Then the players and [ChunkMap::events] are ticked with the tick random, the night is skipped once enough players sleep and the world is autosaved when due
+    /// 3. The block changes in [ChunkMap::changes] are sent. Including the ones made by the tasks
+    pub fn tick<V>(
+        &mut self,
+        chunks: &ChunkMap<W, V>,
+        ticks: &TickManager,
+        game: &AxolotlGame<W>,
+    ) -> Result<TickKind, Disconnected>
+    where
+        V: LevelReader<W> + LevelWriter<W> + Debug,
+        Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+    {
+        let kind = ticks.begin_tick();
+        if kind == TickKind::Full {
+            // The tick the scheduler runs below. Changes made while handling the updates belong to it
+            chunks.set_tick(chunks.scheduler.current_tick() + 1);
+        }
+        self.receive(chunks, ticks)?;
+        let mut players = Vec::new();
+        let mut sleep = Vec::new();
+        let mut events = Vec::new();
+        chunks.simulation.lock().tick(|_, random| {
+            if kind == TickKind::Full {
+                chunks.run_block_updates(game);
+                chunks.scheduler.run_tick(chunks);
+                players = chunks.tick_players(random);
+                events = chunks.tick_events(random);
+                sleep = chunks.tick_sleep();
+                chunks.autosave();
+            }
+        });
+        for (player, status) in players {
+            self.outgoing
+                .send(ServerUpdateOut::PlayerStatus { player, status })?;
+        }
+        for update in sleep {
+            self.outgoing.send(ServerUpdateOut::Sleep(update))?;
+        }
+        for (id, action) in events {
+            self.outgoing.send(ServerUpdateOut::Event { id, action })?;
+        }
+        chunks.changes.flush(&self.outgoing)?;
+        Ok(kind)
+    }
+    /// Runs `count` ticks one after another without waiting for the tick rate.
+    ///
+    /// With a deterministic [ChunkMap::simulation] the same world and updates always give the same result
+    pub fn tick_n<V>(
+        &mut self,
+        count: u64,
+        chunks: &ChunkMap<W, V>,
+        ticks: &TickManager,
+        game: &AxolotlGame<W>,
+    ) -> Result<(), Disconnected>
+    where
+        V: LevelReader<W> + LevelWriter<W> + Debug,
+        Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+    {
+        for _ in 0..count {
+            self.tick(chunks, ticks, game)?;
+        }
+        Ok(())
+    }
+    fn receive<V>(
+        &mut self,
+        chunks: &ChunkMap<W, V>,
+        ticks: &TickManager,
+    ) -> Result<(), Disconnected>
+    where
+        V: LevelReader<W> + LevelWriter<W> + Debug,
+        Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+    {
+        for update in self.incoming.drain() {
+            if let ServerUpdateIn::Tick(command) = update {
+                self.outgoing
+                    .send(ServerUpdateOut::TickChanged(ticks.apply(command)))?;
+                continue;
+            }
+            let sender = CoalescingSender {
+                coalescer: &chunks.changes,
+                outgoing: &self.outgoing,
+            };
+            chunks.handle_server_update(update, &sender)?;
+        }
+        Ok(())
+    }
+}
+/// Channels backed by [flume]
+pub type FlumeWorldChannels<W> =
+    WorldChannels<W, flume::Sender<ServerUpdateOut<W>>, flume::Receiver<ServerUpdateIn<W>>>;
+
+/// Creates unbounded flume channels. Returns the world side and the server side
+pub fn flume_channels<W: World>() -> (
+    FlumeWorldChannels<W>,
+    (
+        flume::Sender<ServerUpdateIn<W>>,
+        flume::Receiver<ServerUpdateOut<W>>,
+    ),
+) {
+    let (in_sender, in_receiver) = flume::unbounded();
+    let (out_sender, out_receiver) = flume::unbounded();
+    (
+        WorldChannels::new(out_sender, in_receiver),
+        (in_sender, out_receiver),
+    )
+}
+
+impl<W: World, V: LevelReader<W> + LevelWriter<W> + Debug> ChunkMap<W, V>
+where
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+{
+    /// Errors are logged. Only a disconnected `outgoing` is returned
+    pub fn handle_server_update(
+        &self,
+        update: ServerUpdateIn<W>,
+        outgoing: &impl UpdateSender<ServerUpdateOut<W>>,
+    ) -> Result<(), Disconnected> {
+        match update {
+            ServerUpdateIn::Chunk(update) => {
+                let out = match &update {
+                    ChunkUpdate::Load { x, z, .. } => {
+                        ServerUpdateOut::ChunkLoaded(ChunkPos::new(*x, *z))
+                    }
+                    ChunkUpdate::Unload { x, z } => {
+                        ServerUpdateOut::ChunkUnloaded(ChunkPos::new(*x, *z))
+                    }
+                };
+                match self.handle_update(update) {
+                    Ok(()) => outgoing.send(out)?,
+                    Err(error) => log::warn!("Error handling chunk update: {:?}", error),
+                }
+            }
+            ServerUpdateIn::SetBlock {
+                position,
+                block,
+                actor: Some(player),
+            } => {
+                // The change is sent from ChunkMap::changes
+                let (action, allowed) = if block.is_air() {
+                    let allowed = self.can_perform(&player, &position, ProtectedAction::BreakBlock);
+                    if allowed {
+                        self.set_block(position, block, Some(player));
+                    }
+                    (ProtectedAction::BreakBlock, allowed)
+                } else {
+                    let placed = self.place_blocks(player, vec![(position, block)]);
+                    (ProtectedAction::PlaceBlock, placed.is_some())
+                };
+                if !allowed {
+                    outgoing.send(ServerUpdateOut::Denied {
+                        player,
+                        position,
+                        action,
+                    })?;
+                }
+            }
+            ServerUpdateIn::SetBlock {
+                position,
+                block,
+                actor: None,
+            } => {
+                // Sent from ChunkMap::changes
+                self.set_block(position, block, None);
+            }
+            ServerUpdateIn::OpenContainer { player, position } => {
+                if let Some(block) = self.open_container(player, position) {
+                    outgoing.send(ServerUpdateOut::ContainerOpened {
+                        player,
+                        position,
+                        block,
+                    })?;
+                } else if !self.can_perform(&player, &position, ProtectedAction::OpenContainer) {
+                    outgoing.send(ServerUpdateOut::Denied {
+                        player,
+                        position,
+                        action: ProtectedAction::OpenContainer,
+                    })?;
+                }
+            }
+            ServerUpdateIn::UseBlock { player, position } => {
+                // The change is sent from ChunkMap::changes
+                if self.use_block(player, position).is_none()
+                    && !self.can_perform(&player, &position, ProtectedAction::PlaceBlock)
+                {
+                    outgoing.send(ServerUpdateOut::Denied {
+                        player,
+                        position,
+                        action: ProtectedAction::PlaceBlock,
+                    })?;
+                }
+            }
+            ServerUpdateIn::Chat { sender, message } => {
+                self.record_event(WorldEvent::Chat { sender, message });
+            }
+            ServerUpdateIn::PlayerJoin {
+                player,
+                source_world,
+            } => match self.players.join(player, source_world) {
+                Ok(Some(status)) => {
+                    outgoing.send(ServerUpdateOut::PlayerStatus { player, status })?;
+                    match self.track_player(player) {
+                        Ok(Some(movement)) => outgoing.send(ServerUpdateOut::PlayerMoved {
+                            player,
+                            location: movement.location,
+                            viewers: self.tracker.viewers(&player),
+                        })?,
+                        Ok(None) => {}
+                        Err(error) => log::warn!("Error tracking player {}: {:?}", player, error),
+                    }
+                }
+                Ok(None) => log::warn!("Player {} is loaded by another world", player),
+                Err(error) => log::warn!("Error loading player {}: {:?}", player, error),
+            },
+            ServerUpdateIn::PlayerMove { player, location } => {
+                match self.move_player(player, location) {
+                    Some(Ok(())) => outgoing.send(ServerUpdateOut::PlayerMoved {
+                        player,
+                        location,
+                        viewers: self.tracker.viewers(&player),
+                    })?,
+                    Some(Err(error)) => {
+                        if let Some(movement) = self.tracker.get(&player) {
+                            outgoing.send(ServerUpdateOut::MoveRejected {
+                                player,
+                                location: movement.location,
+                                error,
+                            })?;
+                        }
+                    }
+                    None => log::warn!("Player {} moved outside of the world", player),
+                }
+            }
+            ServerUpdateIn::PlayerLeave { player } => {
+                if let Some(update) = self.sleep.wake_up(&player) {
+                    outgoing.send(ServerUpdateOut::Sleep(update))?;
+                }
+                if let Err(error) = self.untrack_player(player) {
+                    log::warn!("Error releasing the chunks of {}: {:?}", player, error);
+                }
+                if let Err(error) = self.players.leave(&player) {
+                    log::warn!("Error saving player {}: {:?}", player, error);
+                }
+            }
+            ServerUpdateIn::UseItem { player, item } => {
+                if let Some(status) = self.players.eat(&player, &item) {
+                    outgoing.send(ServerUpdateOut::PlayerStatus { player, status })?;
+                }
+            }
+            ServerUpdateIn::EnterBed { player, bed } => match self.enter_bed(player, bed) {
+                Some(Ok(update)) => outgoing.send(ServerUpdateOut::Sleep(update))?,
+                Some(Err(error)) => {
+                    outgoing.send(ServerUpdateOut::SleepFailed { player, error })?
+                }
+                None => log::warn!("Player {} used a bed outside of the world", player),
+            },
+            ServerUpdateIn::LeaveBed { player } => {
+                if let Some(update) = self.sleep.wake_up(&player) {
+                    outgoing.send(ServerUpdateOut::Sleep(update))?;
+                }
+            }
+            ServerUpdateIn::BadOmen { player, level } => {
+                self.players
+                    .update(&player, |player| player.add_bad_omen(level));
+            }
+            ServerUpdateIn::SaveAll => match self.save_all() {
+                Ok(()) => outgoing.send(ServerUpdateOut::Saved)?,
+                Err(error) => log::warn!("Error saving chunks: {:?}", error),
+            },
+            ServerUpdateIn::Tick(command) => {
+                log::warn!("Tick command {:?} sent without a tick manager", command);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use axolotl_api::world::protection::ProtectedAction;
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+    use axolotl_world::entity::player::PlayerData;
+    use axolotl_world::level::LevelDat;
+
+    use crate::channel::UpdateReceiver;
+    use crate::world::chunk::ChunkMap;
+    use crate::world::entity::movement::InvalidMove;
+    use crate::world::entity::properties::Location;
+    use crate::world::events::{EventAction, WorldEvents, EVENTS_FILE};
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::level::accessor::memory::MemoryPlayerAccess;
+    use crate::world::level::configs::WorldSettings;
+    use crate::world::protection::{ChunkClaims, CLAIMS_FILE};
+    use crate::world::recorder::{EventLog, EventRecorder, WorldEvent};
+    use crate::world::sleep::{SleepError, SleepUpdate, BED_DIMENSION, FULLY_ASLEEP_TICKS};
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+    use crate::world::tick_rate::TickManager;
+    use crate::world::updates::{flume_channels, ServerUpdateIn, ServerUpdateOut};
+
+    #[test]
+    pub fn test_task_changes_are_sent() {
+        let game = Arc::new(test_game(&["stone"]));
+        let stone = test_block(&game, "stone");
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone());
+        map.load_chunk_task(0, 0, None).unwrap();
+        let (mut channels, (_incoming, mut outgoing)) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+
+        let position = BlockPosition::new(1, 64, 1);
+        let placed = stone.clone();
+        map.scheduler.schedule(0, move |chunks| {
+            chunks.set_block(position, placed, None);
+        });
+        channels.tick(&map, &ticks, &game).unwrap();
+        let sent = outgoing.drain();
+        assert_eq!(sent.len(), 1);
+        match &sent[0] {
+            ServerUpdateOut::BlockChanged {
+                position: changed,
+                block,
+            } => {
+                assert_eq!(*changed, position);
+                assert_eq!(block.as_ref(), Some(&stone));
+            }
+            other => panic!("Expected a block change {:?}", other),
+        }
+        assert!(map.changes.is_empty());
+    }
+
+    #[test]
+    pub fn test_claimed_updates() {
+        let game = Arc::new(test_game(&["stone", "chest"]));
+        let folder = std::env::temp_dir().join(format!("axolotl_updates_{}", Uuid::new_v4()));
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let claims = ChunkClaims::open(&folder).unwrap();
+        claims.claim(ChunkPos::new(0, 0), owner);
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone()).with_claims(claims);
+        map.load_chunk_task(0, 0, None).unwrap();
+        let (mut channels, (incoming, mut outgoing)) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+
+        let chest = BlockPosition::new(1, 64, 1);
+        let set_block = |actor| ServerUpdateIn::SetBlock {
+            position: chest,
+            block: test_block(&game, "chest"),
+            actor: Some(actor),
+        };
+        incoming.send(set_block(other)).unwrap();
+        incoming.send(set_block(owner)).unwrap();
+        incoming
+            .send(ServerUpdateIn::OpenContainer {
+                player: other,
+                position: chest,
+            })
+            .unwrap();
+        incoming
+            .send(ServerUpdateIn::OpenContainer {
+                player: owner,
+                position: chest,
+            })
+            .unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        let sent = outgoing.drain();
+        assert_eq!(sent.len(), 4);
+        assert!(matches!(
+            sent[0],
+            ServerUpdateOut::Denied {
+                player,
+                action: ProtectedAction::PlaceBlock,
+                ..
+            } if player == other
+        ));
+        assert!(matches!(
+            sent[1],
+            ServerUpdateOut::Denied {
+                player,
+                action: ProtectedAction::OpenContainer,
+                ..
+            } if player == other
+        ));
+        assert!(matches!(
+            sent[2],
+            ServerUpdateOut::ContainerOpened { player, .. } if player == owner
+        ));
+        assert!(matches!(sent[3], ServerUpdateOut::BlockChanged { .. }));
+
+        incoming.send(ServerUpdateIn::SaveAll).unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        assert!(folder.join(CLAIMS_FILE).exists());
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn test_autosave() {
+        let game = Arc::new(test_game(&["stone"]));
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone()).with_settings(
+            WorldSettings {
+                autosave_interval: 2,
+                ..WorldSettings::default()
+            },
+        );
+        map.load_chunk_task(0, 0, None).unwrap();
+        map.load_chunk_task(1, 0, None).unwrap();
+        map.set_block(
+            BlockPosition::new(1, 64, 1),
+            test_block(&game, "stone"),
+            None,
+        );
+        let (mut channels, _server) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+
+        channels.tick(&map, &ticks, &game).unwrap();
+        assert!(map.accessor.is_empty());
+        channels.tick(&map, &ticks, &game).unwrap();
+        // Only the modified chunk is saved and it stays loaded
+        assert_eq!(map.accessor.len(), 1);
+        assert!(map.accessor.contains_chunk(&ChunkPos::new(0, 0)));
+        assert!(map.thread_safe_chunks.contains(&ChunkPos::new(0, 0)));
+        // Not modified since the last save
+        assert_eq!(map.save_modified().unwrap(), 0);
+    }
+    #[test]
+    pub fn test_block_updates() {
+        let game = Arc::new(test_game(&["stone", "torch"]));
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone());
+        map.load_chunk_task(0, 0, None).unwrap();
+        let (mut channels, _server) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+
+        let support = BlockPosition::new(1, 64, 1);
+        let torch = BlockPosition::new(1, 65, 1);
+        map.set_block(support, test_block(&game, "stone"), None);
+        map.set_block(torch, test_block(&game, "torch"), None);
+        channels.tick(&map, &ticks, &game).unwrap();
+        assert!(map.block_updates.is_empty());
+        assert!(map.get_block(torch).is_some_and(|block| !block.is_air()));
+
+        map.set_block(support, test_block(&game, "air"), None);
+        channels.tick(&map, &ticks, &game).unwrap();
+        assert!(map.get_block(torch).is_none_or(|block| block.is_air()));
+        // Stone is not a note block
+        assert_eq!(map.use_block(Uuid::new_v4(), support), None);
+    }
+    #[test]
+    pub fn test_player_updates() {
+        let game = Arc::new(test_game(&[]));
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game)
+            .with_player_access(MemoryPlayerAccess::default());
+        let (mut channels, (incoming, mut outgoing)) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+        let player = Uuid::new_v4();
+        incoming
+            .send(ServerUpdateIn::PlayerJoin {
+                player,
+                source_world: 0,
+            })
+            .unwrap();
+        // Not hungry
+        incoming
+            .send(ServerUpdateIn::UseItem {
+                player,
+                item: "minecraft:bread".to_string(),
+            })
+            .unwrap();
+        incoming
+            .send(ServerUpdateIn::UseItem {
+                player,
+                item: "minecraft:golden_apple".to_string(),
+            })
+            .unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        let sent = outgoing.drain();
+        assert_eq!(sent.len(), 3);
+        match &sent[0] {
+            ServerUpdateOut::PlayerStatus {
+                player: joined,
+                status,
+            } => {
+                assert_eq!(*joined, player);
+                assert_eq!(status.food_level, 20);
+            }
+            other => panic!("Expected a player status {:?}", other),
+        }
+        assert!(matches!(sent[1], ServerUpdateOut::PlayerMoved { .. }));
+        assert!(matches!(
+            sent[2],
+            ServerUpdateOut::PlayerStatus { status, .. } if status.saturation > 5.0
+        ));
+
+        incoming
+            .send(ServerUpdateIn::PlayerLeave { player })
+            .unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        assert!(map.players.is_empty());
+        assert!(map.tracker.get(&player).is_none());
+        assert!(map.tickets.is_empty());
+    }
+    #[test]
+    pub fn test_player_movement() {
+        let game = Arc::new(test_game(&["stone"]));
+        let access = MemoryPlayerAccess::default();
+        let walker = Uuid::new_v4();
+        let spectator = Uuid::new_v4();
+        access
+            .save_player(
+                walker,
+                &PlayerData {
+                    pos: vec![0.5, 64.0, 0.5],
+                    ..PlayerData::default()
+                },
+            )
+            .unwrap();
+        access
+            .save_player(
+                spectator,
+                &PlayerData {
+                    pos: vec![5.5, 64.0, 0.5],
+                    player_game_type: 3,
+                    ..PlayerData::default()
+                },
+            )
+            .unwrap();
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone())
+            .with_settings(WorldSettings {
+                simulation_distance: 1,
+                ..WorldSettings::default()
+            })
+            .with_player_access(access);
+        let (mut channels, (incoming, mut outgoing)) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+        for player in [walker, spectator] {
+            incoming
+                .send(ServerUpdateIn::PlayerJoin {
+                    player,
+                    source_world: 0,
+                })
+                .unwrap();
+        }
+        channels.handle_incoming(&map, &ticks).unwrap();
+        outgoing.drain();
+        // Spectators keep no chunks loaded
+        assert_eq!(map.tickets.len(), 9);
+        map.set_block(
+            BlockPosition::new(2, 64, 0),
+            test_block(&game, "stone"),
+            None,
+        );
+        channels.handle_incoming(&map, &ticks).unwrap();
+        outgoing.drain();
+
+        let moved = Location::new(1.5, 64.0, 0.5, 0.0, 0.0);
+        let moves = [
+            (walker, moved),
+            (walker, Location::new(2.5, 64.0, 0.5, 0.0, 0.0)),
+            (spectator, Location::new(2.5, 64.0, 0.5, 0.0, 0.0)),
+        ];
+        for (player, location) in moves {
+            incoming
+                .send(ServerUpdateIn::PlayerMove { player, location })
+                .unwrap();
+        }
+        channels.handle_incoming(&map, &ticks).unwrap();
+        let sent = outgoing.drain();
+        assert_eq!(sent.len(), 3);
+        // Only the spectator sees the walker
+        match &sent[0] {
+            ServerUpdateOut::PlayerMoved {
+                player, viewers, ..
+            } => {
+                assert_eq!(*player, walker);
+                assert_eq!(*viewers, vec![spectator]);
+            }
+            other => panic!("Expected a move {:?}", other),
+        }
+        match &sent[1] {
+            ServerUpdateOut::MoveRejected {
+                location, error, ..
+            } => {
+                assert_eq!(*location, moved);
+                assert_eq!(*error, InvalidMove::Collision);
+            }
+            other => panic!("Expected a rejected move {:?}", other),
+        }
+        // Spectators fly through blocks and are seen by no one
+        assert!(matches!(
+            &sent[2],
+            ServerUpdateOut::PlayerMoved { viewers, .. } if viewers.is_empty()
+        ));
+        // Saved with the player
+        assert_eq!(
+            map.players.get(&walker).unwrap().data.pos,
+            vec![1.5, 64.0, 0.5]
+        );
+
+        incoming
+            .send(ServerUpdateIn::PlayerLeave { player: walker })
+            .unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        assert!(map.tickets.is_empty());
+    }
+    #[test]
+    pub fn test_sleep_updates() {
+        let game = Arc::new(test_game(&[]));
+        let access = MemoryPlayerAccess::default();
+        let player = Uuid::new_v4();
+        access
+            .save_player(
+                player,
+                &PlayerData {
+                    pos: vec![0.5, 64.0, 0.5],
+                    ..PlayerData::default()
+                },
+            )
+            .unwrap();
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone())
+            .with_level(LevelDat {
+                day_time: 13000,
+                ..LevelDat::default()
+            })
+            .with_player_access(access);
+        map.load_chunk_task(0, 0, None).unwrap();
+        let (mut channels, (incoming, mut outgoing)) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+        incoming
+            .send(ServerUpdateIn::PlayerJoin {
+                player,
+                source_world: 0,
+            })
+            .unwrap();
+        incoming
+            .send(ServerUpdateIn::EnterBed {
+                player,
+                bed: BlockPosition::new(10, 64, 10),
+            })
+            .unwrap();
+        incoming
+            .send(ServerUpdateIn::EnterBed {
+                player,
+                bed: BlockPosition::new(1, 64, 0),
+            })
+            .unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        let sent = outgoing.drain();
+        assert_eq!(sent.len(), 4);
+        assert!(matches!(
+            sent[2],
+            ServerUpdateOut::SleepFailed {
+                error: SleepError::TooFarAway,
+                ..
+            }
+        ));
+        match &sent[3] {
+            ServerUpdateOut::Sleep(update) => assert_eq!(
+                *update,
+                SleepUpdate::SleepingPlayers {
+                    sleeping: 1,
+                    required: 1
+                }
+            ),
+            other => panic!("Expected a sleep update {:?}", other),
+        }
+        let data = map.players.get(&player).unwrap().data;
+        assert_eq!(
+            (data.spawn_x, data.spawn_y, data.spawn_z),
+            (Some(1), Some(64), Some(0))
+        );
+        assert_eq!(data.spawn_dimension.as_deref(), Some(BED_DIMENSION));
+
+        channels
+            .tick_n(FULLY_ASLEEP_TICKS as u64, &map, &ticks, &game)
+            .unwrap();
+        let sent = outgoing.drain();
+        assert!(sent.iter().any(|update| matches!(
+            update,
+            ServerUpdateOut::Sleep(SleepUpdate::NightSkipped { day_time: 24000 })
+        )));
+        assert_eq!(map.level.read().day_time, 24000);
+        assert!(map.sleep.is_empty());
+    }
+    #[test]
+    pub fn test_bad_omen_starts_raid() {
+        let game = Arc::new(test_game(&["bell"]));
+        let folder = std::env::temp_dir().join(format!("axolotl_raid_{}", Uuid::new_v4()));
+        let access = MemoryPlayerAccess::default();
+        let player = Uuid::new_v4();
+        access
+            .save_player(
+                player,
+                &PlayerData {
+                    pos: vec![10.5, 64.0, 10.5],
+                    ..PlayerData::default()
+                },
+            )
+            .unwrap();
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone())
+            .with_level(LevelDat {
+                difficulty: 2,
+                ..LevelDat::default()
+            })
+            .with_player_access(access)
+            .with_events(WorldEvents::open(&folder).unwrap());
+        map.load_chunk_task(0, 0, None).unwrap();
+        let bell = BlockPosition::new(1, 64, 1);
+        map.set_block(bell, test_block(&game, "bell"), None);
+        let (mut channels, (incoming, mut outgoing)) = flume_channels::<TestWorld>();
+        let ticks = TickManager::default();
+        incoming
+            .send(ServerUpdateIn::PlayerJoin {
+                player,
+                source_world: 0,
+            })
+            .unwrap();
+        incoming
+            .send(ServerUpdateIn::BadOmen { player, level: 1 })
+            .unwrap();
+        channels.tick(&map, &ticks, &game).unwrap();
+        let events = map.events.as_ref().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.find_near(bell, 0), Some(0));
+        assert_eq!(map.players.get(&player).unwrap().bad_omen, None);
+        let sent = outgoing.drain();
+        assert!(sent.iter().any(|update| matches!(
+            update,
+            ServerUpdateOut::Event {
+                id: 0,
+                action: EventAction::BarChanged(_)
+            }
+        )));
+
+        incoming.send(ServerUpdateIn::SaveAll).unwrap();
+        channels.handle_incoming(&map, &ticks).unwrap();
+        assert!(folder.join(EVENTS_FILE).exists());
+        let saved = WorldEvents::open(&folder).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(
+            saved.find_village(BlockPosition::new(0, 64, 0), 2),
+            Some(bell)
+        );
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn test_chat_is_recorded() {
+        let game = Arc::new(test_game(&[]));
+        let path = std::env::temp_dir().join(format!("axolotl_chat_{}.jsonl", Uuid::new_v4()));
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game)
+            .with_recorder(EventRecorder::open(&path).unwrap());
+        let (mut channels, (incoming, _outgoing)) = flume_channels::<TestWorld>();
+        let sender = Some(Uuid::new_v4());
+        incoming
+            .send(ServerUpdateIn::Chat {
+                sender,
+                message: "Hello".to_string(),
+            })
+            .unwrap();
+        channels
+            .handle_incoming(&map, &TickManager::default())
+            .unwrap();
+        drop(map);
+
+        let log = EventLog::read(&path).unwrap();
+        assert_eq!(
+            log.events[0].event,
+            WorldEvent::Chat {
+                sender,
+                message: "Hello".to_string()
+            }
+        );
+        std::fs::remove_file(path).ok();
+    }
+}
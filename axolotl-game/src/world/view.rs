@@ -0,0 +1,336 @@
+//! The [WorldView] given to plugins
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use log::warn;
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+use axolotl_api::events::EventHandler;
+use axolotl_api::world::protection::ProtectedAction;
+use axolotl_api::world::view::{
+    BlockChangeEvent, EntitySpawnEvent, Sound, ViewError, ViewTask, WorldView,
+};
+use axolotl_api::world::{BlockPosition, GenericLocation, World};
+
+use crate::channel::UpdateSender;
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::ChunkMap;
+use crate::world::entity::MinecraftEntity;
+use crate::world::level::accessor::{LevelReader, LevelWriter};
+use crate::world::recorder::WorldEvent;
+use crate::world::updates::ServerUpdateOut;
+use crate::Error;
+
+pub type BlockChangeHandler<W> =
+    Box<dyn EventHandler<BlockChangeEvent<PlacedBlock<W>>> + Send + Sync>;
+pub type EntitySpawnHandler = Box<dyn EventHandler<EntitySpawnEvent> + Send + Sync>;
+pub type PluginTask<W> = ViewTask<PlacedBlock<W>, MinecraftEntity>;
+
+/// The event handlers plugins registered on a world. Plugin tasks run on the [ChunkMap::scheduler]
+pub struct PluginHooks<W: World> {
+    block_change: RwLock<Vec<BlockChangeHandler<W>>>,
+    entity_spawn: RwLock<Vec<EntitySpawnHandler>>,
+}
+impl<W: World> Default for PluginHooks<W> {
+    fn default() -> Self {
+        Self {
+            block_change: RwLock::default(),
+            entity_spawn: RwLock::default(),
+        }
+    }
+}
+impl<W: World> Debug for PluginHooks<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginHooks")
+            .field("block_change", &self.block_change.read().len())
+            .field("entity_spawn", &self.entity_spawn.read().len())
+            .finish()
+    }
+}
+impl<W: World> PluginHooks<W> {
+    pub fn on_block_change(
+        &self,
+        handler: impl EventHandler<BlockChangeEvent<PlacedBlock<W>>> + Send + Sync + 'static,
+    ) {
+        self.block_change.write().push(Box::new(handler));
+    }
+    pub fn on_entity_spawn(
+        &self,
+        handler: impl EventHandler<EntitySpawnEvent> + Send + Sync + 'static,
+    ) {
+        self.entity_spawn.write().push(Box::new(handler));
+    }
+    /// False if any handler cancelled the change. Handler errors are logged and do not cancel
+    pub fn fire_block_change(&self, event: &BlockChangeEvent<PlacedBlock<W>>) -> bool {
+        self.block_change
+            .read()
+            .iter()
+            .all(|handler| match handler.handle(event.clone()) {
+                Ok(allowed) => allowed,
+                Err(error) => {
+                    warn!("Block change handler failed: {:?}", error);
+                    true
+                }
+            })
+    }
+    /// False if any handler cancelled the spawn
+    pub fn fire_entity_spawn(&self, event: &EntitySpawnEvent) -> bool {
+        self.entity_spawn
+            .read()
+            .iter()
+            .all(|handler| match handler.handle(event.clone()) {
+                Ok(allowed) => allowed,
+                Err(error) => {
+                    warn!("Entity spawn handler failed: {:?}", error);
+                    true
+                }
+            })
+    }
+}
+
+/// A world as seen by a plugin. The chunks are only reachable through the [WorldView] methods
+pub struct AxolotlWorldView<'world, W: World, V, S>
+where
+    V: LevelReader<W> + LevelWriter<W> + Debug,
+{
+    name: &'world str,
+    chunks: &'world ChunkMap<W, V>,
+    hooks: &'world Arc<PluginHooks<W>>,
+    outgoing: &'world S,
+}
+impl<'world, W: World, V, S> AxolotlWorldView<'world, W, V, S>
+where
+    V: LevelReader<W> + LevelWriter<W> + Debug,
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+    S: UpdateSender<ServerUpdateOut<W>>,
+    ChunkMap<W, V>: Sync,
+    PluginHooks<W>: Sync,
+{
+    pub fn new(
+        name: &'world str,
+        chunks: &'world ChunkMap<W, V>,
+        hooks: &'world Arc<PluginHooks<W>>,
+        outgoing: &'world S,
+    ) -> Self {
+        Self {
+            name,
+            chunks,
+            hooks,
+            outgoing,
+        }
+    }
+}
+
+impl<W: World + 'static, V, S> WorldView for AxolotlWorldView<'_, W, V, S>
+where
+    V: LevelReader<W> + LevelWriter<W> + Debug + 'static,
+    Error: From<<V as LevelWriter<W>>::Error> + From<<V as LevelReader<W>>::Error>,
+    S: UpdateSender<ServerUpdateOut<W>> + 'static,
+    ChunkMap<W, V>: Sync,
+    PluginHooks<W>: Sync,
+{
+    type Block = PlacedBlock<W>;
+    type Entity = MinecraftEntity;
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn get_block(&self, position: BlockPosition) -> Option<Self::Block> {
+        self.chunks.get_block(position)
+    }
+
+    fn set_block(
+        &self,
+        actor: Option<Uuid>,
+        position: BlockPosition,
+        block: Self::Block,
+    ) -> Result<Option<Self::Block>, ViewError> {
+        if !self.chunks.is_chunk_loaded(&position.chunk_pos()) {
+            return Err(ViewError::NotLoaded);
+        }
+        if let Some(actor) = actor {
+            let action = if block.is_air() {
+                ProtectedAction::BreakBlock
+            } else {
+                ProtectedAction::PlaceBlock
+            };
+            if !self.chunks.can_perform(&actor, &position, action) {
+                return Err(ViewError::Denied);
+            }
+        }
+        let event = BlockChangeEvent {
+            actor,
+            position,
+            old: self.chunks.get_block(position),
+            new: block,
+        };
+        if !self.hooks.fire_block_change(&event) {
+            return Err(ViewError::Cancelled);
+        }
+        // The change is sent from ChunkMap::changes
+        self.chunks
+            .set_block(position, event.new, actor)
+            .ok_or(ViewError::NotLoaded)
+    }
+
+    /// The spawn is recorded by the [ChunkMap::recorder]. Entities are not kept until they are simulated
+    fn spawn_entity(
+        &self,
+        actor: Option<Uuid>,
+        entity: Self::Entity,
+        location: GenericLocation,
+    ) -> Result<Uuid, ViewError> {
+        if !self
+            .chunks
+            .is_chunk_loaded(&BlockPosition::from(location).chunk_pos())
+        {
+            return Err(ViewError::NotLoaded);
+        }
+        let event = EntitySpawnEvent {
+            actor,
+            entity_type: entity.entity_type(),
+            location,
+        };
+        if !self.hooks.fire_entity_spawn(&event) {
+            return Err(ViewError::Cancelled);
+        }
+        let uuid = Uuid::new_v4();
+        self.chunks.record_event(WorldEvent::EntitySpawn {
+            uuid,
+            entity_type: event.entity_type.to_string(),
+            position: [event.location.x, event.location.y as f64, event.location.z],
+        });
+        Ok(uuid)
+    }
+
+    fn play_sound(&self, sound: Sound) -> Result<(), ViewError> {
+        if self.outgoing.send(ServerUpdateOut::Sound(sound)).is_err() {
+            warn!("Sound was not sent");
+        }
+        Ok(())
+    }
+
+    /// Runs on the [ChunkMap::scheduler] with a view of the same world
+    fn schedule_task(
+        &self,
+        delay_ticks: u32,
+        task: ViewTask<Self::Block, Self::Entity>,
+    ) -> Result<(), ViewError> {
+        let name = self.name.to_string();
+        let hooks = self.hooks.clone();
+        let outgoing = self.outgoing.clone();
+        self.chunks.scheduler.schedule(delay_ticks, move |chunks| {
+            task(&AxolotlWorldView::new(&name, chunks, &hooks, &outgoing));
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use axolotl_api::events::{EventHandler, NoError};
+    use axolotl_api::world::view::{BlockChangeEvent, ViewError, WorldView};
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::placed_block::PlacedBlock;
+    use crate::world::chunk::ChunkMap;
+    use crate::world::generator::AxolotlGenerator;
+    use crate::world::protection::ChunkClaims;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+    use crate::world::updates::ServerUpdateOut;
+    use crate::world::view::{AxolotlWorldView, PluginHooks, PluginTask};
+
+    /// Cancels changes at `cancel`
+    struct TestHandler {
+        fired: AtomicU32,
+        cancel: BlockPosition,
+    }
+    impl EventHandler<BlockChangeEvent<PlacedBlock<TestWorld>>> for TestHandler {
+        fn handle(&self, event: BlockChangeEvent<PlacedBlock<TestWorld>>) -> Result<bool, NoError> {
+            self.fired.fetch_add(1, Ordering::Relaxed);
+            Ok(event.position != self.cancel)
+        }
+    }
+
+    #[test]
+    pub fn test_set_block() {
+        let game = Arc::new(test_game(&["stone"]));
+        let stone = test_block(&game, "stone");
+        let owner = Uuid::new_v4();
+        let claims = ChunkClaims::open(
+            std::env::temp_dir().join(format!("axolotl_view_{}", Uuid::new_v4())),
+        )
+        .unwrap();
+        claims.claim(ChunkPos::new(0, 0), owner);
+        let map =
+            ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone()).with_protection(claims);
+        map.load_chunk_task(0, 0, None).unwrap();
+        let hooks = Arc::new(PluginHooks::default());
+        let handler = Arc::new(TestHandler {
+            fired: AtomicU32::new(0),
+            cancel: BlockPosition::new(5, 64, 5),
+        });
+        hooks.on_block_change(handler.clone());
+        let (outgoing, _receiver) = flume::unbounded::<ServerUpdateOut<TestWorld>>();
+        let view = AxolotlWorldView::new("test", &map, &hooks, &outgoing);
+
+        let position = BlockPosition::new(1, 64, 1);
+        assert_eq!(
+            view.set_block(Some(Uuid::new_v4()), position, stone.clone()),
+            Err(ViewError::Denied)
+        );
+        // Denied before the event fires
+        assert_eq!(handler.fired.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            view.set_block(Some(owner), BlockPosition::new(5, 64, 5), stone.clone()),
+            Err(ViewError::Cancelled)
+        );
+        assert_eq!(handler.fired.load(Ordering::Relaxed), 1);
+        assert_eq!(map.get_block(BlockPosition::new(5, 64, 5)), None);
+        assert_eq!(
+            view.set_block(Some(owner), position, stone.clone()),
+            Ok(None)
+        );
+        assert_eq!(handler.fired.load(Ordering::Relaxed), 2);
+        assert_eq!(map.get_block(position), Some(stone));
+        assert_eq!(
+            view.set_block(
+                None,
+                BlockPosition::new(100, 64, 0),
+                test_block(&game, "stone")
+            ),
+            Err(ViewError::NotLoaded)
+        );
+    }
+    #[test]
+    pub fn test_task() {
+        let game = Arc::new(test_game(&["stone"]));
+        let stone = test_block(&game, "stone");
+        let map = ChunkMap::in_memory(AxolotlGenerator::Debug(), game.clone());
+        map.load_chunk_task(0, 0, None).unwrap();
+        let hooks = Arc::new(PluginHooks::default());
+        let (outgoing, _receiver) = flume::unbounded::<ServerUpdateOut<TestWorld>>();
+        let view = AxolotlWorldView::new("test", &map, &hooks, &outgoing);
+
+        let position = BlockPosition::new(1, 64, 1);
+        let placed = stone.clone();
+        let task: PluginTask<TestWorld> = Box::new(move |view| {
+            assert_eq!(view.name(), "test");
+            view.set_block(None, position, placed).unwrap();
+        });
+        view.schedule_task(1, task).unwrap();
+        assert_eq!(map.scheduler.len(), 1);
+        assert_eq!(map.get_block(position), None);
+        map.scheduler.run_tick(&map);
+        assert_eq!(map.get_block(position), Some(stone));
+        assert!(map.scheduler.is_empty());
+    }
+}
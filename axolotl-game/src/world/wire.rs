@@ -0,0 +1,501 @@
+//! Converts the output of a world into plain serializable data.
+//!
+//! [ServerUpdateOut] carries game types such as [PlacedBlock]. The types here only carry ids, names and packed data
+//! so every protocol implementation can consume the same world output and write it in its own format.
+//!
+//! Block states are the ids of the data version the server runs
+use minecraft_protocol::packets::play::client::chunk::GetVanillaId;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use axolotl_api::world::protection::ProtectedAction;
+use axolotl_api::world::{BlockPosition, World};
+use axolotl_api::OwnedNameSpaceKey;
+
+use crate::mapping::ProtocolMappings;
+use crate::world::chunk::consts::BITS_PER_BLOCK;
+use crate::world::chunk::placed_block::PlacedBlock;
+use crate::world::chunk::sections::biome_section::AxolotlBiomeSection;
+use crate::world::chunk::sections::blocks_section::AxolotlBlockSection;
+use crate::world::chunk::AxolotlChunk;
+use crate::world::events::EventAction;
+use crate::world::players::PlayerStatus;
+use crate::world::sleep::SleepUpdate;
+use crate::world::updates::ServerUpdateOut;
+
+/// The state id of air
+pub const AIR_STATE: u32 = 0;
+
+/// The state id of the block. None is air
+pub fn block_state<W: World>(block: Option<&PlacedBlock<W>>) -> u32 {
+    block.map_or(AIR_STATE, |block| block.get_vanilla_id() as u32)
+}
+
+/// A block within a section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionBlock {
+    /// 0-15
+    pub x: u8,
+    pub y: u8,
+    pub z: u8,
+    pub state: u32,
+}
+impl SectionBlock {
+    /// The vanilla multi block change format. `state << 12 | x << 8 | z << 4 | y`
+    pub fn packed(&self) -> i64 {
+        ((self.state as i64) << 12)
+            | ((self.x as i64) << 8)
+            | ((self.z as i64) << 4)
+            | self.y as i64
+    }
+}
+
+/// Many blocks changed in one section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionUpdatePayload {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub section_y: i16,
+    pub blocks: Vec<SectionBlock>,
+}
+
+/// A palette and the values packed into longs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PalettedPayload<T> {
+    /// The whole section is one value
+    Single(T),
+    Indirect {
+        bits_per_entry: u8,
+        palette: Vec<T>,
+        data: Vec<u64>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionPayload {
+    pub y: i8,
+    pub non_air_blocks: u16,
+    pub blocks: PalettedPayload<u32>,
+    /// Biome names. Protocols map them to the ids of their registry
+    pub biomes: PalettedPayload<String>,
+}
+
+/// Everything needed to send a chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkDataPayload {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    /// Ordered from the bottom of the world up
+    pub sections: Vec<SectionPayload>,
+}
+impl ChunkDataPayload {
+    pub fn from_chunk<W: World>(chunk: &AxolotlChunk<W>) -> Self {
+        let sections = chunk
+            .sections
+            .as_ref()
+            .iter()
+            .map(|section| SectionPayload {
+                y: section.y,
+                non_air_blocks: (4096 - section.blocks.count_air()) as u16,
+                blocks: block_payload(&section.blocks),
+                biomes: biome_payload(&section.biomes),
+            })
+            .collect();
+        Self {
+            chunk_x: chunk.chunk_pos.x(),
+            chunk_z: chunk.chunk_pos.z(),
+            sections,
+        }
+    }
+}
+impl PalettedPayload<u32> {
+    /// Maps the block states to the ids of the protocol version
+    pub fn remap(&mut self, mappings: &ProtocolMappings, version: i32) {
+        match self {
+            PalettedPayload::Single(state) => *state = mappings.map_block_state(version, *state),
+            PalettedPayload::Indirect { palette, .. } => {
+                for state in palette.iter_mut() {
+                    *state = mappings.map_block_state(version, *state);
+                }
+            }
+        }
+    }
+}
+impl ChunkDataPayload {
+    /// Maps the block states to the ids of the protocol version
+    pub fn remap(&mut self, mappings: &ProtocolMappings, version: i32) {
+        for section in self.sections.iter_mut() {
+            section.blocks.remap(mappings, version);
+        }
+    }
+}
+fn block_payload<W: World>(section: &AxolotlBlockSection<W>) -> PalettedPayload<u32> {
+    match section {
+        AxolotlBlockSection::Empty => PalettedPayload::Single(AIR_STATE),
+        AxolotlBlockSection::SingleBlock(block) => {
+            PalettedPayload::Single(block_state(Some(block)))
+        }
+        AxolotlBlockSection::Full {
+            blocks,
+            block_palette,
+        } => PalettedPayload::Indirect {
+            bits_per_entry: BITS_PER_BLOCK as u8,
+            palette: block_palette
+                .iter()
+                .map(|block| block_state(Some(block)))
+                .collect(),
+            data: blocks.data.clone(),
+        },
+    }
+}
+fn biome_payload(section: &AxolotlBiomeSection) -> PalettedPayload<String> {
+    match section {
+        AxolotlBiomeSection::SingleBiome(biome) => PalettedPayload::Single(biome.to_string()),
+        AxolotlBiomeSection::Full {
+            biome_palette,
+            biomes,
+        } => PalettedPayload::Indirect {
+            bits_per_entry: biomes.bits_per_block as u8,
+            palette: biome_palette
+                .iter()
+                .map(OwnedNameSpaceKey::to_string)
+                .collect(),
+            data: biomes.data.clone(),
+        },
+    }
+}
+
+/// A [ServerUpdateOut] without any game types
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WireUpdate {
+    BlockChanged {
+        position: BlockPosition,
+        state: u32,
+    },
+    SectionChanged(SectionUpdatePayload),
+    /// The chunk data is sent with [ChunkDataPayload::from_chunk]
+    ChunkLoaded {
+        chunk_x: i32,
+        chunk_z: i32,
+    },
+    ChunkUnloaded {
+        chunk_x: i32,
+        chunk_z: i32,
+    },
+    ContainerOpened {
+        player: Uuid,
+        position: BlockPosition,
+        state: u32,
+    },
+    /// The protection of the world stopped a player
+    Denied {
+        player: Uuid,
+        position: BlockPosition,
+        action: ProtectedAction,
+    },
+    Saved,
+    TickChanged {
+        tick_rate: f32,
+        frozen: bool,
+    },
+    SleepingPlayers {
+        sleeping: usize,
+        required: usize,
+    },
+    WokeUp {
+        player: Uuid,
+        bed: BlockPosition,
+    },
+    NightSkipped {
+        day_time: i64,
+    },
+    /// The message of the [crate::world::sleep::SleepError]
+    SleepFailed {
+        player: Uuid,
+        message: String,
+    },
+    Sound {
+        sound: String,
+        x: f64,
+        y: f64,
+        z: f64,
+        volume: f32,
+        pitch: f32,
+    },
+    PlayerStatus {
+        player: Uuid,
+        status: PlayerStatus,
+    },
+    /// Only sent to the viewers
+    PlayerMoved {
+        player: Uuid,
+        x: f64,
+        y: f64,
+        z: f64,
+        yaw: f32,
+        pitch: f32,
+        viewers: Vec<Uuid>,
+    },
+    /// The player is teleported back. The message of the [crate::world::entity::movement::InvalidMove]
+    MoveRejected {
+        player: Uuid,
+        x: f64,
+        y: f64,
+        z: f64,
+        yaw: f32,
+        pitch: f32,
+        message: String,
+    },
+    Event {
+        id: u32,
+        action: EventAction,
+    },
+}
+impl WireUpdate {
+    /// Maps the block states to the ids of the protocol version
+    pub fn remap(&mut self, mappings: &ProtocolMappings, version: i32) {
+        match self {
+            WireUpdate::BlockChanged { state, .. } | WireUpdate::ContainerOpened { state, .. } => {
+                *state = mappings.map_block_state(version, *state)
+            }
+            WireUpdate::SectionChanged(payload) => {
+                for block in payload.blocks.iter_mut() {
+                    block.state = mappings.map_block_state(version, block.state);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+impl<W: World> From<&ServerUpdateOut<W>> for WireUpdate {
+    fn from(update: &ServerUpdateOut<W>) -> Self {
+        match update {
+            ServerUpdateOut::BlockChanged { position, block } => WireUpdate::BlockChanged {
+                position: *position,
+                state: block_state(block.as_ref()),
+            },
+            ServerUpdateOut::SectionChanged {
+                chunk,
+                section_y,
+                blocks,
+            } => WireUpdate::SectionChanged(SectionUpdatePayload {
+                chunk_x: chunk.x(),
+                chunk_z: chunk.z(),
+                section_y: *section_y,
+                blocks: blocks
+                    .iter()
+                    .map(|(position, block)| {
+                        let (x, y, z) = position.section_coords();
+                        SectionBlock {
+                            x,
+                            y,
+                            z,
+                            state: block_state(block.as_ref()),
+                        }
+                    })
+                    .collect(),
+            }),
+            ServerUpdateOut::ChunkLoaded(pos) => WireUpdate::ChunkLoaded {
+                chunk_x: pos.x(),
+                chunk_z: pos.z(),
+            },
+            ServerUpdateOut::ChunkUnloaded(pos) => WireUpdate::ChunkUnloaded {
+                chunk_x: pos.x(),
+                chunk_z: pos.z(),
+            },
+            ServerUpdateOut::ContainerOpened {
+                player,
+                position,
+                block,
+            } => WireUpdate::ContainerOpened {
+                player: *player,
+                position: *position,
+                state: block_state(Some(block)),
+            },
+            ServerUpdateOut::Denied {
+                player,
+                position,
+                action,
+            } => WireUpdate::Denied {
+                player: *player,
+                position: *position,
+                action: *action,
+            },
+            ServerUpdateOut::Saved => WireUpdate::Saved,
+            ServerUpdateOut::TickChanged(status) => WireUpdate::TickChanged {
+                tick_rate: status.tick_rate,
+                frozen: status.frozen,
+            },
+            ServerUpdateOut::Sleep(SleepUpdate::SleepingPlayers { sleeping, required }) => {
+                WireUpdate::SleepingPlayers {
+                    sleeping: *sleeping,
+                    required: *required,
+                }
+            }
+            ServerUpdateOut::Sleep(SleepUpdate::WokeUp { player, bed }) => WireUpdate::WokeUp {
+                player: *player,
+                bed: *bed,
+            },
+            ServerUpdateOut::Sleep(SleepUpdate::NightSkipped { day_time }) => {
+                WireUpdate::NightSkipped {
+                    day_time: *day_time,
+                }
+            }
+            ServerUpdateOut::SleepFailed { player, error } => WireUpdate::SleepFailed {
+                player: *player,
+                message: error.to_string(),
+            },
+            ServerUpdateOut::Sound(sound) => WireUpdate::Sound {
+                sound: sound.sound.to_string(),
+                x: sound.location.x,
+                y: sound.location.y as f64,
+                z: sound.location.z,
+                volume: sound.volume,
+                pitch: sound.pitch,
+            },
+            ServerUpdateOut::PlayerStatus { player, status } => WireUpdate::PlayerStatus {
+                player: *player,
+                status: *status,
+            },
+            ServerUpdateOut::PlayerMoved {
+                player,
+                location,
+                viewers,
+            } => WireUpdate::PlayerMoved {
+                player: *player,
+                x: location.x,
+                y: location.y,
+                z: location.z,
+                yaw: location.yaw,
+                pitch: location.pitch,
+                viewers: viewers.clone(),
+            },
+            ServerUpdateOut::MoveRejected {
+                player,
+                location,
+                error,
+            } => WireUpdate::MoveRejected {
+                player: *player,
+                x: location.x,
+                y: location.y,
+                z: location.z,
+                yaw: location.yaw,
+                pitch: location.pitch,
+                message: error.to_string(),
+            },
+            ServerUpdateOut::Event { id, action } => WireUpdate::Event {
+                id: *id,
+                action: action.clone(),
+            },
+        }
+    }
+}
+impl<W: World> From<ServerUpdateOut<W>> for WireUpdate {
+    fn from(update: ServerUpdateOut<W>) -> Self {
+        WireUpdate::from(&update)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use axolotl_api::world::protection::ProtectedAction;
+    use axolotl_api::world::BlockPosition;
+    use axolotl_api::world_gen::chunk::ChunkPos;
+
+    use crate::world::chunk::AxolotlChunk;
+    use crate::world::test_world::{test_block, test_game, TestWorld};
+    use crate::world::updates::ServerUpdateOut;
+    use crate::world::wire::{
+        block_state, ChunkDataPayload, PalettedPayload, SectionBlock, SectionUpdatePayload,
+        WireUpdate,
+    };
+
+    #[test]
+    pub fn test_section_update() {
+        let update = ServerUpdateOut::<TestWorld>::SectionChanged {
+            chunk: ChunkPos::new(-1, 0),
+            section_y: -1,
+            blocks: vec![
+                (BlockPosition::new(-1, -16, 3), None),
+                (BlockPosition::new(-16, -1, 15), None),
+            ],
+        };
+        let wire = WireUpdate::from(&update);
+        assert_eq!(
+            wire,
+            WireUpdate::SectionChanged(SectionUpdatePayload {
+                chunk_x: -1,
+                chunk_z: 0,
+                section_y: -1,
+                blocks: vec![
+                    SectionBlock {
+                        x: 15,
+                        y: 0,
+                        z: 3,
+                        state: 0
+                    },
+                    SectionBlock {
+                        x: 0,
+                        y: 15,
+                        z: 15,
+                        state: 0
+                    },
+                ],
+            })
+        );
+        let json = serde_json::to_string(&wire).unwrap();
+        assert_eq!(serde_json::from_str::<WireUpdate>(&json).unwrap(), wire);
+    }
+    #[test]
+    pub fn test_player_updates() {
+        let game = test_game(&["chest"]);
+        let player = Uuid::new_v4();
+        let position = BlockPosition::new(4, 70, -2);
+        let chest = test_block(&game, "chest");
+        let opened = WireUpdate::from(&ServerUpdateOut::ContainerOpened {
+            player,
+            position,
+            block: chest.clone(),
+        });
+        let denied = WireUpdate::from(&ServerUpdateOut::<TestWorld>::Denied {
+            player,
+            position,
+            action: ProtectedAction::OpenContainer,
+        });
+        assert_eq!(
+            opened,
+            WireUpdate::ContainerOpened {
+                player,
+                position,
+                state: block_state(Some(&chest)),
+            }
+        );
+        assert_eq!(
+            denied,
+            WireUpdate::Denied {
+                player,
+                position,
+                action: ProtectedAction::OpenContainer,
+            }
+        );
+        for wire in [opened, denied] {
+            let json = serde_json::to_string(&wire).unwrap();
+            assert_eq!(serde_json::from_str::<WireUpdate>(&json).unwrap(), wire);
+        }
+    }
+    #[test]
+    pub fn test_empty_chunk() {
+        let chunk = AxolotlChunk::<TestWorld>::new(ChunkPos::new(2, -3));
+        let payload = ChunkDataPayload::from_chunk(&chunk);
+        assert_eq!(payload.sections.len(), 24);
+        assert_eq!(payload.sections[0].y, -4);
+        assert!(payload
+            .sections
+            .iter()
+            .all(|section| section.non_air_blocks == 0
+                && section.blocks == PalettedPayload::Single(0)));
+    }
+}
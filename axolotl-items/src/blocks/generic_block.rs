@@ -10,9 +10,13 @@ use serde::Deserialize;
 use axolotl_api::events::{EventHandler, NoError};
 use axolotl_api::game::Game;
 use axolotl_api::item::block::{Block, BlockPlaceEvent, BlockState, BlockStateValue};
+use axolotl_api::item::metadata::BlockMetadata;
+use axolotl_api::item::placement::PlacementContext;
 use axolotl_api::item::ItemType;
+use axolotl_api::world::BlockPosition;
 use axolotl_api::{NamespacedId, NumericId};
 
+use crate::blocks::placement;
 use crate::blocks::raw_state::RawState;
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -100,6 +104,7 @@ pub struct BlockProperties {
     pub id: usize,
     pub default_state: usize,
     pub states: Vec<VanillaState>,
+    pub metadata: BlockMetadata,
 }
 impl BlockProperties {
     pub fn process_state(
@@ -141,6 +146,7 @@ impl NumericId for GenericBlock {
 impl GenericBlock {
     pub fn new(
         raw_block: RawBlock,
+        metadata: BlockMetadata,
         materials: &HashMap<String, Arc<Material>>,
         raw_states: &mut std::collections::HashMap<String, RawState>,
     ) -> Self {
@@ -156,10 +162,31 @@ impl GenericBlock {
             states,
             default_state,
             is_air: raw_block.properties.is_air,
+            metadata,
         };
         Self(value)
     }
 }
+impl GenericBlock {
+    pub fn get_state(&self, state_id: usize) -> Option<&VanillaState> {
+        self.0
+            .states
+            .iter()
+            .find(|state| state.state_id == state_id)
+    }
+    /// Returns the same state with the block water-logged or not
+    pub fn set_waterlogged(&self, state: &VanillaState, waterlogged: bool) -> Option<VanillaState> {
+        if !state.can_waterlog() {
+            return None;
+        }
+        placement::with_property(
+            &self.0.states,
+            state,
+            "waterlogged",
+            BlockStateValue::String(waterlogged.to_string()),
+        )
+    }
+}
 impl ItemType for GenericBlock {}
 
 impl<G: Game> EventHandler<BlockPlaceEvent<'_, G>> for GenericBlock {
@@ -180,7 +207,23 @@ impl<G: Game> Block<G> for GenericBlock {
         self.0.is_air
     }
 
+    fn metadata(&self) -> &BlockMetadata {
+        &self.0.metadata
+    }
+
     fn get_default_state(&self) -> Cow<'_, Self::State> {
         Cow::Borrowed(&self.0.states[self.0.default_state])
     }
+
+    fn get_placement_state(&self, context: &PlacementContext) -> Option<Self::State> {
+        placement::placement_state(&self.0.key, &self.0.states, self.0.default_state, context)
+    }
+
+    fn get_additional_placements(
+        &self,
+        context: &PlacementContext,
+        state: &Self::State,
+    ) -> Vec<(BlockPosition, Self::State)> {
+        placement::additional_placements(&self.0.states, context, state)
+    }
 }
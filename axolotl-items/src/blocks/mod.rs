@@ -5,14 +5,19 @@ use std::sync::Arc;
 use axolotl_api::events::{EventHandler, NoError};
 use axolotl_api::game::Game;
 use axolotl_api::item::block::{Block, BlockPlaceEvent};
+use axolotl_api::item::metadata::{BlockMetadata, AIR_BLOCK_METADATA};
+use axolotl_api::item::placement::PlacementContext;
 use axolotl_api::item::ItemType;
+use axolotl_api::world::BlockPosition;
 use axolotl_api::{NamespacedId, NumericId};
 use generic_block::GenericBlock;
 
 use crate::blocks::generic_block::VanillaState;
 
 pub mod generic_block;
+pub mod placement;
 pub(crate) mod raw_state;
+pub mod redstone;
 pub mod v19;
 
 pub type MinecraftBlock<G> = Arc<InnerMinecraftBlock<G>>;
@@ -103,6 +108,17 @@ impl<G: Game> Block<G> for InnerMinecraftBlock<G> {
         }
     }
 
+    fn metadata(&self) -> &BlockMetadata {
+        match self {
+            InnerMinecraftBlock::GenericBlock(v) => &v.0.metadata,
+            InnerMinecraftBlock::DynamicBlock(v) => {
+                let block = v.as_ref();
+                block.metadata()
+            }
+            InnerMinecraftBlock::Air { .. } => &AIR_BLOCK_METADATA,
+        }
+    }
+
     fn get_default_state(&self) -> Cow<'_, Self::State> {
         match self {
             InnerMinecraftBlock::GenericBlock(v) => {
@@ -114,4 +130,34 @@ impl<G: Game> Block<G> for InnerMinecraftBlock<G> {
             InnerMinecraftBlock::Air { .. } => Cow::Owned(VanillaState::default()),
         }
     }
+
+    fn get_placement_state(&self, context: &PlacementContext) -> Option<Self::State> {
+        match self {
+            InnerMinecraftBlock::GenericBlock(v) => {
+                <GenericBlock as Block<G>>::get_placement_state(v, context)
+            }
+            InnerMinecraftBlock::DynamicBlock(v) => {
+                let block = v.as_ref();
+                block.get_placement_state(context)
+            }
+            InnerMinecraftBlock::Air { .. } => None,
+        }
+    }
+
+    fn get_additional_placements(
+        &self,
+        context: &PlacementContext,
+        state: &Self::State,
+    ) -> Vec<(BlockPosition, Self::State)> {
+        match self {
+            InnerMinecraftBlock::GenericBlock(v) => {
+                <GenericBlock as Block<G>>::get_additional_placements(v, context, state)
+            }
+            InnerMinecraftBlock::DynamicBlock(v) => {
+                let block = v.as_ref();
+                block.get_additional_placements(context, state)
+            }
+            InnerMinecraftBlock::Air { .. } => Vec::new(),
+        }
+    }
 }
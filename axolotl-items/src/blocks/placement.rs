@@ -0,0 +1,138 @@
+//! Picks vanilla block states from a [PlacementContext].
+//!
+//! The rules are chosen from the properties the block has. So most blocks do not need their own implementation
+use ahash::AHashMap;
+
+use axolotl_api::item::block::BlockStateValue;
+use axolotl_api::item::placement::PlacementContext;
+use axolotl_api::world::{BlockFace, BlockPosition};
+
+use crate::blocks::generic_block::VanillaState;
+
+/// Blocks that break without a solid block below them
+pub fn requires_support(key: &str) -> bool {
+    matches!(
+        key,
+        "torch" | "soul_torch" | "redstone_torch" | "rail" | "powered_rail" | "detector_rail"
+    ) || key.ends_with("_carpet")
+        || key.ends_with("_pressure_plate")
+        || key.ends_with("_door")
+        || key.ends_with("_sapling")
+}
+#[inline]
+fn string(value: &str) -> BlockStateValue {
+    BlockStateValue::String(value.to_string())
+}
+#[inline]
+fn is_value(value: &BlockStateValue, expected: &str) -> bool {
+    matches!(value, BlockStateValue::String(v) if v == expected)
+}
+/// Finds the state with exactly these values
+pub fn find_state<'state>(
+    states: &'state [VanillaState],
+    values: &AHashMap<String, BlockStateValue>,
+) -> Option<&'state VanillaState> {
+    states.iter().find(|state| state.values.eq(values))
+}
+/// Finds the state with one property changed
+pub fn with_property(
+    states: &[VanillaState],
+    state: &VanillaState,
+    name: &str,
+    value: BlockStateValue,
+) -> Option<VanillaState> {
+    let mut values = state.values.clone();
+    values.insert(name.to_string(), value);
+    find_state(states, &values).cloned()
+}
+/// Returns the state a vanilla block should be placed with
+pub fn placement_state(
+    key: &str,
+    states: &[VanillaState],
+    default_state: usize,
+    context: &PlacementContext,
+) -> Option<VanillaState> {
+    let default = states.get(default_state)?;
+    let position = context.placement_position();
+    if requires_support(key) && !context.has_support_below() {
+        return None;
+    }
+    let all_directions = states.iter().any(|state| {
+        state
+            .values
+            .get("facing")
+            .is_some_and(|v| is_value(v, "up"))
+    });
+    // Stairs and doors face away from the player. Everything else faces the player
+    let faces_away = default.values.contains_key("shape") || key.ends_with("_door");
+
+    let mut values = default.values.clone();
+    for (name, value) in values.iter_mut() {
+        match name.as_str() {
+            "facing" => {
+                let facing = if all_directions {
+                    context.looking_direction().opposite()
+                } else if faces_away {
+                    context.horizontal_facing()
+                } else {
+                    context.horizontal_facing().opposite()
+                };
+                *value = string(facing.name());
+            }
+            "axis" => {
+                *value = string(context.clicked_face.axis());
+            }
+            "type" if is_value(value, "top") || is_value(value, "bottom") => {
+                *value = string(if context.is_top_half() {
+                    "top"
+                } else {
+                    "bottom"
+                });
+            }
+            "half" => {
+                if is_value(value, "lower") || is_value(value, "upper") {
+                    if !context
+                        .world
+                        .is_replaceable(&position.relative(BlockFace::Up))
+                    {
+                        return None;
+                    }
+                    *value = string("lower");
+                } else {
+                    *value = string(if context.is_top_half() {
+                        "top"
+                    } else {
+                        "bottom"
+                    });
+                }
+            }
+            "waterlogged" => {
+                *value = string(if context.world.is_water_source(&position) {
+                    "true"
+                } else {
+                    "false"
+                });
+            }
+            _ => {}
+        }
+    }
+    find_state(states, &values).cloned()
+}
+/// The upper half of doors and other two block tall blocks
+pub fn additional_placements(
+    states: &[VanillaState],
+    context: &PlacementContext,
+    state: &VanillaState,
+) -> Vec<(BlockPosition, VanillaState)> {
+    match state.values.get("half") {
+        Some(half) if is_value(half, "lower") => {
+            let mut values = state.values.clone();
+            values.insert("half".to_string(), string("upper"));
+            let upper = context.placement_position().relative(BlockFace::Up);
+            find_state(states, &values)
+                .map(|state| vec![(upper, state.clone())])
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
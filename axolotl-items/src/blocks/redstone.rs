@@ -0,0 +1,108 @@
+//! Block state rules for redstone components.
+use axolotl_api::item::block::{BlockState, BlockStateValue};
+use axolotl_api::world::BlockFace;
+
+use crate::blocks::generic_block::VanillaState;
+use crate::blocks::placement;
+
+/// The highest note a note block can play
+pub const MAX_NOTE: i32 = 24;
+pub const MAX_SIGNAL: u8 = 15;
+
+/// Reads the `facing` property
+pub fn get_facing(state: &VanillaState) -> Option<BlockFace> {
+    let BlockStateValue::String(facing) = state.get("facing")? else {
+        return None;
+    };
+    BlockFace::ALL
+        .into_iter()
+        .find(|face| face.name() == facing.as_str())
+}
+
+/// Reads the `note` property of a note block
+pub fn get_note(state: &VanillaState) -> Option<i32> {
+    match state.get("note")? {
+        BlockStateValue::Int(note) => Some(*note),
+        BlockStateValue::String(note) => note.parse().ok(),
+        _ => None,
+    }
+}
+/// Moves a note block to the next pitch. Wraps back to 0 after [MAX_NOTE]
+pub fn cycle_note(states: &[VanillaState], state: &VanillaState) -> Option<VanillaState> {
+    let note = get_note(state)?;
+    let next = if note >= MAX_NOTE { 0 } else { note + 1 };
+    placement::with_property(
+        states,
+        state,
+        "note",
+        BlockStateValue::String(next.to_string()),
+    )
+}
+/// The pitch multiplier sent with the note block sound
+pub fn note_pitch(note: i32) -> f32 {
+    2f32.powf((note - 12) as f32 / 12.0)
+}
+
+/// A block entity with an inventory that a comparator can read
+pub trait ComparatorContainer {
+    /// The number of slots
+    fn size(&self) -> usize;
+    /// The count and max stack size of the item in the slot. None if it is empty
+    fn get_slot(&self, slot: usize) -> Option<(u32, u32)>;
+}
+/// The signal strength a comparator reads from a container.
+///
+/// Matches vanilla. Any item gives at least 1 and a full container gives 15
+pub fn comparator_signal(container: &impl ComparatorContainer) -> u8 {
+    let size = container.size();
+    if size == 0 {
+        return 0;
+    }
+    let mut fullness = 0f32;
+    let mut any = false;
+    for slot in 0..size {
+        if let Some((count, max_stack)) = container.get_slot(slot) {
+            if count > 0 && max_stack > 0 {
+                fullness += count as f32 / max_stack as f32;
+                any = true;
+            }
+        }
+    }
+    if !any {
+        return 0;
+    }
+    let fullness = fullness / size as f32;
+    (1 + (fullness * 14.0) as u8).min(MAX_SIGNAL)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::blocks::redstone::{comparator_signal, ComparatorContainer};
+
+    struct TestContainer(Vec<Option<(u32, u32)>>);
+    impl ComparatorContainer for TestContainer {
+        fn size(&self) -> usize {
+            self.0.len()
+        }
+
+        fn get_slot(&self, slot: usize) -> Option<(u32, u32)> {
+            self.0[slot]
+        }
+    }
+
+    #[test]
+    pub fn test_comparator_signal() {
+        assert_eq!(comparator_signal(&TestContainer(vec![None; 27])), 0);
+        let mut slots = vec![None; 27];
+        slots[0] = Some((1, 64));
+        assert_eq!(comparator_signal(&TestContainer(slots)), 1);
+        assert_eq!(
+            comparator_signal(&TestContainer(vec![Some((64, 64)); 27])),
+            15
+        );
+        // A single sword fills a hopper slot
+        let mut slots = vec![None; 5];
+        slots[0] = Some((1, 1));
+        assert_eq!(comparator_signal(&TestContainer(slots)), 3);
+    }
+}
@@ -5,11 +5,15 @@ use axolotl_data_rs::blocks::{Block as RawBlock, Material};
 
 use axolotl_api::events::{Event, EventHandler};
 use axolotl_api::game::Game;
-use axolotl_api::item::block::{Block, BlockPlaceEvent};
+use axolotl_api::item::block::{Block, BlockPlaceEvent, BlockStateValue};
+use axolotl_api::item::metadata::BlockMetadata;
+use axolotl_api::item::placement::PlacementContext;
 use axolotl_api::item::ItemType;
+use axolotl_api::world::BlockPosition;
 use axolotl_api::{NamespacedId, NumericId};
 
 use crate::blocks::generic_block::{BlockProperties, VanillaState};
+use crate::blocks::placement::find_state;
 use crate::blocks::raw_state::RawState;
 
 #[derive(Debug, Clone)]
@@ -19,10 +23,12 @@ pub struct BedBlock {
     pub states: Vec<VanillaState>,
     pub material: Arc<Material>,
     pub key: String,
+    pub metadata: BlockMetadata,
 }
 impl BedBlock {
     pub fn new(
         raw_block: RawBlock,
+        metadata: BlockMetadata,
         materials: &HashMap<String, Arc<Material>>,
         raw_states: &mut std::collections::HashMap<String, RawState>,
     ) -> Self {
@@ -32,6 +38,7 @@ impl BedBlock {
             default_state,
             states,
             key: raw_block.name,
+            metadata,
             material: materials
                 .get(&raw_block.properties.material)
                 .expect("Material not found")
@@ -40,6 +47,17 @@ impl BedBlock {
     }
 }
 
+impl BedBlock {
+    fn with_part(&self, state: &VanillaState, part: &str) -> Option<VanillaState> {
+        let mut values = state.values.clone();
+        values.insert(
+            "part".to_string(),
+            BlockStateValue::String(part.to_string()),
+        );
+        find_state(&self.states, &values).cloned()
+    }
+}
+
 impl ItemType for BedBlock {}
 
 impl NumericId for BedBlock {
@@ -77,4 +95,39 @@ impl<G: Game> Block<G> for BedBlock {
     fn is_air(&self) -> bool {
         false
     }
+
+    fn metadata(&self) -> &BlockMetadata {
+        &self.metadata
+    }
+    /// The foot is placed at the clicked position and the head in the direction the player is looking
+    fn get_placement_state(&self, context: &PlacementContext) -> Option<Self::State> {
+        let facing = context.horizontal_facing();
+        let head = context.placement_position().relative(facing);
+        if !context.world.is_replaceable(&head) {
+            return None;
+        }
+        let mut values = self.states.get(self.default_state)?.values.clone();
+        values.insert(
+            "facing".to_string(),
+            BlockStateValue::String(facing.name().to_string()),
+        );
+        values.insert(
+            "part".to_string(),
+            BlockStateValue::String("foot".to_string()),
+        );
+        find_state(&self.states, &values).cloned()
+    }
+
+    fn get_additional_placements(
+        &self,
+        context: &PlacementContext,
+        state: &Self::State,
+    ) -> Vec<(BlockPosition, Self::State)> {
+        let head = context
+            .placement_position()
+            .relative(context.horizontal_facing());
+        self.with_part(state, "head")
+            .map(|state| vec![(head, state)])
+            .unwrap_or_default()
+    }
 }
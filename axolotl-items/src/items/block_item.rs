@@ -1,5 +1,6 @@
 use axolotl_api::events::{Event, EventHandler};
 use axolotl_api::game::Game;
+use axolotl_api::item::properties::ItemProperties;
 use axolotl_api::item::{Item, ItemLeftClick, ItemType};
 use axolotl_api::{NamespacedId, NumericId};
 
@@ -9,6 +10,7 @@ use crate::blocks::MinecraftBlock;
 pub struct BlockItem<G: Game> {
     pub block: MinecraftBlock<G>,
     pub id: usize,
+    pub properties: ItemProperties,
 }
 
 impl<G: Game> ItemType for BlockItem<G> {}
@@ -38,4 +40,8 @@ impl<G: Game> EventHandler<ItemLeftClick<G>> for BlockItem<G> {
     }
 }
 
-impl<G: Game> Item<G> for BlockItem<G> {}
+impl<G: Game> Item<G> for BlockItem<G> {
+    fn properties(&self) -> &ItemProperties {
+        &self.properties
+    }
+}
@@ -0,0 +1,46 @@
+use axolotl_api::events::{Event, EventHandler};
+use axolotl_api::game::Game;
+use axolotl_api::item::properties::ItemProperties;
+use axolotl_api::item::{Item, ItemLeftClick, ItemType};
+use axolotl_api::{NamespacedId, NumericId};
+
+/// An item without special behavior. Tools and materials
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericItem {
+    pub id: usize,
+    pub key: String,
+    pub properties: ItemProperties,
+}
+
+impl ItemType for GenericItem {}
+
+impl NumericId for GenericItem {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl NamespacedId for GenericItem {
+    fn namespace(&self) -> &str {
+        "minecraft"
+    }
+
+    fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl<G: Game> EventHandler<ItemLeftClick<G>> for GenericItem {
+    fn handle(
+        &self,
+        _event: ItemLeftClick<G>,
+    ) -> Result<<ItemLeftClick<G> as Event>::Result, <ItemLeftClick<G> as Event>::Error> {
+        Ok(())
+    }
+}
+
+impl<G: Game> Item<G> for GenericItem {
+    fn properties(&self) -> &ItemProperties {
+        &self.properties
+    }
+}
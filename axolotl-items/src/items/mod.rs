@@ -2,11 +2,14 @@ use std::sync::Arc;
 
 use axolotl_api::events::{Event, EventHandler};
 use axolotl_api::game::Game;
+use axolotl_api::item::properties::{ItemProperties, DEFAULT_ITEM_PROPERTIES};
 use axolotl_api::item::{Item, ItemLeftClick, ItemType};
 use axolotl_api::NumericId;
 use block_item::BlockItem;
+use generic_item::GenericItem;
 
 pub mod block_item;
+pub mod generic_item;
 
 pub type MinecraftItem<G> = Arc<InnerMinecraftItem<G>>;
 
@@ -14,12 +17,16 @@ pub type MinecraftItem<G> = Arc<InnerMinecraftItem<G>>;
 pub enum InnerMinecraftItem<G: Game> {
     Air,
     BlockItem(BlockItem<G>),
+    GenericItem(GenericItem),
 }
 impl<G: Game> PartialEq for InnerMinecraftItem<G> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (InnerMinecraftItem::Air, InnerMinecraftItem::Air) => true,
             (InnerMinecraftItem::BlockItem(a), InnerMinecraftItem::BlockItem(b)) => a.id == b.id,
+            (InnerMinecraftItem::GenericItem(a), InnerMinecraftItem::GenericItem(b)) => {
+                a.id == b.id
+            }
             _ => false,
         }
     }
@@ -30,6 +37,7 @@ impl<G: Game> NumericId for InnerMinecraftItem<G> {
     fn id(&self) -> usize {
         match self {
             InnerMinecraftItem::BlockItem(item) => item.id(),
+            InnerMinecraftItem::GenericItem(item) => item.id(),
             InnerMinecraftItem::Air => 0,
         }
     }
@@ -42,9 +50,18 @@ impl<G: Game> EventHandler<ItemLeftClick<G>> for InnerMinecraftItem<G> {
     ) -> Result<<ItemLeftClick<G> as Event>::Result, <ItemLeftClick<G> as Event>::Error> {
         match self {
             InnerMinecraftItem::BlockItem(item) => item.handle(event),
+            InnerMinecraftItem::GenericItem(item) => item.handle(event),
             InnerMinecraftItem::Air => Ok(()),
         }
     }
 }
 
-impl<G: Game> Item<G> for InnerMinecraftItem<G> {}
+impl<G: Game> Item<G> for InnerMinecraftItem<G> {
+    fn properties(&self) -> &ItemProperties {
+        match self {
+            InnerMinecraftItem::BlockItem(item) => &item.properties,
+            InnerMinecraftItem::GenericItem(item) => &item.properties,
+            InnerMinecraftItem::Air => &DEFAULT_ITEM_PROPERTIES,
+        }
+    }
+}
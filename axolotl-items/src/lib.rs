@@ -5,15 +5,21 @@ use std::sync::Arc;
 use ahash::HashMapExt;
 use axolotl_data_rs::blocks::{Block as RawBlock, Material};
 use log::{debug, warn};
+use serde::Deserialize;
 use thiserror::Error;
 
 use axolotl_api::game::{Game, Registry};
+use axolotl_api::item::metadata::BlockMetadata;
+use axolotl_api::item::properties::{ItemProperties, ToolTier};
 use axolotl_api::{NamespacedId, NumericId};
 
 use crate::blocks::generic_block::GenericBlock;
 use crate::blocks::raw_state::RawState;
 use crate::blocks::v19::bed::BedBlock;
 use crate::blocks::{InnerMinecraftBlock, MinecraftBlock};
+use crate::items::block_item::BlockItem;
+use crate::items::generic_item::GenericItem;
+use crate::items::{InnerMinecraftItem, MinecraftItem};
 
 pub mod blocks;
 pub mod items;
@@ -45,6 +51,24 @@ pub fn load_materials(
     Ok(materials)
 }
 
+/// Reads the [BlockMetadata] from the `properties` of a block. Missing values use the defaults
+fn load_metadata(block: &serde_json::Value) -> BlockMetadata {
+    let Some(properties) = block.get("properties") else {
+        return BlockMetadata::default();
+    };
+    match BlockMetadata::deserialize(properties) {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            warn!(
+                "Invalid block metadata for {}: {}",
+                block.get("name").unwrap_or(&serde_json::Value::Null),
+                error
+            );
+            BlockMetadata::default()
+        }
+    }
+}
+
 pub fn load_blocks<G: Game>(
     minecraft_data: PathBuf,
     data_dump: PathBuf,
@@ -60,16 +84,17 @@ pub fn load_blocks<G: Game>(
     let data = minecraft_data.join("blocks.json");
     debug!("Loading block data");
 
-    let blocks: Vec<RawBlock> =
-        serde_json::from_reader(std::fs::File::open(data).unwrap()).unwrap();
+    let blocks: Vec<serde_json::Value> = serde_json::from_reader(std::fs::File::open(data)?)?;
 
     for block in blocks {
+        let metadata = load_metadata(&block);
+        let block: RawBlock = serde_json::from_value(block)?;
         // Turn block into generic block
         if !block.tags.is_empty() {
             let tag = &block.tags[0];
             match tag.name.as_str() {
                 "BedBlock" => {
-                    let block = BedBlock::new(block, materials, &mut states);
+                    let block = BedBlock::new(block, metadata, materials, &mut states);
                     register.register_with_id(
                         &format!("minecraft:{}", block.key),
                         block.id(),
@@ -95,7 +120,7 @@ pub fn load_blocks<G: Game>(
             }
         }
         // Default to generic block
-        let block = GenericBlock::new(block, materials, &mut states);
+        let block = GenericBlock::new(block, metadata, materials, &mut states);
         register.register_with_id(
             &format!("minecraft:{}", block.0.key),
             block.id(),
@@ -104,3 +129,68 @@ pub fn load_blocks<G: Game>(
     }
     Ok(())
 }
+
+/// Reads the [ItemProperties] of an item. Tools without a tier in the data get it from their name
+fn load_item_properties(name: &str, item: &serde_json::Value) -> ItemProperties {
+    let mut properties = match ItemProperties::deserialize(item) {
+        Ok(properties) => properties,
+        Err(error) => {
+            warn!("Invalid item properties for {}: {}", name, error);
+            ItemProperties::default()
+        }
+    };
+    if properties.tool_tier.is_none() {
+        if let Some((tier, tag)) = ToolTier::from_item_name(name) {
+            properties.tool_tier = Some(tier);
+            if properties.max_damage == 0 {
+                properties.max_damage = tier.uses();
+            }
+            if let Some(tag) = tag {
+                properties.block_tags.push(tag.to_string());
+            }
+        }
+    }
+    if properties.max_damage > 0 {
+        properties.max_stack_size = 1;
+    }
+    properties
+}
+/// Loads the items from `items.json`. Items with the same name as a block become block items
+pub fn load_items<G: Game>(
+    minecraft_data: PathBuf,
+    blocks: &impl Registry<MinecraftBlock<G>>,
+    register: &mut impl Registry<MinecraftItem<G>>,
+) -> Result<(), Error> {
+    let data = minecraft_data.join("items.json");
+    debug!("Loading item data");
+    let items: Vec<serde_json::Value> = serde_json::from_reader(std::fs::File::open(data)?)?;
+    for item in items {
+        let (Some(id), Some(name)) = (
+            item.get("id").and_then(serde_json::Value::as_u64),
+            item.get("name").and_then(serde_json::Value::as_str),
+        ) else {
+            warn!("Item without an id or name: {}", item);
+            continue;
+        };
+        let id = id as usize;
+        let key = format!("minecraft:{}", name);
+        let properties = load_item_properties(name, &item);
+        let item = if name == "air" {
+            InnerMinecraftItem::Air
+        } else if let Some(block) = blocks.get_by_namespace(&key) {
+            InnerMinecraftItem::BlockItem(BlockItem {
+                block: block.clone(),
+                id,
+                properties,
+            })
+        } else {
+            InnerMinecraftItem::GenericItem(GenericItem {
+                id,
+                key: name.to_string(),
+                properties,
+            })
+        };
+        register.register_with_id(&key, id, Arc::new(item));
+    }
+    Ok(())
+}
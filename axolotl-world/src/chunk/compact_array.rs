@@ -15,7 +15,7 @@ impl CompactArray {
     pub fn new(bits_per_block: usize, length: usize) -> Self {
         let values_per_u64 = Self::calc_values_per_u64(bits_per_block);
 
-        let data = vec![0; length / values_per_u64];
+        let data = vec![0; length.div_ceil(values_per_u64)];
 
         CompactArray {
             bits_per_block,
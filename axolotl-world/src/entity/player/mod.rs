@@ -1,4 +1,56 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
-pub struct PlayerData {}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerData {
+    #[serde(rename = "foodLevel")]
+    pub food_level: i32,
+    #[serde(rename = "foodSaturationLevel")]
+    pub food_saturation_level: f32,
+    #[serde(rename = "foodExhaustionLevel")]
+    pub food_exhaustion_level: f32,
+    #[serde(rename = "foodTickTimer")]
+    pub food_tick_timer: i32,
+    #[serde(rename = "Health")]
+    pub health: f32,
+    /// Remaining breath in ticks
+    #[serde(rename = "Air")]
+    pub air: i16,
+    /// The bed or respawn anchor. None to respawn at the world spawn
+    #[serde(rename = "SpawnX", skip_serializing_if = "Option::is_none")]
+    pub spawn_x: Option<i32>,
+    #[serde(rename = "SpawnY", skip_serializing_if = "Option::is_none")]
+    pub spawn_y: Option<i32>,
+    #[serde(rename = "SpawnZ", skip_serializing_if = "Option::is_none")]
+    pub spawn_z: Option<i32>,
+    #[serde(rename = "SpawnDimension", skip_serializing_if = "Option::is_none")]
+    pub spawn_dimension: Option<String>,
+    /// 0 survival, 1 creative, 2 adventure and 3 spectator
+    #[serde(rename = "playerGameType")]
+    pub player_game_type: i32,
+    /// x, y and z. Empty for a new player
+    #[serde(rename = "Pos")]
+    pub pos: Vec<f64>,
+    /// Yaw and pitch
+    #[serde(rename = "Rotation")]
+    pub rotation: Vec<f32>,
+}
+impl Default for PlayerData {
+    fn default() -> Self {
+        Self {
+            food_level: 20,
+            food_saturation_level: 5.0,
+            food_exhaustion_level: 0.0,
+            food_tick_timer: 0,
+            health: 20.0,
+            air: 300,
+            spawn_x: None,
+            spawn_y: None,
+            spawn_z: None,
+            spawn_dimension: None,
+            player_game_type: 0,
+            pos: Vec::new(),
+            rotation: Vec::new(),
+        }
+    }
+}
@@ -131,6 +131,10 @@ pub fn default_game_rules() -> HashMap<String, Value> {
     game_rules.insert("maxEntityCramming".to_string(), Value::Number(24.into()));
     game_rules.insert("mobGriefing".to_string(), Value::Bool(true));
     game_rules.insert("naturalRegeneration".to_string(), Value::Bool(true));
+    game_rules.insert(
+        "playersSleepingPercentage".to_string(),
+        Value::Number(100.into()),
+    );
     game_rules.insert("randomTickSpeed".to_string(), Value::Number(3.into()));
     game_rules.insert("reducedDebugInfo".to_string(), Value::Bool(false));
     game_rules.insert("sendCommandFeedback".to_string(), Value::Bool(true));
@@ -14,6 +14,7 @@ pub mod entity;
 pub mod item;
 pub mod level;
 pub mod region;
+pub mod validate;
 pub mod world;
 #[test]
 pub fn test_build() {
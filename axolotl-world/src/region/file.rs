@@ -56,6 +56,20 @@ impl RegionFile {
             })
         }
     }
+    /// Opens the region without write access. A missing file is treated as an empty region
+    pub fn open_read_only(path: PathBuf) -> Result<Self, Error> {
+        let region_header = if path.exists() {
+            let mut file = OpenOptions::new().read(true).open(&path)?;
+            RegionHeader::read_region_header(&mut file)?
+        } else {
+            RegionHeader::default()
+        };
+        Ok(Self {
+            file: path,
+            region_header,
+            write_buffer: vec![],
+        })
+    }
     pub fn write_chunk<FileType: RegionFileType + Serialize>(
         &mut self,
         data: FileType,
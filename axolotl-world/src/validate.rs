@@ -0,0 +1,498 @@
+//! Load time validation of a world folder.
+//!
+//! Scans the level.dat, region headers and player files and collects every problem into a [ValidationReport].
+//! With [ValidateOptions::repair] the problems that can be fixed without user input are repaired on disk.
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use axolotl_nbt::serde_impl;
+use axolotl_types::OwnedNameSpaceKey;
+use flate2::read::GzDecoder;
+use log::{debug, warn};
+use uuid::Uuid;
+
+use crate::chunk::{PaletteItem, RawChunk};
+use crate::entity::player::PlayerData;
+use crate::level::RootWrapper;
+use crate::region::file::RegionFile;
+use crate::region::{RegionHeader, RegionLocation};
+use crate::Error;
+
+/// The size of a region header. Locations and timestamps
+pub const REGION_HEADER_SIZE: u64 = 8192;
+pub const SECTOR_SIZE: u64 = 4096;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidateOptions {
+    /// Fix the problems that can be fixed automatically.
+    ///
+    /// Unknown blocks are replaced with air and broken region header entries are cleared so the chunk is regenerated.
+    ///
+    /// Chunks that can not be read are left alone. Axolotl may be missing support for something vanilla can read
+    pub repair: bool,
+    /// Read every chunk and check the palettes. This is slow on large worlds
+    pub check_chunks: bool,
+}
+
+/// Used to check saved palettes against the loaded data packs
+pub trait PaletteLookup {
+    fn is_known_block(&self, key: &OwnedNameSpaceKey) -> bool;
+
+    fn is_known_biome(&self, key: &OwnedNameSpaceKey) -> bool;
+    /// The block unknown blocks are replaced with
+    fn replacement_block(&self) -> PaletteItem {
+        PaletteItem {
+            name: OwnedNameSpaceKey::new("minecraft".to_string(), "air".to_string()),
+            properties: Default::default(),
+        }
+    }
+    /// The biome unknown biomes are replaced with
+    fn replacement_biome(&self) -> PaletteItem {
+        PaletteItem {
+            name: OwnedNameSpaceKey::new("minecraft".to_string(), "plains".to_string()),
+            properties: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProblemKind {
+    MissingLevelDat,
+    UnreadableLevelDat(String),
+    InvalidLevelDat(&'static str),
+    InvalidRegionFileName,
+    /// The file is smaller than the header
+    TruncatedRegionHeader,
+    /// The location points into the header or past the end of the file
+    ChunkOutOfBounds {
+        index: usize,
+        location: RegionLocation,
+    },
+    /// Two chunks claim the same sectors
+    OverlappingChunks {
+        index: usize,
+        other: usize,
+    },
+    /// The chunk length does not fit in the sectors it was given
+    InvalidChunkLength {
+        index: usize,
+        length: u32,
+    },
+    UnreadableChunk {
+        index: usize,
+        error: String,
+    },
+    UnknownBlock {
+        chunk: (i32, i32),
+        name: String,
+    },
+    UnknownBiome {
+        chunk: (i32, i32),
+        name: String,
+    },
+    InvalidPlayerFileName,
+    UnreadablePlayerFile(String),
+}
+impl Display for ProblemKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProblemKind::MissingLevelDat => write!(f, "level.dat is missing"),
+            ProblemKind::UnreadableLevelDat(error) => {
+                write!(f, "level.dat is unreadable: {}", error)
+            }
+            ProblemKind::InvalidLevelDat(field) => write!(f, "level.dat has an invalid {}", field),
+            ProblemKind::InvalidRegionFileName => write!(f, "Region file name is not r.x.z.mca"),
+            ProblemKind::TruncatedRegionHeader => write!(f, "Region header is truncated"),
+            ProblemKind::ChunkOutOfBounds { index, location } => write!(
+                f,
+                "Chunk {} points outside the file ({}, {} sectors)",
+                index, location.0, location.1
+            ),
+            ProblemKind::OverlappingChunks { index, other } => {
+                write!(f, "Chunk {} overlaps chunk {}", index, other)
+            }
+            ProblemKind::InvalidChunkLength { index, length } => {
+                write!(f, "Chunk {} has an invalid length of {}", index, length)
+            }
+            ProblemKind::UnreadableChunk { index, error } => {
+                write!(f, "Chunk {} is unreadable: {}", index, error)
+            }
+            ProblemKind::UnknownBlock { chunk, name } => {
+                write!(f, "Chunk {:?} contains unknown block {}", chunk, name)
+            }
+            ProblemKind::UnknownBiome { chunk, name } => {
+                write!(f, "Chunk {:?} contains unknown biome {}", chunk, name)
+            }
+            ProblemKind::InvalidPlayerFileName => write!(f, "Player file name is not a UUID"),
+            ProblemKind::UnreadablePlayerFile(error) => {
+                write!(f, "Player file is unreadable: {}", error)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldProblem {
+    pub path: PathBuf,
+    pub kind: ProblemKind,
+    /// True if the problem was repaired on disk
+    pub repaired: bool,
+}
+impl Display for WorldProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.kind)?;
+        if self.repaired {
+            write!(f, " (repaired)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub problems: Vec<WorldProblem>,
+    pub regions_checked: usize,
+    pub chunks_checked: usize,
+    pub player_files_checked: usize,
+}
+
+impl ValidationReport {
+    /// No problems were found
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+    /// Problems that still exist on disk
+    pub fn unrepaired(&self) -> impl Iterator<Item = &WorldProblem> {
+        self.problems.iter().filter(|problem| !problem.repaired)
+    }
+    pub fn push(&mut self, path: impl Into<PathBuf>, kind: ProblemKind, repaired: bool) {
+        let problem = WorldProblem {
+            path: path.into(),
+            kind,
+            repaired,
+        };
+        debug!("World Problem {}", problem);
+        self.problems.push(problem);
+    }
+}
+
+/// Validates the entire world.
+///
+/// `dimensions` are the folders of other dimensions that contain their own region folder.
+/// Must not be called while the regions are open by the server.
+pub fn validate_world<'a>(
+    world_folder: &Path,
+    player_folder: &Path,
+    dimensions: impl IntoIterator<Item = &'a PathBuf>,
+    options: ValidateOptions,
+    palettes: Option<&dyn PaletteLookup>,
+) -> Result<ValidationReport, Error> {
+    let mut report = ValidationReport::default();
+    validate_level_dat(&world_folder.join("level.dat"), &mut report);
+    validate_region_folder(&world_folder.join("region"), options, palettes, &mut report)?;
+    for dimension in dimensions {
+        let dimension = if dimension.is_absolute() {
+            dimension.clone()
+        } else {
+            world_folder.join(dimension)
+        };
+        validate_region_folder(&dimension.join("region"), options, palettes, &mut report)?;
+    }
+    validate_player_folder(player_folder, &mut report)?;
+    Ok(report)
+}
+
+pub fn validate_level_dat(path: &Path, report: &mut ValidationReport) {
+    if !path.exists() {
+        report.push(path, ProblemKind::MissingLevelDat, false);
+        return;
+    }
+    let level_dat: Result<RootWrapper, Error> =
+        File::open(path).map_err(Error::from).and_then(|file| {
+            serde_impl::from_buf_reader_binary(BufReader::new(GzDecoder::new(file)))
+                .map_err(Error::from)
+        });
+    match level_dat {
+        Ok(RootWrapper { data }) => {
+            if data.data_version <= 0 {
+                report.push(path, ProblemKind::InvalidLevelDat("DataVersion"), false);
+            }
+            if data.border_size <= 0.0 {
+                report.push(path, ProblemKind::InvalidLevelDat("BorderSize"), false);
+            }
+            if data.world_gen_settings.dimensions.is_empty() {
+                report.push(
+                    path,
+                    ProblemKind::InvalidLevelDat("WorldGenSettings"),
+                    false,
+                );
+            }
+        }
+        Err(error) => {
+            report.push(
+                path,
+                ProblemKind::UnreadableLevelDat(error.to_string()),
+                false,
+            );
+        }
+    }
+}
+
+pub fn validate_region_folder(
+    folder: &Path,
+    options: ValidateOptions,
+    palettes: Option<&dyn PaletteLookup>,
+    report: &mut ValidationReport,
+) -> Result<(), Error> {
+    if !folder.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().and_then(|v| v.to_str()) != Some("mca") {
+            continue;
+        }
+        validate_region_file(&path, options, palettes, report)?;
+    }
+    Ok(())
+}
+
+/// Parses the region position from a file name such as `r.1.-2.mca`
+pub fn parse_region_file_name(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((x, z))
+}
+
+pub fn validate_region_file(
+    path: &Path,
+    options: ValidateOptions,
+    palettes: Option<&dyn PaletteLookup>,
+    report: &mut ValidationReport,
+) -> Result<(), Error> {
+    report.regions_checked += 1;
+    if parse_region_file_name(path).is_none() {
+        report.push(path, ProblemKind::InvalidRegionFileName, false);
+    }
+    let file_size = std::fs::metadata(path)?.len();
+    if file_size < REGION_HEADER_SIZE {
+        // Nothing past the header can exist. So a new header loses nothing
+        if options.repair {
+            let mut file = OpenOptions::new().write(true).open(path)?;
+            file.set_len(0)?;
+            RegionHeader::initialize(&mut file)?;
+            file.flush()?;
+        }
+        report.push(path, ProblemKind::TruncatedRegionHeader, options.repair);
+        return Ok(());
+    }
+    let header = {
+        let mut file = File::open(path)?;
+        RegionHeader::read_region_header(&mut file)?
+    };
+    let mut region = RegionFile {
+        file: path.to_path_buf(),
+        region_header: header,
+        write_buffer: vec![],
+    };
+    let sectors = file_size / SECTOR_SIZE;
+    let mut broken = Vec::new();
+
+    // Check that every location is inside the file and no sectors are shared
+    let mut used = Vec::new();
+    for (index, location) in region.region_header.locations.iter().enumerate() {
+        if location.0 == 0 && location.1 == 0 {
+            continue;
+        }
+        let start = location.0 as u64;
+        let end = start + location.1 as u64;
+        if start < 2 || location.1 == 0 || end > sectors {
+            report.push(
+                path,
+                ProblemKind::ChunkOutOfBounds {
+                    index,
+                    location: *location,
+                },
+                options.repair,
+            );
+            broken.push(index);
+            continue;
+        }
+        used.push((start, end, index));
+    }
+    used.sort_unstable();
+    for window in used.windows(2) {
+        let (_, end, other) = window[0];
+        let (start, _, index) = window[1];
+        if start < end {
+            report.push(
+                path,
+                ProblemKind::OverlappingChunks { index, other },
+                options.repair,
+            );
+            broken.push(index);
+        }
+    }
+
+    for (_, _, index) in used {
+        if broken.contains(&index) {
+            continue;
+        }
+        let location = region.region_header.locations[index];
+        report.chunks_checked += 1;
+        let chunk_header = match region.read_chunk_header(&location) {
+            Ok(chunk_header) => chunk_header,
+            Err(error) => {
+                report.push(
+                    path,
+                    ProblemKind::UnreadableChunk {
+                        index,
+                        error: error.to_string(),
+                    },
+                    false,
+                );
+                continue;
+            }
+        };
+        if chunk_header.length == 0
+            || chunk_header.length as u64 + 4 > location.1 as u64 * SECTOR_SIZE
+        {
+            report.push(
+                path,
+                ProblemKind::InvalidChunkLength {
+                    index,
+                    length: chunk_header.length,
+                },
+                options.repair,
+            );
+            broken.push(index);
+            continue;
+        }
+        if !options.check_chunks {
+            continue;
+        }
+        match region.read_chunk::<RawChunk>(&location) {
+            Ok(Some((_, mut chunk))) => {
+                if let Some(palettes) = palettes {
+                    if check_palettes(path, &mut chunk, palettes, options, report) {
+                        region.write_chunk(chunk)?;
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                // Never repaired. The data might still be readable by vanilla
+                report.push(
+                    path,
+                    ProblemKind::UnreadableChunk {
+                        index,
+                        error: error.to_string(),
+                    },
+                    false,
+                );
+            }
+        }
+    }
+    if options.repair {
+        // The chunks are dropped from the header so they are regenerated on load
+        for index in broken {
+            warn!("Removing chunk {} from region {}", index, path.display());
+            region.region_header.locations[index] = RegionLocation::default();
+            region.region_header.timestamps[index] = 0;
+        }
+        region.save()?;
+    }
+    Ok(())
+}
+
+/// Returns true if the chunk was modified
+fn check_palettes(
+    path: &Path,
+    chunk: &mut RawChunk,
+    palettes: &dyn PaletteLookup,
+    options: ValidateOptions,
+    report: &mut ValidationReport,
+) -> bool {
+    let chunk_pos = (chunk.x_pos, chunk.z_pos);
+    let mut modified = false;
+    for section in chunk.sections.iter_mut() {
+        if let Some(block_states) = section.block_states.as_mut() {
+            for item in block_states.palette.iter_mut() {
+                if palettes.is_known_block(&item.name) {
+                    continue;
+                }
+                report.push(
+                    path,
+                    ProblemKind::UnknownBlock {
+                        chunk: chunk_pos,
+                        name: item.name.to_string(),
+                    },
+                    options.repair,
+                );
+                if options.repair {
+                    *item = palettes.replacement_block();
+                    modified = true;
+                }
+            }
+        }
+        if let Some(biomes) = section.biomes.as_mut() {
+            for item in biomes.palette.iter_mut() {
+                if palettes.is_known_biome(&item.name) {
+                    continue;
+                }
+                report.push(
+                    path,
+                    ProblemKind::UnknownBiome {
+                        chunk: chunk_pos,
+                        name: item.name.to_string(),
+                    },
+                    options.repair,
+                );
+                if options.repair {
+                    *item = palettes.replacement_biome();
+                    modified = true;
+                }
+            }
+        }
+    }
+    modified
+}
+
+/// Player files are only reported. Deleting them would lose inventories
+pub fn validate_player_folder(folder: &Path, report: &mut ValidationReport) -> Result<(), Error> {
+    if !folder.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().and_then(|v| v.to_str()) != Some("dat") {
+            continue;
+        }
+        report.player_files_checked += 1;
+        let valid_name = path
+            .file_stem()
+            .and_then(|v| v.to_str())
+            .map(|v| Uuid::parse_str(v).is_ok())
+            .unwrap_or(false);
+        if !valid_name {
+            report.push(&path, ProblemKind::InvalidPlayerFileName, false);
+        }
+        let player: Result<PlayerData, serde_impl::Error> =
+            serde_impl::from_buf_reader_binary(BufReader::new(GzDecoder::new(File::open(&path)?)));
+        if let Err(error) = player {
+            report.push(
+                &path,
+                ProblemKind::UnreadablePlayerFile(error.to_string()),
+                false,
+            );
+        }
+    }
+    Ok(())
+}
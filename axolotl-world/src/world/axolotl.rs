@@ -121,14 +121,18 @@ impl World for AxolotlWorld {
     }
 
     fn get_level_dat(&self) -> &Self::LevelDat {
-        todo!()
+        &self.level_dat
     }
 
     fn get_level_dat_mut(&mut self) -> &mut Self::LevelDat {
-        todo!()
+        &mut self.level_dat
     }
 
     fn get_world_folder(&self) -> &PathBuf {
-        todo!()
+        &self.world_folder
+    }
+
+    fn get_player_folder(&self) -> PathBuf {
+        self.player_folder.clone()
     }
 }
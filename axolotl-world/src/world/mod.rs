@@ -9,6 +9,7 @@ use serde::Serialize;
 use uuid::Uuid;
 
 use crate::entity::player::PlayerData;
+use crate::validate::{PaletteLookup, ValidateOptions, ValidationReport};
 
 pub mod axolotl;
 pub mod bukkit;
@@ -36,4 +37,24 @@ pub trait World {
     fn get_level_dat_mut(&mut self) -> &mut Self::LevelDat;
 
     fn get_world_folder(&self) -> &PathBuf;
+
+    fn get_player_folder(&self) -> PathBuf {
+        self.get_world_folder().join("playerdata")
+    }
+    /// Scans the world for problems. See [crate::validate]
+    ///
+    /// The world should not be in use while validating
+    fn validate(
+        &self,
+        options: ValidateOptions,
+        palettes: Option<&dyn PaletteLookup>,
+    ) -> Result<ValidationReport, crate::Error> {
+        crate::validate::validate_world(
+            self.get_world_folder(),
+            &self.get_player_folder(),
+            self.get_dimensions().values(),
+            options,
+            palettes,
+        )
+    }
 }
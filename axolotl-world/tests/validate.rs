@@ -0,0 +1,102 @@
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, remove_file, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use axolotl_world::region::{RegionHeader, RegionLocation};
+use axolotl_world::validate::{
+    parse_region_file_name, validate_region_file, ProblemKind, ValidateOptions, ValidationReport,
+};
+
+#[test]
+pub fn test_region_name() {
+    assert_eq!(
+        parse_region_file_name(&PathBuf::from("r.1.-2.mca")),
+        Some((1, -2))
+    );
+    assert_eq!(parse_region_file_name(&PathBuf::from("r.1.mca")), None);
+}
+
+#[test]
+pub fn test_repair_header() {
+    let folder = temp_dir().join(format!("axolotl_repair_header_{}", std::process::id()));
+    create_dir_all(&folder).unwrap();
+    let path = folder.join("r.5.5.mca");
+    if path.exists() {
+        remove_file(&path).unwrap();
+    }
+    {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&path)
+            .unwrap();
+        let mut header = RegionHeader::default();
+        // Points past the end of the file
+        header.locations[3] = RegionLocation(10, 1);
+        // Points into the header
+        header.locations[4] = RegionLocation(1, 1);
+        header.write_region(&mut file).unwrap();
+    }
+    let options = ValidateOptions {
+        repair: true,
+        check_chunks: false,
+    };
+    let mut report = ValidationReport::default();
+    validate_region_file(&path, options, None, &mut report).unwrap();
+    assert_eq!(report.problems.len(), 2);
+    assert!(report
+        .problems
+        .iter()
+        .all(|problem| problem.repaired
+            && matches!(problem.kind, ProblemKind::ChunkOutOfBounds { .. })));
+
+    let header = RegionHeader::read_region_header(&mut File::open(&path).unwrap()).unwrap();
+    assert_eq!(header.locations[3], RegionLocation::default());
+    assert_eq!(header.locations[4], RegionLocation::default());
+
+    let mut report = ValidationReport::default();
+    validate_region_file(&path, options, None, &mut report).unwrap();
+    assert!(report.is_valid());
+    remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+pub fn test_unreadable_chunk_is_kept() {
+    let folder = temp_dir().join(format!("axolotl_unreadable_chunk_{}", std::process::id()));
+    create_dir_all(&folder).unwrap();
+    let path = folder.join("r.6.6.mca");
+    if path.exists() {
+        remove_file(&path).unwrap();
+    }
+    {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&path)
+            .unwrap();
+        let mut header = RegionHeader::default();
+        header.locations[0] = RegionLocation(2, 1);
+        header.write_region(&mut file).unwrap();
+        // A zlib chunk that does not decompress
+        let mut sector = vec![0u8; 4096];
+        sector[..9].copy_from_slice(&[0, 0, 0, 5, 2, 0xde, 0xad, 0xbe, 0xef]);
+        file.write_all(&sector).unwrap();
+    }
+    let options = ValidateOptions {
+        repair: true,
+        check_chunks: true,
+    };
+    let mut report = ValidationReport::default();
+    validate_region_file(&path, options, None, &mut report).unwrap();
+    assert_eq!(report.problems.len(), 1);
+    assert!(!report.problems[0].repaired);
+    assert!(matches!(
+        report.problems[0].kind,
+        ProblemKind::UnreadableChunk { index: 0, .. }
+    ));
+
+    let header = RegionHeader::read_region_header(&mut File::open(&path).unwrap()).unwrap();
+    assert_eq!(header.locations[0], RegionLocation(2, 1));
+    remove_dir_all(&folder).unwrap();
+}